@@ -0,0 +1,255 @@
+use rtsc_parser::ast::*;
+
+use crate::{
+    expr::{ASSIGN, CALL},
+    Codegen,
+};
+
+fn accessibility(accessibility: Accessibility) -> &'static str {
+    match accessibility {
+        Accessibility::Public => "public",
+        Accessibility::Protected => "protected",
+        Accessibility::Private => "private",
+    }
+}
+
+impl Codegen<'_> {
+    /// A function declaration or expression without a `;` for a missing body.
+    pub(crate) fn function(&mut self, id: Option<&Ident>, function: &Function) {
+        self.comments_before(function.span.start);
+        if function.is_async {
+            self.write("async");
+            self.space();
+        }
+        self.write("function");
+        if function.is_generator {
+            self.write("*");
+        }
+        if let Some(id) = id {
+            self.space();
+            self.ident(id);
+        }
+        self.function_rest(function);
+    }
+
+    /// `async`, `*`, `get` or `set` in front of the key of a method.
+    pub(crate) fn method_head(&mut self, kind: MethodKind, function: &Function) {
+        if function.is_async {
+            self.write("async");
+            self.space();
+        }
+        if function.is_generator {
+            self.write("*");
+        }
+        match kind {
+            MethodKind::Getter => {
+                self.write("get");
+                self.space();
+            }
+            MethodKind::Setter => {
+                self.write("set");
+                self.space();
+            }
+            MethodKind::Method | MethodKind::Constructor => {}
+        }
+    }
+
+    /// The type parameters, parameters, return type and body of a function.
+    pub(crate) fn function_rest(&mut self, function: &Function) {
+        self.type_params(function.type_params.as_ref());
+        self.write("(");
+        self.comma_list(&function.params, |p, param| p.param(param));
+        // the comments left before the return type or the body are in the parentheses
+        let end = match (&function.return_type, &function.body) {
+            (Some(ty), _) => ty.span().start,
+            (None, Some(body)) => body.span.start,
+            (None, None) => function.span.end,
+        };
+        self.comments_before(end);
+        self.write(")");
+        self.type_ann(function.return_type.as_deref());
+        if let Some(body) = &function.body {
+            self.space();
+            self.block(body);
+        }
+    }
+
+    fn param(&mut self, param: &Param) {
+        self.comments_before(param.span.start);
+        self.decorators(&param.decorators, false);
+        if let Some(a) = param.accessibility {
+            self.write(accessibility(a));
+            self.space();
+        }
+        if param.is_override {
+            self.write("override");
+            self.space();
+        }
+        if param.is_readonly {
+            self.write("readonly");
+            self.space();
+        }
+        self.pat(&param.pat);
+    }
+
+    /// Prints decorators, each on a line of its own if `own_line`.
+    pub(crate) fn decorators(&mut self, decorators: &[Decorator], own_line: bool) {
+        for decorator in decorators {
+            self.comments_before(decorator.span.start);
+            self.write("@");
+            self.expr_in(&decorator.expr, CALL);
+            if own_line {
+                self.newline();
+            } else {
+                self.space();
+            }
+        }
+    }
+
+    /// A class without its decorators, which go in front of `export` and `declare`.
+    pub(crate) fn class(&mut self, id: Option<&Ident>, class: &Class) {
+        if class.is_abstract {
+            self.write("abstract");
+            self.space();
+        }
+        self.write("class");
+        if let Some(id) = id {
+            self.space();
+            self.ident(id);
+        }
+        self.type_params(class.type_params.as_ref());
+        if let Some(super_class) = &class.super_class {
+            self.space();
+            self.write("extends");
+            self.space();
+            self.expr_in(super_class, CALL);
+            self.type_args(class.super_type_args.as_ref());
+        }
+        if !class.implements.is_empty() {
+            self.space();
+            self.write("implements");
+            self.space();
+            self.comma_list(&class.implements, |p, implements| {
+                p.expr_with_type_args(implements)
+            });
+        }
+        self.space();
+        self.write("{");
+        self.indent += 1;
+        for member in &class.body {
+            self.newline();
+            self.class_member(member);
+        }
+        self.comments_before(class.span.end.saturating_sub(1));
+        self.indent -= 1;
+        if !class.body.is_empty() || self.needs_newline {
+            self.newline();
+        }
+        self.write("}");
+    }
+
+    fn class_member(&mut self, member: &ClassMember) {
+        match member {
+            ClassMember::Method(method) => {
+                self.comments_before(method.span.start);
+                self.decorators(&method.decorators, false);
+                self.modifiers(
+                    method.accessibility,
+                    method.is_static,
+                    method.is_abstract,
+                    method.is_override,
+                );
+                self.method_head(method.kind, &method.function);
+                self.prop_name(&method.key);
+                if method.is_optional {
+                    self.write("?");
+                }
+                self.function_rest(&method.function);
+                if method.function.body.is_none() {
+                    self.write(";");
+                }
+                self.trailing_comments(method.span);
+            }
+            ClassMember::Prop(prop) => {
+                self.comments_before(prop.span.start);
+                self.decorators(&prop.decorators, false);
+                if prop.is_declare {
+                    self.write("declare");
+                    self.space();
+                }
+                self.modifiers(
+                    prop.accessibility,
+                    prop.is_static,
+                    prop.is_abstract,
+                    prop.is_override,
+                );
+                if prop.is_readonly {
+                    self.write("readonly");
+                    self.space();
+                }
+                if prop.is_accessor {
+                    self.write("accessor");
+                    self.space();
+                }
+                self.prop_name(&prop.key);
+                if prop.is_optional {
+                    self.write("?");
+                }
+                if prop.definite {
+                    self.write("!");
+                }
+                self.type_ann(prop.type_ann.as_deref());
+                if let Some(value) = &prop.value {
+                    self.space();
+                    self.write("=");
+                    self.space();
+                    self.expr_in(value, ASSIGN);
+                }
+                self.write(";");
+                self.trailing_comments(prop.span);
+            }
+            ClassMember::IndexSignature(signature) => {
+                self.comments_before(signature.span.start);
+                self.index_signature(signature);
+                // a `;` after it would be a member of its own, so the next member goes
+                // on a new line, even minified
+                self.trailing_comments(signature.span);
+                self.needs_newline = true;
+            }
+            ClassMember::StaticBlock(block) => {
+                self.comments_before(block.span.start);
+                self.write("static");
+                self.space();
+                self.block(&block.body);
+            }
+            ClassMember::Empty(span) => {
+                self.comments_before(span.start);
+                self.write(";");
+            }
+            ClassMember::Invalid(_) => {}
+        }
+    }
+
+    fn modifiers(
+        &mut self,
+        accessibility: Option<Accessibility>,
+        is_static: bool,
+        is_abstract: bool,
+        is_override: bool,
+    ) {
+        if let Some(a) = accessibility {
+            self.write(self::accessibility(a));
+            self.space();
+        }
+        for (set, modifier) in [
+            (is_static, "static"),
+            (is_abstract, "abstract"),
+            (is_override, "override"),
+        ] {
+            if set {
+                self.write(modifier);
+                self.space();
+            }
+        }
+    }
+}
@@ -0,0 +1,760 @@
+use std::borrow::Cow;
+
+use rtsc_parser::ast::*;
+
+use crate::{Codegen, QuoteStyle};
+
+// How tightly expressions bind, from a sequence to a primary expression. An operand
+// binding looser than its place allows is put in parentheses. The binary operators are
+// `BINARY` plus their precedence in the parser.
+pub(crate) const SEQ: u8 = 0;
+/// Assignments, arrow functions and `yield`, which is where arguments and
+/// initializers start.
+pub(crate) const ASSIGN: u8 = 1;
+const COND: u8 = 2;
+const BINARY: u8 = 2;
+const RELATIONAL: u8 = BINARY + 8;
+const UNARY: u8 = 16;
+const POSTFIX: u8 = 17;
+/// Calls, `new` and member expressions, the left-hand side of an assignment.
+pub(crate) const CALL: u8 = 18;
+const PRIMARY: u8 = 19;
+
+fn precedence(expr: &Expr) -> u8 {
+    match expr {
+        Expr::Seq(_) => SEQ,
+        Expr::Assign(_) | Expr::Arrow(_) | Expr::Yield(_) => ASSIGN,
+        Expr::Cond(_) => COND,
+        Expr::Binary(e) => BINARY + binary_precedence(e.op),
+        Expr::TsAs(_) | Expr::TsSatisfies(_) | Expr::TsConstAssertion(_) => RELATIONAL,
+        Expr::Unary(_) | Expr::Await(_) | Expr::TsTypeAssertion(_) => UNARY,
+        Expr::Update(e) if e.prefix => UNARY,
+        Expr::Update(_) => POSTFIX,
+        Expr::Call(_)
+        | Expr::New(_)
+        | Expr::Member(_)
+        | Expr::TaggedTpl(_)
+        | Expr::TsNonNull(_)
+        | Expr::TsInstantiation(_) => CALL,
+        _ => PRIMARY,
+    }
+}
+
+/// The precedence of a binary operator in the parser.
+fn binary_precedence(op: BinaryOperator) -> u8 {
+    match op {
+        BinaryOperator::NullishCoalescing => 1,
+        BinaryOperator::LogicalOr => 2,
+        BinaryOperator::LogicalAnd => 3,
+        BinaryOperator::BitOr => 4,
+        BinaryOperator::BitXor => 5,
+        BinaryOperator::BitAnd => 6,
+        BinaryOperator::Eq | BinaryOperator::EqEq | BinaryOperator::Ne | BinaryOperator::NeNe => 7,
+        BinaryOperator::Lt
+        | BinaryOperator::Le
+        | BinaryOperator::Gt
+        | BinaryOperator::Ge
+        | BinaryOperator::In
+        | BinaryOperator::Instanceof => 8,
+        BinaryOperator::LShift | BinaryOperator::RShift | BinaryOperator::ZeroFillRightShift => 9,
+        BinaryOperator::Add | BinaryOperator::Sub => 10,
+        BinaryOperator::Mul | BinaryOperator::Div | BinaryOperator::Mod => 11,
+        BinaryOperator::Exp => 12,
+    }
+}
+
+pub(crate) fn binary_operator(op: BinaryOperator) -> &'static str {
+    match op {
+        BinaryOperator::Eq => "==",
+        BinaryOperator::EqEq => "===",
+        BinaryOperator::Ne => "!=",
+        BinaryOperator::NeNe => "!==",
+        BinaryOperator::Lt => "<",
+        BinaryOperator::Le => "<=",
+        BinaryOperator::Gt => ">",
+        BinaryOperator::Ge => ">=",
+        BinaryOperator::LShift => "<<",
+        BinaryOperator::RShift => ">>",
+        BinaryOperator::ZeroFillRightShift => ">>>",
+        BinaryOperator::Add => "+",
+        BinaryOperator::Sub => "-",
+        BinaryOperator::Mul => "*",
+        BinaryOperator::Div => "/",
+        BinaryOperator::Mod => "%",
+        BinaryOperator::Exp => "**",
+        BinaryOperator::BitOr => "|",
+        BinaryOperator::BitXor => "^",
+        BinaryOperator::BitAnd => "&",
+        BinaryOperator::LogicalOr => "||",
+        BinaryOperator::LogicalAnd => "&&",
+        BinaryOperator::NullishCoalescing => "??",
+        BinaryOperator::In => "in",
+        BinaryOperator::Instanceof => "instanceof",
+    }
+}
+
+fn assign_operator(op: AssignOperator) -> &'static str {
+    match op {
+        AssignOperator::Assign => "=",
+        AssignOperator::AddAssign => "+=",
+        AssignOperator::SubAssign => "-=",
+        AssignOperator::MulAssign => "*=",
+        AssignOperator::DivAssign => "/=",
+        AssignOperator::ModAssign => "%=",
+        AssignOperator::ExpAssign => "**=",
+        AssignOperator::LeftShiftAssign => "<<=",
+        AssignOperator::RightShiftAssign => ">>=",
+        AssignOperator::ZeroFillRightShiftAssign => ">>>=",
+        AssignOperator::BitOrAssign => "|=",
+        AssignOperator::BitXorAssign => "^=",
+        AssignOperator::BitAndAssign => "&=",
+        AssignOperator::LogicalAndAssign => "&&=",
+        AssignOperator::LogicalOrAssign => "||=",
+        AssignOperator::NullishAssign => "??=",
+    }
+}
+
+fn unary_operator(op: UnaryOperator) -> &'static str {
+    match op {
+        UnaryOperator::Minus => "-",
+        UnaryOperator::Plus => "+",
+        UnaryOperator::Bang => "!",
+        UnaryOperator::Tilde => "~",
+        UnaryOperator::Typeof => "typeof",
+        UnaryOperator::Void => "void",
+        UnaryOperator::Delete => "delete",
+    }
+}
+
+/// The expression an expression starts with, like `a` in `a.b + c`.
+fn leftmost<'e, 'a>(expr: &'e Expr<'a>) -> &'e Expr<'a> {
+    let next = match expr {
+        Expr::Binary(e) => &e.left,
+        Expr::Call(e) => &e.callee,
+        Expr::Member(e) => &e.object,
+        Expr::Cond(e) => &e.test,
+        Expr::TaggedTpl(e) => &e.tag,
+        Expr::Update(e) if !e.prefix => &e.arg,
+        Expr::TsAs(e) => &e.expr,
+        Expr::TsSatisfies(e) => &e.expr,
+        Expr::TsConstAssertion(e) => &e.expr,
+        Expr::TsNonNull(e) => &e.expr,
+        Expr::TsInstantiation(e) => &e.expr,
+        Expr::Seq(e) => match e.exprs.first() {
+            Some(first) => first,
+            None => return expr,
+        },
+        Expr::Assign(e) => match &*e.left {
+            Pat::Expr(left) => left,
+            _ => return expr,
+        },
+        _ => return expr,
+    };
+    leftmost(next)
+}
+
+/// Whether an expression statement would be read as something else, as `{}` would be a
+/// block and `function () {}()` a declaration.
+pub(crate) fn starts_like_declaration(expr: &Expr) -> bool {
+    match leftmost(expr) {
+        Expr::Object(_) => true,
+        Expr::Assign(e) => matches!(*e.left, Pat::Object(_)),
+        _ => starts_with_function(expr),
+    }
+}
+
+/// Whether an expression starts with a function or a class, which `export default`
+/// would take as a declaration.
+pub(crate) fn starts_with_function(expr: &Expr) -> bool {
+    matches!(leftmost(expr), Expr::Fn(_) | Expr::Class(_))
+}
+
+/// Whether `new` would take the call in an expression as its own arguments, as in
+/// `new (a())()`.
+fn has_call(expr: &Expr) -> bool {
+    match expr {
+        Expr::Call(_) => true,
+        Expr::Member(e) => has_call(&e.object),
+        Expr::TaggedTpl(e) => has_call(&e.tag),
+        Expr::TsNonNull(e) => has_call(&e.expr),
+        _ => false,
+    }
+}
+
+/// A string literal with the other quotes. Escapes are kept as they are, except for
+/// the old quote, which no longer needs one, and the new one, which now does.
+/// Closes a string recovered from an unterminated one, which would otherwise take in
+/// what is printed after it.
+fn terminated(raw: &str) -> Cow<str> {
+    let Some(quote) = raw.chars().next() else {
+        return Cow::Borrowed(raw);
+    };
+    let trailing_backslashes = |text: &str| text.len() - text.trim_end_matches('\\').len();
+    let body = &raw[1..];
+    match body.strip_suffix(quote) {
+        Some(inner) if trailing_backslashes(inner) % 2 == 0 => Cow::Borrowed(raw),
+        _ => {
+            // a backslash at the end would escape the closing quote
+            let body = match trailing_backslashes(body) % 2 {
+                1 => &body[..body.len() - 1],
+                _ => body,
+            };
+            Cow::Owned(format!("{quote}{body}{quote}"))
+        }
+    }
+}
+
+fn requote(raw: &str, quote: char) -> Cow<str> {
+    if raw.starts_with(quote) || raw.len() < 2 {
+        return Cow::Borrowed(raw);
+    }
+    let mut out = String::with_capacity(raw.len() + 2);
+    out.push(quote);
+    let mut chars = raw[1..raw.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(escaped @ ('"' | '\'')) if escaped != quote => out.push(escaped),
+                Some(escaped) => {
+                    out.push('\\');
+                    out.push(escaped);
+                }
+                None => out.push('\\'),
+            },
+            c if c == quote => {
+                out.push('\\');
+                out.push(c);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push(quote);
+    Cow::Owned(out)
+}
+
+impl Codegen<'_> {
+    pub(crate) fn expr(&mut self, expr: &Expr) {
+        self.expr_in(expr, SEQ);
+    }
+
+    /// Prints an expression in a place which takes expressions binding at least as
+    /// tightly as `min`, in parentheses if it binds looser.
+    pub(crate) fn expr_in(&mut self, expr: &Expr, min: u8) {
+        if precedence(expr) < min {
+            self.parenthesized(expr);
+        } else {
+            self.expr_inner(expr);
+        }
+    }
+
+    pub(crate) fn parenthesized(&mut self, expr: &Expr) {
+        self.write("(");
+        self.expr(expr);
+        self.write(")");
+    }
+
+    /// The expression in front of `.`, `[` or arguments.
+    fn callee(&mut self, expr: &Expr) {
+        // `new a` takes the arguments after it as its own
+        if matches!(expr, Expr::New(NewExpr { args: None, .. })) {
+            self.parenthesized(expr);
+        } else {
+            self.expr_in(expr, CALL);
+        }
+    }
+
+    fn expr_inner(&mut self, expr: &Expr) {
+        self.comments_before(expr.span().start);
+        match expr {
+            Expr::Ident(id) => self.ident(id),
+            Expr::This(_) => self.write("this"),
+            Expr::Super(_) => self.write("super"),
+            Expr::Import(_) => self.write("import"),
+            Expr::Lit(lit) => self.lit(lit),
+            Expr::Array(array) => {
+                self.write("[");
+                self.elements(&array.elems, |p, elem| p.expr_or_spread(elem));
+                self.comments_before(array.span.end.saturating_sub(1));
+                self.write("]");
+            }
+            Expr::Object(object) => self.object_lit(object),
+            Expr::Fn(f) => self.function(f.id.as_ref(), &f.function),
+            Expr::Class(c) => {
+                self.decorators(&c.class.decorators, false);
+                self.class(c.id.as_ref(), &c.class);
+            }
+            Expr::Arrow(arrow) => self.arrow(arrow),
+            Expr::Tpl(tpl) => self.tpl(tpl),
+            Expr::TaggedTpl(e) => {
+                self.callee(&e.tag);
+                self.type_args(e.type_args.as_ref());
+                self.tpl(&e.tpl);
+            }
+            Expr::Unary(e) => {
+                self.write(unary_operator(e.op));
+                if matches!(
+                    e.op,
+                    UnaryOperator::Typeof | UnaryOperator::Void | UnaryOperator::Delete
+                ) {
+                    self.space();
+                }
+                self.expr_in(&e.arg, UNARY);
+            }
+            Expr::Update(e) => {
+                let op = match e.op {
+                    UpdateOperator::Increment => "++",
+                    UpdateOperator::Decrement => "--",
+                };
+                if e.prefix {
+                    self.write(op);
+                    self.expr_in(&e.arg, UNARY);
+                } else {
+                    self.expr_in(&e.arg, CALL);
+                    self.write(op);
+                }
+            }
+            Expr::Binary(e) => self.binary(e),
+            Expr::Assign(e) => {
+                self.pat(&e.left);
+                self.space();
+                self.write(assign_operator(e.op));
+                self.space();
+                self.expr_in(&e.right, ASSIGN);
+            }
+            Expr::Cond(e) => {
+                self.expr_in(&e.test, COND + 1);
+                self.space();
+                self.write("?");
+                self.space();
+                self.expr_in(&e.cons, ASSIGN);
+                self.space();
+                self.write(":");
+                self.space();
+                self.expr_in(&e.alt, ASSIGN);
+            }
+            Expr::Call(e) => {
+                self.callee(&e.callee);
+                if e.optional {
+                    self.write("?.");
+                }
+                self.type_args(e.type_args.as_ref());
+                self.args(&e.args, e.span);
+            }
+            Expr::New(e) => {
+                self.write("new");
+                self.space();
+                if has_call(&e.callee) {
+                    self.parenthesized(&e.callee);
+                } else {
+                    self.expr_in(&e.callee, CALL);
+                }
+                self.type_args(e.type_args.as_ref());
+                if let Some(args) = &e.args {
+                    self.args(args, e.span);
+                }
+            }
+            Expr::Member(e) => self.member(e),
+            Expr::Seq(e) => self.comma_list(&e.exprs, |p, expr| p.expr_in(expr, ASSIGN)),
+            Expr::Paren(e) => {
+                self.write("(");
+                self.expr(&e.expr);
+                self.comments_before(e.span.end.saturating_sub(1));
+                self.write(")");
+            }
+            Expr::Yield(e) => {
+                self.write("yield");
+                if e.delegate {
+                    self.write("*");
+                }
+                if let Some(arg) = &e.arg {
+                    self.space();
+                    self.expr_in(arg, ASSIGN);
+                }
+            }
+            Expr::Await(e) => {
+                self.write("await");
+                self.space();
+                self.expr_in(&e.arg, UNARY);
+            }
+            Expr::MetaProp(e) => {
+                self.ident(&e.meta);
+                self.write(".");
+                self.ident(&e.prop);
+            }
+            Expr::PrivateName(name) => self.private_name(name),
+            Expr::TsAs(e) => {
+                self.expr_in(&e.expr, RELATIONAL);
+                self.space();
+                self.write("as");
+                self.space();
+                self.ty(&e.type_ann);
+            }
+            Expr::TsSatisfies(e) => {
+                self.expr_in(&e.expr, RELATIONAL);
+                self.space();
+                self.write("satisfies");
+                self.space();
+                self.ty(&e.type_ann);
+            }
+            Expr::TsConstAssertion(e) => {
+                self.expr_in(&e.expr, RELATIONAL);
+                self.space();
+                self.write("as");
+                self.space();
+                self.write("const");
+            }
+            Expr::TsNonNull(e) => {
+                self.expr_in(&e.expr, CALL);
+                self.write("!");
+            }
+            Expr::TsTypeAssertion(e) => {
+                self.write("<");
+                self.ty(&e.type_ann);
+                self.write(">");
+                self.expr_in(&e.expr, UNARY);
+            }
+            Expr::TsInstantiation(e) => {
+                self.expr_in(&e.expr, CALL);
+                self.type_args(Some(&e.type_args));
+            }
+            Expr::JsxElement(el) => self.jsx_element(el),
+            Expr::JsxFragment(fragment) => self.jsx_fragment(fragment),
+            Expr::Invalid(_) => self.left_out_expr = true,
+        }
+    }
+
+    fn binary(&mut self, e: &BinaryExpr) {
+        let prec = BINARY + binary_precedence(e.op);
+        // `??` cannot be mixed with `||` and `&&` without parentheses
+        let mixes_nullish = |operand: &Expr| {
+            let Expr::Binary(operand) = operand else {
+                return false;
+            };
+            let logical = |op| matches!(op, BinaryOperator::LogicalOr | BinaryOperator::LogicalAnd);
+            (e.op == BinaryOperator::NullishCoalescing && logical(operand.op))
+                || (logical(e.op) && operand.op == BinaryOperator::NullishCoalescing)
+        };
+        // `**` is right-associative, and takes no unary expression on its left
+        let (left_min, right_min) = if e.op == BinaryOperator::Exp {
+            (POSTFIX, prec)
+        } else {
+            (prec, prec + 1)
+        };
+        if mixes_nullish(&e.left) {
+            self.parenthesized(&e.left);
+        } else {
+            self.expr_in(&e.left, left_min);
+        }
+        self.space();
+        self.write(binary_operator(e.op));
+        self.space();
+        if mixes_nullish(&e.right) {
+            self.parenthesized(&e.right);
+        } else {
+            self.expr_in(&e.right, right_min);
+        }
+    }
+
+    fn member(&mut self, e: &MemberExpr) {
+        self.callee(&e.object);
+        // `1.a` would be read as the number `1.` followed by `a`
+        if let Expr::Lit(Lit::Num(num)) = &*e.object {
+            if !e.optional && num.raw.bytes().all(|b| b.is_ascii_digit() || b == b'_') {
+                self.raw(" ");
+            }
+        }
+        match &e.prop {
+            MemberProp::Ident(id) => {
+                self.write(if e.optional { "?." } else { "." });
+                self.ident(id);
+            }
+            MemberProp::Private(name) => {
+                self.write(if e.optional { "?." } else { "." });
+                self.private_name(name);
+            }
+            MemberProp::Computed(computed) => {
+                if e.optional {
+                    self.write("?.");
+                }
+                self.write("[");
+                self.expr(&computed.expr);
+                self.write("]");
+            }
+        }
+    }
+
+    fn args(&mut self, args: &[ExprOrSpread], span: rtsc_parser::Span) {
+        self.write("(");
+        self.comma_list(args, |p, arg| p.expr_or_spread(arg));
+        self.comments_before(span.end.saturating_sub(1));
+        self.write(")");
+    }
+
+    pub(crate) fn expr_or_spread(&mut self, e: &ExprOrSpread) {
+        if let Some(spread) = e.spread {
+            self.comments_before(spread.start);
+            self.write("...");
+        }
+        self.expr_in(&e.expr, ASSIGN);
+    }
+
+    pub(crate) fn ident(&mut self, id: &Ident) {
+        self.comments_before(id.span.start);
+        self.write(&id.name);
+    }
+
+    pub(crate) fn private_name(&mut self, name: &PrivateName) {
+        self.comments_before(name.span.start);
+        self.write(&format!("#{}", name.name));
+    }
+
+    pub(crate) fn str(&mut self, s: &Str) {
+        self.comments_before(s.span.start);
+        let raw = terminated(s.raw);
+        let raw = match self.options.quotes {
+            QuoteStyle::Preserve => raw,
+            QuoteStyle::Double => Cow::Owned(requote(&raw, '"').into_owned()),
+            QuoteStyle::Single => Cow::Owned(requote(&raw, '\'').into_owned()),
+        };
+        self.write(&raw);
+    }
+
+    pub(crate) fn lit(&mut self, lit: &Lit) {
+        self.comments_before(lit.span().start);
+        match lit {
+            Lit::Str(s) => self.str(s),
+            Lit::Num(num) => self.write(num.raw),
+            Lit::BigInt(big) => self.write(big.raw),
+            Lit::Bool(b) => self.write(if b.value { "true" } else { "false" }),
+            Lit::Null(_) => self.write("null"),
+            Lit::Regex(regex) => {
+                self.write(&format!("/{}/{}", regex.pattern, regex.flags));
+                self.after_regex = true;
+            }
+        }
+    }
+
+    fn tpl(&mut self, tpl: &Tpl) {
+        self.comments_before(tpl.span.start);
+        self.raw("`");
+        for (i, quasi) in tpl.quasis.iter().enumerate() {
+            self.raw(quasi.raw);
+            if let Some(expr) = tpl.exprs.get(i) {
+                self.raw("${");
+                self.expr(expr);
+                self.raw("}");
+            }
+        }
+        self.raw("`");
+    }
+
+    pub(crate) fn object_lit(&mut self, object: &ObjectLit) {
+        self.write("{");
+        if object.props.is_empty() {
+            self.comments_before(object.span.end.saturating_sub(1));
+            self.write("}");
+            return;
+        }
+        // methods have bodies of their own, so every property gets a line
+        let multiline = object
+            .props
+            .iter()
+            .any(|prop| matches!(prop, Prop::Method(_)));
+        if multiline {
+            self.indent += 1;
+            for (i, prop) in object.props.iter().enumerate() {
+                if i > 0 {
+                    self.write(",");
+                }
+                self.newline();
+                self.prop(prop);
+            }
+            self.comments_before(object.span.end.saturating_sub(1));
+            self.indent -= 1;
+            self.newline();
+        } else {
+            self.space();
+            self.comma_list(&object.props, |p, prop| p.prop(prop));
+            self.comments_before(object.span.end.saturating_sub(1));
+            self.space();
+        }
+        self.write("}");
+    }
+
+    fn prop(&mut self, prop: &Prop) {
+        match prop {
+            Prop::KeyValue(p) => {
+                self.prop_name(&p.key);
+                self.write(":");
+                self.space();
+                self.expr_in(&p.value, ASSIGN);
+            }
+            Prop::Shorthand(p) => {
+                self.ident(&p.key);
+                if let Some(init) = &p.init {
+                    self.space();
+                    self.write("=");
+                    self.space();
+                    self.expr_in(init, ASSIGN);
+                }
+            }
+            Prop::Method(p) => {
+                self.comments_before(p.span.start);
+                self.method_head(p.kind, &p.function);
+                self.prop_name(&p.key);
+                self.function_rest(&p.function);
+            }
+            Prop::Spread(p) => {
+                self.comments_before(p.span.start);
+                self.write("...");
+                self.expr_in(&p.expr, ASSIGN);
+            }
+        }
+    }
+
+    pub(crate) fn prop_name(&mut self, name: &PropName) {
+        match name {
+            PropName::Ident(id) => self.ident(id),
+            PropName::Str(s) => self.str(s),
+            PropName::Num(num) => {
+                self.comments_before(num.span.start);
+                self.write(num.raw);
+            }
+            PropName::BigInt(big) => {
+                self.comments_before(big.span.start);
+                self.write(big.raw);
+            }
+            PropName::Computed(computed) => {
+                self.comments_before(computed.span.start);
+                self.write("[");
+                self.expr_in(&computed.expr, ASSIGN);
+                self.write("]");
+            }
+            PropName::Private(name) => self.private_name(name),
+        }
+    }
+
+    fn arrow(&mut self, arrow: &ArrowExpr) {
+        if arrow.is_async {
+            self.write("async");
+            self.space();
+        }
+        let simple_param = match &arrow.params[..] {
+            [Pat::Ident(param)] => {
+                param.type_ann.is_none()
+                    && !param.optional
+                    && arrow.type_params.is_none()
+                    && arrow.return_type.is_none()
+            }
+            _ => false,
+        };
+        if simple_param {
+            self.pat(&arrow.params[0]);
+        } else {
+            if let Some(type_params) = &arrow.type_params {
+                // `<T>(a) => a` would be a JSX element in TSX
+                let trailing_comma =
+                    matches!(&type_params.params[..], [param] if param.constraint.is_none());
+                self.type_param_list(type_params, trailing_comma);
+            }
+            self.write("(");
+            self.comma_list(&arrow.params, |p, param| p.pat(param));
+            self.write(")");
+            self.type_ann(arrow.return_type.as_deref());
+        }
+        self.space();
+        self.write("=>");
+        self.space();
+        match &arrow.body {
+            ArrowBody::Block(block) => self.block(block),
+            ArrowBody::Expr(expr) => {
+                if matches!(leftmost(expr), Expr::Object(_)) {
+                    self.parenthesized(expr);
+                } else {
+                    self.expr_in(expr, ASSIGN);
+                }
+            }
+        }
+    }
+
+    // ---------- patterns ----------
+
+    pub(crate) fn pat(&mut self, pat: &Pat) {
+        self.comments_before(pat.span().start);
+        match pat {
+            Pat::Ident(id) => self.binding_ident(id, false),
+            Pat::Array(array) => {
+                self.write("[");
+                self.elements(&array.elems, |p, elem| p.pat(elem));
+                self.comments_before(array.span.end.saturating_sub(1));
+                self.write("]");
+                if array.optional {
+                    self.write("?");
+                }
+                self.type_ann(array.type_ann.as_deref());
+            }
+            Pat::Object(object) => {
+                self.write("{");
+                if !object.props.is_empty() {
+                    self.space();
+                    self.comma_list(&object.props, |p, prop| p.object_pat_prop(prop));
+                    self.space();
+                }
+                self.write("}");
+                if object.optional {
+                    self.write("?");
+                }
+                self.type_ann(object.type_ann.as_deref());
+            }
+            Pat::Assign(assign) => {
+                self.pat(&assign.left);
+                self.space();
+                self.write("=");
+                self.space();
+                self.expr_in(&assign.right, ASSIGN);
+            }
+            Pat::Rest(rest) => self.rest_pat(rest),
+            Pat::Expr(expr) => self.expr_in(expr, CALL),
+        }
+    }
+
+    /// An identifier to bind, with `!` if it is `definite`, as in `let a!: T`.
+    pub(crate) fn binding_ident(&mut self, id: &BindingIdent, definite: bool) {
+        self.ident(&id.id);
+        if id.optional {
+            self.write("?");
+        }
+        if definite {
+            self.write("!");
+        }
+        self.type_ann(id.type_ann.as_deref());
+    }
+
+    fn object_pat_prop(&mut self, prop: &ObjectPatProp) {
+        match prop {
+            ObjectPatProp::KeyValue(p) => {
+                self.prop_name(&p.key);
+                self.write(":");
+                self.space();
+                self.pat(&p.value);
+            }
+            ObjectPatProp::Assign(p) => {
+                self.ident(&p.key);
+                if let Some(value) = &p.value {
+                    self.space();
+                    self.write("=");
+                    self.space();
+                    self.expr_in(value, ASSIGN);
+                }
+            }
+            ObjectPatProp::Rest(rest) => self.rest_pat(rest),
+        }
+    }
+
+    fn rest_pat(&mut self, rest: &RestPat) {
+        self.comments_before(rest.span.start);
+        self.write("...");
+        self.pat(&rest.arg);
+        self.type_ann(rest.type_ann.as_deref());
+    }
+}
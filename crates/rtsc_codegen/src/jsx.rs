@@ -0,0 +1,111 @@
+use rtsc_parser::ast::*;
+
+use crate::{expr::ASSIGN, Codegen};
+
+impl Codegen<'_> {
+    pub(crate) fn jsx_element(&mut self, el: &JsxElement) {
+        let opening = &el.opening;
+        self.raw("<");
+        self.jsx_element_name(&opening.name);
+        self.type_args(opening.type_args.as_ref());
+        for attr in &opening.attrs {
+            // attributes are always apart, even minified
+            self.raw(" ");
+            match attr {
+                JsxAttrOrSpread::Attr(attr) => self.jsx_attr(attr),
+                JsxAttrOrSpread::Spread(spread) => {
+                    self.comments_before(spread.span.start);
+                    self.raw("{...");
+                    self.expr_in(&spread.expr, ASSIGN);
+                    self.raw("}");
+                }
+            }
+        }
+        self.comments_before(opening.span.end.saturating_sub(1));
+        if opening.self_closing {
+            if !self.options.minify {
+                self.raw(" ");
+            }
+            self.raw("/>");
+            return;
+        }
+        self.raw(">");
+        self.jsx_children(&el.children);
+        if let Some(closing) = &el.closing {
+            self.raw("</");
+            self.jsx_element_name(&closing.name);
+            self.comments_before(closing.span.end.saturating_sub(1));
+            self.raw(">");
+        }
+    }
+
+    pub(crate) fn jsx_fragment(&mut self, fragment: &JsxFragment) {
+        self.raw("<>");
+        self.jsx_children(&fragment.children);
+        self.raw("</>");
+    }
+
+    fn jsx_children(&mut self, children: &[JsxChild]) {
+        for child in children {
+            match child {
+                // the text is printed as written, with its whitespace
+                JsxChild::Text(text) => self.raw(text.value),
+                JsxChild::ExprContainer(container) => self.jsx_expr_container(container),
+                JsxChild::Spread(spread) => {
+                    self.raw("{...");
+                    self.expr_in(&spread.expr, ASSIGN);
+                    self.comments_before(spread.span.end.saturating_sub(1));
+                    self.raw("}");
+                }
+                JsxChild::Element(el) => self.jsx_element(el),
+                JsxChild::Fragment(fragment) => self.jsx_fragment(fragment),
+            }
+        }
+    }
+
+    fn jsx_expr_container(&mut self, container: &JsxExprContainer) {
+        self.raw("{");
+        if let Some(expr) = &container.expr {
+            self.expr_in(expr, ASSIGN);
+        }
+        self.comments_before(container.span.end.saturating_sub(1));
+        self.raw("}");
+    }
+
+    fn jsx_attr(&mut self, attr: &JsxAttr) {
+        self.comments_before(attr.span.start);
+        match &attr.name {
+            JsxAttrName::Ident(id) => self.raw(&id.name),
+            JsxAttrName::Namespaced(name) => self.jsx_namespaced_name(name),
+        }
+        let Some(value) = &attr.value else {
+            return;
+        };
+        self.raw("=");
+        match value {
+            // JSX strings have no escapes, so they keep their quotes
+            JsxAttrValue::Str(s) => self.raw(s.raw),
+            JsxAttrValue::ExprContainer(container) => self.jsx_expr_container(container),
+            JsxAttrValue::Element(el) => self.jsx_element(el),
+            JsxAttrValue::Fragment(fragment) => self.jsx_fragment(fragment),
+        }
+    }
+
+    fn jsx_element_name(&mut self, name: &JsxElementName) {
+        match name {
+            JsxElementName::Ident(id) => self.raw(&id.name),
+            JsxElementName::Member(member) => {
+                self.jsx_element_name(&member.object);
+                self.raw(".");
+                self.raw(&member.prop.name);
+            }
+            JsxElementName::Namespaced(name) => self.jsx_namespaced_name(name),
+        }
+    }
+
+    fn jsx_namespaced_name(&mut self, name: &JsxNamespacedName) {
+        self.raw(&name.ns.name);
+        self.raw(":");
+        self.raw(&name.name.name);
+    }
+}
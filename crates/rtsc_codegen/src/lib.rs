@@ -0,0 +1,666 @@
+//! Prints a [`Program`] back to TypeScript or JavaScript source.
+//!
+//! The output parses back to the same tree, spans aside. Parentheses written in the
+//! source are [`ParenExpr`](rtsc_parser::ast::ParenExpr) and
+//! [`TsParenType`](rtsc_parser::ast::TsParenType) nodes and are printed where they are;
+//! the printer only adds the parentheses an expression built by hand needs to keep its
+//! shape.
+
+mod class;
+mod expr;
+mod jsx;
+mod stmt;
+mod typescript;
+
+use rtsc_parser::{
+    ast::Program,
+    comments::{Comment, CommentKind, Comments},
+    Span,
+};
+
+/// The quotes string literals are printed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuoteStyle {
+    /// The quotes each string was written with.
+    #[default]
+    Preserve,
+    Double,
+    Single,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodegenOptions {
+    /// Leaves out the whitespace and line breaks the program does not need.
+    pub minify: bool,
+    /// Strings in JSX attributes keep their quotes either way, since they cannot hold
+    /// escapes.
+    pub quotes: QuoteStyle,
+    /// Ends every statement with `;`. Otherwise a statement only gets one where the
+    /// next would run into it, like before a line starting with `(`. Class members and
+    /// the members of types always get theirs.
+    pub semicolons: bool,
+    /// Prints the comments given to [`Codegen::print_with_comments`].
+    pub comments: bool,
+}
+
+impl Default for CodegenOptions {
+    fn default() -> Self {
+        Self {
+            minify: false,
+            quotes: QuoteStyle::Preserve,
+            semicolons: true,
+            comments: true,
+        }
+    }
+}
+
+impl CodegenOptions {
+    /// The options for the smallest output: no whitespace, comments or semicolons that
+    /// can be left out.
+    pub fn minified() -> Self {
+        Self {
+            minify: true,
+            quotes: QuoteStyle::Preserve,
+            semicolons: false,
+            comments: false,
+        }
+    }
+}
+
+const INDENT: &str = "    ";
+
+pub struct Codegen<'a> {
+    options: CodegenOptions,
+    out: String,
+    indent: usize,
+    /// Nothing, not even the indentation, has been written on the current line.
+    line_start: bool,
+    /// A line comment was written, so the next token goes on a new line.
+    needs_newline: bool,
+    /// A `;` was left out at the end of a statement, to be written if the next token
+    /// needs it.
+    pending_semi: bool,
+    /// A line break was written since the `;` was left out.
+    broke_line: bool,
+    /// The last token was a regular expression, whose flags a word would run into.
+    after_regex: bool,
+    /// The last thing written was a block comment.
+    after_comment: bool,
+    /// An expression that could not be parsed was left out, so the statement it ends
+    /// keeps its `;` for the next line not to run into it.
+    left_out_expr: bool,
+    /// The comments to print in source order, and which of them were printed.
+    comments: Vec<&'a Comment>,
+    printed: Vec<bool>,
+    /// The first comment not yet passed by the printer.
+    next_comment: usize,
+    attached: Option<&'a Comments>,
+}
+
+impl<'a> Codegen<'a> {
+    pub fn new(options: CodegenOptions) -> Self {
+        Self {
+            options,
+            out: String::new(),
+            indent: 0,
+            line_start: true,
+            needs_newline: false,
+            pending_semi: false,
+            broke_line: false,
+            after_regex: false,
+            after_comment: false,
+            left_out_expr: false,
+            comments: vec![],
+            printed: vec![],
+            next_comment: 0,
+            attached: None,
+        }
+    }
+
+    pub fn print(mut self, program: &Program) -> String {
+        self.stmts(&program.body);
+        self.finish()
+    }
+
+    /// Prints the program with its comments, as returned by
+    /// [`parse_with_comments`](rtsc_parser::parse_with_comments). A comment is printed
+    /// before the first node after it, or at the end of its line if it trails a
+    /// statement or a member.
+    pub fn print_with_comments(mut self, program: &Program, comments: &'a Comments) -> String {
+        if self.options.comments {
+            self.comments = comments.iter().collect();
+            self.printed = vec![false; self.comments.len()];
+            self.attached = Some(comments);
+        }
+        self.print(program)
+    }
+
+    fn finish(mut self) -> String {
+        self.comments_before(usize::MAX);
+        // nothing follows the last statement
+        self.pending_semi = false;
+        if !self.options.minify && !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+        self.out
+    }
+
+    /// Writes a token, with a space in front if it would otherwise run into the last.
+    fn write(&mut self, text: &str) {
+        let Some(first) = text.chars().next() else {
+            return;
+        };
+        self.prepare(first);
+        if let Some(last) = self.out.chars().next_back() {
+            let space = (self.after_regex && is_word_char(first))
+                || (self.after_comment
+                    && !self.options.minify
+                    && !last.is_whitespace()
+                    && !matches!(first, ')' | ']' | '}' | ',' | ';'))
+                || needs_space(last, first);
+            if space {
+                self.out.push(' ');
+            }
+        }
+        self.out.push_str(text);
+        self.after_regex = false;
+        self.after_comment = false;
+    }
+
+    /// Writes text which must come out as it is, like the text of JSX or a template.
+    fn raw(&mut self, text: &str) {
+        let Some(first) = text.chars().next() else {
+            return;
+        };
+        self.prepare(first);
+        self.out.push_str(text);
+        self.after_regex = false;
+        self.after_comment = false;
+    }
+
+    /// Starts a token: breaks the line after a line comment, indents a new line and
+    /// decides on a left out `;`.
+    fn prepare(&mut self, first: char) {
+        if self.needs_newline {
+            self.needs_newline = false;
+            self.line_break();
+        }
+        if self.line_start {
+            self.line_start = false;
+            self.write_indent();
+        }
+        if self.pending_semi {
+            self.pending_semi = false;
+            // a line break ends a statement unless the next line would continue it, and a
+            // `;` there would be the statement itself
+            let continues = matches!(first, '(' | '[' | '`' | '+' | '-' | '/' | '<' | ';');
+            if first != '}' && (!self.broke_line || continues) {
+                self.out.push(';');
+            }
+        }
+    }
+
+    fn write_indent(&mut self) {
+        if !self.options.minify {
+            for _ in 0..self.indent {
+                self.out.push_str(INDENT);
+            }
+        }
+    }
+
+    fn line_break(&mut self) {
+        if !self.line_start {
+            self.out.push('\n');
+            self.line_start = true;
+            self.broke_line = true;
+        }
+    }
+
+    /// A line break where a readable program has one.
+    fn newline(&mut self) {
+        if !self.options.minify {
+            self.line_break();
+        }
+    }
+
+    fn space(&mut self) {
+        if !self.options.minify && !self.line_start && !self.out.ends_with(' ') {
+            self.out.push(' ');
+        }
+    }
+
+    /// Ends a statement.
+    fn semi(&mut self) {
+        if self.options.semicolons || std::mem::take(&mut self.left_out_expr) {
+            self.write(";");
+        } else {
+            self.pending_semi = true;
+            self.broke_line = false;
+        }
+    }
+
+    /// Prints the comments which end at or before `pos`.
+    fn comments_before(&mut self, pos: usize) {
+        while let Some(comment) = self.comments.get(self.next_comment) {
+            if comment.span.end > pos {
+                break;
+            }
+            self.next_comment += 1;
+            if !self.printed[self.next_comment - 1] {
+                self.comment(self.next_comment - 1);
+            }
+        }
+    }
+
+    /// Prints the comments at the end of the line of a node, before the line break.
+    fn trailing_comments(&mut self, node: Span) {
+        let Some(attached) = self.attached else {
+            return;
+        };
+        for comment in attached.trailing(node) {
+            let index = self
+                .comments
+                .partition_point(|c| c.span.start < comment.span.start);
+            if !self.printed[index] {
+                self.comment(index);
+            }
+        }
+    }
+
+    fn comment(&mut self, index: usize) {
+        self.printed[index] = true;
+        let comment = self.comments[index];
+        if self.needs_newline {
+            self.needs_newline = false;
+            self.line_break();
+        }
+        let own_line = self.line_start;
+        if self.line_start {
+            self.line_start = false;
+            self.write_indent();
+        } else if let Some(last) = self.out.chars().next_back() {
+            // `/` and `<` would run into the comment, as `//*` or `</`
+            let space = if self.options.minify {
+                matches!(last, '/' | '<')
+            } else {
+                !last.is_whitespace() && !matches!(last, '(' | '[')
+            };
+            if space {
+                self.out.push(' ');
+            }
+        }
+        match comment.kind {
+            CommentKind::Line => {
+                self.out.push_str("//");
+                self.out.push_str(&comment.text);
+                self.needs_newline = true;
+            }
+            CommentKind::Block => {
+                self.out.push_str("/*");
+                self.out.push_str(&comment.text);
+                self.out.push_str("*/");
+                if own_line && !self.options.minify {
+                    self.needs_newline = true;
+                }
+            }
+        }
+        self.after_regex = false;
+        self.after_comment = true;
+    }
+
+    /// Prints `items` separated by commas.
+    fn comma_list<T>(&mut self, items: &[T], mut print: impl FnMut(&mut Self, &T)) {
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                self.write(",");
+                self.space();
+            }
+            print(self, item);
+        }
+    }
+
+    /// Prints the elements of an array or an array pattern, where `None` is a hole.
+    fn elements<T>(&mut self, elems: &[Option<T>], mut print: impl FnMut(&mut Self, &T)) {
+        for (i, elem) in elems.iter().enumerate() {
+            let last = i + 1 == elems.len();
+            if let Some(elem) = elem {
+                print(self, elem);
+            }
+            // a hole at the end needs a comma of its own, as in `[a, ,]`
+            if !last || elem.is_none() {
+                self.write(",");
+                if !last {
+                    self.space();
+                }
+            }
+        }
+    }
+}
+
+/// Prints a program with the default options.
+pub fn print(program: &Program) -> String {
+    Codegen::new(CodegenOptions::default()).print(program)
+}
+
+/// Prints a program with its comments and the default options.
+pub fn print_with_comments(program: &Program, comments: &Comments) -> String {
+    Codegen::new(CodegenOptions::default()).print_with_comments(program, comments)
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '$' | '\\' | '\u{200c}' | '\u{200d}')
+}
+
+/// Whether two tokens written next to each other would be read as other tokens, like
+/// `a - -b` as `a--b` or `a! == b` as `a!==b`.
+fn needs_space(last: char, first: char) -> bool {
+    (is_word_char(last) && is_word_char(first))
+        || matches!(
+            (last, first),
+            ('+', '+')
+                | ('-', '-')
+                | ('/', '/')
+                | ('/', '*')
+                | ('<', '!')
+                | ('<', '<')
+                | ('<', '/')
+                | ('-', '>')
+                | ('>', '=')
+                | ('!', '=')
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use rtsc_parser::{
+        parse, parse_with_comments, read_source,
+        visit::{VisitMut, VisitMutWith},
+        Allocator, Atom, Parser, ParserOptions, SourceType, Syntax,
+    };
+
+    use super::*;
+
+    fn print_with(source: &str, options: CodegenOptions) -> String {
+        let allocator = Allocator::new();
+        let (program, comments, errors) = parse_with_comments(&allocator, source);
+        assert!(errors.is_empty(), "{errors:?}");
+        Codegen::new(options).print_with_comments(&program, &comments)
+    }
+
+    #[test]
+    fn prints_readable_code() {
+        let source = "import {a,type B as C} from 'mod';\nexport default class Foo<T extends object = {}> extends Bar<T> implements Baz {\n#x?: number = 1;\nconstructor(private readonly y: string) {super()}\nget z(): T { return this.#x as any }\nstatic async *gen() { yield* [1, , 2] }\n}\nif (a) b(); else if (c) { d() } else e();\nfor (let i = 0, j; i < 10; i++) continue;\nlabel: while (!done) { switch (x) { case 1: break label; default: } }\ntype U<K> = { [P in keyof K]?: K[P] } | [a: string, ...rest: number[]];\nconst f = async <T,>(x: T): Promise<T> => ({ x }), g = (a = 1, {b, ...c}: any) => a + b * c;\n";
+        assert_eq!(
+            print_with(source, CodegenOptions::default()),
+            r#"import { a, type B as C } from 'mod';
+export default class Foo<T extends object = {}> extends Bar<T> implements Baz {
+    #x?: number = 1;
+    constructor(private readonly y: string) {
+        super();
+    }
+    get z(): T {
+        return this.#x as any;
+    }
+    static async *gen() {
+        yield* [1, , 2];
+    }
+}
+if (a)
+    b();
+else if (c) {
+    d();
+} else
+    e();
+for (let i = 0, j; i < 10; i++)
+    continue;
+label: while (!done) {
+    switch (x) {
+        case 1:
+            break label;
+        default:
+    }
+}
+type U<K> = { [P in keyof K]?: K[P] } | [a: string, ...rest: number[]];
+const f = async <T,>(x: T): Promise<T> => ({ x }), g = (a = 1, { b, ...c }: any) => a + b * c;
+"#
+        );
+    }
+
+    #[test]
+    fn minifies() {
+        let source = "let a = b - -c + +d, e = /re/g in f;\nif (a) { g(); }\nvar h = typeof a === 'string' ? 1..toString() : 2 .toString();\n";
+        assert_eq!(
+            print_with(source, CodegenOptions::minified()),
+            "let a=b- -c+ +d,e=/re/g in f;if(a){g()}var h=typeof a==='string'?1..toString():2 .toString()"
+        );
+    }
+
+    #[test]
+    fn leaves_out_semicolons() {
+        let options = CodegenOptions {
+            semicolons: false,
+            ..CodegenOptions::default()
+        };
+        assert_eq!(
+            print_with(
+                "a = b;\n(c || d).e();\nf();\n[g] = h;\nclass I { j = 1 }\n",
+                options
+            ),
+            "a = b\n;(c || d).e()\nf()\n;[g] = h\nclass I {\n    j = 1;\n}\n"
+        );
+    }
+
+    #[test]
+    fn prints_recovered_programs() {
+        let print_recovered = |source: &str, options: CodegenOptions| {
+            let allocator = Allocator::new();
+            let (program, comments, errors) = parse_with_comments(&allocator, source);
+            assert!(!errors.is_empty());
+            Codegen::new(options).print_with_comments(&program, &comments)
+        };
+        let options = CodegenOptions {
+            semicolons: false,
+            ..CodegenOptions::default()
+        };
+        assert_eq!(print_recovered("a = \"b\\", options.clone()), "a = \"b\"\n");
+        assert_eq!(print_recovered("a = ;\nb()", options), "a = ;\nb()\n");
+    }
+
+    #[test]
+    fn requotes_strings() {
+        let source = r#"a("it's", 'say "hi"', '\'', "\u0041");"#;
+        let with_quotes = |quotes| {
+            let options = CodegenOptions {
+                quotes,
+                ..CodegenOptions::default()
+            };
+            print_with(source, options)
+        };
+        assert_eq!(
+            with_quotes(QuoteStyle::Double),
+            "a(\"it's\", \"say \\\"hi\\\"\", \"'\", \"\\u0041\");\n"
+        );
+        assert_eq!(
+            with_quotes(QuoteStyle::Single),
+            "a('it\\'s', 'say \"hi\"', '\\'', '\\u0041');\n"
+        );
+    }
+
+    #[test]
+    fn keeps_comments() {
+        let source = "/** Docs. */\nfunction f(/* no params */) {\n  // inside\n  return 1; // one\n}\nlet a = [/* empty */];\n";
+        assert_eq!(
+            print_with(source, CodegenOptions::default()),
+            "/** Docs. */\nfunction f(/* no params */) {\n    // inside\n    return 1; // one\n}\nlet a = [/* empty */];\n"
+        );
+        let options = CodegenOptions {
+            comments: false,
+            ..CodegenOptions::default()
+        };
+        assert_eq!(
+            print_with(source, options),
+            "function f() {\n    return 1;\n}\nlet a = [];\n"
+        );
+    }
+
+    #[test]
+    fn parenthesizes_built_trees() {
+        use rtsc_parser::{
+            allocator::{Box, Vec},
+            ast::*,
+        };
+
+        let allocator = Allocator::new();
+        // `(a + b) * c`, made from the expressions of a parsed program
+        let product = || {
+            let (program, _) = parse(&allocator, "a + b;\nc;");
+            let mut exprs = program.body.into_iter().map(|stmt| match stmt {
+                Stmt::Expr(stmt) => stmt.expr,
+                stmt => panic!("expected an expression statement, got {stmt:?}"),
+            });
+            Expr::Binary(BinaryExpr {
+                op: BinaryOperator::Mul,
+                left: exprs.next().unwrap(),
+                right: exprs.next().unwrap(),
+                span: Span::new(0, 0),
+            })
+        };
+        // and a member of the product
+        let member = Expr::Member(MemberExpr {
+            object: Box::new_in(product(), &allocator),
+            prop: MemberProp::Ident(Ident {
+                name: Atom::new_in("d", &allocator),
+                span: Span::new(0, 0),
+            }),
+            optional: false,
+            span: Span::new(0, 0),
+        });
+        let object = Expr::Object(ObjectLit {
+            props: Vec::new_in(&allocator),
+            span: Span::new(0, 0),
+        });
+        let body = [product(), member, object].into_iter().map(|expr| {
+            Stmt::Expr(ExprStmt {
+                expr: Box::new_in(expr, &allocator),
+                span: Span::new(0, 0),
+            })
+        });
+        let program = Program {
+            body: Vec::from_iter_in(body, &allocator),
+            span: Span::new(0, 0),
+        };
+        assert_eq!(print(&program), "(a + b) * c;\n((a + b) * c).d;\n({});\n");
+    }
+
+    /// Empties every span, so that programs parsed from different text compare equal.
+    struct ClearSpans;
+
+    impl VisitMut<'_> for ClearSpans {
+        fn visit_mut_span(&mut self, span: &mut Span) {
+            *span = Span::new(0, 0);
+        }
+    }
+
+    fn without_spans(mut program: Program<'_>) -> Program<'_> {
+        program.visit_mut_with(&mut ClearSpans);
+        program
+    }
+
+    fn fixtures(dir: &Path, files: &mut Vec<std::path::PathBuf>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                fixtures(&path, files);
+            } else if matches!(
+                path.extension().and_then(|ext| ext.to_str()),
+                Some("ts" | "tsx" | "js" | "jsx")
+            ) {
+                files.push(path);
+            }
+        }
+    }
+
+    /// Prints every fixture under `xtask/tests` and checks that the output parses to the
+    /// same program with as many comments. A program recovered from errors prints
+    /// without the parts that could not be parsed, so its output must parse with no more
+    /// errors and round-trip in turn.
+    fn round_trips_fixtures(options: CodegenOptions) {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../xtask/tests");
+        let mut files = vec![];
+        fixtures(&root, &mut files);
+        files.sort();
+        assert!(
+            files.len() > 6000,
+            "missing fixtures under {}",
+            root.display()
+        );
+
+        let mut failures = vec![];
+        for path in &files {
+            let source = read_source(path).unwrap();
+            let syntax = match path.extension().and_then(|ext| ext.to_str()) {
+                Some("tsx") => Syntax::Tsx,
+                Some("js") => Syntax::Javascript,
+                Some("jsx") => Syntax::Jsx,
+                _ => Syntax::Typescript,
+            };
+            let parser_options = ParserOptions {
+                source_type: SourceType::Script,
+                syntax,
+                ..ParserOptions::default()
+            };
+            let allocator = Allocator::new();
+            let reprint = |program: &Program, comments: &Comments| {
+                let printed = Codegen::new(options.clone()).print_with_comments(program, comments);
+                let printed = allocator.alloc_str(&printed);
+                Parser::with_options(&allocator, printed, parser_options).parse_with_comments()
+            };
+            let round_trips = |program: Program, comments: &Comments, errors: usize| {
+                let (reparsed, reparsed_comments, reparsed_errors) = reprint(&program, comments);
+                reparsed_errors.len() == errors
+                    && (!options.comments || reparsed_comments.len() == comments.len())
+                    && without_spans(reparsed) == without_spans(program)
+            };
+
+            let (program, comments, errors) =
+                Parser::with_options(&allocator, &source, parser_options).parse_with_comments();
+            let ok = if errors.is_empty() {
+                round_trips(program, &comments, 0)
+            } else {
+                let (reparsed, reparsed_comments, reparsed_errors) = reprint(&program, &comments);
+                reparsed_errors.len() <= errors.len()
+                    && round_trips(reparsed, &reparsed_comments, reparsed_errors.len())
+            };
+            if !ok {
+                failures.push(path.display().to_string());
+            }
+        }
+        assert!(
+            failures.is_empty(),
+            "{} of {} fixtures did not round-trip:\n{}",
+            failures.len(),
+            files.len(),
+            failures.join("\n")
+        );
+    }
+
+    #[test]
+    fn round_trips() {
+        round_trips_fixtures(CodegenOptions::default());
+    }
+
+    #[test]
+    fn round_trips_minified() {
+        round_trips_fixtures(CodegenOptions::minified());
+    }
+
+    #[test]
+    fn round_trips_without_semicolons() {
+        round_trips_fixtures(CodegenOptions {
+            semicolons: false,
+            ..CodegenOptions::default()
+        });
+    }
+}
@@ -0,0 +1,607 @@
+use rtsc_parser::ast::*;
+
+use crate::{
+    expr::{starts_like_declaration, starts_with_function, ASSIGN},
+    Codegen,
+};
+
+impl Codegen<'_> {
+    pub(crate) fn stmts(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            self.newline();
+            self.stmt(stmt);
+        }
+    }
+
+    pub(crate) fn block(&mut self, block: &BlockStmt) {
+        self.block_of(&block.stmts, block.span);
+    }
+
+    /// Prints `{`, the statements on lines of their own and `}`.
+    fn block_of(&mut self, stmts: &[Stmt], span: rtsc_parser::Span) {
+        self.comments_before(span.start);
+        self.write("{");
+        self.indent += 1;
+        self.stmts(stmts);
+        self.comments_before(span.end.saturating_sub(1));
+        self.indent -= 1;
+        if !stmts.is_empty() || self.needs_newline {
+            self.newline();
+        }
+        self.write("}");
+    }
+
+    pub(crate) fn stmt(&mut self, stmt: &Stmt) {
+        self.comments_before(stmt.span().start);
+        match stmt {
+            Stmt::Block(block) => self.block(block),
+            Stmt::Empty(_) => self.write(";"),
+            Stmt::Debugger(_) => {
+                self.write("debugger");
+                self.semi();
+            }
+            Stmt::Expr(s) => {
+                if starts_like_declaration(&s.expr) {
+                    self.parenthesized(&s.expr);
+                } else {
+                    self.expr(&s.expr);
+                }
+                self.semi();
+            }
+            Stmt::Var(decl) => {
+                self.var_decl(decl);
+                self.semi();
+            }
+            Stmt::If(s) => {
+                self.write("if");
+                self.space();
+                self.write("(");
+                self.expr(&s.test);
+                self.write(")");
+                self.body(&s.cons);
+                if let Some(alt) = &s.alt {
+                    if matches!(*s.cons, Stmt::Block(_)) {
+                        self.space();
+                    } else {
+                        self.newline();
+                    }
+                    self.write("else");
+                    if matches!(**alt, Stmt::If(_)) {
+                        self.space();
+                        self.stmt(alt);
+                    } else {
+                        self.body(alt);
+                    }
+                }
+            }
+            Stmt::For(s) => {
+                self.write("for");
+                self.space();
+                self.write("(");
+                match &s.init {
+                    Some(ForInit::Var(decl)) => self.var_decl(decl),
+                    Some(ForInit::Expr(expr)) => self.expr(expr),
+                    None => {}
+                }
+                self.write(";");
+                if let Some(test) = &s.test {
+                    self.space();
+                    self.expr(test);
+                }
+                self.write(";");
+                if let Some(update) = &s.update {
+                    self.space();
+                    self.expr(update);
+                }
+                self.write(")");
+                self.body(&s.body);
+            }
+            Stmt::ForIn(s) => {
+                self.write("for");
+                self.space();
+                self.write("(");
+                self.for_head(&s.left);
+                self.write("in");
+                self.space();
+                self.expr(&s.right);
+                self.write(")");
+                self.body(&s.body);
+            }
+            Stmt::ForOf(s) => {
+                self.write("for");
+                if s.is_await {
+                    self.write("await");
+                }
+                self.space();
+                self.write("(");
+                self.for_head(&s.left);
+                self.write("of");
+                self.space();
+                self.expr_in(&s.right, ASSIGN);
+                self.write(")");
+                self.body(&s.body);
+            }
+            Stmt::While(s) => {
+                self.write("while");
+                self.space();
+                self.write("(");
+                self.expr(&s.test);
+                self.write(")");
+                self.body(&s.body);
+            }
+            Stmt::DoWhile(s) => {
+                self.write("do");
+                self.body(&s.body);
+                if matches!(*s.body, Stmt::Block(_)) {
+                    self.space();
+                } else {
+                    self.newline();
+                }
+                self.write("while");
+                self.space();
+                self.write("(");
+                self.expr(&s.test);
+                self.write(")");
+                self.semi();
+            }
+            Stmt::Return(s) => {
+                self.write("return");
+                if let Some(arg) = &s.arg {
+                    self.space();
+                    self.expr(arg);
+                }
+                self.semi();
+            }
+            Stmt::Break(s) => {
+                self.write("break");
+                if let Some(label) = &s.label {
+                    self.space();
+                    self.ident(label);
+                }
+                self.semi();
+            }
+            Stmt::Continue(s) => {
+                self.write("continue");
+                if let Some(label) = &s.label {
+                    self.space();
+                    self.ident(label);
+                }
+                self.semi();
+            }
+            Stmt::Throw(s) => {
+                self.write("throw");
+                self.space();
+                self.expr(&s.arg);
+                self.semi();
+            }
+            Stmt::Try(s) => {
+                self.write("try");
+                self.space();
+                self.block(&s.block);
+                if let Some(handler) = &s.handler {
+                    self.space();
+                    self.comments_before(handler.span.start);
+                    self.write("catch");
+                    self.space();
+                    if let Some(param) = &handler.param {
+                        self.write("(");
+                        self.pat(param);
+                        self.write(")");
+                        self.space();
+                    }
+                    self.block(&handler.body);
+                }
+                if let Some(finalizer) = &s.finalizer {
+                    self.space();
+                    self.write("finally");
+                    self.space();
+                    self.block(finalizer);
+                }
+            }
+            Stmt::Switch(s) => self.switch(s),
+            Stmt::Labeled(s) => {
+                self.ident(&s.label);
+                self.write(":");
+                self.space();
+                self.stmt(&s.body);
+            }
+            Stmt::With(s) => {
+                self.write("with");
+                self.space();
+                self.write("(");
+                self.expr(&s.object);
+                self.write(")");
+                self.body(&s.body);
+            }
+            Stmt::Fn(decl) => self.fn_decl(decl),
+            Stmt::Class(decl) => {
+                self.decorators(&decl.class.decorators, true);
+                self.class_decl(decl);
+            }
+            Stmt::Interface(decl) => self.interface(decl),
+            Stmt::TypeAlias(decl) => {
+                self.declare(decl.declare);
+                self.write("type");
+                self.space();
+                self.ident(&decl.id);
+                self.type_params(decl.type_params.as_ref());
+                self.space();
+                self.write("=");
+                self.space();
+                self.ty(&decl.ty);
+                self.semi();
+            }
+            Stmt::Enum(decl) => self.enum_decl(decl),
+            Stmt::Module(decl) => self.module_decl(decl),
+            Stmt::Import(decl) => self.import(decl),
+            Stmt::ImportEquals(decl) => {
+                if decl.is_export {
+                    self.write("export");
+                    self.space();
+                }
+                self.write("import");
+                self.space();
+                if decl.is_type_only {
+                    self.write("type");
+                    self.space();
+                }
+                self.ident(&decl.id);
+                self.space();
+                self.write("=");
+                self.space();
+                match &decl.module_ref {
+                    TsModuleRef::Entity(name) => self.entity_name(name),
+                    TsModuleRef::External(src) => {
+                        self.write("require(");
+                        self.str(src);
+                        self.write(")");
+                    }
+                }
+                self.semi();
+            }
+            Stmt::ExportDecl(decl) => {
+                // decorators go in front of `export`
+                if let Stmt::Class(class) = &*decl.decl {
+                    self.decorators(&class.class.decorators, true);
+                }
+                self.write("export");
+                self.space();
+                match &*decl.decl {
+                    Stmt::Class(class) => self.class_decl(class),
+                    decl => self.stmt(decl),
+                }
+            }
+            Stmt::ExportNamed(decl) => self.export_named(decl),
+            Stmt::ExportAll(decl) => {
+                self.write("export");
+                self.space();
+                if decl.type_only {
+                    self.write("type");
+                    self.space();
+                }
+                self.write("*");
+                self.space();
+                self.write("from");
+                self.space();
+                self.module_src(&decl.src, decl.with.as_ref());
+                self.semi();
+            }
+            Stmt::ExportDefault(decl) => self.export_default(decl),
+            Stmt::ExportAssign(decl) => {
+                self.write("export");
+                self.space();
+                self.write("=");
+                self.space();
+                self.expr(&decl.expr);
+                self.semi();
+            }
+            Stmt::ExportAsNamespace(decl) => {
+                self.write("export");
+                self.space();
+                self.write("as");
+                self.space();
+                self.write("namespace");
+                self.space();
+                self.ident(&decl.id);
+                self.semi();
+            }
+            Stmt::Invalid(_) => {}
+        }
+        self.trailing_comments(stmt.span());
+    }
+
+    /// The statement of an `if`, a loop or a label: a block after a space, anything
+    /// else on a line of its own.
+    fn body(&mut self, body: &Stmt) {
+        if let Stmt::Block(block) = body {
+            self.space();
+            self.block(block);
+        } else {
+            self.indent += 1;
+            self.newline();
+            self.stmt(body);
+            self.indent -= 1;
+        }
+    }
+
+    pub(crate) fn declare(&mut self, declare: bool) {
+        if declare {
+            self.write("declare");
+            self.space();
+        }
+    }
+
+    /// A variable declaration without its `;`, which a `for` does not have.
+    fn var_decl(&mut self, decl: &VarDecl) {
+        self.declare(decl.declare);
+        self.write(match decl.kind {
+            VarKind::Var => "var",
+            VarKind::Let => "let",
+            VarKind::Const => "const",
+        });
+        self.space();
+        self.comma_list(&decl.decls, |p, declarator| {
+            p.comments_before(declarator.span.start);
+            match &declarator.name {
+                Pat::Ident(id) => p.binding_ident(id, declarator.definite),
+                name => p.pat(name),
+            }
+            if let Some(init) = &declarator.init {
+                p.space();
+                p.write("=");
+                p.space();
+                p.expr_in(init, ASSIGN);
+            }
+        });
+    }
+
+    fn for_head(&mut self, head: &ForHead) {
+        match head {
+            ForHead::Var(decl) => self.var_decl(decl),
+            ForHead::Pat(pat) => self.pat(pat),
+        }
+        self.space();
+    }
+
+    fn switch(&mut self, s: &SwitchStmt) {
+        self.write("switch");
+        self.space();
+        self.write("(");
+        self.expr(&s.discriminant);
+        self.write(")");
+        self.space();
+        self.write("{");
+        self.indent += 1;
+        for case in &s.cases {
+            self.newline();
+            self.comments_before(case.span.start);
+            match &case.test {
+                Some(test) => {
+                    self.write("case");
+                    self.space();
+                    self.expr(test);
+                }
+                None => self.write("default"),
+            }
+            self.write(":");
+            self.indent += 1;
+            self.stmts(&case.cons);
+            self.indent -= 1;
+        }
+        self.comments_before(s.span.end.saturating_sub(1));
+        self.indent -= 1;
+        if !s.cases.is_empty() || self.needs_newline {
+            self.newline();
+        }
+        self.write("}");
+    }
+
+    fn fn_decl(&mut self, decl: &FnDecl) {
+        self.declare(decl.declare);
+        self.function(Some(&decl.id), &decl.function);
+        if decl.function.body.is_none() {
+            self.semi();
+        }
+    }
+
+    fn class_decl(&mut self, decl: &ClassDecl) {
+        self.declare(decl.declare);
+        self.class(Some(&decl.id), &decl.class);
+    }
+
+    fn module_decl(&mut self, decl: &TsModuleDecl) {
+        self.declare(decl.declare);
+        match &decl.id {
+            // `global` is its own keyword
+            TsModuleName::Ident(id) if decl.global => self.ident(id),
+            TsModuleName::Ident(id) => {
+                self.write("namespace");
+                self.space();
+                self.ident(id);
+            }
+            TsModuleName::Str(s) => {
+                self.write("module");
+                self.space();
+                self.str(s);
+            }
+        }
+        let mut body = decl.body.as_ref();
+        while let Some(TsModuleBody::Namespace(inner)) = body {
+            self.write(".");
+            if let TsModuleName::Ident(id) = &inner.id {
+                self.ident(id);
+            }
+            body = inner.body.as_ref();
+        }
+        match body {
+            Some(TsModuleBody::Block(block)) => {
+                self.space();
+                self.block_of(&block.body, block.span);
+            }
+            _ => self.semi(),
+        }
+    }
+
+    fn import(&mut self, decl: &ImportDecl) {
+        self.write("import");
+        self.space();
+        if decl.type_only {
+            self.write("type");
+            self.space();
+        }
+        if !decl.specifiers.is_empty() || decl.type_only {
+            let mut named = vec![];
+            let mut first = true;
+            for specifier in &decl.specifiers {
+                match specifier {
+                    ImportSpecifier::Default(local) => {
+                        self.ident(local);
+                        first = false;
+                    }
+                    ImportSpecifier::Namespace(local) => {
+                        if !first {
+                            self.write(",");
+                            self.space();
+                        }
+                        self.write("*");
+                        self.space();
+                        self.write("as");
+                        self.space();
+                        self.ident(local);
+                        first = false;
+                    }
+                    ImportSpecifier::Named(specifier) => named.push(specifier),
+                }
+            }
+            if !named.is_empty() || first {
+                if !first {
+                    self.write(",");
+                    self.space();
+                }
+                self.write("{");
+                if !named.is_empty() {
+                    self.space();
+                }
+                self.comma_list(&named, |p, specifier| {
+                    p.comments_before(specifier.span.start);
+                    if specifier.is_type_only {
+                        p.write("type");
+                        p.space();
+                    }
+                    if let Some(imported) = &specifier.imported {
+                        p.module_export_name(imported);
+                        p.space();
+                        p.write("as");
+                        p.space();
+                    }
+                    p.ident(&specifier.local);
+                });
+                if !named.is_empty() {
+                    self.space();
+                }
+                self.write("}");
+            }
+            self.space();
+            self.write("from");
+            self.space();
+        }
+        self.module_src(&decl.src, decl.with.as_ref());
+        self.semi();
+    }
+
+    fn export_named(&mut self, decl: &ExportNamed) {
+        self.write("export");
+        self.space();
+        if decl.type_only {
+            self.write("type");
+            self.space();
+        }
+        if let [ExportSpecifier::Namespace(name)] = &decl.specifiers[..] {
+            self.write("*");
+            self.space();
+            self.write("as");
+            self.space();
+            self.module_export_name(name);
+        } else {
+            self.write("{");
+            if !decl.specifiers.is_empty() {
+                self.space();
+            }
+            self.comma_list(&decl.specifiers, |p, specifier| match specifier {
+                ExportSpecifier::Named(specifier) => {
+                    p.comments_before(specifier.span.start);
+                    if specifier.is_type_only {
+                        p.write("type");
+                        p.space();
+                    }
+                    p.module_export_name(&specifier.orig);
+                    if let Some(exported) = &specifier.exported {
+                        p.space();
+                        p.write("as");
+                        p.space();
+                        p.module_export_name(exported);
+                    }
+                }
+                ExportSpecifier::Namespace(name) => p.module_export_name(name),
+            });
+            if !decl.specifiers.is_empty() {
+                self.space();
+            }
+            self.write("}");
+        }
+        if let Some(src) = &decl.src {
+            self.space();
+            self.write("from");
+            self.space();
+            self.module_src(src, decl.with.as_ref());
+        }
+        self.semi();
+    }
+
+    fn export_default(&mut self, decl: &ExportDefault) {
+        if let DefaultDecl::Class(class) = &decl.decl {
+            self.decorators(&class.class.decorators, true);
+        }
+        self.write("export");
+        self.space();
+        self.write("default");
+        self.space();
+        match &decl.decl {
+            DefaultDecl::Fn(f) => {
+                self.function(f.id.as_ref(), &f.function);
+                if f.function.body.is_none() {
+                    self.semi();
+                }
+            }
+            DefaultDecl::Class(class) => self.class(class.id.as_ref(), &class.class),
+            DefaultDecl::Interface(decl) => self.interface(decl),
+            DefaultDecl::Expr(expr) => {
+                // a function or a class here would be a declaration
+                if starts_with_function(expr) {
+                    self.parenthesized(expr);
+                } else {
+                    self.expr_in(expr, ASSIGN);
+                }
+                self.semi();
+            }
+        }
+    }
+
+    /// The module an import or export is from, with its import attributes.
+    fn module_src(&mut self, src: &Str, with: Option<&ObjectLit>) {
+        self.str(src);
+        if let Some(with) = with {
+            self.space();
+            self.write("with");
+            self.space();
+            self.object_lit(with);
+        }
+    }
+
+    fn module_export_name(&mut self, name: &ModuleExportName) {
+        match name {
+            ModuleExportName::Ident(id) => self.ident(id),
+            ModuleExportName::Str(s) => self.str(s),
+        }
+    }
+}
@@ -0,0 +1,504 @@
+use rtsc_parser::ast::*;
+
+use crate::{expr::ASSIGN, Codegen};
+
+fn keyword(kind: TsKeywordKind) -> &'static str {
+    match kind {
+        TsKeywordKind::Any => "any",
+        TsKeywordKind::Unknown => "unknown",
+        TsKeywordKind::Number => "number",
+        TsKeywordKind::Object => "object",
+        TsKeywordKind::Boolean => "boolean",
+        TsKeywordKind::BigInt => "bigint",
+        TsKeywordKind::String => "string",
+        TsKeywordKind::Symbol => "symbol",
+        TsKeywordKind::Void => "void",
+        TsKeywordKind::Undefined => "undefined",
+        TsKeywordKind::Null => "null",
+        TsKeywordKind::Never => "never",
+        TsKeywordKind::Intrinsic => "intrinsic",
+    }
+}
+
+impl Codegen<'_> {
+    pub(crate) fn ty(&mut self, ty: &TsType) {
+        self.comments_before(ty.span().start);
+        match ty {
+            TsType::Keyword(t) => self.write(keyword(t.kind)),
+            TsType::This(_) => self.write("this"),
+            TsType::Ref(t) => {
+                self.entity_name(&t.name);
+                self.type_args(t.type_args.as_ref());
+            }
+            TsType::Lit(t) => match &t.lit {
+                TsLit::Str(s) => self.str(s),
+                TsLit::Num(num) => self.write(num.raw),
+                TsLit::BigInt(big) => self.write(big.raw),
+                TsLit::Bool(b) => self.write(if b.value { "true" } else { "false" }),
+                TsLit::Tpl(tpl) => {
+                    self.raw("`");
+                    for (i, quasi) in tpl.quasis.iter().enumerate() {
+                        self.raw(quasi.raw);
+                        if let Some(ty) = tpl.types.get(i) {
+                            self.raw("${");
+                            self.ty(ty);
+                            self.raw("}");
+                        }
+                    }
+                    self.raw("`");
+                }
+            },
+            TsType::Array(t) => {
+                self.ty(&t.elem);
+                self.write("[]");
+            }
+            TsType::Tuple(t) => {
+                self.write("[");
+                self.comma_list(&t.elems, |p, elem| p.tuple_element(elem));
+                self.write("]");
+            }
+            TsType::Union(t) => self.type_list(&t.types, "|"),
+            TsType::Intersection(t) => self.type_list(&t.types, "&"),
+            TsType::Fn(t) => {
+                self.type_params(t.type_params.as_ref());
+                self.fn_type_rest(&t.params, &t.return_type);
+            }
+            TsType::Constructor(t) => {
+                if t.is_abstract {
+                    self.write("abstract");
+                    self.space();
+                }
+                self.write("new");
+                self.space();
+                self.type_params(t.type_params.as_ref());
+                self.fn_type_rest(&t.params, &t.return_type);
+            }
+            TsType::TypeLit(t) => {
+                self.write("{");
+                if !t.members.is_empty() {
+                    self.space();
+                    for (i, member) in t.members.iter().enumerate() {
+                        if i > 0 {
+                            self.write(";");
+                            self.space();
+                        }
+                        self.type_element(member);
+                    }
+                    self.space();
+                }
+                self.comments_before(t.span.end.saturating_sub(1));
+                self.write("}");
+            }
+            TsType::Paren(t) => {
+                self.write("(");
+                self.ty(&t.ty);
+                self.write(")");
+            }
+            TsType::TypeOperator(t) => {
+                self.write(match t.op {
+                    TsTypeOperatorOp::KeyOf => "keyof",
+                    TsTypeOperatorOp::Unique => "unique",
+                    TsTypeOperatorOp::ReadOnly => "readonly",
+                });
+                self.space();
+                self.ty(&t.ty);
+            }
+            TsType::IndexedAccess(t) => {
+                self.ty(&t.obj);
+                self.write("[");
+                self.ty(&t.index);
+                self.write("]");
+            }
+            TsType::Conditional(t) => {
+                self.ty(&t.check);
+                self.space();
+                self.write("extends");
+                self.space();
+                self.ty(&t.extends);
+                self.space();
+                self.write("?");
+                self.space();
+                self.ty(&t.true_type);
+                self.space();
+                self.write(":");
+                self.space();
+                self.ty(&t.false_type);
+            }
+            TsType::Infer(t) => {
+                self.write("infer");
+                self.space();
+                self.type_param(&t.param);
+            }
+            TsType::Mapped(t) => self.mapped(t),
+            TsType::Query(t) => {
+                self.write("typeof");
+                self.space();
+                match &t.expr {
+                    TsTypeQueryExpr::Entity(name) => self.entity_name(name),
+                    TsTypeQueryExpr::Import(import) => self.import_type(import),
+                }
+                self.type_args(t.type_args.as_ref());
+            }
+            TsType::Import(t) => self.import_type(t),
+            TsType::Predicate(t) => {
+                if t.asserts {
+                    self.write("asserts");
+                    self.space();
+                }
+                match &t.param {
+                    TsPredicateParam::Ident(id) => self.ident(id),
+                    TsPredicateParam::This(_) => self.write("this"),
+                }
+                if let Some(ty) = &t.ty {
+                    self.space();
+                    self.write("is");
+                    self.space();
+                    self.ty(ty);
+                }
+            }
+        }
+    }
+
+    /// The types of a union or an intersection. One type on its own keeps the `|` or
+    /// `&` in front it was written with.
+    fn type_list(&mut self, types: &[TsType], op: &str) {
+        if types.len() == 1 {
+            self.write(op);
+            self.space();
+        }
+        for (i, ty) in types.iter().enumerate() {
+            if i > 0 {
+                self.space();
+                self.write(op);
+                self.space();
+            }
+            self.ty(ty);
+        }
+    }
+
+    fn fn_type_rest(&mut self, params: &[Pat], return_type: &TsType) {
+        self.write("(");
+        self.comma_list(params, |p, param| p.pat(param));
+        self.write(")");
+        self.space();
+        self.write("=>");
+        self.space();
+        self.ty(return_type);
+    }
+
+    fn tuple_element(&mut self, elem: &TsTupleElement) {
+        self.comments_before(elem.span.start);
+        if elem.rest {
+            self.write("...");
+        }
+        match &elem.label {
+            Some(label) => {
+                self.ident(label);
+                if elem.optional {
+                    self.write("?");
+                }
+                self.write(":");
+                self.space();
+                self.ty(&elem.ty);
+            }
+            None => {
+                self.ty(&elem.ty);
+                if elem.optional {
+                    self.write("?");
+                }
+            }
+        }
+    }
+
+    fn mapped(&mut self, t: &TsMappedType) {
+        let modifier = |p: &mut Self, modifier: Option<TruePlusMinus>| match modifier {
+            Some(TruePlusMinus::Plus) => p.write("+"),
+            Some(TruePlusMinus::Minus) => p.write("-"),
+            _ => {}
+        };
+        self.write("{");
+        self.space();
+        if t.readonly.is_some() {
+            modifier(self, t.readonly);
+            self.write("readonly");
+            self.space();
+        }
+        self.write("[");
+        self.ident(&t.type_param.name);
+        self.space();
+        self.write("in");
+        self.space();
+        if let Some(constraint) = &t.type_param.constraint {
+            self.ty(constraint);
+        }
+        if let Some(name_type) = &t.name_type {
+            self.space();
+            self.write("as");
+            self.space();
+            self.ty(name_type);
+        }
+        self.write("]");
+        if t.optional.is_some() {
+            modifier(self, t.optional);
+            self.write("?");
+        }
+        if let Some(ty) = &t.ty {
+            self.write(":");
+            self.space();
+            self.ty(ty);
+        }
+        self.space();
+        self.write("}");
+    }
+
+    fn import_type(&mut self, t: &TsImportType) {
+        self.write("import(");
+        self.str(&t.arg);
+        self.write(")");
+        if let Some(qualifier) = &t.qualifier {
+            self.write(".");
+            self.entity_name(qualifier);
+        }
+        self.type_args(t.type_args.as_ref());
+    }
+
+    pub(crate) fn entity_name(&mut self, name: &TsEntityName) {
+        match name {
+            TsEntityName::Ident(id) => self.ident(id),
+            TsEntityName::Qualified(q) => {
+                self.entity_name(&q.left);
+                self.write(".");
+                self.ident(&q.right);
+            }
+        }
+    }
+
+    /// `: T`, after a binding or the parameters of a function.
+    pub(crate) fn type_ann(&mut self, ty: Option<&TsType>) {
+        if let Some(ty) = ty {
+            self.write(":");
+            self.space();
+            self.ty(ty);
+        }
+    }
+
+    pub(crate) fn type_params(&mut self, params: Option<&TsTypeParamDecl>) {
+        if let Some(params) = params {
+            self.type_param_list(params, false);
+        }
+    }
+
+    pub(crate) fn type_param_list(&mut self, params: &TsTypeParamDecl, trailing_comma: bool) {
+        self.comments_before(params.span.start);
+        self.write("<");
+        self.comma_list(&params.params, |p, param| p.type_param(param));
+        if trailing_comma {
+            self.write(",");
+        }
+        self.write(">");
+    }
+
+    fn type_param(&mut self, param: &TsTypeParam) {
+        self.comments_before(param.span.start);
+        for (set, modifier) in [
+            (param.is_const, "const"),
+            (param.is_in, "in"),
+            (param.is_out, "out"),
+        ] {
+            if set {
+                self.write(modifier);
+                self.space();
+            }
+        }
+        self.ident(&param.name);
+        if let Some(constraint) = &param.constraint {
+            self.space();
+            self.write("extends");
+            self.space();
+            self.ty(constraint);
+        }
+        if let Some(default) = &param.default {
+            self.space();
+            self.write("=");
+            self.space();
+            self.ty(default);
+        }
+    }
+
+    pub(crate) fn type_args(&mut self, args: Option<&TsTypeArgs>) {
+        if let Some(args) = args {
+            self.comments_before(args.span.start);
+            self.write("<");
+            self.comma_list(&args.params, |p, ty| p.ty(ty));
+            self.write(">");
+        }
+    }
+
+    pub(crate) fn expr_with_type_args(&mut self, e: &TsExprWithTypeArgs) {
+        self.expr(&e.expr);
+        self.type_args(e.type_args.as_ref());
+    }
+
+    fn signature(
+        &mut self,
+        type_params: Option<&TsTypeParamDecl>,
+        params: &[Pat],
+        return_type: Option<&TsType>,
+    ) {
+        self.type_params(type_params);
+        self.write("(");
+        self.comma_list(params, |p, param| p.pat(param));
+        self.write(")");
+        self.type_ann(return_type);
+    }
+
+    pub(crate) fn index_signature(&mut self, signature: &TsIndexSignature) {
+        if signature.is_static {
+            self.write("static");
+            self.space();
+        }
+        if signature.readonly {
+            self.write("readonly");
+            self.space();
+        }
+        self.write("[");
+        self.comma_list(&signature.params, |p, param| p.pat(param));
+        self.write("]");
+        self.type_ann(signature.type_ann.as_deref());
+    }
+
+    fn type_element(&mut self, element: &TsTypeElement) {
+        match element {
+            TsTypeElement::Property(p) => {
+                self.comments_before(p.span.start);
+                if p.readonly {
+                    self.write("readonly");
+                    self.space();
+                }
+                self.prop_name(&p.key);
+                if p.optional {
+                    self.write("?");
+                }
+                self.type_ann(p.type_ann.as_deref());
+            }
+            TsTypeElement::Method(m) => {
+                self.comments_before(m.span.start);
+                self.prop_name(&m.key);
+                if m.optional {
+                    self.write("?");
+                }
+                self.signature(m.type_params.as_ref(), &m.params, m.return_type.as_deref());
+            }
+            TsTypeElement::Call(c) => {
+                self.comments_before(c.span.start);
+                self.signature(c.type_params.as_ref(), &c.params, c.return_type.as_deref());
+            }
+            TsTypeElement::Construct(c) => {
+                self.comments_before(c.span.start);
+                self.write("new");
+                self.space();
+                self.signature(c.type_params.as_ref(), &c.params, c.return_type.as_deref());
+            }
+            TsTypeElement::Index(signature) => {
+                self.comments_before(signature.span.start);
+                self.index_signature(signature);
+            }
+            TsTypeElement::Getter(g) => {
+                self.comments_before(g.span.start);
+                self.write("get");
+                self.space();
+                self.prop_name(&g.key);
+                self.write("()");
+                self.type_ann(g.return_type.as_deref());
+            }
+            TsTypeElement::Setter(s) => {
+                self.comments_before(s.span.start);
+                self.write("set");
+                self.space();
+                self.prop_name(&s.key);
+                self.write("(");
+                self.pat(&s.param);
+                self.write(")");
+            }
+            TsTypeElement::Invalid(_) => {}
+        }
+    }
+
+    pub(crate) fn interface(&mut self, decl: &TsInterfaceDecl) {
+        self.declare(decl.declare);
+        self.write("interface");
+        self.space();
+        self.ident(&decl.id);
+        self.type_params(decl.type_params.as_ref());
+        if !decl.extends.is_empty() {
+            self.space();
+            self.write("extends");
+            self.space();
+            self.comma_list(&decl.extends, |p, extends| p.expr_with_type_args(extends));
+        }
+        self.space();
+        self.write("{");
+        self.indent += 1;
+        for member in &decl.body {
+            self.newline();
+            self.type_element(member);
+            self.write(";");
+            if let Some(span) = type_element_span(member) {
+                self.trailing_comments(span);
+            }
+        }
+        self.comments_before(decl.span.end.saturating_sub(1));
+        self.indent -= 1;
+        if !decl.body.is_empty() || self.needs_newline {
+            self.newline();
+        }
+        self.write("}");
+    }
+
+    pub(crate) fn enum_decl(&mut self, decl: &TsEnumDecl) {
+        self.declare(decl.declare);
+        if decl.is_const {
+            self.write("const");
+            self.space();
+        }
+        self.write("enum");
+        self.space();
+        self.ident(&decl.id);
+        self.space();
+        self.write("{");
+        self.indent += 1;
+        for (i, member) in decl.members.iter().enumerate() {
+            if i > 0 {
+                self.write(",");
+            }
+            self.newline();
+            self.comments_before(member.span.start);
+            self.prop_name(&member.id);
+            if let Some(init) = &member.init {
+                self.space();
+                self.write("=");
+                self.space();
+                self.expr_in(init, ASSIGN);
+            }
+        }
+        self.comments_before(decl.span.end.saturating_sub(1));
+        self.indent -= 1;
+        if !decl.members.is_empty() || self.needs_newline {
+            self.newline();
+        }
+        self.write("}");
+    }
+}
+
+fn type_element_span(element: &TsTypeElement) -> Option<rtsc_parser::Span> {
+    Some(match element {
+        TsTypeElement::Property(p) => p.span,
+        TsTypeElement::Method(m) => m.span,
+        TsTypeElement::Call(c) => c.span,
+        TsTypeElement::Construct(c) => c.span,
+        TsTypeElement::Index(i) => i.span,
+        TsTypeElement::Getter(g) => g.span,
+        TsTypeElement::Setter(s) => s.span,
+        TsTypeElement::Invalid(_) => return None,
+    })
+}
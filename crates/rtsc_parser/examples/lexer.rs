@@ -1,4 +1,4 @@
-use rtsc_parser::Lexer;
+use rtsc_parser::{Allocator, Lexer, LineIndex};
 
 fn main() {
     let code = "class C extends null {
@@ -13,11 +13,22 @@ fn main() {
             return Object.create(null);
         }
     }";
-    let l = Lexer::new(code);
+    let allocator = Allocator::new();
+    let l = Lexer::new(&allocator, code);
     let (tokens, errors) = l.lex();
 
     if errors.is_empty() {
-        println!("Tokens: {:?}", tokens);
+        let index = LineIndex::new(code);
+        for token in tokens {
+            let pos = index.line_col(token.span.start);
+            println!(
+                "{}:{} {:?} {:?}",
+                pos.line + 1,
+                pos.col + 1,
+                token.kind,
+                token.raw(code)
+            );
+        }
     } else {
         for e in errors {
             let e = e.with_source_code(code);
@@ -0,0 +1,478 @@
+//! The bump [`Allocator`] a parse keeps its atoms and its syntax tree in, which frees
+//! all of them at once when it is dropped, and the [`Box`] and [`Vec`] the nodes of the
+//! tree are held in.
+//!
+//! Nothing in the allocator is dropped, so neither a `Box` nor a `Vec` drops what it
+//! holds. The nodes need no drop: their text is borrowed from the source or from the
+//! allocator, and their children are in it.
+
+use std::{
+    alloc::{self, Layout},
+    cell::{Cell, RefCell},
+    fmt,
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+    ptr::{self, NonNull},
+    slice,
+};
+
+// the size of the first chunk, doubled for each new one up to `MAX_CHUNK_SIZE`
+const MIN_CHUNK_SIZE: usize = 4 * 1024;
+const MAX_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Memory handed out by bumping a pointer through chunks, for a parser and what it
+/// builds. Values in it are never dropped, only freed with the allocator, so only
+/// values which need no drop can go in.
+pub struct Allocator {
+    // the free part of the current chunk
+    ptr: Cell<*mut u8>,
+    end: Cell<*mut u8>,
+    chunks: RefCell<std::vec::Vec<(NonNull<u8>, Layout)>>,
+}
+
+impl Allocator {
+    pub fn new() -> Self {
+        Self {
+            ptr: Cell::new(ptr::null_mut()),
+            end: Cell::new(ptr::null_mut()),
+            chunks: RefCell::new(vec![]),
+        }
+    }
+
+    /// Moves `value` into the allocator.
+    // every call hands out memory of its own, so the borrows do not overlap
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T>(&self, value: T) -> &mut T {
+        debug_assert!(!mem::needs_drop::<T>(), "the allocator never drops values");
+        let ptr = self.alloc_layout(Layout::new::<T>()).cast::<T>();
+        // SAFETY: the memory is fresh, and aligned and sized for a `T`
+        unsafe {
+            ptr.as_ptr().write(value);
+            &mut *ptr.as_ptr()
+        }
+    }
+
+    /// Copies `text` into the allocator.
+    pub fn alloc_str(&self, text: &str) -> &str {
+        let ptr = self.alloc_layout(Layout::for_value(text));
+        // SAFETY: the memory is fresh and `text.len()` bytes long
+        unsafe {
+            ptr::copy_nonoverlapping(text.as_ptr(), ptr.as_ptr(), text.len());
+            let bytes = std::slice::from_raw_parts(ptr.as_ptr(), text.len());
+            std::str::from_utf8_unchecked(bytes)
+        }
+    }
+
+    /// The number of bytes of the chunks held, used or not.
+    pub fn capacity(&self) -> usize {
+        self.chunks
+            .borrow()
+            .iter()
+            .map(|(_, layout)| layout.size())
+            .sum()
+    }
+
+    pub(crate) fn alloc_layout(&self, layout: Layout) -> NonNull<u8> {
+        if let Some(ptr) = self.bump(layout) {
+            return ptr;
+        }
+        self.grow(layout);
+        self.bump(layout).expect("a new chunk fits the layout")
+    }
+
+    /// Makes the memory at `ptr`, which is `old_size` bytes long, `new_size` bytes long
+    /// if it was the last memory handed out and its chunk has room for the rest.
+    fn grow_in_place(&self, ptr: NonNull<u8>, old_size: usize, new_size: usize) -> bool {
+        let end = ptr.as_ptr().wrapping_add(old_size);
+        if end != self.ptr.get() || new_size - old_size > self.end.get() as usize - end as usize {
+            return false;
+        }
+        self.ptr.set(ptr.as_ptr().wrapping_add(new_size));
+        true
+    }
+
+    fn bump(&self, layout: Layout) -> Option<NonNull<u8>> {
+        let ptr = self.ptr.get();
+        let start = (ptr as usize).checked_add(ptr.align_offset(layout.align()))?;
+        let end = start.checked_add(layout.size())?;
+        if ptr.is_null() || end > self.end.get() as usize {
+            return None;
+        }
+        // moving the pointer within its chunk keeps its provenance
+        let start = ptr.wrapping_add(start - ptr as usize);
+        self.ptr.set(ptr.wrapping_add(end - ptr as usize));
+        NonNull::new(start)
+    }
+
+    fn grow(&self, layout: Layout) {
+        let mut chunks = self.chunks.borrow_mut();
+        let last = chunks.last().map_or(MIN_CHUNK_SIZE / 2, |(_, l)| l.size());
+        let size = (last * 2)
+            .min(MAX_CHUNK_SIZE)
+            .max(layout.size() + layout.align());
+        let chunk = Layout::from_size_align(size, mem::align_of::<usize>())
+            .expect("a chunk size below isize::MAX");
+        // SAFETY: the size of a chunk is not zero
+        let ptr = NonNull::new(unsafe { alloc::alloc(chunk) })
+            .unwrap_or_else(|| alloc::handle_alloc_error(chunk));
+        chunks.push((ptr, chunk));
+        self.ptr.set(ptr.as_ptr());
+        self.end.set(ptr.as_ptr().wrapping_add(size));
+    }
+}
+
+impl Default for Allocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Allocator {
+    fn drop(&mut self) {
+        for (ptr, layout) in self.chunks.get_mut().drain(..) {
+            // SAFETY: the chunk was allocated with this layout and is freed once
+            unsafe { alloc::dealloc(ptr.as_ptr(), layout) };
+        }
+    }
+}
+
+impl fmt::Debug for Allocator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Allocator")
+            .field("capacity", &self.capacity())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A value in an [`Allocator`], which a `Box` owns like a [`std::boxed::Box`] does.
+/// The value stays where it is while the box is moved around, so its address tells it
+/// apart for as long as the allocator lives.
+pub struct Box<'a, T>(NonNull<T>, PhantomData<(&'a (), T)>);
+
+impl<'a, T> Box<'a, T> {
+    pub fn new_in(value: T, allocator: &'a Allocator) -> Self {
+        Self(NonNull::from(allocator.alloc(value)), PhantomData)
+    }
+
+    /// Moves the value out of the box. Its memory is freed with the allocator.
+    pub fn unbox(self) -> T {
+        // SAFETY: the box owns the value and is gone after it is read
+        unsafe { self.0.as_ptr().read() }
+    }
+
+    /// Replaces the value with what `f` makes of it, in the same place.
+    pub fn map(self, f: impl FnOnce(T) -> T) -> Self {
+        // SAFETY: the value is read out and the place written again before the box is
+        // handed back. Should `f` panic the place is never read, since the box is gone.
+        unsafe { self.0.as_ptr().write(f(self.0.as_ptr().read())) };
+        self
+    }
+}
+
+impl<T> Deref for Box<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: the box owns the value, which lives as long as the allocator
+        unsafe { self.0.as_ref() }
+    }
+}
+
+impl<T> DerefMut for Box<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: as for `deref`, and the box is borrowed mutably
+        unsafe { self.0.as_mut() }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Box<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: PartialEq> PartialEq for Box<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Eq> Eq for Box<'_, T> {}
+
+/// A growable list in an [`Allocator`], like a [`std::vec::Vec`]. It grows in place
+/// while nothing was allocated after it, and otherwise moves to new memory and leaves
+/// the old one to the allocator.
+pub struct Vec<'a, T> {
+    ptr: NonNull<T>,
+    len: usize,
+    cap: usize,
+    allocator: &'a Allocator,
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T> Vec<'a, T> {
+    pub fn new_in(allocator: &'a Allocator) -> Self {
+        debug_assert!(!mem::needs_drop::<T>(), "the allocator never drops values");
+        Self {
+            ptr: NonNull::dangling(),
+            len: 0,
+            cap: if mem::size_of::<T>() == 0 {
+                usize::MAX
+            } else {
+                0
+            },
+            allocator,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn with_capacity_in(capacity: usize, allocator: &'a Allocator) -> Self {
+        let mut vec = Self::new_in(allocator);
+        vec.reserve(capacity);
+        vec
+    }
+
+    pub fn from_iter_in(iter: impl IntoIterator<Item = T>, allocator: &'a Allocator) -> Self {
+        let mut vec = Self::new_in(allocator);
+        vec.extend(iter);
+        vec
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    /// Makes room for at least `additional` more elements.
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = self.len.checked_add(additional).expect("capacity overflow");
+        if needed <= self.cap {
+            return;
+        }
+        let cap = needed.max(self.cap * 2).max(4);
+        let layout = Layout::array::<T>(cap).expect("capacity overflow");
+        let old_size = self.cap * mem::size_of::<T>();
+        if self.cap > 0
+            && self
+                .allocator
+                .grow_in_place(self.ptr.cast(), old_size, layout.size())
+        {
+            self.cap = cap;
+            return;
+        }
+        let ptr = self.allocator.alloc_layout(layout).cast::<T>();
+        // SAFETY: the new memory is fresh and has room for the elements
+        unsafe { ptr::copy_nonoverlapping(self.ptr.as_ptr(), ptr.as_ptr(), self.len) };
+        self.ptr = ptr;
+        self.cap = cap;
+    }
+
+    pub fn push(&mut self, value: T) {
+        self.reserve(1);
+        // SAFETY: there is room for one more element
+        unsafe { self.ptr.as_ptr().add(self.len).write(value) };
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.len = self.len.checked_sub(1)?;
+        // SAFETY: the element was in the list, which no longer has it
+        Some(unsafe { self.ptr.as_ptr().add(self.len).read() })
+    }
+
+    /// Puts `value` at `index`, moving the elements after it one place to the right.
+    pub fn insert(&mut self, index: usize, value: T) {
+        assert!(index <= self.len, "insertion index is out of bounds");
+        self.reserve(1);
+        // SAFETY: there is room for one more element, and `index` is at most the length
+        unsafe {
+            let place = self.ptr.as_ptr().add(index);
+            ptr::copy(place, place.add(1), self.len - index);
+            place.write(value);
+        }
+        self.len += 1;
+    }
+
+    /// Takes the element at `index` out, moving the elements after it one place to the
+    /// left.
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "removal index is out of bounds");
+        self.len -= 1;
+        // SAFETY: the element is in the list, and the ones after it are moved over it
+        unsafe {
+            let place = self.ptr.as_ptr().add(index);
+            let value = place.read();
+            ptr::copy(place.add(1), place, self.len - index);
+            value
+        }
+    }
+
+    /// Replaces every element with what `f` makes of it, in the same place.
+    pub fn map(self, mut f: impl FnMut(T) -> T) -> Self {
+        for index in 0..self.len {
+            // SAFETY: as for `Box::map`, the element is read out and its place written
+            // again. Should `f` panic, the list is gone and the place never read.
+            unsafe {
+                let place = self.ptr.as_ptr().add(index);
+                place.write(f(place.read()));
+            }
+        }
+        self
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<T> Deref for Vec<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        // SAFETY: the first `len` elements are in the list
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T> DerefMut for Vec<'_, T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        // SAFETY: as for `deref`, and the list is borrowed mutably
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T> Extend<T> for Vec<'_, T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Vec<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: PartialEq> PartialEq for Vec<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Eq> Eq for Vec<'_, T> {}
+
+impl<'v, T> IntoIterator for &'v Vec<'_, T> {
+    type Item = &'v T;
+    type IntoIter = slice::Iter<'v, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'v, T> IntoIterator for &'v mut Vec<'_, T> {
+    type Item = &'v mut T;
+    type IntoIter = slice::IterMut<'v, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> IntoIterator for Vec<'a, T> {
+    type Item = T;
+    type IntoIter = IntoIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { vec: self, next: 0 }
+    }
+}
+
+/// Moves the elements out of a [`Vec`].
+pub struct IntoIter<'a, T> {
+    vec: Vec<'a, T>,
+    next: usize,
+}
+
+impl<T> Iterator for IntoIter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.next == self.vec.len {
+            return None;
+        }
+        // SAFETY: the element is in the list and is read once
+        let value = unsafe { self.vec.ptr.as_ptr().add(self.next).read() };
+        self.next += 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.vec.len - self.next;
+        (len, Some(len))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocates_aligned_values_across_chunks() {
+        let allocator = Allocator::new();
+        let mut values = vec![];
+        for i in 0..10_000u64 {
+            let byte = allocator.alloc(i as u8);
+            values.push((allocator.alloc(i), *byte));
+        }
+        for (i, (value, byte)) in values.iter().enumerate() {
+            assert_eq!(**value, i as u64);
+            assert_eq!(*byte, i as u8);
+            assert_eq!(*value as *const u64 as usize % mem::align_of::<u64>(), 0);
+        }
+        assert!(allocator.chunks.borrow().len() > 1);
+
+        let big = "a".repeat(2 * MAX_CHUNK_SIZE);
+        assert_eq!(allocator.alloc_str(&big), big);
+        assert_eq!(allocator.alloc_str(""), "");
+    }
+
+    #[test]
+    fn boxes_and_vecs() {
+        let allocator = Allocator::new();
+        let boxed = Box::new_in(1u32, &allocator);
+        let address = &*boxed as *const u32;
+        let boxed = boxed.map(|value| value + 1);
+        assert_eq!(&*boxed as *const u32, address);
+        assert_eq!(boxed.unbox(), 2);
+
+        let mut vec = Vec::new_in(&allocator);
+        for i in 0..100u32 {
+            vec.push(i);
+        }
+        // the list grows in place while it was allocated last, and moves after that
+        let start = vec.as_ptr();
+        assert_eq!(vec.capacity(), 128);
+        let other = Vec::from_iter_in([1u8, 2], &allocator);
+        vec.extend(100..=128);
+        assert_ne!(vec.as_ptr(), start);
+        assert_eq!(*other, [1, 2]);
+        assert!(vec.iter().copied().eq(0..=128));
+
+        vec.insert(0, 200);
+        assert_eq!(vec.remove(1), 0);
+        assert_eq!(vec.pop(), Some(128));
+        vec.truncate(3);
+        assert_eq!(vec.into_iter().collect::<std::vec::Vec<_>>(), [200, 1, 2]);
+    }
+}
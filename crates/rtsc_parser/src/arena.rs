@@ -0,0 +1,354 @@
+//! The nodes of a parsed [`Program`] addressed by [`NodeId`], with the parent of every
+//! node in a side table.
+//!
+//! The tree lives in the [`Allocator`](crate::Allocator) of its parse, and the arena
+//! borrows it there instead of copying it. The nodes are numbered in the order a walk
+//! from the root meets them, so a node's descendants come right after it, and their kinds
+//! and spans are read from the nodes themselves. A node never moves in the allocator, so
+//! its address finds its id. Passes over the tree keep what they learn about nodes in a
+//! [`NodeMap`] keyed by their ids.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+use crate::{
+    ast::Program,
+    cst::{CollectNodes, NodeSink, SyntaxKind},
+    visit::{AstNode, Node},
+    Span,
+};
+
+/// The index of a node in an [`AstArena`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(u32);
+
+impl NodeId {
+    /// The id of the program, which is the first node.
+    pub const ROOT: NodeId = NodeId(0);
+
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// A [`NodeId`] known to be a `T`, like an `Id<Ident>`, which gets the node back
+/// from the arena without a check.
+pub struct Id<T> {
+    id: NodeId,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Id<T> {
+    pub fn node_id(self) -> NodeId {
+        self.id
+    }
+}
+
+impl<T> Clone for Id<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Id<T> {}
+
+impl<T> PartialEq for Id<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for Id<T> {}
+
+impl<T> Hash for Id<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Id<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Id({})", self.id.0)
+    }
+}
+
+impl<T> From<Id<T>> for NodeId {
+    fn from(id: Id<T>) -> Self {
+        id.id
+    }
+}
+
+/// The nodes of a program, with the tables to go from a node to its parent and its
+/// children in constant time.
+pub struct AstArena<'a> {
+    // the nodes by their ids, which give their kinds and spans
+    nodes: Vec<AstNode<'a>>,
+    parents: Vec<Option<NodeId>>,
+    // the id after the last descendant of each node
+    ends: Vec<u32>,
+    // the ids of the nodes by their address and kind
+    ids: HashMap<(usize, SyntaxKind), NodeId>,
+    // the nodes entered but not yet exited while building
+    stack: Vec<NodeId>,
+}
+
+impl<'a> AstArena<'a> {
+    pub fn new(program: &'a Program<'a>) -> Self {
+        let mut arena = Self {
+            nodes: vec![],
+            parents: vec![],
+            ends: vec![],
+            ids: HashMap::new(),
+            stack: vec![],
+        };
+        program.collect_nodes(&mut arena);
+        debug_assert!(arena.stack.is_empty());
+        arena
+    }
+
+    /// The number of nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn root(&self) -> Id<Program<'a>> {
+        Id {
+            id: NodeId::ROOT,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Every node id, parents before children.
+    pub fn ids(&self) -> impl Iterator<Item = NodeId> {
+        (0..self.len() as u32).map(NodeId)
+    }
+
+    pub fn kind(&self, id: impl Into<NodeId>) -> SyntaxKind {
+        self.node(id).kind()
+    }
+
+    pub fn span(&self, id: impl Into<NodeId>) -> Span {
+        self.node(id).span()
+    }
+
+    /// The parent of a node, which only the program has none of.
+    pub fn parent(&self, id: impl Into<NodeId>) -> Option<NodeId> {
+        self.parents[id.into().index()]
+    }
+
+    /// The parent, grandparent and so on of a node, up to the program.
+    pub fn ancestors(&self, id: impl Into<NodeId>) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::successors(self.parent(id), |&id| self.parent(id))
+    }
+
+    /// The children of a node in source order.
+    pub fn children(&self, id: impl Into<NodeId>) -> impl Iterator<Item = NodeId> + '_ {
+        let id = id.into();
+        let end = self.ends[id.index()];
+        let mut next = id.0 + 1;
+        std::iter::from_fn(move || {
+            // a child's siblings start where its descendants end
+            let child = (next < end).then_some(next)?;
+            next = self.ends[child as usize];
+            Some(NodeId(child))
+        })
+    }
+
+    /// Whether `id` is `ancestor` or one of its descendants.
+    pub fn is_descendant(&self, id: impl Into<NodeId>, ancestor: impl Into<NodeId>) -> bool {
+        let (id, ancestor) = (id.into(), ancestor.into());
+        ancestor <= id && id.0 < self.ends[ancestor.index()]
+    }
+
+    /// The id of a node of the program, found by its address. Enums like
+    /// [`Expr`](crate::ast::Expr) are not nodes themselves, the structs they hold are.
+    pub fn id<T: Node<'a>>(&self, node: &T) -> Option<Id<T>> {
+        let key = (node as *const T as usize, T::KIND);
+        self.ids.get(&key).map(|&id| Id {
+            id,
+            _marker: PhantomData,
+        })
+    }
+
+    /// The id of a node if it is a `T`.
+    pub fn downcast<T: Node<'a>>(&self, id: NodeId) -> Option<Id<T>> {
+        (self.kind(id) == T::KIND).then_some(Id {
+            id,
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn get<T: Node<'a>>(&self, id: Id<T>) -> &'a T {
+        T::from_node(self.node(id)).expect("the id belongs to a node of this arena")
+    }
+
+    /// The node in the tree, which is its struct or for leaves like `this` its span.
+    pub fn node(&self, id: impl Into<NodeId>) -> AstNode<'a> {
+        self.nodes[id.into().index()]
+    }
+}
+
+impl<'a> NodeSink<'a> for AstArena<'a> {
+    fn enter(&mut self, _: SyntaxKind, _: Span, node: AstNode<'a>) {
+        let id = NodeId(self.nodes.len() as u32);
+        self.nodes.push(node);
+        self.parents.push(self.stack.last().copied());
+        self.ends.push(id.0 + 1);
+        self.ids.insert((node.addr(), node.kind()), id);
+        self.stack.push(id);
+    }
+
+    fn exit(&mut self) {
+        let id = self.stack.pop().expect("a node to exit");
+        self.ends[id.index()] = self.nodes.len() as u32;
+    }
+}
+
+impl fmt::Debug for AstArena<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AstArena")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Data about nodes kept next to the tree, like the symbol an identifier refers to.
+#[derive(Debug, Clone)]
+pub struct NodeMap<V> {
+    values: Vec<Option<V>>,
+}
+
+impl<V> Default for NodeMap<V> {
+    fn default() -> Self {
+        Self { values: vec![] }
+    }
+}
+
+impl<V> NodeMap<V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the value of a node and returns the one it had.
+    pub fn insert(&mut self, id: impl Into<NodeId>, value: V) -> Option<V> {
+        let index = id.into().index();
+        if index >= self.values.len() {
+            self.values.resize_with(index + 1, || None);
+        }
+        self.values[index].replace(value)
+    }
+
+    pub fn get(&self, id: impl Into<NodeId>) -> Option<&V> {
+        self.values.get(id.into().index())?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, id: impl Into<NodeId>) -> Option<&mut V> {
+        self.values.get_mut(id.into().index())?.as_mut()
+    }
+
+    pub fn remove(&mut self, id: impl Into<NodeId>) -> Option<V> {
+        self.values.get_mut(id.into().index())?.take()
+    }
+
+    pub fn contains(&self, id: impl Into<NodeId>) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// The nodes with a value and their values, in the order of their ids.
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, &V)> {
+        self.values
+            .iter()
+            .enumerate()
+            .filter_map(|(index, value)| Some((NodeId(index as u32), value.as_ref()?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ast::{BinaryExpr, Expr, Ident, Stmt},
+        parse, Allocator,
+    };
+
+    #[test]
+    fn parents_and_children() {
+        let allocator = Allocator::new();
+        let (program, errors) = parse(&allocator, "let a = b + c;\nthis;");
+        assert!(errors.is_empty(), "{errors:?}");
+        let arena = AstArena::new(&program);
+        let kinds = |ids: Vec<NodeId>| ids.into_iter().map(|id| arena.kind(id)).collect::<Vec<_>>();
+
+        let root = arena.root();
+        assert_eq!(arena.get(root).span, program.span);
+        assert_eq!(
+            kinds(arena.children(root).collect()),
+            [SyntaxKind::VarDecl, SyntaxKind::ExprStmt]
+        );
+
+        let Stmt::Var(decl) = &program.body[0] else {
+            panic!("a variable declaration");
+        };
+        let Some(Expr::Binary(binary)) = decl.decls[0].init.as_deref() else {
+            panic!("a binary expression");
+        };
+        let id = arena.id::<BinaryExpr>(binary).unwrap();
+        assert_eq!(arena.span(id), Span::new(8, 13));
+        assert_eq!(
+            kinds(arena.ancestors(id).collect()),
+            [
+                SyntaxKind::VarDeclarator,
+                SyntaxKind::VarDecl,
+                SyntaxKind::Program
+            ]
+        );
+        let operands = arena.children(id).collect::<Vec<_>>();
+        let names = operands
+            .iter()
+            .map(|&operand| arena.get(arena.downcast::<Ident>(operand).unwrap()).name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["b", "c"]);
+        assert!(arena.is_descendant(operands[1], id));
+        assert!(!arena.is_descendant(id, operands[1]));
+
+        // `this` has no struct of its own, but is a node all the same
+        let this = arena.ids().last().unwrap();
+        assert_eq!(arena.kind(this), SyntaxKind::ThisExpr);
+        assert!(matches!(arena.node(this), AstNode::Leaf(..)));
+        assert_eq!(arena.span(this), Span::new(15, 19));
+        assert_eq!(
+            arena.parent(this).map(|id| arena.kind(id)),
+            Some(SyntaxKind::ExprStmt)
+        );
+    }
+
+    #[test]
+    fn node_maps() {
+        let allocator = Allocator::new();
+        let (program, _) = parse(&allocator, "a(b, c)");
+        let arena = AstArena::new(&program);
+        let mut names = NodeMap::new();
+        for id in arena.ids() {
+            if let Some(ident) = arena.downcast::<Ident>(id) {
+                names.insert(ident, arena.get(ident).name.to_string());
+            }
+        }
+        let found = names
+            .iter()
+            .map(|(_, name)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(found, ["a", "b", "c"]);
+        let (first, _) = names.iter().next().unwrap();
+        assert_eq!(names.remove(first).as_deref(), Some("a"));
+        assert!(!names.contains(first));
+        assert!(names.get(NodeId::ROOT).is_none());
+    }
+}
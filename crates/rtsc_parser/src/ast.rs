@@ -0,0 +1,1722 @@
+//! The syntax tree produced by [`Parser`](crate::Parser), which lives in the
+//! [`Allocator`](crate::Allocator) of the parse: children are held in its
+//! [`Box`](crate::allocator::Box) and [`Vec`](crate::allocator::Vec), and text is
+//! borrowed from the source or from the allocator. Dropping the allocator frees the
+//! whole tree at once.
+//!
+//! Every node carries the [`Span`] of the source text it was parsed from.
+//! Node names follow the TypeScript compiler where ECMAScript has no name of its own,
+//! and TypeScript-only nodes are prefixed with `Ts`.
+
+use crate::{
+    allocator::{Box, Vec},
+    Atom, Span,
+};
+
+#[derive(Debug, PartialEq)]
+pub struct Program<'a> {
+    pub body: Vec<'a, Stmt<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Ident<'a> {
+    pub name: Atom<'a>,
+    pub span: Span,
+}
+
+/// `#name` in classes. `name` does not include the `#`.
+#[derive(Debug, PartialEq)]
+pub struct PrivateName<'a> {
+    pub name: Atom<'a>,
+    pub span: Span,
+}
+
+// ---------- statements ----------
+
+#[derive(Debug, PartialEq)]
+pub enum Stmt<'a> {
+    Block(BlockStmt<'a>),
+    Empty(Span),
+    Debugger(Span),
+    Expr(ExprStmt<'a>),
+    Var(VarDecl<'a>),
+    If(IfStmt<'a>),
+    For(ForStmt<'a>),
+    ForIn(ForInStmt<'a>),
+    ForOf(ForOfStmt<'a>),
+    While(WhileStmt<'a>),
+    DoWhile(DoWhileStmt<'a>),
+    Return(ReturnStmt<'a>),
+    Break(BreakStmt<'a>),
+    Continue(ContinueStmt<'a>),
+    Throw(ThrowStmt<'a>),
+    Try(TryStmt<'a>),
+    Switch(SwitchStmt<'a>),
+    Labeled(LabeledStmt<'a>),
+    With(WithStmt<'a>),
+    Fn(FnDecl<'a>),
+    Class(ClassDecl<'a>),
+    Interface(TsInterfaceDecl<'a>),
+    TypeAlias(TsTypeAliasDecl<'a>),
+    Enum(TsEnumDecl<'a>),
+    Module(TsModuleDecl<'a>),
+    Import(ImportDecl<'a>),
+    ImportEquals(TsImportEqualsDecl<'a>),
+    /// `export` followed by a declaration, e.g. `export const a = 1;`
+    ExportDecl(ExportDecl<'a>),
+    /// `export { a as b };`, `export * as ns from "mod";`
+    ExportNamed(ExportNamed<'a>),
+    /// `export * from "mod";`
+    ExportAll(ExportAll<'a>),
+    ExportDefault(ExportDefault<'a>),
+    /// `export = a;`
+    ExportAssign(TsExportAssign<'a>),
+    /// `export as namespace A;`
+    ExportAsNamespace(TsExportAsNamespace<'a>),
+    /// The tokens of a statement that failed to parse, skipped during error recovery.
+    Invalid(Span),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BlockStmt<'a> {
+    pub stmts: Vec<'a, Stmt<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ExprStmt<'a> {
+    pub expr: Box<'a, Expr<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VarKind {
+    Var,
+    Let,
+    Const,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct VarDecl<'a> {
+    pub kind: VarKind,
+    pub declare: bool,
+    pub decls: Vec<'a, VarDeclarator<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct VarDeclarator<'a> {
+    pub name: Pat<'a>,
+    /// `let a!: number;`
+    pub definite: bool,
+    pub init: Option<Box<'a, Expr<'a>>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct IfStmt<'a> {
+    pub test: Box<'a, Expr<'a>>,
+    pub cons: Box<'a, Stmt<'a>>,
+    pub alt: Option<Box<'a, Stmt<'a>>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ForInit<'a> {
+    Var(VarDecl<'a>),
+    Expr(Box<'a, Expr<'a>>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ForStmt<'a> {
+    pub init: Option<ForInit<'a>>,
+    pub test: Option<Box<'a, Expr<'a>>>,
+    pub update: Option<Box<'a, Expr<'a>>>,
+    pub body: Box<'a, Stmt<'a>>,
+    pub span: Span,
+}
+
+/// The left-hand side of `for-in` and `for-of` loops.
+#[derive(Debug, PartialEq)]
+pub enum ForHead<'a> {
+    Var(VarDecl<'a>),
+    Pat(Box<'a, Pat<'a>>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ForInStmt<'a> {
+    pub left: ForHead<'a>,
+    pub right: Box<'a, Expr<'a>>,
+    pub body: Box<'a, Stmt<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ForOfStmt<'a> {
+    pub is_await: bool,
+    pub left: ForHead<'a>,
+    pub right: Box<'a, Expr<'a>>,
+    pub body: Box<'a, Stmt<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct WhileStmt<'a> {
+    pub test: Box<'a, Expr<'a>>,
+    pub body: Box<'a, Stmt<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DoWhileStmt<'a> {
+    pub body: Box<'a, Stmt<'a>>,
+    pub test: Box<'a, Expr<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ReturnStmt<'a> {
+    pub arg: Option<Box<'a, Expr<'a>>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BreakStmt<'a> {
+    pub label: Option<Ident<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ContinueStmt<'a> {
+    pub label: Option<Ident<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ThrowStmt<'a> {
+    pub arg: Box<'a, Expr<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TryStmt<'a> {
+    pub block: BlockStmt<'a>,
+    pub handler: Option<CatchClause<'a>>,
+    pub finalizer: Option<BlockStmt<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CatchClause<'a> {
+    pub param: Option<Pat<'a>>,
+    pub body: BlockStmt<'a>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SwitchStmt<'a> {
+    pub discriminant: Box<'a, Expr<'a>>,
+    pub cases: Vec<'a, SwitchCase<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SwitchCase<'a> {
+    /// `None` for `default:`
+    pub test: Option<Box<'a, Expr<'a>>>,
+    pub cons: Vec<'a, Stmt<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct LabeledStmt<'a> {
+    pub label: Ident<'a>,
+    pub body: Box<'a, Stmt<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct WithStmt<'a> {
+    pub object: Box<'a, Expr<'a>>,
+    pub body: Box<'a, Stmt<'a>>,
+    pub span: Span,
+}
+
+// ---------- functions and classes ----------
+
+#[derive(Debug, PartialEq)]
+pub struct FnDecl<'a> {
+    pub id: Ident<'a>,
+    pub declare: bool,
+    pub function: Function<'a>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Function<'a> {
+    pub params: Vec<'a, Param<'a>>,
+    /// `None` for overload signatures and ambient declarations.
+    pub body: Option<BlockStmt<'a>>,
+    pub is_async: bool,
+    pub is_generator: bool,
+    pub type_params: Option<TsTypeParamDecl<'a>>,
+    pub return_type: Option<Box<'a, TsType<'a>>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Param<'a> {
+    pub decorators: Vec<'a, Decorator<'a>>,
+    /// Modifiers of a constructor parameter property, e.g. `constructor(private readonly a)`.
+    pub accessibility: Option<Accessibility>,
+    pub is_readonly: bool,
+    pub is_override: bool,
+    pub pat: Pat<'a>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Decorator<'a> {
+    pub expr: Box<'a, Expr<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Accessibility {
+    Public,
+    Protected,
+    Private,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ClassDecl<'a> {
+    pub id: Ident<'a>,
+    pub declare: bool,
+    pub class: Class<'a>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Class<'a> {
+    pub decorators: Vec<'a, Decorator<'a>>,
+    pub is_abstract: bool,
+    pub type_params: Option<TsTypeParamDecl<'a>>,
+    pub super_class: Option<Box<'a, Expr<'a>>>,
+    pub super_type_args: Option<TsTypeArgs<'a>>,
+    pub implements: Vec<'a, TsExprWithTypeArgs<'a>>,
+    pub body: Vec<'a, ClassMember<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ClassMember<'a> {
+    Method(ClassMethod<'a>),
+    Prop(ClassProp<'a>),
+    IndexSignature(TsIndexSignature<'a>),
+    StaticBlock(StaticBlock<'a>),
+    /// A stray `;` in the class body.
+    Empty(Span),
+    /// The tokens of a member that failed to parse, skipped during error recovery.
+    Invalid(Span),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MethodKind {
+    Method,
+    Getter,
+    Setter,
+    Constructor,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ClassMethod<'a> {
+    pub decorators: Vec<'a, Decorator<'a>>,
+    pub kind: MethodKind,
+    pub key: PropName<'a>,
+    pub function: Function<'a>,
+    pub is_static: bool,
+    pub accessibility: Option<Accessibility>,
+    pub is_abstract: bool,
+    pub is_override: bool,
+    pub is_optional: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ClassProp<'a> {
+    pub decorators: Vec<'a, Decorator<'a>>,
+    pub key: PropName<'a>,
+    pub value: Option<Box<'a, Expr<'a>>>,
+    pub type_ann: Option<Box<'a, TsType<'a>>>,
+    pub is_static: bool,
+    pub accessibility: Option<Accessibility>,
+    pub is_abstract: bool,
+    pub is_override: bool,
+    pub is_readonly: bool,
+    pub is_declare: bool,
+    /// `accessor a = 1;`
+    pub is_accessor: bool,
+    pub is_optional: bool,
+    pub definite: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct StaticBlock<'a> {
+    pub body: BlockStmt<'a>,
+    pub span: Span,
+}
+
+/// The name of a property in object literals, classes, interfaces and enums.
+#[derive(Debug, PartialEq)]
+pub enum PropName<'a> {
+    Ident(Ident<'a>),
+    Str(Str<'a>),
+    Num(Num<'a>),
+    BigInt(BigInt<'a>),
+    Computed(ComputedPropName<'a>),
+    Private(PrivateName<'a>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ComputedPropName<'a> {
+    pub expr: Box<'a, Expr<'a>>,
+    pub span: Span,
+}
+
+// ---------- expressions ----------
+
+#[derive(Debug, PartialEq)]
+pub enum Expr<'a> {
+    Ident(Ident<'a>),
+    This(Span),
+    Super(Span),
+    /// The `import` of a dynamic `import("mod")` call.
+    Import(Span),
+    Lit(Lit<'a>),
+    Array(ArrayLit<'a>),
+    Object(ObjectLit<'a>),
+    Fn(FnExpr<'a>),
+    Class(ClassExpr<'a>),
+    Arrow(ArrowExpr<'a>),
+    Tpl(Tpl<'a>),
+    TaggedTpl(TaggedTpl<'a>),
+    Unary(UnaryExpr<'a>),
+    Update(UpdateExpr<'a>),
+    Binary(BinaryExpr<'a>),
+    Assign(AssignExpr<'a>),
+    Cond(CondExpr<'a>),
+    Call(CallExpr<'a>),
+    New(NewExpr<'a>),
+    Member(MemberExpr<'a>),
+    Seq(SeqExpr<'a>),
+    Paren(ParenExpr<'a>),
+    Yield(YieldExpr<'a>),
+    Await(AwaitExpr<'a>),
+    /// `new.target` and `import.meta`
+    MetaProp(MetaProp<'a>),
+    /// `#a` on the left of `in`, as in `#a in obj`.
+    PrivateName(PrivateName<'a>),
+    TsAs(TsAsExpr<'a>),
+    TsSatisfies(TsSatisfiesExpr<'a>),
+    /// `a as const`
+    TsConstAssertion(TsConstAssertion<'a>),
+    /// `a!`
+    TsNonNull(TsNonNullExpr<'a>),
+    /// `<T>a`
+    TsTypeAssertion(TsTypeAssertion<'a>),
+    /// `f<T>` without a call
+    TsInstantiation(TsInstantiation<'a>),
+    JsxElement(JsxElement<'a>),
+    /// `<>...</>`
+    JsxFragment(JsxFragment<'a>),
+    /// Stands in for a missing expression, as in `a + ;`. It has an empty span right
+    /// after the token before it.
+    Invalid(Span),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Lit<'a> {
+    Str(Str<'a>),
+    Num(Num<'a>),
+    BigInt(BigInt<'a>),
+    Bool(Bool),
+    Null(Span),
+    Regex(Regex<'a>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Str<'a> {
+    pub value: Atom<'a>,
+    /// The source text including the quotes.
+    pub raw: &'a str,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Num<'a> {
+    pub value: f64,
+    pub raw: &'a str,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BigInt<'a> {
+    /// Decimal digits without the `n` suffix.
+    pub value: &'a str,
+    pub raw: &'a str,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Bool {
+    pub value: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Regex<'a> {
+    pub pattern: &'a str,
+    pub flags: &'a str,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ExprOrSpread<'a> {
+    /// The span of `...` if the element is spread.
+    pub spread: Option<Span>,
+    pub expr: Box<'a, Expr<'a>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ArrayLit<'a> {
+    /// `None` for holes, as in `[a, , b]`.
+    pub elems: Vec<'a, Option<ExprOrSpread<'a>>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ObjectLit<'a> {
+    pub props: Vec<'a, Prop<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Prop<'a> {
+    KeyValue(KeyValueProp<'a>),
+    /// `{ a }`, or `{ a = 1 }` which is only valid as a destructuring target.
+    Shorthand(ShorthandProp<'a>),
+    Method(MethodProp<'a>),
+    Spread(SpreadProp<'a>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct KeyValueProp<'a> {
+    pub key: PropName<'a>,
+    pub value: Box<'a, Expr<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ShorthandProp<'a> {
+    pub key: Ident<'a>,
+    pub init: Option<Box<'a, Expr<'a>>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MethodProp<'a> {
+    /// Never [`MethodKind::Constructor`].
+    pub kind: MethodKind,
+    pub key: PropName<'a>,
+    pub function: Function<'a>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SpreadProp<'a> {
+    pub expr: Box<'a, Expr<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FnExpr<'a> {
+    pub id: Option<Ident<'a>>,
+    pub function: Function<'a>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ClassExpr<'a> {
+    pub id: Option<Ident<'a>>,
+    pub class: Class<'a>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ArrowExpr<'a> {
+    pub params: Vec<'a, Pat<'a>>,
+    pub body: ArrowBody<'a>,
+    pub is_async: bool,
+    pub type_params: Option<TsTypeParamDecl<'a>>,
+    pub return_type: Option<Box<'a, TsType<'a>>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ArrowBody<'a> {
+    Block(BlockStmt<'a>),
+    Expr(Box<'a, Expr<'a>>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Tpl<'a> {
+    /// The text parts; there is always one more of them than `exprs`.
+    pub quasis: Vec<'a, TplElement<'a>>,
+    pub exprs: Vec<'a, Expr<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TplElement<'a> {
+    /// `None` if the text has an invalid escape, which is only allowed in tagged templates.
+    pub cooked: Option<&'a str>,
+    pub raw: &'a str,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TaggedTpl<'a> {
+    pub tag: Box<'a, Expr<'a>>,
+    pub type_args: Option<TsTypeArgs<'a>>,
+    pub tpl: Tpl<'a>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOperator {
+    Minus,  // -
+    Plus,   // +
+    Bang,   // !
+    Tilde,  // ~
+    Typeof, // typeof
+    Void,   // void
+    Delete, // delete
+}
+
+#[derive(Debug, PartialEq)]
+pub struct UnaryExpr<'a> {
+    pub op: UnaryOperator,
+    pub arg: Box<'a, Expr<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateOperator {
+    Increment, // ++
+    Decrement, // --
+}
+
+#[derive(Debug, PartialEq)]
+pub struct UpdateExpr<'a> {
+    pub op: UpdateOperator,
+    pub prefix: bool,
+    pub arg: Box<'a, Expr<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOperator {
+    Eq,                 // ==
+    EqEq,               // ===
+    Ne,                 // !=
+    NeNe,               // !==
+    Lt,                 // <
+    Le,                 // <=
+    Gt,                 // >
+    Ge,                 // >=
+    LShift,             // <<
+    RShift,             // >>
+    ZeroFillRightShift, // >>>
+    Add,                // +
+    Sub,                // -
+    Mul,                // *
+    Div,                // /
+    Mod,                // %
+    Exp,                // **
+    BitOr,              // |
+    BitXor,             // ^
+    BitAnd,             // &
+    LogicalOr,          // ||
+    LogicalAnd,         // &&
+    NullishCoalescing,  // ??
+    In,                 // in
+    Instanceof,         // instanceof
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BinaryExpr<'a> {
+    pub op: BinaryOperator,
+    pub left: Box<'a, Expr<'a>>,
+    pub right: Box<'a, Expr<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssignOperator {
+    Assign,                   // =
+    AddAssign,                // +=
+    SubAssign,                // -=
+    MulAssign,                // *=
+    DivAssign,                // /=
+    ModAssign,                // %=
+    ExpAssign,                // **=
+    LeftShiftAssign,          // <<=
+    RightShiftAssign,         // >>=
+    ZeroFillRightShiftAssign, // >>>=
+    BitOrAssign,              // |=
+    BitXorAssign,             // ^=
+    BitAndAssign,             // &=
+    LogicalAndAssign,         // &&=
+    LogicalOrAssign,          // ||=
+    NullishAssign,            // ??=
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AssignExpr<'a> {
+    pub op: AssignOperator,
+    /// A destructuring pattern for `=`, otherwise an identifier or member expression.
+    pub left: Box<'a, Pat<'a>>,
+    pub right: Box<'a, Expr<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CondExpr<'a> {
+    pub test: Box<'a, Expr<'a>>,
+    pub cons: Box<'a, Expr<'a>>,
+    pub alt: Box<'a, Expr<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CallExpr<'a> {
+    pub callee: Box<'a, Expr<'a>>,
+    pub type_args: Option<TsTypeArgs<'a>>,
+    pub args: Vec<'a, ExprOrSpread<'a>>,
+    /// `a?.()`
+    pub optional: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct NewExpr<'a> {
+    pub callee: Box<'a, Expr<'a>>,
+    pub type_args: Option<TsTypeArgs<'a>>,
+    /// `None` for `new A` without parentheses.
+    pub args: Option<Vec<'a, ExprOrSpread<'a>>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MemberProp<'a> {
+    Ident(Ident<'a>),
+    Private(PrivateName<'a>),
+    Computed(ComputedPropName<'a>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MemberExpr<'a> {
+    pub object: Box<'a, Expr<'a>>,
+    pub prop: MemberProp<'a>,
+    /// `a?.b` and `a?.[b]`
+    pub optional: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SeqExpr<'a> {
+    pub exprs: Vec<'a, Expr<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParenExpr<'a> {
+    pub expr: Box<'a, Expr<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct YieldExpr<'a> {
+    pub arg: Option<Box<'a, Expr<'a>>>,
+    /// `yield*`
+    pub delegate: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AwaitExpr<'a> {
+    pub arg: Box<'a, Expr<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MetaProp<'a> {
+    pub meta: Ident<'a>,
+    pub prop: Ident<'a>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsAsExpr<'a> {
+    pub expr: Box<'a, Expr<'a>>,
+    pub type_ann: Box<'a, TsType<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsSatisfiesExpr<'a> {
+    pub expr: Box<'a, Expr<'a>>,
+    pub type_ann: Box<'a, TsType<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsConstAssertion<'a> {
+    pub expr: Box<'a, Expr<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsNonNullExpr<'a> {
+    pub expr: Box<'a, Expr<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsTypeAssertion<'a> {
+    pub type_ann: Box<'a, TsType<'a>>,
+    pub expr: Box<'a, Expr<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsInstantiation<'a> {
+    pub expr: Box<'a, Expr<'a>>,
+    pub type_args: TsTypeArgs<'a>,
+    pub span: Span,
+}
+
+// ---------- patterns ----------
+
+#[derive(Debug, PartialEq)]
+pub enum Pat<'a> {
+    Ident(BindingIdent<'a>),
+    Array(ArrayPat<'a>),
+    Object(ObjectPat<'a>),
+    /// A pattern with a default value, `a = 1`
+    Assign(AssignPat<'a>),
+    Rest(RestPat<'a>),
+    /// A member expression as an assignment target, e.g. `[a.b] = c`
+    Expr(Box<'a, Expr<'a>>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BindingIdent<'a> {
+    pub id: Ident<'a>,
+    pub type_ann: Option<Box<'a, TsType<'a>>>,
+    /// `a?: T` in parameters
+    pub optional: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ArrayPat<'a> {
+    pub elems: Vec<'a, Option<Pat<'a>>>,
+    pub type_ann: Option<Box<'a, TsType<'a>>>,
+    pub optional: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ObjectPat<'a> {
+    pub props: Vec<'a, ObjectPatProp<'a>>,
+    pub type_ann: Option<Box<'a, TsType<'a>>>,
+    pub optional: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ObjectPatProp<'a> {
+    /// `{ a: b }`
+    KeyValue(KeyValuePatProp<'a>),
+    /// `{ a }` or `{ a = 1 }`
+    Assign(AssignPatProp<'a>),
+    Rest(RestPat<'a>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct KeyValuePatProp<'a> {
+    pub key: PropName<'a>,
+    pub value: Box<'a, Pat<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AssignPatProp<'a> {
+    pub key: Ident<'a>,
+    pub value: Option<Box<'a, Expr<'a>>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AssignPat<'a> {
+    pub left: Box<'a, Pat<'a>>,
+    pub right: Box<'a, Expr<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RestPat<'a> {
+    pub arg: Box<'a, Pat<'a>>,
+    pub type_ann: Option<Box<'a, TsType<'a>>>,
+    pub span: Span,
+}
+
+// ---------- modules ----------
+
+#[derive(Debug, PartialEq)]
+pub struct ImportDecl<'a> {
+    pub specifiers: Vec<'a, ImportSpecifier<'a>>,
+    pub src: Str<'a>,
+    /// `import type { A } from "mod";`
+    pub type_only: bool,
+    /// `with { type: "json" }`, or the older `assert { ... }`
+    pub with: Option<ObjectLit<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ImportSpecifier<'a> {
+    /// `import a from "mod";`
+    Default(Ident<'a>),
+    /// `import * as ns from "mod";`
+    Namespace(Ident<'a>),
+    /// `import { a as b } from "mod";`
+    Named(ImportNamedSpecifier<'a>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ImportNamedSpecifier<'a> {
+    /// `None` unless renamed with `as`.
+    pub imported: Option<ModuleExportName<'a>>,
+    pub local: Ident<'a>,
+    pub is_type_only: bool,
+    pub span: Span,
+}
+
+/// An exported name, which can be a string since ES2022: `export { a as "b" }`
+#[derive(Debug, PartialEq)]
+pub enum ModuleExportName<'a> {
+    Ident(Ident<'a>),
+    Str(Str<'a>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ExportDecl<'a> {
+    pub decl: Box<'a, Stmt<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ExportNamed<'a> {
+    pub specifiers: Vec<'a, ExportSpecifier<'a>>,
+    pub src: Option<Str<'a>>,
+    pub type_only: bool,
+    pub with: Option<ObjectLit<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ExportSpecifier<'a> {
+    /// `export { a as b }`
+    Named(ExportNamedSpecifier<'a>),
+    /// `export * as ns from "mod";`
+    Namespace(ModuleExportName<'a>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ExportNamedSpecifier<'a> {
+    pub orig: ModuleExportName<'a>,
+    /// `None` unless renamed with `as`.
+    pub exported: Option<ModuleExportName<'a>>,
+    pub is_type_only: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ExportAll<'a> {
+    pub src: Str<'a>,
+    pub type_only: bool,
+    pub with: Option<ObjectLit<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ExportDefault<'a> {
+    pub decl: DefaultDecl<'a>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DefaultDecl<'a> {
+    Fn(FnExpr<'a>),
+    Class(ClassExpr<'a>),
+    Interface(TsInterfaceDecl<'a>),
+    Expr(Box<'a, Expr<'a>>),
+}
+
+// ---------- TypeScript ----------
+
+#[derive(Debug, PartialEq)]
+pub struct TsInterfaceDecl<'a> {
+    pub id: Ident<'a>,
+    pub declare: bool,
+    pub type_params: Option<TsTypeParamDecl<'a>>,
+    pub extends: Vec<'a, TsExprWithTypeArgs<'a>>,
+    pub body: Vec<'a, TsTypeElement<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsTypeAliasDecl<'a> {
+    pub id: Ident<'a>,
+    pub declare: bool,
+    pub type_params: Option<TsTypeParamDecl<'a>>,
+    pub ty: Box<'a, TsType<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsEnumDecl<'a> {
+    pub id: Ident<'a>,
+    pub declare: bool,
+    pub is_const: bool,
+    pub members: Vec<'a, TsEnumMember<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsEnumMember<'a> {
+    pub id: PropName<'a>,
+    pub init: Option<Box<'a, Expr<'a>>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsModuleDecl<'a> {
+    pub declare: bool,
+    /// `declare global { ... }`
+    pub global: bool,
+    pub id: TsModuleName<'a>,
+    /// `None` for shorthand ambient modules, `declare module "mod";`
+    pub body: Option<TsModuleBody<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TsModuleName<'a> {
+    Ident(Ident<'a>),
+    Str(Str<'a>),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TsModuleBody<'a> {
+    Block(TsModuleBlock<'a>),
+    /// The rest of a dotted name, `B.C { ... }` in `namespace A.B.C { ... }`
+    Namespace(Box<'a, TsModuleDecl<'a>>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsModuleBlock<'a> {
+    pub body: Vec<'a, Stmt<'a>>,
+    pub span: Span,
+}
+
+/// `import a = require("mod");` and `import a = B.C;`
+#[derive(Debug, PartialEq)]
+pub struct TsImportEqualsDecl<'a> {
+    pub is_export: bool,
+    pub is_type_only: bool,
+    pub id: Ident<'a>,
+    pub module_ref: TsModuleRef<'a>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TsModuleRef<'a> {
+    Entity(TsEntityName<'a>),
+    /// `require("mod")`
+    External(Str<'a>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsExportAssign<'a> {
+    pub expr: Box<'a, Expr<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsExportAsNamespace<'a> {
+    pub id: Ident<'a>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TsEntityName<'a> {
+    Ident(Ident<'a>),
+    Qualified(Box<'a, TsQualifiedName<'a>>),
+}
+
+/// `A.B`
+#[derive(Debug, PartialEq)]
+pub struct TsQualifiedName<'a> {
+    pub left: TsEntityName<'a>,
+    pub right: Ident<'a>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsTypeParamDecl<'a> {
+    pub params: Vec<'a, TsTypeParam<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsTypeParam<'a> {
+    pub name: Ident<'a>,
+    pub is_in: bool,
+    pub is_out: bool,
+    pub is_const: bool,
+    pub constraint: Option<Box<'a, TsType<'a>>>,
+    pub default: Option<Box<'a, TsType<'a>>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsTypeArgs<'a> {
+    pub params: Vec<'a, TsType<'a>>,
+    pub span: Span,
+}
+
+/// A heritage clause entry, e.g. `B<T>` in `class A implements B<T>`.
+#[derive(Debug, PartialEq)]
+pub struct TsExprWithTypeArgs<'a> {
+    pub expr: Box<'a, Expr<'a>>,
+    pub type_args: Option<TsTypeArgs<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TsType<'a> {
+    Keyword(TsKeywordType),
+    This(Span),
+    Ref(TsTypeRef<'a>),
+    Lit(TsLitType<'a>),
+    Array(TsArrayType<'a>),
+    Tuple(TsTupleType<'a>),
+    Union(TsUnionType<'a>),
+    Intersection(TsIntersectionType<'a>),
+    Fn(TsFnType<'a>),
+    Constructor(TsConstructorType<'a>),
+    TypeLit(TsTypeLit<'a>),
+    Paren(TsParenType<'a>),
+    TypeOperator(TsTypeOperator<'a>),
+    IndexedAccess(TsIndexedAccessType<'a>),
+    Conditional(TsConditionalType<'a>),
+    Infer(TsInferType<'a>),
+    Mapped(TsMappedType<'a>),
+    /// `typeof a.b`
+    Query(TsTypeQuery<'a>),
+    /// `import("mod").A<B>`
+    Import(TsImportType<'a>),
+    /// `a is T`, `asserts a`, `asserts a is T`
+    Predicate(TsTypePredicate<'a>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TsKeywordKind {
+    Any,
+    Unknown,
+    Number,
+    Object,
+    Boolean,
+    BigInt,
+    String,
+    Symbol,
+    Void,
+    Undefined,
+    Null,
+    Never,
+    Intrinsic,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsKeywordType {
+    pub kind: TsKeywordKind,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsTypeRef<'a> {
+    pub name: TsEntityName<'a>,
+    pub type_args: Option<TsTypeArgs<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TsLit<'a> {
+    Str(Str<'a>),
+    /// Negative numbers like `-1` are number literal types too.
+    Num(Num<'a>),
+    BigInt(BigInt<'a>),
+    Bool(Bool),
+    Tpl(TsTplLitType<'a>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsLitType<'a> {
+    pub lit: TsLit<'a>,
+    pub span: Span,
+}
+
+/// `` `prefix-${T}` ``
+#[derive(Debug, PartialEq)]
+pub struct TsTplLitType<'a> {
+    pub quasis: Vec<'a, TplElement<'a>>,
+    pub types: Vec<'a, TsType<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsArrayType<'a> {
+    pub elem: Box<'a, TsType<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsTupleType<'a> {
+    pub elems: Vec<'a, TsTupleElement<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsTupleElement<'a> {
+    /// `[name: T]`
+    pub label: Option<Ident<'a>>,
+    pub ty: Box<'a, TsType<'a>>,
+    /// `[T?]`
+    pub optional: bool,
+    /// `[...T]`
+    pub rest: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsUnionType<'a> {
+    pub types: Vec<'a, TsType<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsIntersectionType<'a> {
+    pub types: Vec<'a, TsType<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsFnType<'a> {
+    pub type_params: Option<TsTypeParamDecl<'a>>,
+    pub params: Vec<'a, Pat<'a>>,
+    pub return_type: Box<'a, TsType<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsConstructorType<'a> {
+    pub is_abstract: bool,
+    pub type_params: Option<TsTypeParamDecl<'a>>,
+    pub params: Vec<'a, Pat<'a>>,
+    pub return_type: Box<'a, TsType<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsTypeLit<'a> {
+    pub members: Vec<'a, TsTypeElement<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsParenType<'a> {
+    pub ty: Box<'a, TsType<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TsTypeOperatorOp {
+    KeyOf,
+    Unique,
+    ReadOnly,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsTypeOperator<'a> {
+    pub op: TsTypeOperatorOp,
+    pub ty: Box<'a, TsType<'a>>,
+    pub span: Span,
+}
+
+/// `T[K]`
+#[derive(Debug, PartialEq)]
+pub struct TsIndexedAccessType<'a> {
+    pub obj: Box<'a, TsType<'a>>,
+    pub index: Box<'a, TsType<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsConditionalType<'a> {
+    pub check: Box<'a, TsType<'a>>,
+    pub extends: Box<'a, TsType<'a>>,
+    pub true_type: Box<'a, TsType<'a>>,
+    pub false_type: Box<'a, TsType<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsInferType<'a> {
+    pub param: TsTypeParam<'a>,
+    pub span: Span,
+}
+
+/// The `+`/`-` modifiers of `readonly` and `?` in mapped types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TruePlusMinus {
+    True,
+    Plus,
+    Minus,
+}
+
+/// `{ readonly [K in keyof T as N]?: T[K] }`
+#[derive(Debug, PartialEq)]
+pub struct TsMappedType<'a> {
+    pub readonly: Option<TruePlusMinus>,
+    /// `K in keyof T`, with `keyof T` as the constraint.
+    pub type_param: TsTypeParam<'a>,
+    pub name_type: Option<Box<'a, TsType<'a>>>,
+    pub optional: Option<TruePlusMinus>,
+    pub ty: Option<Box<'a, TsType<'a>>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TsTypeQueryExpr<'a> {
+    Entity(TsEntityName<'a>),
+    Import(TsImportType<'a>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsTypeQuery<'a> {
+    pub expr: TsTypeQueryExpr<'a>,
+    pub type_args: Option<TsTypeArgs<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsImportType<'a> {
+    pub arg: Str<'a>,
+    pub qualifier: Option<TsEntityName<'a>>,
+    pub type_args: Option<TsTypeArgs<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TsPredicateParam<'a> {
+    Ident(Ident<'a>),
+    This(Span),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsTypePredicate<'a> {
+    pub asserts: bool,
+    pub param: TsPredicateParam<'a>,
+    /// `None` for `asserts a`
+    pub ty: Option<Box<'a, TsType<'a>>>,
+    pub span: Span,
+}
+
+/// A member of an interface or a type literal.
+#[derive(Debug, PartialEq)]
+pub enum TsTypeElement<'a> {
+    Property(TsPropertySignature<'a>),
+    Method(TsMethodSignature<'a>),
+    Call(TsCallSignature<'a>),
+    Construct(TsConstructSignature<'a>),
+    Index(TsIndexSignature<'a>),
+    Getter(TsGetterSignature<'a>),
+    Setter(TsSetterSignature<'a>),
+    /// The tokens of a member that failed to parse, skipped during error recovery.
+    Invalid(Span),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsPropertySignature<'a> {
+    pub readonly: bool,
+    pub key: PropName<'a>,
+    pub optional: bool,
+    pub type_ann: Option<Box<'a, TsType<'a>>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsMethodSignature<'a> {
+    pub key: PropName<'a>,
+    pub optional: bool,
+    pub type_params: Option<TsTypeParamDecl<'a>>,
+    pub params: Vec<'a, Pat<'a>>,
+    pub return_type: Option<Box<'a, TsType<'a>>>,
+    pub span: Span,
+}
+
+/// `(a: T): U`
+#[derive(Debug, PartialEq)]
+pub struct TsCallSignature<'a> {
+    pub type_params: Option<TsTypeParamDecl<'a>>,
+    pub params: Vec<'a, Pat<'a>>,
+    pub return_type: Option<Box<'a, TsType<'a>>>,
+    pub span: Span,
+}
+
+/// `new (a: T): U`
+#[derive(Debug, PartialEq)]
+pub struct TsConstructSignature<'a> {
+    pub type_params: Option<TsTypeParamDecl<'a>>,
+    pub params: Vec<'a, Pat<'a>>,
+    pub return_type: Option<Box<'a, TsType<'a>>>,
+    pub span: Span,
+}
+
+/// `[key: string]: T`, in interfaces, type literals and classes.
+#[derive(Debug, PartialEq)]
+pub struct TsIndexSignature<'a> {
+    pub readonly: bool,
+    pub is_static: bool,
+    pub params: Vec<'a, Pat<'a>>,
+    pub type_ann: Option<Box<'a, TsType<'a>>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsGetterSignature<'a> {
+    pub key: PropName<'a>,
+    pub return_type: Option<Box<'a, TsType<'a>>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TsSetterSignature<'a> {
+    pub key: PropName<'a>,
+    pub param: Pat<'a>,
+    pub span: Span,
+}
+
+// ---------- JSX ----------
+
+#[derive(Debug, PartialEq)]
+pub struct JsxElement<'a> {
+    pub opening: JsxOpeningElement<'a>,
+    pub children: Vec<'a, JsxChild<'a>>,
+    /// `None` for a self-closing element like `<br />`.
+    pub closing: Option<JsxClosingElement<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct JsxOpeningElement<'a> {
+    pub name: JsxElementName<'a>,
+    /// `<Select<string> />`
+    pub type_args: Option<TsTypeArgs<'a>>,
+    pub attrs: Vec<'a, JsxAttrOrSpread<'a>>,
+    pub self_closing: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct JsxClosingElement<'a> {
+    pub name: JsxElementName<'a>,
+    pub span: Span,
+}
+
+/// The name of a tag. `this` in `<this.el>` is an [`Ident`] too.
+#[derive(Debug, PartialEq)]
+pub enum JsxElementName<'a> {
+    Ident(Ident<'a>),
+    /// `<a.b.c>`
+    Member(JsxMemberExpr<'a>),
+    /// `<svg:rect>`
+    Namespaced(JsxNamespacedName<'a>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct JsxMemberExpr<'a> {
+    pub object: Box<'a, JsxElementName<'a>>,
+    pub prop: Ident<'a>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct JsxNamespacedName<'a> {
+    pub ns: Ident<'a>,
+    pub name: Ident<'a>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum JsxAttrOrSpread<'a> {
+    Attr(JsxAttr<'a>),
+    /// `{...props}`
+    Spread(JsxSpreadAttr<'a>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct JsxAttr<'a> {
+    pub name: JsxAttrName<'a>,
+    /// `None` for a boolean attribute like `<input disabled />`.
+    pub value: Option<JsxAttrValue<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum JsxAttrName<'a> {
+    /// Names may contain `-`, as in `data-id`.
+    Ident(Ident<'a>),
+    /// `xlink:href`
+    Namespaced(JsxNamespacedName<'a>),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum JsxAttrValue<'a> {
+    /// A string without escapes: `value` is the text between the quotes.
+    Str(Str<'a>),
+    ExprContainer(JsxExprContainer<'a>),
+    Element(JsxElement<'a>),
+    Fragment(JsxFragment<'a>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct JsxSpreadAttr<'a> {
+    pub expr: Box<'a, Expr<'a>>,
+    pub span: Span,
+}
+
+/// `{expr}`, in attributes and among children.
+#[derive(Debug, PartialEq)]
+pub struct JsxExprContainer<'a> {
+    /// `None` for `{}`, which may hold only comments.
+    pub expr: Option<Box<'a, Expr<'a>>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum JsxChild<'a> {
+    Text(JsxText<'a>),
+    ExprContainer(JsxExprContainer<'a>),
+    /// `{...children}`
+    Spread(JsxSpreadChild<'a>),
+    Element(JsxElement<'a>),
+    Fragment(JsxFragment<'a>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct JsxText<'a> {
+    /// The text as written, whitespace included. HTML entities are not decoded.
+    pub value: &'a str,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct JsxSpreadChild<'a> {
+    pub expr: Box<'a, Expr<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct JsxFragment<'a> {
+    pub children: Vec<'a, JsxChild<'a>>,
+    pub span: Span,
+}
+
+// ---------- spans ----------
+
+impl Stmt<'_> {
+    pub fn span(&self) -> Span {
+        match self {
+            Stmt::Block(s) => s.span,
+            Stmt::Empty(span) | Stmt::Debugger(span) | Stmt::Invalid(span) => *span,
+            Stmt::Expr(s) => s.span,
+            Stmt::Var(s) => s.span,
+            Stmt::If(s) => s.span,
+            Stmt::For(s) => s.span,
+            Stmt::ForIn(s) => s.span,
+            Stmt::ForOf(s) => s.span,
+            Stmt::While(s) => s.span,
+            Stmt::DoWhile(s) => s.span,
+            Stmt::Return(s) => s.span,
+            Stmt::Break(s) => s.span,
+            Stmt::Continue(s) => s.span,
+            Stmt::Throw(s) => s.span,
+            Stmt::Try(s) => s.span,
+            Stmt::Switch(s) => s.span,
+            Stmt::Labeled(s) => s.span,
+            Stmt::With(s) => s.span,
+            Stmt::Fn(s) => s.function.span,
+            Stmt::Class(s) => s.class.span,
+            Stmt::Interface(s) => s.span,
+            Stmt::TypeAlias(s) => s.span,
+            Stmt::Enum(s) => s.span,
+            Stmt::Module(s) => s.span,
+            Stmt::Import(s) => s.span,
+            Stmt::ImportEquals(s) => s.span,
+            Stmt::ExportDecl(s) => s.span,
+            Stmt::ExportNamed(s) => s.span,
+            Stmt::ExportAll(s) => s.span,
+            Stmt::ExportDefault(s) => s.span,
+            Stmt::ExportAssign(s) => s.span,
+            Stmt::ExportAsNamespace(s) => s.span,
+        }
+    }
+}
+
+impl Expr<'_> {
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Ident(e) => e.span,
+            Expr::This(span) | Expr::Super(span) | Expr::Import(span) | Expr::Invalid(span) => {
+                *span
+            }
+            Expr::Lit(e) => e.span(),
+            Expr::Array(e) => e.span,
+            Expr::Object(e) => e.span,
+            Expr::Fn(e) => e.function.span,
+            Expr::Class(e) => e.class.span,
+            Expr::Arrow(e) => e.span,
+            Expr::Tpl(e) => e.span,
+            Expr::TaggedTpl(e) => e.span,
+            Expr::Unary(e) => e.span,
+            Expr::Update(e) => e.span,
+            Expr::Binary(e) => e.span,
+            Expr::Assign(e) => e.span,
+            Expr::Cond(e) => e.span,
+            Expr::Call(e) => e.span,
+            Expr::New(e) => e.span,
+            Expr::Member(e) => e.span,
+            Expr::Seq(e) => e.span,
+            Expr::Paren(e) => e.span,
+            Expr::Yield(e) => e.span,
+            Expr::Await(e) => e.span,
+            Expr::MetaProp(e) => e.span,
+            Expr::PrivateName(e) => e.span,
+            Expr::TsAs(e) => e.span,
+            Expr::TsSatisfies(e) => e.span,
+            Expr::TsConstAssertion(e) => e.span,
+            Expr::TsNonNull(e) => e.span,
+            Expr::TsTypeAssertion(e) => e.span,
+            Expr::TsInstantiation(e) => e.span,
+            Expr::JsxElement(e) => e.span,
+            Expr::JsxFragment(e) => e.span,
+        }
+    }
+}
+
+impl Lit<'_> {
+    pub fn span(&self) -> Span {
+        match self {
+            Lit::Str(l) => l.span,
+            Lit::Num(l) => l.span,
+            Lit::BigInt(l) => l.span,
+            Lit::Bool(l) => l.span,
+            Lit::Null(span) => *span,
+            Lit::Regex(l) => l.span,
+        }
+    }
+}
+
+impl PropName<'_> {
+    pub fn span(&self) -> Span {
+        match self {
+            PropName::Ident(p) => p.span,
+            PropName::Str(p) => p.span,
+            PropName::Num(p) => p.span,
+            PropName::BigInt(p) => p.span,
+            PropName::Computed(p) => p.span,
+            PropName::Private(p) => p.span,
+        }
+    }
+}
+
+impl Pat<'_> {
+    pub fn span(&self) -> Span {
+        match self {
+            Pat::Ident(p) => p.span,
+            Pat::Array(p) => p.span,
+            Pat::Object(p) => p.span,
+            Pat::Assign(p) => p.span,
+            Pat::Rest(p) => p.span,
+            Pat::Expr(e) => e.span(),
+        }
+    }
+}
+
+impl JsxElementName<'_> {
+    pub fn span(&self) -> Span {
+        match self {
+            JsxElementName::Ident(n) => n.span,
+            JsxElementName::Member(n) => n.span,
+            JsxElementName::Namespaced(n) => n.span,
+        }
+    }
+}
+
+impl TsEntityName<'_> {
+    pub fn span(&self) -> Span {
+        match self {
+            TsEntityName::Ident(i) => i.span,
+            TsEntityName::Qualified(q) => q.span,
+        }
+    }
+}
+
+impl TsType<'_> {
+    pub fn span(&self) -> Span {
+        match self {
+            TsType::Keyword(t) => t.span,
+            TsType::This(span) => *span,
+            TsType::Ref(t) => t.span,
+            TsType::Lit(t) => t.span,
+            TsType::Array(t) => t.span,
+            TsType::Tuple(t) => t.span,
+            TsType::Union(t) => t.span,
+            TsType::Intersection(t) => t.span,
+            TsType::Fn(t) => t.span,
+            TsType::Constructor(t) => t.span,
+            TsType::TypeLit(t) => t.span,
+            TsType::Paren(t) => t.span,
+            TsType::TypeOperator(t) => t.span,
+            TsType::IndexedAccess(t) => t.span,
+            TsType::Conditional(t) => t.span,
+            TsType::Infer(t) => t.span,
+            TsType::Mapped(t) => t.span,
+            TsType::Query(t) => t.span,
+            TsType::Import(t) => t.span,
+            TsType::Predicate(t) => t.span,
+        }
+    }
+}
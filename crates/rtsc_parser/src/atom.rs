@@ -0,0 +1,273 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::{BuildHasherDefault, Hash, Hasher},
+    ops::Deref,
+    ptr,
+    sync::OnceLock,
+};
+
+use crate::{Allocator, ContextualKeyword, Keyword};
+
+/// An interned string, like the name of an identifier or the value of a string
+/// literal, which lives as long as the [`Allocator`] of the parse it comes from.
+///
+/// The lexer hands out one atom per text, so atoms of the same parse are equal when
+/// they are the same atom. Atoms of different parses are told apart by a hash taken
+/// when they were interned, and only compare their text if it is the same.
+#[derive(Clone, Copy)]
+pub struct Atom<'a>(&'a AtomEntry<'a>);
+
+#[derive(Clone, Copy)]
+struct AtomEntry<'a> {
+    hash: u64,
+    // the position in `PREINTERNED`, or `NOT_PREINTERNED`
+    index: u32,
+    text: &'a str,
+}
+
+const NOT_PREINTERNED: u32 = u32::MAX;
+
+/// The literal words `null`, `true` and `false`, interned after the keywords.
+pub(crate) const LITERAL_WORDS: [&str; 3] = ["null", "true", "false"];
+
+const PREINTERNED_LEN: usize =
+    Keyword::ALL.len() + LITERAL_WORDS.len() + ContextualKeyword::ALL.len();
+
+/// The atoms of the words every interner starts with: the keywords in the order of
+/// [`Keyword::ALL`], the literal words and then the contextual keywords in the order of
+/// [`ContextualKeyword::ALL`]. Telling what a word is takes a look at this table by its
+/// atom.
+static PREINTERNED: [AtomEntry<'static>; PREINTERNED_LEN] = preinterned();
+
+const fn preinterned() -> [AtomEntry<'static>; PREINTERNED_LEN] {
+    let mut entries = [AtomEntry::new("", NOT_PREINTERNED); PREINTERNED_LEN];
+    let mut index = 0;
+    while index < PREINTERNED_LEN {
+        let text = if index < Keyword::ALL.len() {
+            Keyword::ALL[index].as_str()
+        } else if index < Keyword::ALL.len() + LITERAL_WORDS.len() {
+            LITERAL_WORDS[index - Keyword::ALL.len()]
+        } else {
+            ContextualKeyword::ALL[index - Keyword::ALL.len() - LITERAL_WORDS.len()].as_str()
+        };
+        entries[index] = AtomEntry::new(text, index as u32);
+        index += 1;
+    }
+    entries
+}
+
+impl<'a> AtomEntry<'a> {
+    const fn new(text: &'a str, index: u32) -> Self {
+        Self {
+            hash: hash(text),
+            index,
+            text,
+        }
+    }
+}
+
+/// The FNV-1a hash of `text`.
+const fn hash(text: &str) -> u64 {
+    let bytes = text.as_bytes();
+    let mut hash = 0xcbf2_9ce4_8422_2325;
+    let mut index = 0;
+    while index < bytes.len() {
+        hash ^= bytes[index] as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+        index += 1;
+    }
+    hash
+}
+
+/// FNV-1a for the hash maps of atoms too, which is faster than the default hasher for
+/// words as short as most names.
+#[derive(Default)]
+struct FnvHasher(Option<u64>);
+
+impl Hasher for FnvHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        let mut hash = self.0.unwrap_or(0xcbf2_9ce4_8422_2325);
+        for &byte in bytes {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        self.0 = Some(hash);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0.unwrap_or_default()
+    }
+}
+
+type AtomMap<'a> = HashMap<&'a str, Atom<'a>, BuildHasherDefault<FnvHasher>>;
+
+fn preinterned_atoms() -> &'static AtomMap<'static> {
+    static ATOMS: OnceLock<AtomMap<'static>> = OnceLock::new();
+    ATOMS.get_or_init(|| {
+        PREINTERNED
+            .iter()
+            .map(|entry| (entry.text, Atom(entry)))
+            .collect()
+    })
+}
+
+/// Hands out the atoms of a lexer, one per text. Texts of the source are borrowed from it, and
+/// only texts built while reading, like the value of a string with escapes, are
+/// copied into the allocator.
+pub struct Interner<'a> {
+    allocator: &'a Allocator,
+    atoms: AtomMap<'a>,
+}
+
+impl<'a> Interner<'a> {
+    pub fn new(allocator: &'a Allocator) -> Self {
+        Self {
+            allocator,
+            atoms: preinterned_atoms().clone(),
+        }
+    }
+
+    /// The atom of a text of the source.
+    pub fn intern(&mut self, text: &'a str) -> Atom<'a> {
+        if let Some(&atom) = self.atoms.get(text) {
+            return atom;
+        }
+        let atom = Atom(self.allocator.alloc(AtomEntry::new(text, NOT_PREINTERNED)));
+        self.atoms.insert(text, atom);
+        atom
+    }
+
+    /// The atom of a text which is not in the source, copied if it was not interned.
+    pub fn intern_owned(&mut self, text: &str) -> Atom<'a> {
+        match self.atoms.get(text) {
+            Some(&atom) => atom,
+            None => self.intern(self.allocator.alloc_str(text)),
+        }
+    }
+}
+
+impl<'a> Atom<'a> {
+    /// An atom of `text` outside of any lexer, like for a name given to a node built by
+    /// hand.
+    pub fn new_in(text: &str, allocator: &'a Allocator) -> Self {
+        match preinterned_atoms().get(text) {
+            Some(&atom) => atom,
+            None => {
+                let text = allocator.alloc_str(text);
+                Atom(allocator.alloc(AtomEntry::new(text, NOT_PREINTERNED)))
+            }
+        }
+    }
+
+    /// The atom of a word every interner starts with, like a keyword.
+    pub(crate) fn preinterned(text: &str) -> Option<Atom<'static>> {
+        preinterned_atoms().get(text).copied()
+    }
+
+    pub fn as_str(&self) -> &'a str {
+        self.0.text
+    }
+
+    /// The position of the atom in the table of the words every interner starts with,
+    /// past the end of it for other atoms.
+    pub(crate) fn index(&self) -> usize {
+        self.0.index as usize
+    }
+}
+
+impl PartialEq for Atom<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(self.0, other.0) || (self.0.hash == other.0.hash && self.0.text == other.0.text)
+    }
+}
+
+impl Eq for Atom<'_> {}
+
+impl Hash for Atom<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.0.hash);
+    }
+}
+
+impl Deref for Atom<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for Atom<'_> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq<str> for Atom<'_> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Atom<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl fmt::Debug for Atom<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for Atom<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interns() {
+        let allocator = Allocator::new();
+        let source = "interned interned other";
+        let mut interner = Interner::new(&allocator);
+        let a = interner.intern(&source[..8]);
+        let b = interner.intern(&source[9..17]);
+        assert!(ptr::eq(a.0, b.0));
+        // the text is borrowed from the source
+        assert!(ptr::eq(a.as_str(), &source[..8]));
+        assert!(ptr::eq(interner.intern_owned("interned").0, a.0));
+        assert_ne!(a, interner.intern(&source[18..]));
+        assert_eq!(a, "interned");
+        assert_eq!(format!("{a} {a:?}"), "interned \"interned\"");
+
+        // atoms of another parse are equal by their text
+        let other = Allocator::new();
+        assert_eq!(Atom::new_in("interned", &other), a);
+        assert_ne!(Atom::new_in("internee", &other), a);
+    }
+
+    #[test]
+    fn keywords_are_preinterned() {
+        let allocator = Allocator::new();
+        let mut interner = Interner::new(&allocator);
+        for (index, keyword) in Keyword::ALL.iter().enumerate() {
+            assert_eq!(interner.intern(keyword.as_str()).index(), index);
+        }
+        let start = Keyword::ALL.len() + LITERAL_WORDS.len();
+        for (index, keyword) in ContextualKeyword::ALL.iter().enumerate() {
+            assert_eq!(
+                interner.intern_owned(keyword.as_str()).index(),
+                start + index
+            );
+        }
+        assert_eq!(Atom::new_in("this", &allocator).index(), 25);
+        assert!(interner.intern("notakeyword").index() >= PREINTERNED_LEN);
+    }
+}
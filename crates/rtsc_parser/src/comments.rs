@@ -0,0 +1,310 @@
+//! The comments of a program, kept in a side table next to the AST and built by
+//! [`Parser::parse_with_comments`](crate::Parser::parse_with_comments).
+//!
+//! Every comment is attached to the node nearest to it. A comment after a node on the
+//! same line, with nothing but a line break after it, trails that node. Other comments
+//! lead the node after them, and a comment with no node after it in the same parent,
+//! like the last one in a block, trails the node before it. A comment in a node that
+//! has no other children, like an empty block, dangles in that node.
+
+mod jsdoc;
+
+use std::collections::HashMap;
+
+pub use jsdoc::{plain_text, JsDoc, JsDocLink, JsDocTag, JsDocTagKind, JsDocText};
+
+use crate::{
+    ast::Program,
+    cst::{CollectNodes, SyntaxKind},
+    is_line_terminator, is_whitespace, Span,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentKind {
+    /// `// ...`
+    Line,
+    /// `/* ... */`
+    Block,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentPlacement {
+    Leading,
+    Trailing,
+    Dangling,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    pub kind: CommentKind,
+    /// The span of the comment with its delimiters, without the line break after a
+    /// line comment.
+    pub span: Span,
+    /// The text between the delimiters.
+    pub text: String,
+    pub placement: CommentPlacement,
+    /// The kind and span of the node the comment is attached to.
+    pub node: (SyntaxKind, Span),
+}
+
+impl Comment {
+    /// Whether this is a `/** ... */` comment. `/**/` is not one.
+    pub fn is_jsdoc(&self) -> bool {
+        self.kind == CommentKind::Block && self.text.starts_with('*')
+    }
+
+    pub fn jsdoc(&self) -> Option<JsDoc> {
+        self.is_jsdoc()
+            .then(|| JsDoc::parse(&self.text[1..], self.span.start + 3))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Comments {
+    /// Every comment in source order.
+    comments: Vec<Comment>,
+    /// The indices of the comments attached to the nodes with a span.
+    attached: HashMap<Span, Vec<usize>>,
+}
+
+impl Comments {
+    /// Reads the comments at `spans`, which are in source order, and attaches them to
+    /// the nodes of `program`.
+    pub(crate) fn new(source: &str, spans: &[Span], program: &Program) -> Self {
+        let mut nodes = vec![];
+        program.collect_nodes(&mut nodes);
+        // the program is the root below, and placeholders for missing nodes are no
+        // place for a comment
+        nodes.retain(|(kind, span)| *kind != SyntaxKind::Program && span.start != span.end);
+        nodes.sort_by_key(|(_, span)| (span.start, std::cmp::Reverse(span.end)));
+        let mut nodes = nodes.into_iter().peekable();
+
+        let root = (SyntaxKind::Program, program.span);
+        let mut stack = vec![Frame {
+            node: root,
+            last_child: None,
+        }];
+        let mut comments = Self::default();
+        for &span in spans {
+            let (kind, span, text) = read_comment(source, span);
+            while let Some(&(kind, node)) = nodes.peek() {
+                if node.start >= span.start {
+                    break;
+                }
+                nodes.next();
+                close_frames(&mut stack, node.start);
+                // nodes which overlap the end of their parent cannot be nested
+                if node.end <= stack.last().unwrap().node.1.end {
+                    stack.push(Frame {
+                        node: (kind, node),
+                        last_child: None,
+                    });
+                }
+            }
+            close_frames(&mut stack, span.start);
+
+            let parent = stack.last().unwrap();
+            let prev = parent.last_child;
+            let next = nodes
+                .peek()
+                .copied()
+                .filter(|(_, node)| node.end <= parent.node.1.end);
+            let (placement, node) = match (prev, next) {
+                (Some(prev), _)
+                    if !source[prev.1.end..span.start].contains(is_line_terminator)
+                        && ends_line(&source[span.end..]) =>
+                {
+                    (CommentPlacement::Trailing, prev)
+                }
+                (_, Some(next)) => (CommentPlacement::Leading, next),
+                (Some(prev), None) => (CommentPlacement::Trailing, prev),
+                (None, None) => (CommentPlacement::Dangling, parent.node),
+            };
+            comments
+                .attached
+                .entry(node.1)
+                .or_default()
+                .push(comments.comments.len());
+            comments.comments.push(Comment {
+                kind,
+                span,
+                text,
+                placement,
+                node,
+            });
+        }
+        comments
+    }
+
+    pub fn len(&self) -> usize {
+        self.comments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.comments.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Comment> {
+        self.comments.iter()
+    }
+
+    /// The comment with exactly this span.
+    pub fn get(&self, span: Span) -> Option<&Comment> {
+        let i = self
+            .comments
+            .binary_search_by_key(&span.start, |comment| comment.span.start)
+            .ok()?;
+        Some(&self.comments[i]).filter(|comment| comment.span == span)
+    }
+
+    /// The comments attached to the node with this span, in source order.
+    pub fn attached(&self, node: Span) -> impl Iterator<Item = &Comment> {
+        self.attached
+            .get(&node)
+            .into_iter()
+            .flatten()
+            .map(|&i| &self.comments[i])
+    }
+
+    pub fn leading(&self, node: Span) -> impl Iterator<Item = &Comment> {
+        self.attached(node)
+            .filter(|comment| comment.placement == CommentPlacement::Leading)
+    }
+
+    pub fn trailing(&self, node: Span) -> impl Iterator<Item = &Comment> {
+        self.attached(node)
+            .filter(|comment| comment.placement == CommentPlacement::Trailing)
+    }
+
+    /// The JSDoc of the node with this span: the last JSDoc comment before it.
+    pub fn jsdoc(&self, node: Span) -> Option<JsDoc> {
+        self.leading(node)
+            .filter(|comment| comment.is_jsdoc())
+            .last()?
+            .jsdoc()
+    }
+}
+
+struct Frame {
+    node: (SyntaxKind, Span),
+    /// The last child which ended before the current position.
+    last_child: Option<(SyntaxKind, Span)>,
+}
+
+/// Closes the innermost nodes which end at or before `pos`. The root stays open.
+fn close_frames(stack: &mut Vec<Frame>, pos: usize) {
+    while stack.len() > 1 && stack.last().unwrap().node.1.end <= pos {
+        let frame = stack.pop().unwrap();
+        stack.last_mut().unwrap().last_child = Some(frame.node);
+    }
+}
+
+fn read_comment(source: &str, span: Span) -> (CommentKind, Span, String) {
+    let comment = &source[span.start..span.end];
+    let line = ["//", "<!--", "-->"]
+        .iter()
+        .find_map(|open| Some((open.len(), comment.strip_prefix(open)?)));
+    if let Some((open, text)) = line {
+        let text = text.trim_end_matches(is_line_terminator);
+        let span = Span::new(span.start, span.start + open + text.len());
+        (CommentKind::Line, span, text.to_string())
+    } else {
+        let text = &comment[2..];
+        let text = text.strip_suffix("*/").unwrap_or(text);
+        (CommentKind::Block, span, text.to_string())
+    }
+}
+
+/// Whether only whitespace and comments come before the end of the line.
+fn ends_line(mut text: &str) -> bool {
+    loop {
+        text = text.trim_start_matches(is_whitespace);
+        if text.is_empty() || text.starts_with(is_line_terminator) || text.starts_with("//") {
+            return true;
+        }
+        let Some(rest) = text.strip_prefix("/*") else {
+            return false;
+        };
+        match rest.find("*/") {
+            Some(end) if !rest[..end].contains(is_line_terminator) => text = &rest[end + 2..],
+            _ => return true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_with_comments, Allocator};
+
+    use super::*;
+
+    fn attachments(source: &str) -> Vec<(&str, CommentPlacement, SyntaxKind, &str)> {
+        let allocator = Allocator::new();
+        let (_, comments, errors) = parse_with_comments(&allocator, source);
+        assert!(errors.is_empty(), "{errors:?}");
+        comments
+            .iter()
+            .map(|comment| {
+                let (kind, node) = comment.node;
+                (
+                    &source[comment.span.start..comment.span.end],
+                    comment.placement,
+                    kind,
+                    &source[node.start..node.end],
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reads_comments() {
+        let allocator = Allocator::new();
+        let source = "// line\r\nlet a = /* block */ 1;\n/**/";
+        let (_, comments, _) = parse_with_comments(&allocator, source);
+        let comments = comments.iter().collect::<Vec<_>>();
+        assert_eq!(
+            comments
+                .iter()
+                .map(|comment| (comment.kind, comment.span, comment.text.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                (CommentKind::Line, Span::new(0, 7), " line"),
+                (CommentKind::Block, Span::new(17, 28), " block "),
+                (CommentKind::Block, Span::new(32, 36), ""),
+            ]
+        );
+        assert!(!comments[2].is_jsdoc());
+    }
+
+    #[test]
+    fn attaches_to_nearest_node() {
+        use CommentPlacement::*;
+        assert_eq!(
+            attachments("// a\nlet x = 1; // b\nf(/* c */ y);\n{\n  // d\n}\nz; /* e */ w;\n// f"),
+            [
+                ("// a", Leading, SyntaxKind::VarDecl, "let x = 1;"),
+                ("// b", Trailing, SyntaxKind::VarDecl, "let x = 1;"),
+                ("/* c */", Leading, SyntaxKind::Ident, "y"),
+                ("// d", Dangling, SyntaxKind::BlockStmt, "{\n  // d\n}"),
+                ("/* e */", Leading, SyntaxKind::ExprStmt, "w;"),
+                ("// f", Trailing, SyntaxKind::ExprStmt, "w;"),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_jsdoc_of_declarations() {
+        let allocator = Allocator::new();
+        let source = "/** @deprecated use g */\nfunction f() {}\nclass C {\n  /** The size. */\n  size = 1;\n}";
+        let (program, comments, errors) = parse_with_comments(&allocator, source);
+        assert!(errors.is_empty(), "{errors:?}");
+        let doc = comments.jsdoc(program.body[0].span()).unwrap();
+        assert!(doc.is_deprecated());
+
+        let size = source.find("size =").unwrap();
+        let prop = Span::new(size, size + "size = 1;".len());
+        let doc = comments.jsdoc(prop).unwrap();
+        assert_eq!(plain_text(&doc.description), "The size.");
+        assert!(comments.jsdoc(program.body[1].span()).is_none());
+    }
+}
@@ -0,0 +1,472 @@
+//! A parser for JSDoc comments, `/** ... */`.
+//!
+//! Only the structure of a comment is parsed: types are kept as the text between the
+//! braces, and tags the parser does not know are kept with their text.
+
+use crate::{is_line_terminator, Span};
+
+/// A parsed JSDoc comment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsDoc {
+    /// The text before the first tag.
+    pub description: Vec<JsDocText>,
+    pub tags: Vec<JsDocTag>,
+}
+
+/// A run of text in a description, which may contain inline links.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsDocText {
+    Text(String),
+    Link(JsDocLink),
+}
+
+/// An inline `{@link target}` or `{@link target|text}`. `{@linkcode}` and
+/// `{@linkplain}` are read the same way.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsDocLink {
+    /// The name of the tag, like `link`.
+    pub tag: String,
+    /// The name or URL linked to.
+    pub target: String,
+    /// The text to show instead of the target.
+    pub text: Option<String>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsDocTag {
+    /// The name of the tag without the `@`, like `param`.
+    pub name: String,
+    pub kind: JsDocTagKind,
+    /// The span from the `@` to the end of the tag's text.
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsDocTagKind {
+    /// `@param {type} name description`, also written `@arg` or `@argument`. An
+    /// optional parameter is written as `[name]` or `[name=default]`.
+    Param {
+        ty: Option<String>,
+        name: String,
+        optional: bool,
+        default: Option<String>,
+        description: Vec<JsDocText>,
+    },
+    /// `@returns {type} description`, also written `@return`.
+    Returns {
+        ty: Option<String>,
+        description: Vec<JsDocText>,
+    },
+    /// `@type {type}`
+    Type { ty: String },
+    /// `@template {constraint} T, U description`
+    Template {
+        constraint: Option<String>,
+        names: Vec<String>,
+        description: Vec<JsDocText>,
+    },
+    /// `@deprecated description`
+    Deprecated { description: Vec<JsDocText> },
+    /// Any other tag, with the text after its name.
+    Unknown { description: Vec<JsDocText> },
+}
+
+impl JsDoc {
+    /// Parses the text of a JSDoc comment between `/**` and `*/`, where `offset` is
+    /// the position of the text in the source.
+    pub fn parse(text: &str, offset: usize) -> Self {
+        let text = CommentText::new(text, offset);
+        let mut sections = text.tag_starts();
+        let description_end = sections.first().copied().unwrap_or(text.len());
+        sections.push(text.len());
+        let tags = sections
+            .windows(2)
+            .map(|range| text.parse_tag(range[0], range[1]))
+            .collect();
+        Self {
+            description: text.parse_text(0, description_end),
+            tags,
+        }
+    }
+
+    /// The `@deprecated` tag, if there is one.
+    pub fn deprecated(&self) -> Option<&JsDocTag> {
+        self.tags
+            .iter()
+            .find(|tag| matches!(tag.kind, JsDocTagKind::Deprecated { .. }))
+    }
+
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecated().is_some()
+    }
+}
+
+/// The text of a description with links replaced by their text, or their target if
+/// they have none.
+pub fn plain_text(description: &[JsDocText]) -> String {
+    description
+        .iter()
+        .map(|part| match part {
+            JsDocText::Text(text) => text.as_str(),
+            JsDocText::Link(link) => link.text.as_deref().unwrap_or(&link.target),
+        })
+        .collect()
+}
+
+/// The lines of a comment without the leading `*` of each line, joined by `\n`, along
+/// with the source offset of every byte.
+struct CommentText {
+    text: String,
+    offsets: Vec<usize>,
+}
+
+impl CommentText {
+    fn new(source: &str, offset: usize) -> Self {
+        let mut text = String::new();
+        let mut offsets = vec![];
+        let mut line_start = 0;
+        let mut lines = vec![];
+        for (i, c) in source.char_indices() {
+            // `\r\n` ends a line at the `\n`, with the `\r` stripped below
+            if is_line_terminator(c) && !source[i + c.len_utf8()..].starts_with('\n') {
+                lines.push((line_start, i));
+                line_start = i + c.len_utf8();
+            }
+        }
+        lines.push((line_start, source.len()));
+
+        for (n, &(start, end)) in lines.iter().enumerate() {
+            let line = source[start..end].trim_end_matches(is_line_terminator);
+            let mut content = line.trim_start();
+            // the first line follows `/**`, the others start with a `*` and a space
+            if n > 0 {
+                if let Some(rest) = content.strip_prefix('*') {
+                    content = rest.strip_prefix(' ').unwrap_or(rest);
+                }
+                text.push('\n');
+                offsets.push(offset + start);
+            }
+            let content_start = start + line.len() - content.len();
+            text.push_str(content);
+            offsets.extend((0..content.len()).map(|i| offset + content_start + i));
+        }
+        Self { text, offsets }
+    }
+
+    fn len(&self) -> usize {
+        self.text.len()
+    }
+
+    fn span(&self, start: usize, end: usize) -> Span {
+        if start == end {
+            let offset = self
+                .offsets
+                .get(start)
+                .copied()
+                .unwrap_or_else(|| self.offsets.last().map_or(0, |offset| offset + 1));
+            return Span::new(offset, offset);
+        }
+        Span::new(self.offsets[start], self.offsets[end - 1] + 1)
+    }
+
+    /// Where the tags start: at an `@` that begins a line, ignoring whitespace.
+    fn tag_starts(&self) -> Vec<usize> {
+        let mut starts = vec![];
+        let mut line_start = 0;
+        for line in self.text.split('\n') {
+            let content = line.trim_start();
+            let at = line_start + line.len() - content.len();
+            if content.starts_with('@') && content[1..].starts_with(is_tag_name_char) {
+                starts.push(at);
+            }
+            line_start += line.len() + 1;
+        }
+        starts
+    }
+
+    /// Trims whitespace from both ends of a range.
+    fn trim(&self, mut start: usize, mut end: usize) -> (usize, usize) {
+        let text = &self.text[start..end];
+        start += text.len() - text.trim_start().len();
+        end -= text.len() - text.trim_end().len();
+        (start, end.max(start))
+    }
+
+    fn parse_tag(&self, start: usize, end: usize) -> JsDocTag {
+        let (_, end) = self.trim(start, end);
+        let name_end = self.text[start + 1..end]
+            .find(|c| !is_tag_name_char(c))
+            .map_or(end, |len| start + 1 + len);
+        let name = self.text[start + 1..name_end].to_string();
+        let mut pos = name_end;
+        let kind = match name.as_str() {
+            "param" | "arg" | "argument" => {
+                let ty = self.braced(&mut pos, end);
+                let (name, optional, default) = self.param_name(&mut pos, end);
+                JsDocTagKind::Param {
+                    ty,
+                    name,
+                    optional,
+                    default,
+                    description: self.parse_text(pos, end),
+                }
+            }
+            "returns" | "return" => JsDocTagKind::Returns {
+                ty: self.braced(&mut pos, end),
+                description: self.parse_text(pos, end),
+            },
+            "type" => {
+                let ty = self.braced(&mut pos, end).unwrap_or_else(|| {
+                    let (start, end) = self.trim(pos, end);
+                    self.text[start..end].to_string()
+                });
+                JsDocTagKind::Type { ty }
+            }
+            "template" => {
+                let constraint = self.braced(&mut pos, end);
+                let mut names = vec![];
+                loop {
+                    let name = self.word(&mut pos, end, |c| c == ',');
+                    if name.is_empty() {
+                        break;
+                    }
+                    names.push(name.to_string());
+                    let (next, _) = self.trim(pos, end);
+                    if !self.text[next..end].starts_with(',') {
+                        break;
+                    }
+                    pos = next + 1;
+                }
+                JsDocTagKind::Template {
+                    constraint,
+                    names,
+                    description: self.parse_text(pos, end),
+                }
+            }
+            "deprecated" => JsDocTagKind::Deprecated {
+                description: self.parse_text(pos, end),
+            },
+            _ => JsDocTagKind::Unknown {
+                description: self.parse_text(pos, end),
+            },
+        };
+        JsDocTag {
+            name,
+            kind,
+            span: self.span(start, end),
+        }
+    }
+
+    /// Reads a type in braces, which may contain braces of its own.
+    fn braced(&self, pos: &mut usize, end: usize) -> Option<String> {
+        let (start, _) = self.trim(*pos, end);
+        if !self.text[start..end].starts_with('{') {
+            return None;
+        }
+        let mut depth = 0;
+        for (i, c) in self.text[start..end].char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                *pos = start + i + 1;
+                return Some(self.text[start + 1..start + i].trim().to_string());
+            }
+        }
+        // an unclosed brace takes the rest of the tag
+        *pos = end;
+        Some(self.text[start + 1..end].trim().to_string())
+    }
+
+    /// Reads a parameter name, which is optional if it is in brackets.
+    fn param_name(&self, pos: &mut usize, end: usize) -> (String, bool, Option<String>) {
+        let (start, _) = self.trim(*pos, end);
+        let Some(rest) = self.text[start..end].strip_prefix('[') else {
+            return (self.word(pos, end, |_| false).to_string(), false, None);
+        };
+        let close = rest.find(']').unwrap_or(rest.len());
+        *pos = (start + 1 + close + 1).min(end);
+        let (name, default) = match rest[..close].split_once('=') {
+            Some((name, default)) => (name, Some(default.trim().to_string())),
+            None => (&rest[..close], None),
+        };
+        (name.trim().to_string(), true, default)
+    }
+
+    /// Reads a run of characters up to whitespace or `stop`.
+    fn word(&self, pos: &mut usize, end: usize, stop: impl Fn(char) -> bool) -> &str {
+        let (start, _) = self.trim(*pos, end);
+        let len = self.text[start..end]
+            .find(|c: char| c.is_whitespace() || stop(c))
+            .unwrap_or(end - start);
+        *pos = start + len;
+        &self.text[start..start + len]
+    }
+
+    /// Splits text into plain runs and inline links.
+    fn parse_text(&self, start: usize, end: usize) -> Vec<JsDocText> {
+        let (mut pos, end) = self.trim(start, end);
+        let mut parts = vec![];
+        let mut text_start = pos;
+        while let Some(i) = self.text[pos..end].find("{@") {
+            let link_start = pos + i;
+            let Some(close) = self.text[link_start..end].find('}') else {
+                break;
+            };
+            let link_end = link_start + close + 1;
+            let inner = &self.text[link_start + 2..link_end - 1];
+            let tag_len = inner.find(|c| !is_tag_name_char(c)).unwrap_or(inner.len());
+            let tag = &inner[..tag_len];
+            if !matches!(tag, "link" | "linkcode" | "linkplain") {
+                pos = link_start + 2;
+                continue;
+            }
+            if text_start < link_start {
+                parts.push(JsDocText::Text(
+                    self.text[text_start..link_start].to_string(),
+                ));
+            }
+            let rest = inner[tag_len..].trim();
+            let (target, text) = match rest.find(|c: char| c == '|' || c.is_whitespace()) {
+                Some(i) => {
+                    let text = rest[i..].trim_start();
+                    (
+                        &rest[..i],
+                        Some(text.strip_prefix('|').unwrap_or(text).trim()),
+                    )
+                }
+                None => (rest, None),
+            };
+            parts.push(JsDocText::Link(JsDocLink {
+                tag: tag.to_string(),
+                target: target.to_string(),
+                text: text.filter(|text| !text.is_empty()).map(str::to_string),
+                span: self.span(link_start, link_end),
+            }));
+            pos = link_end;
+            text_start = link_end;
+        }
+        if text_start < end {
+            parts.push(JsDocText::Text(self.text[text_start..end].to_string()));
+        }
+        parts
+    }
+}
+
+fn is_tag_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(text: &str) -> JsDocText {
+        JsDocText::Text(text.to_string())
+    }
+
+    #[test]
+    fn description_and_tags() {
+        let doc = JsDoc::parse(
+            "\n * Adds two numbers.\n *\n * @param {number} a - the first\n * @param {number} [b=1] the second,\n *   continued\n * @returns {number} the sum\n ",
+            3,
+        );
+        assert_eq!(doc.description, [text("Adds two numbers.")]);
+        assert_eq!(
+            doc.tags.iter().map(|tag| &tag.kind).collect::<Vec<_>>(),
+            [
+                &JsDocTagKind::Param {
+                    ty: Some("number".to_string()),
+                    name: "a".to_string(),
+                    optional: false,
+                    default: None,
+                    description: vec![text("- the first")],
+                },
+                &JsDocTagKind::Param {
+                    ty: Some("number".to_string()),
+                    name: "b".to_string(),
+                    optional: true,
+                    default: Some("1".to_string()),
+                    description: vec![text("the second,\n  continued")],
+                },
+                &JsDocTagKind::Returns {
+                    ty: Some("number".to_string()),
+                    description: vec![text("the sum")],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn type_template_and_deprecated() {
+        let doc = JsDoc::parse(" @type {{ a: Array<T> }} ", 3);
+        assert_eq!(
+            doc.tags[0].kind,
+            JsDocTagKind::Type {
+                ty: "{ a: Array<T> }".to_string()
+            }
+        );
+
+        let doc = JsDoc::parse(
+            "\n * @template {string} K, V the keys\n * @deprecated Use {@link other} instead.\n * @since 1.0\n ",
+            3,
+        );
+        assert_eq!(
+            doc.tags[0].kind,
+            JsDocTagKind::Template {
+                constraint: Some("string".to_string()),
+                names: vec!["K".to_string(), "V".to_string()],
+                description: vec![text("the keys")],
+            }
+        );
+        assert!(doc.is_deprecated());
+        assert_eq!(doc.tags[2].name, "since");
+        assert_eq!(
+            doc.tags[2].kind,
+            JsDocTagKind::Unknown {
+                description: vec![text("1.0")]
+            }
+        );
+    }
+
+    #[test]
+    fn inline_links() {
+        let source = "/** See {@link Foo.bar | the bar} and {@linkcode baz}. */";
+        let doc = JsDoc::parse(&source[3..source.len() - 2], 3);
+        let link = |target: &str, text: Option<&str>, tag: &str, span| {
+            JsDocText::Link(JsDocLink {
+                tag: tag.to_string(),
+                target: target.to_string(),
+                text: text.map(str::to_string),
+                span,
+            })
+        };
+        assert_eq!(
+            doc.description,
+            [
+                text("See "),
+                link("Foo.bar", Some("the bar"), "link", Span::new(8, 33)),
+                text(" and "),
+                link("baz", None, "linkcode", Span::new(38, 53)),
+                text("."),
+            ]
+        );
+        assert_eq!(&source[8..33], "{@link Foo.bar | the bar}");
+        assert_eq!(plain_text(&doc.description), "See the bar and baz.");
+    }
+
+    #[test]
+    fn tag_spans() {
+        let source = "/**\r\n * @deprecated\r\n * @param x\r\n */";
+        let doc = JsDoc::parse(&source[3..source.len() - 2], 3);
+        let spans = doc
+            .tags
+            .iter()
+            .map(|tag| &source[tag.span.start..tag.span.end])
+            .collect::<Vec<_>>();
+        assert_eq!(spans, ["@deprecated", "@param x"]);
+    }
+}
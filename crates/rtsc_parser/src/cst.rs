@@ -0,0 +1,647 @@
+//! A lossless concrete syntax tree, built by [`Parser::parse_cst`](crate::Parser::parse_cst).
+//!
+//! The green tree is immutable and knows nothing about positions: a [`GreenNode`] only
+//! holds its kind, its width and its children, so subtrees can be shared. Every byte of
+//! the source belongs to exactly one [`GreenToken`], either as its text or as part of
+//! its leading or trailing [`Trivia`], so printing the tree gives back the source.
+//!
+//! The red tree of [`SyntaxNode`]s and [`SyntaxToken`]s is a cheap view on top of it,
+//! which adds parent pointers and absolute offsets.
+//!
+//! Trivia on the same line as a token, up to the line break, is trailing trivia of
+//! that token. Everything from the line break on is leading trivia of the next token,
+//! and trivia at the end of the file is leading trivia of the `Eof` token.
+
+mod syntax_kind;
+
+use std::{fmt, rc::Rc, sync::Arc};
+
+pub use syntax_kind::SyntaxKind;
+pub(crate) use syntax_kind::{CollectNodes, NodeSink};
+
+use crate::{ast::Program, is_line_terminator, is_whitespace, Span, Token, TokenKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    /// Spaces, tabs and other whitespace, except line terminators.
+    Whitespace,
+    /// A single line terminator, where `\r\n` counts as one.
+    Newline,
+    SingleLineComment,
+    MultiLineComment,
+    /// Source text the lexer could not make a token of.
+    Skipped,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GreenToken<'a> {
+    kind: TokenKind<'a>,
+    text: String,
+    leading: Vec<Trivia>,
+    trailing: Vec<Trivia>,
+}
+
+impl<'a> GreenToken<'a> {
+    pub fn new(
+        kind: TokenKind<'a>,
+        text: String,
+        leading: Vec<Trivia>,
+        trailing: Vec<Trivia>,
+    ) -> Self {
+        Self {
+            kind,
+            text,
+            leading,
+            trailing,
+        }
+    }
+
+    pub fn kind(&self) -> &TokenKind<'a> {
+        &self.kind
+    }
+
+    /// The text of the token without its trivia.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn leading_trivia(&self) -> &[Trivia] {
+        &self.leading
+    }
+
+    pub fn trailing_trivia(&self) -> &[Trivia] {
+        &self.trailing
+    }
+
+    fn leading_len(&self) -> usize {
+        self.leading.iter().map(|trivia| trivia.text.len()).sum()
+    }
+
+    /// The length of the token including its trivia.
+    pub fn full_len(&self) -> usize {
+        self.leading_len()
+            + self.text.len()
+            + self
+                .trailing
+                .iter()
+                .map(|trivia| trivia.text.len())
+                .sum::<usize>()
+    }
+}
+
+impl fmt::Display for GreenToken<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for trivia in &self.leading {
+            f.write_str(&trivia.text)?;
+        }
+        f.write_str(&self.text)?;
+        for trivia in &self.trailing {
+            f.write_str(&trivia.text)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GreenElement<'a> {
+    Node(Arc<GreenNode<'a>>),
+    Token(Arc<GreenToken<'a>>),
+}
+
+impl<'a> GreenElement<'a> {
+    pub fn full_len(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.full_len(),
+            GreenElement::Token(token) => token.full_len(),
+        }
+    }
+}
+
+impl fmt::Display for GreenElement<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GreenElement::Node(node) => node.fmt(f),
+            GreenElement::Token(token) => token.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GreenNode<'a> {
+    kind: SyntaxKind,
+    full_len: usize,
+    children: Vec<GreenElement<'a>>,
+}
+
+impl<'a> GreenNode<'a> {
+    pub fn new(kind: SyntaxKind, children: Vec<GreenElement<'a>>) -> Self {
+        Self {
+            kind,
+            full_len: children.iter().map(GreenElement::full_len).sum(),
+            children,
+        }
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    /// The length of the node's text including all trivia.
+    pub fn full_len(&self) -> usize {
+        self.full_len
+    }
+
+    pub fn children(&self) -> &[GreenElement<'a>] {
+        &self.children
+    }
+}
+
+impl fmt::Display for GreenNode<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for child in &self.children {
+            child.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A node of the red tree: a [`GreenNode`] at a known offset in a known parent.
+/// Cloning one is cheap.
+#[derive(Clone)]
+pub struct SyntaxNode<'a>(Rc<NodeData<'a>>);
+
+struct NodeData<'a> {
+    green: Arc<GreenNode<'a>>,
+    parent: Option<SyntaxNode<'a>>,
+    // offset of the node's text including leading trivia
+    offset: usize,
+}
+
+impl<'a> SyntaxNode<'a> {
+    pub fn new_root(green: GreenNode<'a>) -> Self {
+        Self(Rc::new(NodeData {
+            green: Arc::new(green),
+            parent: None,
+            offset: 0,
+        }))
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.green.kind
+    }
+
+    pub fn green(&self) -> &Arc<GreenNode<'a>> {
+        &self.0.green
+    }
+
+    pub fn parent(&self) -> Option<SyntaxNode<'a>> {
+        self.0.parent.clone()
+    }
+
+    /// The node itself, then its parent, and so on up to the root.
+    pub fn ancestors(&self) -> impl Iterator<Item = SyntaxNode<'a>> {
+        std::iter::successors(Some(self.clone()), SyntaxNode::parent)
+    }
+
+    pub fn children_with_tokens(&self) -> Vec<SyntaxElement<'a>> {
+        let mut offset = self.0.offset;
+        self.0
+            .green
+            .children
+            .iter()
+            .map(|child| {
+                let element = match child {
+                    GreenElement::Node(green) => {
+                        SyntaxElement::Node(SyntaxNode(Rc::new(NodeData {
+                            green: green.clone(),
+                            parent: Some(self.clone()),
+                            offset,
+                        })))
+                    }
+                    GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken {
+                        green: green.clone(),
+                        parent: self.clone(),
+                        offset,
+                    }),
+                };
+                offset += child.full_len();
+                element
+            })
+            .collect()
+    }
+
+    pub fn children(&self) -> impl Iterator<Item = SyntaxNode<'a>> {
+        self.children_with_tokens()
+            .into_iter()
+            .filter_map(|element| match element {
+                SyntaxElement::Node(node) => Some(node),
+                SyntaxElement::Token(_) => None,
+            })
+    }
+
+    /// The node and every node below it, parents before children.
+    pub fn descendants(&self) -> impl Iterator<Item = SyntaxNode<'a>> {
+        let mut stack = vec![self.clone()];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            let children = node.children().collect::<Vec<_>>();
+            stack.extend(children.into_iter().rev());
+            Some(node)
+        })
+    }
+
+    /// Every token below the node, in source order.
+    pub fn tokens(&self) -> Vec<SyntaxToken<'a>> {
+        let mut tokens = vec![];
+        for element in self.children_with_tokens() {
+            match element {
+                SyntaxElement::Node(node) => tokens.extend(node.tokens()),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+        tokens
+    }
+
+    /// The span of the node's text including the trivia of its first and last tokens.
+    pub fn full_span(&self) -> Span {
+        Span::new(self.0.offset, self.0.offset + self.0.green.full_len)
+    }
+
+    pub fn first_token(&self) -> Option<SyntaxToken<'a>> {
+        self.children_with_tokens()
+            .into_iter()
+            .find_map(|element| match element {
+                SyntaxElement::Node(node) => node.first_token(),
+                SyntaxElement::Token(token) => Some(token),
+            })
+    }
+
+    pub fn last_token(&self) -> Option<SyntaxToken<'a>> {
+        self.children_with_tokens()
+            .into_iter()
+            .rev()
+            .find_map(|element| match element {
+                SyntaxElement::Node(node) => node.last_token(),
+                SyntaxElement::Token(token) => Some(token),
+            })
+    }
+
+    /// The span of the node's text from its first to its last token, without the
+    /// trivia around them. Empty for placeholder nodes without tokens.
+    pub fn span(&self) -> Span {
+        match (self.first_token(), self.last_token()) {
+            (Some(first), Some(last)) => Span::new(first.span().start, last.span().end),
+            _ => self.full_span(),
+        }
+    }
+}
+
+impl fmt::Display for SyntaxNode<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.green.fmt(f)
+    }
+}
+
+impl fmt::Debug for SyntaxNode<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let span = self.span();
+        write!(f, "{:?}@{}..{}", self.kind(), span.start, span.end)
+    }
+}
+
+/// A token of the red tree, with its trivia.
+#[derive(Clone)]
+pub struct SyntaxToken<'a> {
+    green: Arc<GreenToken<'a>>,
+    parent: SyntaxNode<'a>,
+    // offset of the token including leading trivia
+    offset: usize,
+}
+
+impl<'a> SyntaxToken<'a> {
+    pub fn kind(&self) -> &TokenKind<'a> {
+        &self.green.kind
+    }
+
+    pub fn green(&self) -> &Arc<GreenToken<'a>> {
+        &self.green
+    }
+
+    pub fn text(&self) -> &str {
+        &self.green.text
+    }
+
+    pub fn leading_trivia(&self) -> &[Trivia] {
+        &self.green.leading
+    }
+
+    pub fn trailing_trivia(&self) -> &[Trivia] {
+        &self.green.trailing
+    }
+
+    pub fn parent(&self) -> SyntaxNode {
+        self.parent.clone()
+    }
+
+    /// The span of the token's text without trivia.
+    pub fn span(&self) -> Span {
+        let start = self.offset + self.green.leading_len();
+        Span::new(start, start + self.green.text.len())
+    }
+
+    pub fn full_span(&self) -> Span {
+        Span::new(self.offset, self.offset + self.green.full_len())
+    }
+}
+
+impl fmt::Display for SyntaxToken<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.green.fmt(f)
+    }
+}
+
+impl fmt::Debug for SyntaxToken<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let span = self.span();
+        write!(f, "{:?}@{}..{}", self.text(), span.start, span.end)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SyntaxElement<'a> {
+    Node(SyntaxNode<'a>),
+    Token(SyntaxToken<'a>),
+}
+
+struct Frame<'a> {
+    kind: SyntaxKind,
+    span: Span,
+    children: Vec<GreenElement<'a>>,
+}
+
+/// Builds the green tree of `program` from the tokens the parser consumed. Nodes are
+/// nested by their spans, and tokens go into the innermost node that contains them.
+pub(crate) fn build<'a>(
+    source: &str,
+    tokens: Vec<Token<'a>>,
+    program: &Program<'_>,
+) -> GreenNode<'a> {
+    let mut nodes = vec![];
+    program.collect_nodes(&mut nodes);
+    // outer nodes first; the sort is stable, so equal spans keep parents first
+    nodes.sort_by_key(|(_, span)| (span.start, std::cmp::Reverse(span.end)));
+    let mut nodes = nodes.into_iter().peekable();
+
+    let mut stack = vec![];
+    let mut tokens = green_tokens(source, tokens).into_iter().peekable();
+    while let Some((span, _)) = tokens.peek() {
+        let span = *span;
+        match nodes.peek() {
+            Some((_, node)) if node.start <= span.start => {
+                let (kind, node) = nodes.next().unwrap();
+                // a placeholder for a missing node belongs to the node which ends
+                // where it is, like the `a + ` in `a + ;`
+                close_frames(&mut stack, |frame| {
+                    frame.span.end < node.start
+                        || frame.span.end == node.start && node.start != node.end
+                });
+                // nodes which overlap the end of their parent cannot be nested
+                if stack
+                    .last()
+                    .map_or(true, |parent| node.end <= parent.span.end)
+                {
+                    stack.push(Frame {
+                        kind,
+                        span: node,
+                        children: vec![],
+                    });
+                }
+            }
+            _ => {
+                let (_, token) = tokens.next().unwrap();
+                close_frames(&mut stack, |frame| {
+                    frame.span.end <= span.start || frame.span.end < span.end
+                });
+                stack
+                    .last_mut()
+                    .expect("the program contains every token")
+                    .children
+                    .push(GreenElement::Token(Arc::new(token)));
+            }
+        }
+    }
+    close_frames(&mut stack, |_| true);
+    let root = stack.pop().expect("the program is the root node");
+    GreenNode::new(root.kind, root.children)
+}
+
+/// Closes the innermost nodes while `done` says they end before what comes next.
+/// The root stays open.
+fn close_frames<'a>(stack: &mut Vec<Frame<'a>>, done: impl Fn(&Frame<'a>) -> bool) {
+    while stack.len() > 1 && done(stack.last().unwrap()) {
+        let frame = stack.pop().unwrap();
+        // a node inside a single token, like the text of a template, has no tokens of
+        // its own and is dropped; only placeholders for missing nodes are empty
+        if frame.children.is_empty() && frame.span.start != frame.span.end {
+            continue;
+        }
+        let node = GreenNode::new(frame.kind, frame.children);
+        stack
+            .last_mut()
+            .unwrap()
+            .children
+            .push(GreenElement::Node(Arc::new(node)));
+    }
+}
+
+/// Turns the tokens into green tokens followed by an `Eof` token, sharing out the
+/// source text between them as trivia.
+fn green_tokens<'a>(source: &str, tokens: Vec<Token<'a>>) -> Vec<(Span, GreenToken<'a>)> {
+    let eof = Token {
+        kind: TokenKind::Eof,
+        span: Span::new(source.len(), source.len()),
+        had_line_break_before: false,
+    };
+    let mut green: Vec<(Span, GreenToken<'a>)> = vec![];
+    let mut prev_end = 0;
+    for token in tokens.into_iter().chain(std::iter::once(eof)) {
+        let mut leading = split_trivia(&source[prev_end..token.span.start]);
+        if let Some((_, prev)) = green.last_mut() {
+            let line_end = leading
+                .iter()
+                .position(|trivia| trivia.kind == TriviaKind::Newline)
+                .unwrap_or(leading.len());
+            prev.trailing = leading.drain(..line_end).collect();
+        }
+        let text = source[token.span.start..token.span.end].to_string();
+        green.push((
+            token.span,
+            GreenToken::new(token.kind, text, leading, vec![]),
+        ));
+        prev_end = token.span.end;
+    }
+    green
+}
+
+fn split_trivia(mut text: &str) -> Vec<Trivia> {
+    let mut trivia = vec![];
+    while let Some(c) = text.chars().next() {
+        let (kind, len) = if text.starts_with("\r\n") {
+            (TriviaKind::Newline, 2)
+        } else if is_line_terminator(c) {
+            (TriviaKind::Newline, c.len_utf8())
+        } else if is_whitespace(c) {
+            let len = text
+                .find(|c| !is_whitespace(c) || is_line_terminator(c))
+                .unwrap_or(text.len());
+            (TriviaKind::Whitespace, len)
+        } else if ["//", "<!--", "-->", "#!"]
+            .iter()
+            .any(|p| text.starts_with(p))
+        {
+            // the lexer only reads a hashbang and `<!--` or `-->` as comments where
+            // they are allowed, so the text between tokens can only start with them there
+            let len = text.find(is_line_terminator).unwrap_or(text.len());
+            (TriviaKind::SingleLineComment, len)
+        } else if let Some(rest) = text.strip_prefix("/*") {
+            let len = rest.find("*/").map_or(text.len(), |end| end + 4);
+            (TriviaKind::MultiLineComment, len)
+        } else {
+            let len = text.find(is_whitespace).unwrap_or(text.len());
+            (TriviaKind::Skipped, len)
+        };
+        trivia.push(Trivia {
+            kind,
+            text: text[..len].to_string(),
+        });
+        text = &text[len..];
+    }
+    trivia
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_cst, Allocator};
+
+    fn texts(trivia: &[Trivia]) -> Vec<&str> {
+        trivia.iter().map(|trivia| trivia.text.as_str()).collect()
+    }
+
+    fn find<'a>(root: &SyntaxNode<'a>, kind: SyntaxKind) -> SyntaxNode<'a> {
+        root.descendants()
+            .find(|node| node.kind() == kind)
+            .unwrap_or_else(|| panic!("no {kind:?} in {root:?}"))
+    }
+
+    #[test]
+    fn round_trips_source_text() {
+        let allocator = Allocator::new();
+        for source in [
+            "",
+            "  \n// only a comment\n",
+            "/** doc */\nfunction f<T>(a: Array<Set<T>>): void {\r\n  return /re/g.test(`x${a}y`);\r\n}\n",
+            "let a = 1 + ; class C { m() { if ( } }\n#",
+            "let s = \"unterminated\n",
+            "#!/usr/bin/env node\r\nfoo();\u{2028}bar\u{a0}();\n",
+        ] {
+            let (root, _) = parse_cst(&allocator, source);
+            assert_eq!(root.to_string(), source);
+            assert_eq!(root.full_span(), Span::new(0, source.len()));
+        }
+    }
+
+    #[test]
+    fn attaches_trivia() {
+        let allocator = Allocator::new();
+        let (root, errors) = parse_cst(&allocator, "// a\nlet x = 1; // b\n/* c */ y\n");
+        assert!(errors.is_empty(), "{errors:?}");
+        let tokens = root.tokens();
+        let text = tokens.iter().map(SyntaxToken::text).collect::<Vec<_>>();
+        assert_eq!(text, ["let", "x", "=", "1", ";", "y", ""]);
+
+        assert_eq!(texts(tokens[0].leading_trivia()), ["// a", "\n"]);
+        assert_eq!(texts(tokens[0].trailing_trivia()), [" "]);
+        assert_eq!(texts(tokens[4].trailing_trivia()), [" ", "// b"]);
+        assert_eq!(texts(tokens[5].leading_trivia()), ["\n", "/* c */", " "]);
+
+        let (root, _) = parse_cst(&allocator, "#!node\nx");
+        let leading = root.tokens()[0].leading_trivia().to_vec();
+        assert_eq!(texts(&leading), ["#!node", "\n"]);
+        assert_eq!(leading[0].kind, TriviaKind::SingleLineComment);
+        assert_eq!(
+            tokens[5]
+                .leading_trivia()
+                .iter()
+                .map(|trivia| trivia.kind)
+                .collect::<Vec<_>>(),
+            [
+                TriviaKind::Newline,
+                TriviaKind::MultiLineComment,
+                TriviaKind::Whitespace
+            ]
+        );
+        assert_eq!(*tokens[6].kind(), TokenKind::Eof);
+        assert_eq!(texts(tokens[6].leading_trivia()), ["\n"]);
+
+        assert_eq!(tokens[1].span(), Span::new(9, 10));
+        assert_eq!(tokens[0].full_span(), Span::new(0, 9));
+    }
+
+    #[test]
+    fn nests_nodes_by_span() {
+        let allocator = Allocator::new();
+        let (root, errors) = parse_cst(&allocator, "let x: Map<K, Set<V>> = f(a, b);");
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(root.kind(), SyntaxKind::Program);
+
+        let decl = find(&root, SyntaxKind::VarDecl);
+        assert_eq!(decl.span(), Span::new(0, 32));
+        assert_eq!(
+            decl.parent().map(|node| node.kind()),
+            Some(SyntaxKind::Program)
+        );
+
+        // `>>` is split between the two type references it closes
+        let map = find(&root, SyntaxKind::TsTypeRef);
+        assert_eq!(map.to_string(), "Map<K, Set<V>> ");
+        assert_eq!(map.span(), Span::new(7, 21));
+        let closing = map.last_token().unwrap();
+        assert_eq!((closing.text(), closing.span()), (">", Span::new(20, 21)));
+
+        let call = find(&root, SyntaxKind::CallExpr);
+        assert_eq!(call.to_string(), "f(a, b)");
+        assert_eq!(
+            call.ancestors().map(|node| node.kind()).collect::<Vec<_>>(),
+            [
+                SyntaxKind::CallExpr,
+                SyntaxKind::VarDeclarator,
+                SyntaxKind::VarDecl,
+                SyntaxKind::Program
+            ]
+        );
+        assert_eq!(
+            call.first_token().unwrap().parent().kind(),
+            SyntaxKind::Ident
+        );
+    }
+
+    #[test]
+    fn keeps_placeholders_for_missing_nodes() {
+        let allocator = Allocator::new();
+        let (root, errors) = parse_cst(&allocator, "a + ;");
+        assert_eq!(errors.len(), 1);
+        let invalid = find(&root, SyntaxKind::InvalidExpr);
+        assert!(invalid.tokens().is_empty());
+        assert_eq!(
+            invalid.parent().map(|node| node.kind()),
+            Some(SyntaxKind::BinaryExpr)
+        );
+    }
+}
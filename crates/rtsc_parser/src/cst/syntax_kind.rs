@@ -0,0 +1,200 @@
+//! Node kinds of the CST, and how they are collected from the AST.
+
+use crate::{
+    ast::*,
+    visit::{AstNode, Visit, VisitWith},
+    Span,
+};
+
+/// The kind of a [`SyntaxNode`](super::SyntaxNode). A node is named after the AST
+/// node it was built from. Enum variants that only hold a span get a kind of their
+/// own, like `ThisExpr` for [`Expr::This`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyntaxKind {
+    Program,
+    Ident,
+    PrivateName,
+    EmptyStmt,
+    DebuggerStmt,
+    InvalidStmt,
+    BlockStmt,
+    ExprStmt,
+    VarDecl,
+    VarDeclarator,
+    IfStmt,
+    ForStmt,
+    ForInStmt,
+    ForOfStmt,
+    WhileStmt,
+    DoWhileStmt,
+    ReturnStmt,
+    BreakStmt,
+    ContinueStmt,
+    ThrowStmt,
+    TryStmt,
+    CatchClause,
+    SwitchStmt,
+    SwitchCase,
+    LabeledStmt,
+    WithStmt,
+    FnDecl,
+    Function,
+    Param,
+    Decorator,
+    ClassDecl,
+    Class,
+    EmptyClassMember,
+    InvalidClassMember,
+    ClassMethod,
+    ClassProp,
+    StaticBlock,
+    ComputedPropName,
+    ThisExpr,
+    SuperExpr,
+    ImportExpr,
+    InvalidExpr,
+    NullLit,
+    Str,
+    Num,
+    BigInt,
+    Bool,
+    Regex,
+    ArrayLit,
+    ObjectLit,
+    KeyValueProp,
+    ShorthandProp,
+    MethodProp,
+    SpreadProp,
+    FnExpr,
+    ClassExpr,
+    ArrowExpr,
+    Tpl,
+    TplElement,
+    TaggedTpl,
+    UnaryExpr,
+    UpdateExpr,
+    BinaryExpr,
+    AssignExpr,
+    CondExpr,
+    CallExpr,
+    NewExpr,
+    MemberExpr,
+    SeqExpr,
+    ParenExpr,
+    YieldExpr,
+    AwaitExpr,
+    MetaProp,
+    TsAsExpr,
+    TsSatisfiesExpr,
+    TsConstAssertion,
+    TsNonNullExpr,
+    TsTypeAssertion,
+    TsInstantiation,
+    BindingIdent,
+    ArrayPat,
+    ObjectPat,
+    KeyValuePatProp,
+    AssignPatProp,
+    AssignPat,
+    RestPat,
+    ImportDecl,
+    ImportNamedSpecifier,
+    ExportDecl,
+    ExportNamed,
+    ExportNamedSpecifier,
+    ExportAll,
+    ExportDefault,
+    TsInterfaceDecl,
+    TsTypeAliasDecl,
+    TsEnumDecl,
+    TsEnumMember,
+    TsModuleDecl,
+    TsModuleBlock,
+    TsImportEqualsDecl,
+    TsExportAssign,
+    TsExportAsNamespace,
+    TsQualifiedName,
+    TsTypeParamDecl,
+    TsTypeParam,
+    TsTypeArgs,
+    TsExprWithTypeArgs,
+    TsThisType,
+    TsKeywordType,
+    TsTypeRef,
+    TsLitType,
+    TsTplLitType,
+    TsArrayType,
+    TsTupleType,
+    TsTupleElement,
+    TsUnionType,
+    TsIntersectionType,
+    TsFnType,
+    TsConstructorType,
+    TsTypeLit,
+    TsParenType,
+    TsTypeOperator,
+    TsIndexedAccessType,
+    TsConditionalType,
+    TsInferType,
+    TsMappedType,
+    TsTypeQuery,
+    TsImportType,
+    TsTypePredicate,
+    InvalidTypeElement,
+    TsPropertySignature,
+    TsMethodSignature,
+    TsCallSignature,
+    TsConstructSignature,
+    TsIndexSignature,
+    TsGetterSignature,
+    TsSetterSignature,
+    JsxElement,
+    JsxOpeningElement,
+    JsxClosingElement,
+    JsxMemberExpr,
+    JsxNamespacedName,
+    JsxAttr,
+    JsxSpreadAttr,
+    JsxExprContainer,
+    JsxText,
+    JsxSpreadChild,
+    JsxFragment,
+}
+
+/// Receives the nodes of a subtree from [`CollectNodes`], parents before children.
+pub(crate) trait NodeSink<'a> {
+    /// Starts a node, which `node` is in the tree.
+    fn enter(&mut self, kind: SyntaxKind, span: Span, node: AstNode<'a>);
+
+    /// Ends the node entered last, after its children.
+    fn exit(&mut self) {}
+}
+
+impl NodeSink<'_> for Vec<(SyntaxKind, Span)> {
+    fn enter(&mut self, kind: SyntaxKind, span: Span, _: AstNode<'_>) {
+        self.push((kind, span));
+    }
+}
+
+/// Walks every node in a subtree into a [`NodeSink`].
+pub(crate) trait CollectNodes<'a> {
+    fn collect_nodes(&'a self, nodes: &mut dyn NodeSink<'a>);
+}
+
+impl<'a> CollectNodes<'a> for Program<'a> {
+    fn collect_nodes(&'a self, nodes: &mut dyn NodeSink<'a>) {
+        self.visit_with(&mut Collector(nodes));
+    }
+}
+
+struct Collector<'s, 'a>(&'s mut dyn NodeSink<'a>);
+
+impl<'a> Visit<'a> for Collector<'_, 'a> {
+    fn enter_node(&mut self, kind: SyntaxKind, span: Span, node: AstNode<'a>) {
+        self.0.enter(kind, span, node);
+    }
+
+    fn exit_node(&mut self, _: SyntaxKind, _: Span) {
+        self.0.exit();
+    }
+}
@@ -3,11 +3,6 @@ use thiserror::Error;
 
 use crate::Span;
 
-#[derive(Error, Debug, Diagnostic)]
-#[error("unexpected token `{0}`")]
-#[diagnostic()]
-pub struct UnexpectedToken(pub char, #[label("unexpected token")] pub Span);
-
 #[derive(Error, Debug, Diagnostic)]
 #[error("invalid or unexpected token `{0}`")]
 #[diagnostic()]
@@ -25,7 +20,172 @@ pub struct LegacyDecimalEscape(
     #[label("Legacy decimal escape is not permitted in strict mode")] pub Span,
 );
 
+#[derive(Error, Debug, Diagnostic)]
+#[error("Octal escape sequences are not allowed in strict mode")]
+#[diagnostic()]
+pub struct LegacyOctalEscape(
+    #[label("Octal escape sequences are not allowed in strict mode")] pub Span,
+);
+
 #[derive(Error, Debug, Diagnostic)]
 #[error("Legacy octal literals are not available")]
 #[diagnostic()]
 pub struct LegacyOctalLiteral(#[label("Legacy octal literals are not available")] pub Span);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Decimals with leading zeros are not allowed")]
+#[diagnostic()]
+pub struct LeadingZeroDecimal(#[label("Decimals with leading zeros are not allowed")] pub Span);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Multiple consecutive numeric separators are not permitted")]
+#[diagnostic()]
+pub struct ConsecutiveNumericSeparators(
+    #[label("Multiple consecutive numeric separators are not permitted")] pub Span,
+);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Numeric separators are not allowed here")]
+#[diagnostic()]
+pub struct NumericSeparatorNotAllowed(#[label("Numeric separators are not allowed here")] pub Span);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("A bigint literal must be an integer")]
+#[diagnostic()]
+pub struct InvalidBigInt(#[label("A bigint literal must be an integer")] pub Span);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Unterminated string literal")]
+#[diagnostic()]
+pub struct UnterminatedString(#[label("Unterminated string literal")] pub Span);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Line terminators are not allowed in string literals")]
+#[diagnostic(help("Escape the line break with `\\n` or use a template literal"))]
+pub struct NewlineInString(#[label("Line terminator in string literal")] pub Span);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Hexadecimal digit expected")]
+#[diagnostic()]
+pub struct InvalidHexEscape(#[label("`\\x` must be followed by two hexadecimal digits")] pub Span);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Lone surrogate in string literal")]
+#[diagnostic(severity(Warning), help("The escape is replaced with U+FFFD"))]
+pub struct LoneSurrogate(#[label("This surrogate is not part of a pair")] pub Span);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Unterminated template literal")]
+#[diagnostic()]
+pub struct UnterminatedTemplate(#[label("Unterminated template literal")] pub Span);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Unterminated regular expression literal")]
+#[diagnostic()]
+pub struct UnterminatedRegExp(#[label("Unterminated regular expression literal")] pub Span);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Unknown regular expression flag `{0}`")]
+#[diagnostic()]
+pub struct InvalidRegExpFlag(
+    pub char,
+    #[label("Unknown regular expression flag")] pub Span,
+);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Duplicate regular expression flag `{0}`")]
+#[diagnostic()]
+pub struct DuplicateRegExpFlag(
+    pub char,
+    #[label("Duplicate regular expression flag")] pub Span,
+);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("The Unicode (u) flag and the Unicode Sets (v) flag cannot be set simultaneously")]
+#[diagnostic()]
+pub struct IncompatibleRegExpFlags(#[label("incompatible regular expression flag")] pub Span);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Invalid Unicode escape sequence")]
+#[diagnostic()]
+pub struct InvalidUnicodeEscape(#[label("Invalid Unicode escape sequence")] pub Span);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Invalid character `{0}` in identifier")]
+#[diagnostic()]
+pub struct InvalidIdentifierEscape(
+    pub char,
+    #[label("This escape is not a valid identifier character")] pub Span,
+);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Keywords cannot contain escape characters")]
+#[diagnostic()]
+pub struct EscapedKeyword(#[label("Keywords cannot contain escape characters")] pub Span);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("'#!' can only be used at the start of a file")]
+#[diagnostic()]
+pub struct HashbangNotAtStart(#[label("This is not the start of the file")] pub Span);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Unexpected token. Did you mean `{{'{0}'}}` or `{1}`?")]
+#[diagnostic()]
+pub struct UnexpectedJsxText(
+    pub char,
+    pub &'static str,
+    #[label("This character cannot appear in JSX text")] pub Span,
+);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Unexpected token")]
+#[diagnostic()]
+pub struct UnexpectedToken(#[label("Unexpected token")] pub Span);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("{0} expected")]
+#[diagnostic()]
+pub struct Expected(pub &'static str, #[label("{0} expected")] pub Span);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Identifier expected. '{0}' is a reserved word in strict mode")]
+#[diagnostic()]
+pub struct StrictModeReservedWord(pub String, #[label("This is a reserved word")] pub Span);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Invalid assignment target")]
+#[diagnostic()]
+pub struct InvalidAssignmentTarget(#[label("This expression cannot be assigned to")] pub Span);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Expected corresponding JSX closing tag for '{0}'")]
+#[diagnostic()]
+pub struct MismatchedJsxClosingTag(
+    pub String,
+    #[label("This closing tag does not match")] pub Span,
+);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Expected corresponding closing tag for JSX fragment")]
+#[diagnostic()]
+pub struct MismatchedJsxFragmentClosingTag(#[label("This closing tag does not match")] pub Span);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("JSX element '{0}' has no corresponding closing tag")]
+#[diagnostic()]
+pub struct UnclosedJsxElement(pub String, #[label("This element is not closed")] pub Span);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("JSX fragment has no corresponding closing tag")]
+#[diagnostic()]
+pub struct UnclosedJsxFragment(#[label("This fragment is not closed")] pub Span);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("JSX expressions must have one parent element")]
+#[diagnostic(help("Wrap the elements in a fragment, `<>...</>`"))]
+pub struct MultipleJsxRoots(#[label("A second JSX element")] pub Span);
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("JSX attributes must only be assigned a non-empty expression")]
+#[diagnostic()]
+pub struct EmptyJsxAttribute(#[label("This expression is empty")] pub Span);
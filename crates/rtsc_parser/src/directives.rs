@@ -0,0 +1,303 @@
+//! Comments that TypeScript gives a meaning to.
+//!
+//! The lexer reads them as it skips comments. `@ts-ignore` and `@ts-expect-error` may
+//! appear in any comment, and `// @name: value` compiler options, used by the test
+//! fixtures, in any line comment at the start of a line. The others only count in the
+//! comments at the top of a file, before its first token.
+
+use miette::Error;
+
+use crate::{is_line_terminator, LineIndex, Span};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Directive {
+    pub kind: DirectiveKind,
+    /// The span of the whole comment.
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DirectiveKind {
+    /// `/// <reference path="./a.d.ts" />`, or one of the other [`ReferenceKind`]s
+    /// in place of `path`.
+    Reference { kind: ReferenceKind, value: String },
+    /// `// @ts-ignore`, which suppresses errors on the next line.
+    TsIgnore,
+    /// `// @ts-expect-error`, which suppresses errors on the next line like
+    /// `@ts-ignore`.
+    TsExpectError,
+    /// `// @ts-check`
+    TsCheck,
+    /// `// @ts-nocheck`, which suppresses errors in the whole file.
+    TsNocheck,
+    /// `/** @jsx h */`, or `@jsxFrag`, `@jsxImportSource` or `@jsxRuntime` in place
+    /// of `@jsx`.
+    JsxPragma { name: String, value: String },
+    /// `// @target: es5`, a compiler option for a test fixture.
+    CompilerOption { name: String, value: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceKind {
+    Path,
+    Types,
+    Lib,
+    /// `no-default-lib="true"`
+    NoDefaultLib,
+}
+
+/// Reads the directive in the comment at `span`, if there is one. `header` is true
+/// for comments before the first token of the file.
+pub(crate) fn read_directive(source: &str, span: Span, header: bool) -> Option<Directive> {
+    let comment = &source[span.start..span.end];
+    if let Some(text) = comment.strip_prefix("//") {
+        // the line comment token ends after its line break
+        let text = text.trim_end_matches(is_line_terminator);
+        let span = Span::new(span.start, span.start + 2 + text.len());
+        let at_line_start = span.start == 0 || source[..span.start].ends_with(is_line_terminator);
+        let kind = read_line_directive(text, header, at_line_start)?;
+        Some(Directive { kind, span })
+    } else if let Some(text) = comment.strip_prefix("/*") {
+        let text = text.strip_suffix("*/").unwrap_or(text);
+        let kind = read_block_directive(text, header)?;
+        Some(Directive { kind, span })
+    } else {
+        // `<!--` and `-->` comments hold no directives
+        None
+    }
+}
+
+fn read_line_directive(text: &str, header: bool, at_line_start: bool) -> Option<DirectiveKind> {
+    if let Some(reference) = text.strip_prefix('/').filter(|_| header) {
+        if let Some(kind) = read_reference(reference) {
+            return Some(kind);
+        }
+    }
+    let text = text.strip_prefix('/').unwrap_or(text).trim_start();
+    let name = text.strip_prefix('@')?;
+    if let Some(kind) = read_suppression(name) {
+        return Some(kind);
+    }
+    if header {
+        if name.starts_with("ts-nocheck") {
+            return Some(DirectiveKind::TsNocheck);
+        }
+        if name.starts_with("ts-check") {
+            return Some(DirectiveKind::TsCheck);
+        }
+    }
+    // options are written right after the `//`, as in `// @target: es5`
+    let (name, value) = name.split_once(':')?;
+    let name = name.trim_end();
+    if !at_line_start
+        || name.is_empty()
+        || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return None;
+    }
+    Some(DirectiveKind::CompilerOption {
+        name: name.to_string(),
+        value: value.trim().to_string(),
+    })
+}
+
+fn read_block_directive(text: &str, header: bool) -> Option<DirectiveKind> {
+    // suppressions are read from the last line of a block comment
+    let last_line = text.rsplit(is_line_terminator).next().unwrap_or(text);
+    let last_line = last_line
+        .trim_start()
+        .trim_start_matches(['/', '*'])
+        .trim_start();
+    if let Some(kind) = last_line.strip_prefix('@').and_then(read_suppression) {
+        return Some(kind);
+    }
+    if !header {
+        return None;
+    }
+    text.split(is_line_terminator).find_map(|line| {
+        let (_, pragma) = line.split_once('@')?;
+        let (name, value) = pragma
+            .split_once(char::is_whitespace)
+            .unwrap_or((pragma, ""));
+        let name = name.trim_end_matches(['*', '/']);
+        if !matches!(
+            name.to_ascii_lowercase().as_str(),
+            "jsx" | "jsxfrag" | "jsximportsource" | "jsxruntime"
+        ) {
+            return None;
+        }
+        let value = value.split_whitespace().next().unwrap_or("");
+        Some(DirectiveKind::JsxPragma {
+            name: name.to_string(),
+            value: value.trim_end_matches(['*', '/']).to_string(),
+        })
+    })
+}
+
+fn read_suppression(name: &str) -> Option<DirectiveKind> {
+    if name.starts_with("ts-ignore") {
+        Some(DirectiveKind::TsIgnore)
+    } else if name.starts_with("ts-expect-error") {
+        Some(DirectiveKind::TsExpectError)
+    } else {
+        None
+    }
+}
+
+/// Reads `<reference name="value" />` after the `///`.
+fn read_reference(text: &str) -> Option<DirectiveKind> {
+    let text = text.trim().strip_prefix("<reference")?;
+    let text = text.trim_end().strip_suffix("/>")?.trim();
+    let (name, value) = text.split_once('=')?;
+    let kind = match name.trim() {
+        "path" => ReferenceKind::Path,
+        "types" => ReferenceKind::Types,
+        "lib" => ReferenceKind::Lib,
+        "no-default-lib" => ReferenceKind::NoDefaultLib,
+        _ => return None,
+    };
+    let value = value.trim();
+    let quote = value.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+    let value = value[1..].split(quote).next()?;
+    Some(DirectiveKind::Reference {
+        kind,
+        value: value.to_string(),
+    })
+}
+
+/// Drops the errors suppressed by `@ts-ignore`, `@ts-expect-error` and `@ts-nocheck`,
+/// an error being at the offset of its first label. As in TypeScript, a suppression
+/// applies to the next line that is neither blank nor only a line comment.
+///
+/// This is meant for the diagnostics of later passes, like type checking. Syntax errors
+/// are never suppressed, so [`parse`](crate::parse) reports all of them.
+pub fn suppress(source: &str, directives: &[Directive], errors: Vec<Error>) -> Vec<Error> {
+    if directives
+        .iter()
+        .any(|directive| directive.kind == DirectiveKind::TsNocheck)
+    {
+        return vec![];
+    }
+    let index = LineIndex::new(source);
+    let suppressions = directives
+        .iter()
+        .filter(|directive| {
+            matches!(
+                directive.kind,
+                DirectiveKind::TsIgnore | DirectiveKind::TsExpectError
+            )
+        })
+        .map(|directive| index.line_col(directive.span.end).line)
+        .collect::<Vec<_>>();
+    if suppressions.is_empty() {
+        return errors;
+    }
+    let is_suppressed = |error: &Error| {
+        let Some(offset) = error.labels().and_then(|mut labels| labels.next()) else {
+            return false;
+        };
+        let offset = offset.offset();
+        let line = index.line_col(offset).line;
+        for line in (0..line).rev() {
+            if suppressions.contains(&line) {
+                return true;
+            }
+            let start = index.line_start(line).unwrap_or(source.len());
+            let end = index.line_start(line + 1).unwrap_or(source.len());
+            let text = source[start..end].trim();
+            if !text.is_empty() && !text.starts_with("//") {
+                return false;
+            }
+        }
+        false
+    };
+    errors
+        .into_iter()
+        .filter(|error| !is_suppressed(error))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use miette::LabeledSpan;
+
+    use super::*;
+    use crate::lex_directives;
+
+    fn kinds(source: &str) -> Vec<DirectiveKind> {
+        lex_directives(source)
+            .into_iter()
+            .map(|directive| directive.kind)
+            .collect()
+    }
+
+    #[test]
+    fn header_directives() {
+        let source = "// @target: ES5\n//@jsx: preserve\n/// <reference path=\"./a.d.ts\" />\n/// <reference no-default-lib='true'/>\n// @ts-nocheck\n/** @jsx h */\nlet a;\n/// <reference types=\"node\" />\n// @ts-nocheck\n/** @jsx h */\n";
+        assert_eq!(
+            kinds(source),
+            [
+                DirectiveKind::CompilerOption {
+                    name: "target".to_string(),
+                    value: "ES5".to_string()
+                },
+                DirectiveKind::CompilerOption {
+                    name: "jsx".to_string(),
+                    value: "preserve".to_string()
+                },
+                DirectiveKind::Reference {
+                    kind: ReferenceKind::Path,
+                    value: "./a.d.ts".to_string()
+                },
+                DirectiveKind::Reference {
+                    kind: ReferenceKind::NoDefaultLib,
+                    value: "true".to_string()
+                },
+                DirectiveKind::TsNocheck,
+                DirectiveKind::JsxPragma {
+                    name: "jsx".to_string(),
+                    value: "h".to_string()
+                },
+            ]
+        );
+        assert_eq!(lex_directives(source)[2].span, Span::new(33, 66));
+    }
+
+    #[test]
+    fn suppression_directives() {
+        assert_eq!(
+            kinds("a; // @ts-ignore\n/* @ts-expect-error */\n/* why\n * @ts-ignore */\n/**\n * @ts-ignore\n */\nlet x = 1; // @target: es5"),
+            [
+                DirectiveKind::TsIgnore,
+                DirectiveKind::TsExpectError,
+                DirectiveKind::TsIgnore,
+            ]
+        );
+    }
+
+    #[test]
+    fn suppresses_errors() {
+        // errors of a later pass, at the offsets of `Z`
+        let errors = |source: &str| {
+            let errors = source
+                .match_indices('Z')
+                .map(|(offset, _)| {
+                    miette::miette!(labels = vec![LabeledSpan::at_offset(offset, "")], "Z")
+                })
+                .collect();
+            suppress(source, &lex_directives(source), errors).len()
+        };
+        assert_eq!(errors("// @ts-ignore\nZ;\nZ;"), 1);
+        assert_eq!(errors("// @ts-expect-error\n\n// why\nZ;"), 0);
+        assert_eq!(errors("// @ts-ignore\nf();\nZ;"), 1);
+        assert_eq!(errors("// @ts-nocheck\nZ;\nZ;"), 0);
+    }
+
+    #[test]
+    fn keeps_syntax_errors() {
+        let errors = |source| crate::parse(&crate::Allocator::new(), source).1.len();
+        assert_eq!(errors("// @ts-ignore\nvar = 1;\nvar = 2;"), 2);
+        assert_eq!(errors("// @ts-expect-error\nvar = 1;"), 1);
+        assert_eq!(errors("// @ts-nocheck\nvar = 1;\nvar = 2;"), 2);
+    }
+}
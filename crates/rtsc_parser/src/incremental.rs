@@ -0,0 +1,424 @@
+//! Relexing after an edit, for editors which lex the source on every keystroke.
+//!
+//! The tokens before the edit are kept as they are. Lexing restarts a little before
+//! the edit and stops at the first token after it which was read before in the same
+//! state, from where the old tokens are only shifted by the change in length.
+//!
+//! The old tokens are moved into the lexer of the new source, which interns their
+//! names again and borrows their text from the new source, so that the old source and
+//! its allocator can be dropped after every edit.
+
+use crate::{
+    is_line_terminator, AssignOp, Atom, BinaryOp, Lexer, Span, Token, TokenKind, WordKind,
+};
+
+/// A replacement of the text at `span` with `new_text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// The replaced range of the source before the edit.
+    pub span: Span,
+    pub new_text: String,
+}
+
+impl TextEdit {
+    pub fn new(span: Span, new_text: impl Into<String>) -> Self {
+        Self {
+            span,
+            new_text: new_text.into(),
+        }
+    }
+
+    /// The source after the edit.
+    pub fn apply(&self, source: &str) -> String {
+        let mut text = String::with_capacity(source.len() - self.span.size() + self.new_text.len());
+        text.push_str(&source[..self.span.start]);
+        text.push_str(&self.new_text);
+        text.push_str(&source[self.span.end..]);
+        text
+    }
+}
+
+/// The part of the lexer state between two tokens which cannot be read from the
+/// source around them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct State {
+    template_braces: Vec<usize>,
+    regex_allowed: bool,
+    header: bool,
+}
+
+impl State {
+    fn new() -> Self {
+        Self {
+            template_braces: vec![],
+            regex_allowed: true,
+            header: true,
+        }
+    }
+
+    /// Moves past a token, as the lexer did when it read it.
+    fn advance(&mut self, kind: &TokenKind) {
+        match kind {
+            TokenKind::SingleLineComment | TokenKind::MultiLineComment | TokenKind::Hashbang => {
+                return
+            }
+            TokenKind::TemplateHead { .. } => self.template_braces.push(0),
+            TokenKind::TemplateTail { .. } => {
+                self.template_braces.pop();
+            }
+            TokenKind::LBrace => {
+                if let Some(depth) = self.template_braces.last_mut() {
+                    *depth += 1;
+                }
+            }
+            TokenKind::RBrace => {
+                if let Some(depth) = self.template_braces.last_mut() {
+                    *depth = depth.saturating_sub(1);
+                }
+            }
+            _ => {}
+        }
+        self.regex_allowed = if is_slash(kind) && self.regex_allowed {
+            false
+        } else {
+            !kind.ends_expression()
+        };
+        self.header = false;
+    }
+
+    fn matches(&self, lexer: &Lexer) -> bool {
+        self.regex_allowed == lexer.regex_allowed
+            && self.header == lexer.header
+            && self.template_braces == lexer.template_braces
+    }
+}
+
+fn is_slash(kind: &TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::BinaryOp(BinaryOp::Div) | TokenKind::AssignOp(AssignOp::DivAssign)
+    )
+}
+
+fn is_comment(kind: &TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::SingleLineComment | TokenKind::MultiLineComment
+    )
+}
+
+impl<'a> Lexer<'a> {
+    /// Patches `tokens`, read by [`Lexer::lex`] from the source before `edit`, into the
+    /// tokens of this lexer's source, the source after `edit`, which must have been
+    /// made with the same options.
+    ///
+    /// Only the tokens around the edit are read again. Relexing stops at the first
+    /// token after the edit which is the same as before and leaves the lexer in the
+    /// same state, like the same depth of template substitutions. The old tokens may
+    /// borrow another source and allocator, which the returned ones do not.
+    pub fn relex(mut self, tokens: Vec<Token<'_>>, edit: &TextEdit) -> Vec<Token<'a>> {
+        let old_end = edit.span.end;
+        let new_end = edit.span.start + edit.new_text.len();
+
+        // a token which ends right at the edit may go on into it, and `..` and `?.`
+        // look two characters ahead, so relexing starts one token before
+        let mut start = tokens
+            .partition_point(|token| token.span.end < edit.span.start)
+            .saturating_sub(1);
+        let mut state = State::new();
+        // a `/` which could not be read as a regular expression was read up to the
+        // end of its line
+        let mut restart = None;
+        for (i, token) in tokens[..start].iter().enumerate() {
+            if token.had_line_break_before {
+                restart = None;
+            }
+            if restart.is_none() && state.regex_allowed && is_slash(&token.kind) {
+                restart = Some((i, state.clone()));
+            }
+            state.advance(&token.kind);
+        }
+        if let Some((i, before)) = restart {
+            start = i;
+            state = before;
+        }
+
+        let (pos, line_break) = match start.checked_sub(1).map(|i| &tokens[i]) {
+            Some(prev) if is_comment(&prev.kind) => (
+                prev.span.end,
+                prev.had_line_break_before || prev.raw(self.source).contains(is_line_terminator),
+            ),
+            Some(prev) => (prev.span.end, false),
+            None => (0, false),
+        };
+        self.peeked.clear();
+        self.pos = pos;
+        self.last_pos = pos;
+        self.template_braces = state.template_braces.clone();
+        self.header = state.header;
+        self.line_break = line_break;
+        self.regex_allowed = state.regex_allowed;
+
+        let mut relexed = vec![];
+        // the next old token and the state before it
+        let mut old = start;
+        loop {
+            let token = self.read_token();
+            if token.kind == TokenKind::Eof {
+                return self.reuse_around(&tokens[..start], relexed, &[], |span| span);
+            }
+            if token.span.start >= new_end {
+                let old_start = token.span.start - new_end + old_end;
+                while tokens.get(old).is_some_and(|t| t.span.start < old_start) {
+                    state.advance(&tokens[old].kind);
+                    old += 1;
+                }
+                let shift = |span: Span| {
+                    Span::new(span.start - old_end + new_end, span.end - old_end + new_end)
+                };
+                if let Some(old_token) = tokens.get(old).filter(|t| {
+                    t.span.start == old_start
+                        && shift(t.span) == token.span
+                        && t.kind == token.kind
+                        && t.had_line_break_before == token.had_line_break_before
+                }) {
+                    let mut after = state.clone();
+                    after.advance(&old_token.kind);
+                    if after.matches(&self) {
+                        return self.reuse_around(&tokens[..start], relexed, &tokens[old..], shift);
+                    }
+                }
+            }
+            relexed.push(token);
+        }
+    }
+
+    /// The old tokens `before` the relexed ones and `after` them, moved to `shift` of
+    /// their spans, around the `relexed` tokens.
+    fn reuse_around(
+        &mut self,
+        before: &[Token<'_>],
+        relexed: Vec<Token<'a>>,
+        after: &[Token<'_>],
+        shift: impl Fn(Span) -> Span,
+    ) -> Vec<Token<'a>> {
+        let mut tokens = Vec::with_capacity(before.len() + relexed.len() + after.len());
+        tokens.extend(before.iter().map(|token| self.reuse(token, token.span)));
+        tokens.extend(relexed);
+        tokens.extend(
+            after
+                .iter()
+                .map(|token| self.reuse(token, shift(token.span))),
+        );
+        tokens
+    }
+
+    /// Moves an old token to `span` of this lexer's source, which has the same text.
+    /// Its names are interned again, and its texts are borrowed from the new source
+    /// or copied if they are not in it, like a cooked template with escapes.
+    fn reuse(&mut self, token: &Token<'_>, span: Span) -> Token<'a> {
+        let source = span.source_text(self.source);
+        let allocator = self.allocator;
+        let text = |part: &str| -> &'a str {
+            match source.find(part) {
+                Some(start) => &source[start..start + part.len()],
+                None => allocator.alloc_str(part),
+            }
+        };
+        let mut atom = |atom: Atom<'_>| match source.find(atom.as_str()) {
+            Some(start) => self
+                .interner
+                .intern(&source[start..start + atom.as_str().len()]),
+            None => self.interner.intern_owned(atom.as_str()),
+        };
+        let kind = match &token.kind {
+            TokenKind::Eof => TokenKind::Eof,
+            TokenKind::Arrow => TokenKind::Arrow,
+            TokenKind::Number { value, raw } => TokenKind::Number {
+                value: *value,
+                raw: text(raw),
+            },
+            TokenKind::BigInt { value, raw } => TokenKind::BigInt {
+                value: text(value),
+                raw: text(raw),
+            },
+            TokenKind::String { value, raw } => TokenKind::String {
+                value: atom(*value),
+                raw: text(raw),
+            },
+            TokenKind::RegExp { pattern, flags } => TokenKind::RegExp {
+                pattern: text(pattern),
+                flags: text(flags),
+            },
+            TokenKind::Word(word) => TokenKind::Word(match word {
+                WordKind::Keyword(keyword) => WordKind::Keyword(*keyword),
+                WordKind::Identifier(name) => WordKind::Identifier(atom(*name)),
+                WordKind::True => WordKind::True,
+                WordKind::False => WordKind::False,
+                WordKind::Null => WordKind::Null,
+            }),
+            TokenKind::SingleLineComment => TokenKind::SingleLineComment,
+            TokenKind::MultiLineComment => TokenKind::MultiLineComment,
+            TokenKind::Hashbang => TokenKind::Hashbang,
+            TokenKind::LBrace => TokenKind::LBrace,
+            TokenKind::LParen => TokenKind::LParen,
+            TokenKind::RBrace => TokenKind::RBrace,
+            TokenKind::RParen => TokenKind::RParen,
+            TokenKind::LBracket => TokenKind::LBracket,
+            TokenKind::RBracket => TokenKind::RBracket,
+            TokenKind::Comma => TokenKind::Comma,
+            TokenKind::Dot => TokenKind::Dot,
+            TokenKind::DotDotDot => TokenKind::DotDotDot,
+            TokenKind::Bang => TokenKind::Bang,
+            TokenKind::Semicolon => TokenKind::Semicolon,
+            TokenKind::Colon => TokenKind::Colon,
+            TokenKind::Question => TokenKind::Question,
+            TokenKind::QuestionDot => TokenKind::QuestionDot,
+            TokenKind::Tilde => TokenKind::Tilde,
+            TokenKind::PlusPlus => TokenKind::PlusPlus,
+            TokenKind::MinusMinus => TokenKind::MinusMinus,
+            TokenKind::At => TokenKind::At,
+            TokenKind::Hash => TokenKind::Hash,
+            TokenKind::AssignOp(op) => TokenKind::AssignOp(*op),
+            TokenKind::BinaryOp(op) => TokenKind::BinaryOp(*op),
+            TokenKind::NoSubstitutionTemplate { cooked, raw } => {
+                TokenKind::NoSubstitutionTemplate {
+                    cooked: cooked.map(text),
+                    raw: text(raw),
+                }
+            }
+            TokenKind::TemplateHead { cooked, raw } => TokenKind::TemplateHead {
+                cooked: cooked.map(text),
+                raw: text(raw),
+            },
+            TokenKind::TemplateMiddle { cooked, raw } => TokenKind::TemplateMiddle {
+                cooked: cooked.map(text),
+                raw: text(raw),
+            },
+            TokenKind::TemplateTail { cooked, raw } => TokenKind::TemplateTail {
+                cooked: cooked.map(text),
+                raw: text(raw),
+            },
+            TokenKind::JsxText { raw } => TokenKind::JsxText { raw: text(raw) },
+            TokenKind::Unknown => TokenKind::Unknown,
+        };
+        Token {
+            kind,
+            span,
+            had_line_break_before: token.had_line_break_before,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lex, relex, Allocator};
+
+    /// A xorshift generator, enough to pick edits.
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as usize
+        }
+    }
+
+    #[test]
+    fn relexes_only_around_the_edit() {
+        let allocator = Allocator::new();
+        let source = "let a = 1;\nlet b = `x${a}y`;\n/* c */ f(b / 2);";
+        let tokens = lex(&allocator, source);
+        let edit = TextEdit::new(Span::new(8, 9), "42");
+        let new_source = edit.apply(source);
+        assert_eq!(
+            new_source,
+            "let a = 42;\nlet b = `x${a}y`;\n/* c */ f(b / 2);"
+        );
+        assert_eq!(
+            relex(&allocator, &new_source, tokens.clone(), &edit),
+            lex(&allocator, &new_source)
+        );
+
+        // an opened comment swallows the tokens up to the next `*/`, and removing it
+        // brings them back
+        let edit = TextEdit::new(Span::new(11, 11), "/*");
+        let commented = edit.apply(source);
+        let commented_tokens = relex(&allocator, &commented, tokens, &edit);
+        assert_eq!(commented_tokens, lex(&allocator, &commented));
+        assert_eq!(commented_tokens.len(), 13);
+        let edit = TextEdit::new(Span::new(11, 13), "");
+        assert_eq!(
+            relex(&allocator, source, commented_tokens, &edit),
+            lex(&allocator, source)
+        );
+    }
+
+    #[test]
+    fn drops_the_old_source_after_each_edit() {
+        // each source and allocator is dropped as soon as its tokens are relexed, which
+        // only compiles if the new tokens do not borrow them
+        let source = "let a = 'x';\nconst b = `y${a}\\n`;\nf(/re/g, 1n, c);".to_string();
+        let allocator = Allocator::new();
+        let tokens = lex(&allocator, &source);
+
+        let edit = TextEdit::new(Span::new(4, 5), "alpha");
+        let source_1 = edit.apply(&source);
+        let allocator_1 = Allocator::new();
+        let tokens_1 = relex(&allocator_1, &source_1, tokens, &edit);
+        drop((allocator, source));
+        assert_eq!(tokens_1, lex(&allocator_1, &source_1));
+
+        let edit = TextEdit::new(Span::new(0, 0), "// typing\n");
+        let source_2 = edit.apply(&source_1);
+        let allocator_2 = Allocator::new();
+        let tokens_2 = relex(&allocator_2, &source_2, tokens_1, &edit);
+        drop((allocator_1, source_1));
+        assert_eq!(tokens_2, lex(&allocator_2, &source_2));
+
+        let edit = TextEdit::new(Span::new(22, 25), "'yy'");
+        let source_3 = edit.apply(&source_2);
+        let allocator_3 = Allocator::new();
+        let tokens_3 = relex(&allocator_3, &source_3, tokens_2, &edit);
+        drop((allocator_2, source_2));
+        assert_eq!(tokens_3, lex(&allocator_3, &source_3));
+        assert_eq!(
+            source_3,
+            "// typing\nlet alpha = 'yy';\nconst b = `y${a}\\n`;\nf(/re/g, 1n, c);"
+        );
+    }
+
+    #[test]
+    fn matches_full_relex_after_random_edits() {
+        let sources = [
+            "let a = 1;\nlet b = `x${a}y${ {c: `z${d}`} }w`;\nconst re = /ab+c/g, q = a / b / c;\n",
+            "/* block */ // line\nfunction f(x) { return x?.y ?? .5 ... [1, 2]; }\n'str' + \"ing\"",
+            "// @ts-ignore\nif (a) /=/.test(b); else { c = d /= 2; }\nx = a ? .5 : b;\n",
+            "class C { #p = 0x1F_n; m() { return `${this.#p}` } }\n/** doc */ export {};",
+        ];
+        let snippets = [
+            "", "a", "1", ".", "/", "/*", "*/", "//", "\n", "`", "${", "}", "{", "'", "\"", "?",
+            " ", "=", "x y", "\\",
+        ];
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        let allocator = Allocator::new();
+        for source in sources {
+            let tokens = lex(&allocator, source);
+            for _ in 0..500 {
+                let start = rng.below(source.len() + 1);
+                let end = (start + rng.below(4)).min(source.len());
+                let edit =
+                    TextEdit::new(Span::new(start, end), snippets[rng.below(snippets.len())]);
+                let new_source = edit.apply(source);
+                assert_eq!(
+                    relex(&allocator, &new_source, tokens.clone(), &edit),
+                    lex(&allocator, &new_source),
+                    "{edit:?} in {source:?}"
+                );
+            }
+        }
+    }
+}
@@ -1,71 +1,153 @@
+pub mod allocator;
+pub mod arena;
+pub mod ast;
+mod atom;
+pub mod comments;
+pub mod cst;
 mod diagnostics;
+pub mod directives;
+mod incremental;
+mod line_index;
+mod options;
+mod parser;
+mod source;
+mod span;
+pub mod visit;
 
+use std::collections::VecDeque;
+
+use atom::LITERAL_WORDS;
 use diagnostics::{
-    InvalidOrUnexpectedToken, LegacyDecimalEscape, LegacyOctalLiteral, UnexpectedNumber,
-    UnexpectedToken,
+    ConsecutiveNumericSeparators, DuplicateRegExpFlag, EscapedKeyword, HashbangNotAtStart,
+    IncompatibleRegExpFlags, InvalidBigInt, InvalidHexEscape, InvalidIdentifierEscape,
+    InvalidOrUnexpectedToken, InvalidRegExpFlag, InvalidUnicodeEscape, LeadingZeroDecimal,
+    LegacyDecimalEscape, LegacyOctalEscape, LegacyOctalLiteral, LoneSurrogate, NewlineInString,
+    NumericSeparatorNotAllowed, UnexpectedJsxText, UnexpectedNumber, UnterminatedRegExp,
+    UnterminatedString, UnterminatedTemplate,
 };
-use miette::{Error, SourceOffset, SourceSpan};
-use std::str::Chars;
-
-#[derive(Debug, Clone, PartialEq)]
-pub struct Token {
-    pub kind: TokenKind,
-    span: Span,
-}
+use directives::{read_directive, Directive};
+use miette::Error;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub struct Span {
-    pub start: usize,
-    pub end: usize,
-}
-
-impl Span {
-    pub fn new(start: usize, end: usize) -> Self {
-        Self { start, end }
-    }
+pub use allocator::Allocator;
+pub use atom::{Atom, Interner};
+pub use incremental::TextEdit;
+pub use line_index::{LineCol, LineIndex, WideEncoding, WideLineCol};
+pub use options::{EsVersion, LexerOptions, ParserOptions, SourceType, Syntax};
+pub use parser::{parse, parse_cst, parse_with_comments, Parser};
+pub use source::{decode_source, read_source};
+pub use span::Span;
 
-    pub fn size(&self) -> usize {
-        self.end - self.start
-    }
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token<'a> {
+    pub kind: TokenKind<'a>,
+    pub span: Span,
+    /// Whether a line terminator, possibly inside a comment, comes between the previous
+    /// token and this one. Automatic semicolon insertion and the restricted productions
+    /// like `return` and postfix `++` depend on it.
+    pub had_line_break_before: bool,
 }
 
-impl From<Span> for SourceSpan {
-    fn from(val: Span) -> Self {
-        Self::new(
-            SourceOffset::from(val.start),
-            SourceOffset::from(val.size()),
-        )
+impl Token<'_> {
+    /// The text of the token as written in `source`, the source it was read from.
+    pub fn raw<'s>(&self, source: &'s str) -> &'s str {
+        self.span.source_text(source)
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum TokenKind {
+pub enum TokenKind<'a> {
     Eof,
     Arrow, // =>
-    Number { value: f64 },
-    String { value: String, raw: String },
-    Word(WordKind),
+    Number {
+        value: f64,
+        raw: &'a str,
+    },
+    /// `value` holds the decimal digits of the literal without the `n` suffix.
+    BigInt {
+        value: &'a str,
+        raw: &'a str,
+    },
+    String {
+        value: Atom<'a>,
+        raw: &'a str,
+    },
+    RegExp {
+        pattern: &'a str,
+        flags: &'a str,
+    },
+    Word(WordKind<'a>),
     SingleLineComment,
     MultiLineComment,
-    Backquote,  // `
-    LBrace,     // {
-    LParen,     // (
-    RBrace,     // }
-    RParen,     // )
-    LBracket,   // [
-    RBracket,   // ]
-    Comma,      // ,
-    Dot,        // .
-    DotDotDot,  // ...
-    Bang,       // !
-    Semicolon,  // ;
-    Colon,      // :
-    Question,   // ?
-    Tilde,      // ~
-    PlusPlus,   // ++
-    MinusMinus, // --
+    /// A `#!` comment on the first line, like `#!/usr/bin/env node`.
+    Hashbang,
+    LBrace,      // {
+    LParen,      // (
+    RBrace,      // }
+    RParen,      // )
+    LBracket,    // [
+    RBracket,    // ]
+    Comma,       // ,
+    Dot,         // .
+    DotDotDot,   // ...
+    Bang,        // !
+    Semicolon,   // ;
+    Colon,       // :
+    Question,    // ?
+    QuestionDot, // ?.
+    Tilde,       // ~
+    PlusPlus,    // ++
+    MinusMinus,  // --
+    At,          // @
+    Hash,        // #
     AssignOp(AssignOp),
     BinaryOp(BinaryOp),
+    NoSubstitutionTemplate {
+        cooked: Option<&'a str>,
+        raw: &'a str,
+    }, // `...`
+    TemplateHead {
+        cooked: Option<&'a str>,
+        raw: &'a str,
+    }, // `...${
+    TemplateMiddle {
+        cooked: Option<&'a str>,
+        raw: &'a str,
+    }, // }...${
+    TemplateTail {
+        cooked: Option<&'a str>,
+        raw: &'a str,
+    }, // }...`
+    /// The text between the tags of a JSX element, as written. HTML entities like
+    /// `&amp;` are not decoded.
+    JsxText {
+        raw: &'a str,
+    },
+    Unknown,
+}
+
+impl TokenKind<'_> {
+    /// Whether a token of this kind can be the last token of an expression,
+    /// in which case a following `/` is a division rather than a regular expression.
+    fn ends_expression(&self) -> bool {
+        match self {
+            TokenKind::Number { .. }
+            | TokenKind::BigInt { .. }
+            | TokenKind::String { .. }
+            | TokenKind::RegExp { .. }
+            | TokenKind::NoSubstitutionTemplate { .. }
+            | TokenKind::TemplateTail { .. }
+            | TokenKind::RParen
+            | TokenKind::RBracket
+            | TokenKind::RBrace
+            | TokenKind::PlusPlus
+            | TokenKind::MinusMinus => true,
+            TokenKind::Word(WordKind::Keyword(keyword)) => {
+                matches!(keyword, Keyword::This | Keyword::Super)
+            }
+            TokenKind::Word(_) => true,
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -82,6 +164,10 @@ pub enum AssignOp {
     ZeroFillRightShiftAssign, // >>>=
     RightShiftAssign,         // >>=
     LeftShiftAssign,          // <<=
+    ExpAssign,                // **=
+    LogicalAndAssign,         // &&=
+    LogicalOrAssign,          // ||=
+    NullishAssign,            // ??=
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -107,12 +193,14 @@ pub enum BinaryOp {
     BitAnd,             // &
     LogicalOr,          // ||
     LogicalAnd,         // &&
+    Exp,                // **
+    NullishCoalescing,  // ??
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum WordKind {
+pub enum WordKind<'a> {
     Keyword(Keyword),
-    Identifier(String),
+    Identifier(Atom<'a>),
     True,
     False,
     Null,
@@ -154,85 +242,764 @@ pub enum Keyword {
     While,
     With,
     Yield,
-    // ts
-    Any,
-    Boolean,
-    Declare,
     Enum,
+    // reserved in strict mode code only
     Implements,
     Interface,
-    Module,
-    Namespace,
-    Number,
     Private,
     Protected,
     Public,
+}
+
+impl Keyword {
+    /// Every keyword, in the order the interner starts with them.
+    pub const ALL: [Keyword; 40] = [
+        Keyword::Break,
+        Keyword::Case,
+        Keyword::Catch,
+        Keyword::Class,
+        Keyword::Const,
+        Keyword::Continue,
+        Keyword::Debugger,
+        Keyword::Default,
+        Keyword::Delete,
+        Keyword::Do,
+        Keyword::Else,
+        Keyword::Export,
+        Keyword::Extends,
+        Keyword::Finally,
+        Keyword::For,
+        Keyword::Function,
+        Keyword::If,
+        Keyword::Import,
+        Keyword::In,
+        Keyword::Instanceof,
+        Keyword::New,
+        Keyword::Return,
+        Keyword::Let,
+        Keyword::Super,
+        Keyword::Switch,
+        Keyword::This,
+        Keyword::Throw,
+        Keyword::Try,
+        Keyword::Typeof,
+        Keyword::Var,
+        Keyword::Void,
+        Keyword::While,
+        Keyword::With,
+        Keyword::Yield,
+        Keyword::Enum,
+        Keyword::Implements,
+        Keyword::Interface,
+        Keyword::Private,
+        Keyword::Protected,
+        Keyword::Public,
+    ];
+
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Keyword::Break => "break",
+            Keyword::Case => "case",
+            Keyword::Catch => "catch",
+            Keyword::Class => "class",
+            Keyword::Const => "const",
+            Keyword::Continue => "continue",
+            Keyword::Debugger => "debugger",
+            Keyword::Default => "default",
+            Keyword::Delete => "delete",
+            Keyword::Do => "do",
+            Keyword::Else => "else",
+            Keyword::Export => "export",
+            Keyword::Extends => "extends",
+            Keyword::Finally => "finally",
+            Keyword::For => "for",
+            Keyword::Function => "function",
+            Keyword::If => "if",
+            Keyword::Import => "import",
+            Keyword::In => "in",
+            Keyword::Instanceof => "instanceof",
+            Keyword::New => "new",
+            Keyword::Return => "return",
+            Keyword::Let => "let",
+            Keyword::Super => "super",
+            Keyword::Switch => "switch",
+            Keyword::This => "this",
+            Keyword::Throw => "throw",
+            Keyword::Try => "try",
+            Keyword::Typeof => "typeof",
+            Keyword::Var => "var",
+            Keyword::Void => "void",
+            Keyword::While => "while",
+            Keyword::With => "with",
+            Keyword::Yield => "yield",
+            Keyword::Enum => "enum",
+            Keyword::Implements => "implements",
+            Keyword::Interface => "interface",
+            Keyword::Private => "private",
+            Keyword::Protected => "protected",
+            Keyword::Public => "public",
+        }
+    }
+
+    /// The atom of the keyword, which every interner starts with.
+    pub fn atom(self) -> Atom<'static> {
+        Atom::preinterned(self.as_str()).expect("keywords are pre-interned")
+    }
+}
+
+/// A word which is a keyword in some places and an identifier in others, like `type`
+/// in `type A = B` and `let type = 1`. The lexer reads these as identifiers and the
+/// parser decides what they are from where they appear.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextualKeyword {
+    Abstract,
+    Accessor,
+    Any,
+    As,
+    Assert,
+    Asserts,
+    Async,
+    Await,
+    Bigint,
+    Boolean,
+    Constructor,
+    Declare,
+    From,
+    Get,
+    Global,
+    Infer,
+    Intrinsic,
+    Is,
+    Keyof,
+    Module,
+    Namespace,
+    Never,
+    Number,
+    Object,
+    Of,
+    Out,
+    Override,
+    Readonly,
     Require,
+    Satisfies,
+    Set,
+    Static,
     String,
     Symbol,
+    Type,
+    Undefined,
+    Unique,
+    Unknown,
+    Using,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum Punctuators {}
+impl ContextualKeyword {
+    /// Every contextual keyword, in the order the interner starts with them.
+    pub const ALL: [ContextualKeyword; 39] = [
+        ContextualKeyword::Abstract,
+        ContextualKeyword::Accessor,
+        ContextualKeyword::Any,
+        ContextualKeyword::As,
+        ContextualKeyword::Assert,
+        ContextualKeyword::Asserts,
+        ContextualKeyword::Async,
+        ContextualKeyword::Await,
+        ContextualKeyword::Bigint,
+        ContextualKeyword::Boolean,
+        ContextualKeyword::Constructor,
+        ContextualKeyword::Declare,
+        ContextualKeyword::From,
+        ContextualKeyword::Get,
+        ContextualKeyword::Global,
+        ContextualKeyword::Infer,
+        ContextualKeyword::Intrinsic,
+        ContextualKeyword::Is,
+        ContextualKeyword::Keyof,
+        ContextualKeyword::Module,
+        ContextualKeyword::Namespace,
+        ContextualKeyword::Never,
+        ContextualKeyword::Number,
+        ContextualKeyword::Object,
+        ContextualKeyword::Of,
+        ContextualKeyword::Out,
+        ContextualKeyword::Override,
+        ContextualKeyword::Readonly,
+        ContextualKeyword::Require,
+        ContextualKeyword::Satisfies,
+        ContextualKeyword::Set,
+        ContextualKeyword::Static,
+        ContextualKeyword::String,
+        ContextualKeyword::Symbol,
+        ContextualKeyword::Type,
+        ContextualKeyword::Undefined,
+        ContextualKeyword::Unique,
+        ContextualKeyword::Unknown,
+        ContextualKeyword::Using,
+    ];
+
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            ContextualKeyword::Abstract => "abstract",
+            ContextualKeyword::Accessor => "accessor",
+            ContextualKeyword::Any => "any",
+            ContextualKeyword::As => "as",
+            ContextualKeyword::Assert => "assert",
+            ContextualKeyword::Asserts => "asserts",
+            ContextualKeyword::Async => "async",
+            ContextualKeyword::Await => "await",
+            ContextualKeyword::Bigint => "bigint",
+            ContextualKeyword::Boolean => "boolean",
+            ContextualKeyword::Constructor => "constructor",
+            ContextualKeyword::Declare => "declare",
+            ContextualKeyword::From => "from",
+            ContextualKeyword::Get => "get",
+            ContextualKeyword::Global => "global",
+            ContextualKeyword::Infer => "infer",
+            ContextualKeyword::Intrinsic => "intrinsic",
+            ContextualKeyword::Is => "is",
+            ContextualKeyword::Keyof => "keyof",
+            ContextualKeyword::Module => "module",
+            ContextualKeyword::Namespace => "namespace",
+            ContextualKeyword::Never => "never",
+            ContextualKeyword::Number => "number",
+            ContextualKeyword::Object => "object",
+            ContextualKeyword::Of => "of",
+            ContextualKeyword::Out => "out",
+            ContextualKeyword::Override => "override",
+            ContextualKeyword::Readonly => "readonly",
+            ContextualKeyword::Require => "require",
+            ContextualKeyword::Satisfies => "satisfies",
+            ContextualKeyword::Set => "set",
+            ContextualKeyword::Static => "static",
+            ContextualKeyword::String => "string",
+            ContextualKeyword::Symbol => "symbol",
+            ContextualKeyword::Type => "type",
+            ContextualKeyword::Undefined => "undefined",
+            ContextualKeyword::Unique => "unique",
+            ContextualKeyword::Unknown => "unknown",
+            ContextualKeyword::Using => "using",
+        }
+    }
+
+    /// Whether only TypeScript gives the word a meaning, so that it is always an
+    /// identifier in JavaScript.
+    pub fn is_typescript_only(&self) -> bool {
+        !matches!(
+            self,
+            ContextualKeyword::Accessor
+                | ContextualKeyword::As
+                | ContextualKeyword::Assert
+                | ContextualKeyword::Async
+                | ContextualKeyword::Await
+                | ContextualKeyword::Constructor
+                | ContextualKeyword::From
+                | ContextualKeyword::Get
+                | ContextualKeyword::Of
+                | ContextualKeyword::Set
+                | ContextualKeyword::Static
+                | ContextualKeyword::Using
+        )
+    }
+
+    pub fn from_word(word: &str) -> Option<Self> {
+        Atom::preinterned(word).and_then(Self::from_atom)
+    }
+
+    /// The contextual keyword of a pre-interned word, found by its place in the table of
+    /// them.
+    pub fn from_atom(atom: Atom<'_>) -> Option<Self> {
+        let index = atom
+            .index()
+            .checked_sub(Keyword::ALL.len() + LITERAL_WORDS.len())?;
+        Self::ALL.get(index).copied()
+    }
+}
+
+impl<'a> WordKind<'a> {
+    /// The source text of the word, with escapes in identifiers already resolved.
+    pub fn as_str(&self) -> &'a str {
+        match self {
+            WordKind::Keyword(keyword) => keyword.as_str(),
+            WordKind::Identifier(name) => name.as_str(),
+            WordKind::True => "true",
+            WordKind::False => "false",
+            WordKind::Null => "null",
+        }
+    }
+
+    /// The atom of the word, which for keywords is one of those the interner starts
+    /// with.
+    pub fn atom(&self) -> Atom<'a> {
+        match self {
+            WordKind::Identifier(name) => *name,
+            word => Atom::preinterned(word.as_str()).expect("a pre-interned word"),
+        }
+    }
+
+    /// The contextual keyword this word can be, if it is an identifier.
+    pub fn contextual_keyword(&self) -> Option<ContextualKeyword> {
+        match self {
+            WordKind::Identifier(name) => ContextualKeyword::from_atom(*name),
+            _ => None,
+        }
+    }
+}
 
 pub struct Lexer<'a> {
     source: &'a str,
-    chars: Chars<'a>,
+    // where text which is not a slice of the source, like cooked templates, is kept
+    allocator: &'a Allocator,
+    interner: Interner<'a>,
+    // byte offset of the next character to read
+    pos: usize,
     last_pos: usize,
+    // number of open `{` inside each `${ ... }` substitution we are currently in
+    template_braces: Vec<usize>,
+    options: LexerOptions,
     pub(crate) errors: Vec<Error>,
+    pub(crate) directives: Vec<Directive>,
+    // whether only comments have been read so far
+    header: bool,
+    // whether a line terminator was skipped since the last token other than a comment
+    line_break: bool,
+    // whether a `/` starts a regular expression when the lexer runs on its own
+    regex_allowed: bool,
+    // tokens read ahead by `peek_nth`, each with the state the lexer was in before it
+    peeked: VecDeque<(Token<'a>, LexerCheckpoint<'a>)>,
+    // the number of errors before the last token, which a rescan of it drops
+    token_errors: usize,
+}
+
+/// The state of a [`Lexer`] saved by [`Lexer::checkpoint`].
+#[derive(Debug, Clone)]
+pub struct LexerCheckpoint<'a> {
+    pos: usize,
+    last_pos: usize,
+    template_braces: Vec<usize>,
+    errors: usize,
+    directives: usize,
+    header: bool,
+    line_break: bool,
+    regex_allowed: bool,
+    peeked: VecDeque<(Token<'a>, LexerCheckpoint<'a>)>,
+    token_errors: usize,
 }
 
 impl<'a> Lexer<'a> {
-    pub fn new(source: &'a str) -> Self {
+    /// A lexer of `source` which interns the words and strings it reads into
+    /// `allocator`.
+    pub fn new(allocator: &'a Allocator, source: &'a str) -> Self {
+        Self::with_options(allocator, source, LexerOptions::default())
+    }
+
+    pub fn with_options(allocator: &'a Allocator, source: &'a str, options: LexerOptions) -> Self {
         Self {
             source,
-            chars: source.chars(),
+            allocator,
+            interner: Interner::new(allocator),
+            pos: 0,
             last_pos: 0,
+            template_braces: vec![],
+            options,
             errors: vec![],
+            directives: vec![],
+            header: true,
+            line_break: false,
+            regex_allowed: true,
+            peeked: VecDeque::new(),
+            token_errors: 0,
         }
     }
 
-    pub fn lex(mut self) -> (Vec<Token>, Vec<Error>) {
-        let mut tokens = vec![];
-        loop {
-            let token = self.read_next_token();
+    /// Saves the lexer state, so that it can read ahead and [`Self::rewind`] to it.
+    pub fn checkpoint(&self) -> LexerCheckpoint<'a> {
+        LexerCheckpoint {
+            peeked: self.peeked.clone(),
+            ..self.state()
+        }
+    }
+
+    /// The lexer state without the tokens read ahead.
+    fn state(&self) -> LexerCheckpoint<'a> {
+        LexerCheckpoint {
+            pos: self.pos,
+            last_pos: self.last_pos,
+            template_braces: self.template_braces.clone(),
+            errors: self.errors.len(),
+            directives: self.directives.len(),
+            header: self.header,
+            line_break: self.line_break,
+            regex_allowed: self.regex_allowed,
+            peeked: VecDeque::new(),
+            token_errors: self.token_errors,
+        }
+    }
+
+    /// Goes back to a checkpoint of this lexer. The errors and directives read since
+    /// are dropped.
+    pub fn rewind(&mut self, checkpoint: LexerCheckpoint<'a>) {
+        self.pos = checkpoint.pos;
+        self.last_pos = checkpoint.last_pos;
+        self.template_braces = checkpoint.template_braces;
+        self.errors.truncate(checkpoint.errors);
+        self.directives.truncate(checkpoint.directives);
+        self.header = checkpoint.header;
+        self.line_break = checkpoint.line_break;
+        self.regex_allowed = checkpoint.regex_allowed;
+        self.peeked = checkpoint.peeked;
+        self.token_errors = checkpoint.token_errors;
+    }
+
+    /// The directive comments read so far.
+    pub fn directives(&self) -> &[Directive] {
+        &self.directives
+    }
+
+    pub fn lex(mut self) -> (Vec<Token<'a>>, Vec<Error>) {
+        let tokens = self.read_tokens();
+        (tokens, self.errors.into_iter().collect())
+    }
+
+    /// The token `k` tokens ahead of the next one [`Iterator::next`] returns, so
+    /// `peek_nth(0)` is the next one itself. `None` past the last token.
+    ///
+    /// The errors and directives of peeked tokens are recorded as they are read.
+    pub fn peek_nth(&mut self, k: usize) -> Option<&Token<'a>> {
+        while self.peeked.len() <= k {
+            let state = self.state();
+            let token = self.read_token();
             if token.kind == TokenKind::Eof {
+                return None;
+            }
+            self.peeked.push_back((token, state));
+        }
+        self.peeked.get(k).map(|(token, _)| token)
+    }
+
+    /// Drops the tokens read ahead by [`Self::peek_nth`], going back to the end of the
+    /// last token returned, where the rescans start. Their errors and directives are
+    /// dropped too and reported again once they are read.
+    fn unpeek(&mut self) {
+        if let Some((_, state)) = self.peeked.pop_front() {
+            self.rewind(state);
+        }
+    }
+
+    /// Reads every token up to `Eof`.
+    fn read_tokens(&mut self) -> Vec<Token<'a>> {
+        self.by_ref().collect()
+    }
+
+    /// Reads the next token when the lexer runs on its own. Without a parser to tell us
+    /// whether an expression is expected, a `/` is treated as the start of a regular
+    /// expression whenever the previous token cannot end an expression.
+    fn read_token(&mut self) -> Token<'a> {
+        let mut token = self.read_next_token();
+        match token.kind {
+            TokenKind::Eof
+            | TokenKind::SingleLineComment
+            | TokenKind::MultiLineComment
+            | TokenKind::Hashbang => {}
+            TokenKind::BinaryOp(BinaryOp::Div) | TokenKind::AssignOp(AssignOp::DivAssign)
+                if self.regex_allowed =>
+            {
+                // keep the division if the pattern would be unterminated, e.g. a lone `/=`
+                let pos = self.pos;
+                let errors = self.errors.len();
+                let regex = self.read_regex(&token);
+                if self.errors[errors..]
+                    .iter()
+                    .any(|e| e.downcast_ref::<UnterminatedRegExp>().is_some())
+                {
+                    self.pos = pos;
+                    self.errors.truncate(errors);
+                    self.last_pos = token.span.end;
+                } else {
+                    token = regex;
+                }
+                self.regex_allowed = false;
+            }
+            ref kind => self.regex_allowed = !kind.ends_expression(),
+        }
+        token
+    }
+
+    /// Re-lexes a `/` or `/=` token that was just returned as the start of a
+    /// regular expression literal. The parser calls this when it expects an expression,
+    /// since only it knows whether `/` means division there.
+    pub fn rescan_slash_as_regex(&mut self, token: &Token<'a>) -> Token<'a> {
+        self.unpeek();
+        self.read_regex(token)
+    }
+
+    fn read_regex(&mut self, token: &Token<'a>) -> Token<'a> {
+        if !matches!(
+            token.kind,
+            TokenKind::BinaryOp(BinaryOp::Div) | TokenKind::AssignOp(AssignOp::DivAssign)
+        ) {
+            return token.clone();
+        }
+        let start = token.span.start;
+        // the pattern starts right after the `/`, with the `=` of a `/=`
+        self.pos = start + 1;
+        let mut in_class = false;
+        loop {
+            match self.cur() {
+                Some('/') if !in_class => break,
+                Some(c) if !is_line_terminator(c) => {
+                    self.bump();
+                    match c {
+                        '[' => in_class = true,
+                        ']' => in_class = false,
+                        '\\' => match self.cur() {
+                            Some(c) if !is_line_terminator(c) => {
+                                self.bump();
+                            }
+                            _ => {}
+                        },
+                        _ => {}
+                    }
+                }
+                _ => {
+                    self.last_pos = self.offset();
+                    self.errors.push(
+                        UnterminatedRegExp(Span {
+                            start,
+                            end: self.last_pos,
+                        })
+                        .into(),
+                    );
+                    return Token {
+                        kind: TokenKind::RegExp {
+                            pattern: &self.source[start + 1..self.last_pos],
+                            flags: "",
+                        },
+                        span: Span {
+                            start,
+                            end: self.last_pos,
+                        },
+                        had_line_break_before: token.had_line_break_before,
+                    };
+                }
+            }
+        }
+        let pattern = &self.source[start + 1..self.offset()];
+        self.bump();
+
+        let flags_start = self.offset();
+        while let Some(c) = self.cur() {
+            if !is_ident_part(c) {
                 break;
             }
-            tokens.push(token);
+            let flag_start = self.offset();
+            let flags = &self.source[flags_start..flag_start];
+            self.bump();
+            let span = Span {
+                start: flag_start,
+                end: self.offset(),
+            };
+            if !matches!(c, 'd' | 'g' | 'i' | 'm' | 's' | 'u' | 'v' | 'y') {
+                self.errors.push(InvalidRegExpFlag(c, span).into());
+            } else if flags.contains(c) {
+                self.errors.push(DuplicateRegExpFlag(c, span).into());
+            } else if (c == 'u' && flags.contains('v')) || (c == 'v' && flags.contains('u')) {
+                self.errors.push(IncompatibleRegExpFlags(span).into());
+            }
+        }
+        self.last_pos = self.offset();
+        Token {
+            kind: TokenKind::RegExp {
+                pattern,
+                flags: &self.source[flags_start..self.last_pos],
+            },
+            span: Span {
+                start,
+                end: self.last_pos,
+            },
+            had_line_break_before: token.had_line_break_before,
+        }
+    }
+
+    /// Reads the next child of a JSX element: the text up to the next `{` or `<`, or
+    /// the `{` or `<` itself. The parser calls this in place of reading the next token
+    /// after the `>` of an opening tag and after the `}` of an expression among the
+    /// children.
+    pub fn read_jsx_child(&mut self) -> Token<'a> {
+        self.unpeek();
+        if matches!(self.cur(), None | Some('{' | '<')) {
+            return self.read_next_token();
+        }
+        self.token_errors = self.errors.len();
+        let start = self.offset();
+        while let Some(c) = self.cur() {
+            let entity = match c {
+                '{' | '<' => break,
+                '>' => Some("&gt;"),
+                '}' => Some("&rbrace;"),
+                _ => None,
+            };
+            if let Some(entity) = entity {
+                let offset = self.offset();
+                self.errors
+                    .push(UnexpectedJsxText(c, entity, Span::new(offset, offset + 1)).into());
+            }
+            self.bump();
+        }
+        self.last_pos = self.offset();
+        let had_line_break_before = self.line_break;
+        self.header = false;
+        self.line_break = false;
+        Token {
+            kind: TokenKind::JsxText {
+                raw: &self.source[start..self.last_pos],
+            },
+            span: Span::new(start, self.last_pos),
+            had_line_break_before,
         }
+    }
 
-        (tokens, self.errors.into_iter().collect())
+    /// Re-lexes a word that was just returned as the name of a JSX tag or attribute,
+    /// which may go on with `-` as in `data-id`. Such a name is an identifier even if
+    /// it starts with a keyword.
+    pub fn rescan_jsx_identifier(&mut self, token: &Token<'a>) -> Token<'a> {
+        let TokenKind::Word(word) = &token.kind else {
+            return token.clone();
+        };
+        self.unpeek();
+        if self.pos != token.span.end || self.cur() != Some('-') {
+            return token.clone();
+        }
+        let mut name = word.as_str().to_string();
+        while let Some(c) = self.cur() {
+            if c != '-' && !is_ident_part(c) {
+                break;
+            }
+            self.bump();
+            name.push(c);
+        }
+        self.last_pos = self.offset();
+        Token {
+            kind: TokenKind::Word(WordKind::Identifier(self.interner.intern_owned(&name))),
+            span: Span::new(token.span.start, self.last_pos),
+            had_line_break_before: token.had_line_break_before,
+        }
+    }
+
+    /// Re-lexes a string that was just returned as the value of a JSX attribute. Such a
+    /// string has no escapes, so `"\"` is a backslash, and it may span lines.
+    pub fn rescan_jsx_attribute_string(&mut self, token: &Token<'a>) -> Token<'a> {
+        let Some(quote) = self.source[token.span.start..]
+            .chars()
+            .next()
+            .filter(|c| matches!(c, '"' | '\''))
+        else {
+            return token.clone();
+        };
+        self.unpeek();
+        // drop what reading it as an ordinary string reported
+        self.errors.truncate(self.token_errors);
+        self.pos = token.span.start + 1;
+        let mut value = String::new();
+        loop {
+            match self.bump() {
+                Some(c) if c == quote => break,
+                Some(c) => value.push(c),
+                None => {
+                    self.errors.push(
+                        UnterminatedString(Span::new(token.span.start, self.offset())).into(),
+                    );
+                    break;
+                }
+            }
+        }
+        self.last_pos = self.offset();
+        Token {
+            kind: TokenKind::String {
+                value: self.interner.intern_owned(&value),
+                raw: &self.source[token.span.start..self.last_pos],
+            },
+            span: Span::new(token.span.start, self.last_pos),
+            had_line_break_before: token.had_line_break_before,
+        }
+    }
+
+    /// Goes back to `pos` inside the token just read, like after the first `>` of a
+    /// `>>` which closes a JSX tag.
+    pub(crate) fn reset_to(&mut self, pos: usize) {
+        self.pos = pos;
+        self.last_pos = pos;
     }
 
-    fn cur(&mut self) -> Option<char> {
-        self.chars.clone().next()
+    fn cur(&self) -> Option<char> {
+        match self.source.as_bytes().get(self.pos) {
+            Some(&b) if b.is_ascii() => Some(b as char),
+            Some(_) => self.source[self.pos..].chars().next(),
+            None => None,
+        }
     }
 
     fn peek(&self) -> Option<char> {
-        self.chars.clone().nth(1)
+        let c = self.cur()?;
+        self.source[self.pos + c.len_utf8()..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.cur() == Some(c) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.cur()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.pos..]
+    }
+
+    /// Skips the rest of a line comment, up to and including its line terminator.
+    fn skip_line(&mut self) {
+        while let Some(c) = self.bump() {
+            if is_line_terminator(c) {
+                break;
+            }
+        }
+    }
+
+    /// `<!--` and `-->` comments are only allowed in scripts, by Annex B.
+    fn allows_html_comments(&self) -> bool {
+        self.options.source_type == SourceType::Script
+    }
+
+    /// Whether the `-` just read starts a `-->` comment, which must be the first thing
+    /// on its line besides whitespace and comments.
+    fn at_html_close_comment(&self) -> bool {
+        self.allows_html_comments()
+            && (self.line_break || self.header)
+            && self.rest().starts_with("->")
     }
 
     fn skip_whitespace(&mut self) {
         while let Some(c) = self.cur() {
-            if c.is_whitespace() {
-                self.chars.next();
+            if is_whitespace(c) || is_line_terminator(c) {
+                self.line_break |= is_line_terminator(c);
+                self.bump();
             } else {
                 break;
             }
         }
     }
 
-    fn read_next_kind(&mut self) -> TokenKind {
-        match self.chars.next() {
+    fn read_next_kind(&mut self) -> TokenKind<'a> {
+        match self.bump() {
             Some(c) => match c {
-                '\'' => {
-                    let (value, raw) = self.read_string_literal('\'');
-                    self.last_pos = self.offset();
-                    TokenKind::String { value, raw }
-                }
-                '\"' => {
-                    let (value, raw) = self.read_string_literal('\"');
+                '\'' | '"' => {
+                    let (value, raw) = self.read_string_literal(c);
                     self.last_pos = self.offset();
                     TokenKind::String { value, raw }
                 }
@@ -245,13 +1012,28 @@ impl<'a> Lexer<'a> {
                     TokenKind::LParen
                 }
                 '{' => {
+                    if let Some(depth) = self.template_braces.last_mut() {
+                        *depth += 1;
+                    }
                     self.last_pos = self.offset();
                     TokenKind::LBrace
                 }
-                '}' => {
-                    self.last_pos = self.offset();
-                    TokenKind::RBrace
-                }
+                '}' => match self.template_braces.last_mut() {
+                    Some(0) => {
+                        self.template_braces.pop();
+                        self.read_template(false)
+                    }
+                    Some(depth) => {
+                        *depth -= 1;
+                        self.last_pos = self.offset();
+                        TokenKind::RBrace
+                    }
+                    None => {
+                        self.last_pos = self.offset();
+                        TokenKind::RBrace
+                    }
+                },
+                '`' => self.read_template(true),
                 '[' => {
                     self.last_pos = self.offset();
                     TokenKind::LBracket
@@ -265,26 +1047,37 @@ impl<'a> Lexer<'a> {
                     TokenKind::Colon
                 }
                 '!' => {
-                    self.last_pos = self.offset();
-                    match self.peek() {
-                        Some('=') => {
-                            self.chars.next();
-                            self.last_pos = self.offset();
-                            match self.peek() {
-                                Some('=') => {
-                                    self.chars.next();
-                                    self.last_pos = self.offset();
-                                    TokenKind::BinaryOp(BinaryOp::NeNe)
-                                }
-                                _ => TokenKind::BinaryOp(BinaryOp::Ne),
-                            }
+                    let kind = if self.eat('=') {
+                        if self.eat('=') {
+                            TokenKind::BinaryOp(BinaryOp::NeNe)
+                        } else {
+                            TokenKind::BinaryOp(BinaryOp::Ne)
                         }
-                        _ => TokenKind::Bang,
-                    }
+                    } else {
+                        TokenKind::Bang
+                    };
+                    self.last_pos = self.offset();
+                    kind
                 }
                 '?' => {
+                    let kind = match self.cur() {
+                        Some('?') => {
+                            self.bump();
+                            if self.eat('=') {
+                                TokenKind::AssignOp(AssignOp::NullishAssign)
+                            } else {
+                                TokenKind::BinaryOp(BinaryOp::NullishCoalescing)
+                            }
+                        }
+                        // `a?.5:b` is a conditional expression, not optional chaining
+                        Some('.') if !matches!(self.peek(), Some(c) if c.is_ascii_digit()) => {
+                            self.bump();
+                            TokenKind::QuestionDot
+                        }
+                        _ => TokenKind::Question,
+                    };
                     self.last_pos = self.offset();
-                    TokenKind::Question
+                    kind
                 }
                 ';' => {
                     self.last_pos = self.offset();
@@ -295,63 +1088,56 @@ impl<'a> Lexer<'a> {
                     TokenKind::Comma
                 }
                 '+' => {
+                    let kind = if self.eat('+') {
+                        TokenKind::PlusPlus
+                    } else if self.eat('=') {
+                        TokenKind::AssignOp(AssignOp::AddAssign)
+                    } else {
+                        TokenKind::BinaryOp(BinaryOp::Add)
+                    };
                     self.last_pos = self.offset();
-                    match self.peek() {
-                        Some('=') => {
-                            self.chars.next();
-                            self.last_pos = self.offset();
-                            TokenKind::AssignOp(AssignOp::AddAssign)
-                        }
-                        Some('+') => {
-                            self.chars.next();
-                            self.last_pos = self.offset();
-                            TokenKind::PlusPlus
-                        }
-                        _ => TokenKind::BinaryOp(BinaryOp::Add),
-                    }
+                    kind
                 }
                 '-' => {
+                    let kind = if self.at_html_close_comment() {
+                        self.skip_line();
+                        TokenKind::SingleLineComment
+                    } else if self.eat('-') {
+                        TokenKind::MinusMinus
+                    } else if self.eat('=') {
+                        TokenKind::AssignOp(AssignOp::SubAssign)
+                    } else {
+                        TokenKind::BinaryOp(BinaryOp::Sub)
+                    };
                     self.last_pos = self.offset();
-                    match self.peek() {
-                        Some('-') => {
-                            self.chars.next();
-                            TokenKind::MinusMinus
-                        }
-                        Some('=') => {
-                            self.chars.next();
-                            TokenKind::AssignOp(AssignOp::SubAssign)
-                        }
-                        _ => TokenKind::BinaryOp(BinaryOp::Sub),
-                    }
+                    kind
                 }
                 '*' => {
-                    self.last_pos = self.offset();
-                    match self.peek() {
-                        Some('=') => {
-                            self.chars.next();
-                            TokenKind::AssignOp(AssignOp::MulAssign)
+                    let kind = if self.eat('*') {
+                        if self.eat('=') {
+                            TokenKind::AssignOp(AssignOp::ExpAssign)
+                        } else {
+                            TokenKind::BinaryOp(BinaryOp::Exp)
                         }
-                        _ => TokenKind::BinaryOp(BinaryOp::Mul),
-                    }
+                    } else if self.eat('=') {
+                        TokenKind::AssignOp(AssignOp::MulAssign)
+                    } else {
+                        TokenKind::BinaryOp(BinaryOp::Mul)
+                    };
+                    self.last_pos = self.offset();
+                    kind
                 }
                 '/' => match self.cur() {
                     Some('/') => {
-                        self.chars.next();
-                        self.chars.next();
-                        for c in self.chars.by_ref() {
-                            if is_line_terminator(c) {
-                                break;
-                            }
-                        }
+                        self.skip_line();
                         self.last_pos = self.offset();
                         TokenKind::SingleLineComment
                     }
                     Some('*') => {
-                        self.chars.next();
-                        self.chars.next();
-                        while let Some(c) = self.chars.next() {
-                            if c == '*' && matches!(self.peek(), Some('/')) {
-                                self.chars.next();
+                        self.bump();
+                        while let Some(c) = self.bump() {
+                            if c == '*' && matches!(self.cur(), Some('/')) {
+                                self.bump();
                                 break;
                             }
                         }
@@ -359,7 +1145,7 @@ impl<'a> Lexer<'a> {
                         TokenKind::MultiLineComment
                     }
                     Some('=') => {
-                        self.chars.next();
+                        self.bump();
                         self.last_pos = self.offset();
                         TokenKind::AssignOp(AssignOp::DivAssign)
                     }
@@ -369,231 +1155,192 @@ impl<'a> Lexer<'a> {
                     }
                 },
                 '%' => {
+                    let kind = if self.eat('=') {
+                        TokenKind::AssignOp(AssignOp::ModAssign)
+                    } else {
+                        TokenKind::BinaryOp(BinaryOp::Mod)
+                    };
                     self.last_pos = self.offset();
-                    match self.peek() {
-                        Some('=') => {
-                            self.chars.next();
-                            TokenKind::AssignOp(AssignOp::ModAssign)
-                        }
-                        _ => TokenKind::BinaryOp(BinaryOp::Mod),
-                    }
+                    kind
                 }
                 '=' => {
-                    self.last_pos = self.offset();
-                    match self.cur() {
-                        Some('>') => {
-                            self.chars.next();
-                            self.last_pos = self.offset();
-                            TokenKind::Arrow
+                    let kind = if self.eat('>') {
+                        TokenKind::Arrow
+                    } else if self.eat('=') {
+                        if self.eat('=') {
+                            TokenKind::BinaryOp(BinaryOp::EqEq)
+                        } else {
+                            TokenKind::BinaryOp(BinaryOp::Eq)
                         }
-                        _ => match self.peek() {
-                            Some('=') => {
-                                self.chars.next();
-                                self.last_pos = self.offset();
-                                match self.peek() {
-                                    Some('=') => {
-                                        self.chars.next();
-                                        self.last_pos = self.offset();
-                                        TokenKind::BinaryOp(BinaryOp::Eq)
-                                    }
-                                    _ => TokenKind::BinaryOp(BinaryOp::EqEq),
-                                }
-                            }
-                            _ => TokenKind::AssignOp(AssignOp::Assign),
-                        },
-                    }
+                    } else {
+                        TokenKind::AssignOp(AssignOp::Assign)
+                    };
+                    self.last_pos = self.offset();
+                    kind
                 }
                 '>' => {
-                    self.last_pos = self.offset();
-                    match self.cur() {
-                        Some('=') => {
-                            self.chars.next();
-                            self.last_pos = self.offset();
-                            TokenKind::BinaryOp(BinaryOp::Ge)
-                        }
-                        _ => match self.peek() {
-                            Some('>') => {
-                                self.chars.next();
-                                self.last_pos = self.offset();
-                                match self.peek() {
-                                    Some('>') => {
-                                        self.chars.next();
-                                        self.last_pos = self.offset();
-                                        match self.peek() {
-                                            Some('=') => {
-                                                self.chars.next();
-                                                self.last_pos = self.offset();
-                                                TokenKind::AssignOp(
-                                                    AssignOp::ZeroFillRightShiftAssign,
-                                                )
-                                            }
-                                            _ => TokenKind::BinaryOp(BinaryOp::ZeroFillRightShift),
-                                        }
-                                    }
-                                    Some('=') => {
-                                        self.chars.next();
-                                        self.last_pos = self.offset();
-                                        TokenKind::AssignOp(AssignOp::RightShiftAssign)
-                                    }
-                                    _ => TokenKind::BinaryOp(BinaryOp::RShift),
-                                }
+                    let kind = if self.eat('>') {
+                        if self.eat('>') {
+                            if self.eat('=') {
+                                TokenKind::AssignOp(AssignOp::ZeroFillRightShiftAssign)
+                            } else {
+                                TokenKind::BinaryOp(BinaryOp::ZeroFillRightShift)
                             }
-                            _ => TokenKind::BinaryOp(BinaryOp::Gt),
-                        },
-                    }
+                        } else if self.eat('=') {
+                            TokenKind::AssignOp(AssignOp::RightShiftAssign)
+                        } else {
+                            TokenKind::BinaryOp(BinaryOp::RShift)
+                        }
+                    } else if self.eat('=') {
+                        TokenKind::BinaryOp(BinaryOp::Ge)
+                    } else {
+                        TokenKind::BinaryOp(BinaryOp::Gt)
+                    };
+                    self.last_pos = self.offset();
+                    kind
                 }
                 '<' => {
-                    self.last_pos = self.offset();
-                    match self.cur() {
-                        Some('=') => {
-                            self.chars.next();
-                            self.last_pos = self.offset();
-                            TokenKind::BinaryOp(BinaryOp::Le)
+                    let kind = if self.allows_html_comments() && self.rest().starts_with("!--") {
+                        self.skip_line();
+                        TokenKind::SingleLineComment
+                    } else if self.eat('<') {
+                        if self.eat('=') {
+                            TokenKind::AssignOp(AssignOp::LeftShiftAssign)
+                        } else {
+                            TokenKind::BinaryOp(BinaryOp::LShift)
                         }
-                        _ => match self.peek() {
-                            Some('<') => {
-                                self.chars.next();
-                                self.last_pos = self.offset();
-                                TokenKind::BinaryOp(BinaryOp::LShift)
-                            }
-                            _ => TokenKind::BinaryOp(BinaryOp::Lt),
-                        },
-                    }
+                    } else if self.eat('=') {
+                        TokenKind::BinaryOp(BinaryOp::Le)
+                    } else {
+                        TokenKind::BinaryOp(BinaryOp::Lt)
+                    };
+                    self.last_pos = self.offset();
+                    kind
                 }
                 '&' => {
-                    self.last_pos = self.offset();
-                    match self.cur() {
-                        Some('&') => {
-                            self.chars.next();
-                            self.last_pos = self.offset();
+                    let kind = if self.eat('&') {
+                        if self.eat('=') {
+                            TokenKind::AssignOp(AssignOp::LogicalAndAssign)
+                        } else {
                             TokenKind::BinaryOp(BinaryOp::LogicalAnd)
                         }
-                        Some('=') => {
-                            self.chars.next();
-                            self.last_pos = self.offset();
-                            TokenKind::AssignOp(AssignOp::BitAndAssign)
-                        }
-                        _ => TokenKind::BinaryOp(BinaryOp::BitAnd),
-                    }
+                    } else if self.eat('=') {
+                        TokenKind::AssignOp(AssignOp::BitAndAssign)
+                    } else {
+                        TokenKind::BinaryOp(BinaryOp::BitAnd)
+                    };
+                    self.last_pos = self.offset();
+                    kind
                 }
                 '|' => {
-                    self.last_pos = self.offset();
-                    match self.cur() {
-                        Some('|') => {
-                            self.chars.next();
-                            self.last_pos = self.offset();
+                    let kind = if self.eat('|') {
+                        if self.eat('=') {
+                            TokenKind::AssignOp(AssignOp::LogicalOrAssign)
+                        } else {
                             TokenKind::BinaryOp(BinaryOp::LogicalOr)
                         }
-                        Some('=') => {
-                            self.chars.next();
-                            self.last_pos = self.offset();
-                            TokenKind::AssignOp(AssignOp::BitOrAssign)
-                        }
-                        _ => TokenKind::BinaryOp(BinaryOp::BitOr),
-                    }
+                    } else if self.eat('=') {
+                        TokenKind::AssignOp(AssignOp::BitOrAssign)
+                    } else {
+                        TokenKind::BinaryOp(BinaryOp::BitOr)
+                    };
+                    self.last_pos = self.offset();
+                    kind
                 }
                 '^' => {
+                    let kind = if self.eat('=') {
+                        TokenKind::AssignOp(AssignOp::BitXorAssign)
+                    } else {
+                        TokenKind::BinaryOp(BinaryOp::BitXor)
+                    };
                     self.last_pos = self.offset();
-                    match self.peek() {
-                        Some('=') => {
-                            self.chars.next();
-                            self.last_pos = self.offset();
-                            TokenKind::AssignOp(AssignOp::BitXorAssign)
-                        }
-                        _ => TokenKind::BinaryOp(BinaryOp::BitXor),
-                    }
+                    kind
                 }
                 '~' => {
                     self.last_pos = self.offset();
                     TokenKind::Tilde
                 }
-                '0'..='9' => {
-                    if c == '0' {
-                        let value = match self.cur() {
-                            Some('b') | Some('B') => self.read_binary_number(),
-                            Some('o') | Some('O') => self.read_octal_number(),
-                            Some('x') | Some('X') => self.read_hex_number(),
-                            Some(c) if c.is_whitespace() => 0.0,
-                            Some(c) if ('8'..='9').contains(&c) => {
-                                let start = self.offset();
-                                self.chars.next();
-                                let value = self.read_number(c);
-                                self.errors.push(
-                                    LegacyDecimalEscape(Span {
-                                        start: start - 1,
-                                        end: self.offset(),
-                                    })
-                                    .into(),
-                                );
-                                value
-                            }
-                            Some(c) if ('0'..='7').contains(&c) => {
-                                let start = self.offset();
-                                let value = self.read_octal_number();
-                                self.errors.push(
-                                    LegacyOctalLiteral(Span {
-                                        start: start - 1,
-                                        end: self.offset(),
-                                    })
-                                    .into(),
-                                );
-                                value
-                            }
-                            Some('.') => match self.peek() {
-                                Some(c) if c.is_ascii_digit() => self.read_number('0'),
-                                _ => {
-                                    self.errors.push(
-                                        InvalidOrUnexpectedToken(
-                                            c,
-                                            Span {
-                                                start: self.last_pos,
-                                                end: self.offset(),
-                                            },
-                                        )
-                                        .into(),
-                                    );
-                                    0.0
-                                }
-                            },
-                            _ => {
-                                self.errors.push(
-                                    InvalidOrUnexpectedToken(
-                                        c,
-                                        Span {
-                                            start: self.last_pos,
-                                            end: self.offset(),
-                                        },
-                                    )
-                                    .into(),
-                                );
-                                0.0
-                            }
-                        };
-                        self.last_pos = self.offset();
-                        TokenKind::Number { value }
-                    } else {
-                        let value = self.read_number(c);
-                        self.last_pos = self.offset();
-                        TokenKind::Number { value }
-                    }
+                '0' => {
+                    let start = self.offset() - 1;
+                    let kind = match self.cur() {
+                        Some('b') | Some('B') => self.read_radix_number(start, 2),
+                        Some('o') | Some('O') => self.read_radix_number(start, 8),
+                        Some('x') | Some('X') => self.read_radix_number(start, 16),
+                        Some(c) if c.is_ascii_digit() || c == '_' => self.read_legacy_number(start),
+                        _ => self.read_number(start, String::from('0')),
+                    };
+                    self.last_pos = self.offset();
+                    kind
+                }
+                '1'..='9' => {
+                    let kind = self.read_number(self.offset() - 1, String::from(c));
+                    self.last_pos = self.offset();
+                    kind
                 }
                 '.' => {
                     self.last_pos = self.offset();
                     match self.cur() {
                         Some(c) if c.is_ascii_digit() => {
-                            let value = self.read_number('.');
-                            TokenKind::Number { value }
+                            let kind = self.read_number(self.last_pos - 1, String::from('.'));
+                            self.last_pos = self.offset();
+                            kind
+                        }
+                        Some('.') if self.peek() == Some('.') => {
+                            self.bump();
+                            self.bump();
+                            self.last_pos = self.offset();
+                            TokenKind::DotDotDot
                         }
                         _ => TokenKind::Dot,
                     }
                 }
+                '@' => {
+                    self.last_pos = self.offset();
+                    TokenKind::At
+                }
+                '#' if self.cur() == Some('!') => {
+                    let start = self.pos - 1;
+                    // a byte order mark is not part of the source text
+                    if self.source[..start].is_empty() || &self.source[..start] == "\u{feff}" {
+                        while self.cur().is_some_and(|c| !is_line_terminator(c)) {
+                            self.bump();
+                        }
+                        self.last_pos = self.offset();
+                        TokenKind::Hashbang
+                    } else {
+                        self.bump();
+                        self.last_pos = self.offset();
+                        self.errors.push(
+                            HashbangNotAtStart(Span {
+                                start,
+                                end: self.last_pos,
+                            })
+                            .into(),
+                        );
+                        TokenKind::Unknown
+                    }
+                }
+                '#' => {
+                    self.last_pos = self.offset();
+                    TokenKind::Hash
+                }
 
                 c => {
-                    if is_ident_start(c) {
+                    if is_ident_start(c) || (c == '\\' && self.cur() == Some('u')) {
                         self.read_identifier(c)
                     } else {
                         self.last_pos = self.offset();
-                        panic!("current char: {:?}", c)
+                        self.errors.push(
+                            InvalidOrUnexpectedToken(
+                                c,
+                                Span {
+                                    start: self.last_pos - c.len_utf8(),
+                                    end: self.last_pos,
+                                },
+                            )
+                            .into(),
+                        );
+                        TokenKind::Unknown
                     }
                 }
             },
@@ -604,451 +1351,822 @@ impl<'a> Lexer<'a> {
         }
     }
 
-    fn read_next_token(&mut self) -> Token {
+    fn read_next_token(&mut self) -> Token<'a> {
         self.skip_whitespace();
+        self.token_errors = self.errors.len();
         let start = self.offset();
-        if let Some(c) = self.cur() {
-            if c == '\u{feff}' {
-                self.chars.next();
+        let kind = self.read_next_kind();
+        let span = Span {
+            start,
+            end: self.last_pos,
+        };
+        let had_line_break_before = self.line_break;
+        match kind {
+            TokenKind::SingleLineComment | TokenKind::MultiLineComment => {
+                if let Some(directive) = read_directive(self.source, span, self.header) {
+                    self.directives.push(directive);
+                }
+                // a line comment ends with its line terminator
+                self.line_break |= self.source[span.start..span.end].contains(is_line_terminator);
+            }
+            TokenKind::Hashbang => {}
+            _ => {
+                self.header = false;
+                self.line_break = false;
             }
         }
-        let kind = self.read_next_kind();
-        let end = self.last_pos;
         Token {
             kind,
-            span: Span { start, end },
+            span,
+            had_line_break_before,
         }
     }
 
     fn offset(&self) -> usize {
-        // treat as unicode, not utf-8
-        self.source.chars().count() - self.chars.clone().count()
+        self.pos
     }
 
-    fn read_identifier(&mut self, head: char) -> TokenKind {
-        let mut ident = String::from(head);
-        while let Some(c) = self.cur() {
-            if is_ident_part(c) {
-                ident.push(c);
-                self.chars.next();
-                self.last_pos = self.offset();
-            } else if c.is_whitespace()
-                || c == ':'
-                || c == '<'
-                || c == '>'
-                || c == ';'
-                || c == '{'
-                || c == '}'
-                || c == '('
-                || c == ')'
-                || c == '['
-                || c == ']'
-                || c == '.'
-                || c == ','
-                || c == '?'
-                || c == '|'
-                || c == '+'
-                || c == '-'
-                || c == '*'
-                || c == '/'
-                || c == '='
-                || c == '\\'
-            {
-                break;
-            } else {
+    /// Reads an IdentifierName whose first character (or the `\\` of its first escape)
+    /// has already been consumed.
+    fn read_identifier(&mut self, head: char) -> TokenKind<'a> {
+        let start = self.offset() - head.len_utf8();
+        // the cooked name, only allocated once an escape shows up
+        let mut escaped = None;
+        if head == '\\' {
+            let mut name = String::new();
+            if let Some(c) = self.read_identifier_escape(start, true) {
+                name.push(c);
+            }
+            escaped = Some(name);
+        }
+        loop {
+            match self.cur() {
+                Some('\\') if self.peek() == Some('u') => {
+                    let escape_start = self.offset();
+                    let name =
+                        escaped.get_or_insert_with(|| self.source[start..escape_start].to_string());
+                    self.bump();
+                    if let Some(c) = self.read_identifier_escape(escape_start, false) {
+                        name.push(c);
+                    }
+                }
+                Some(c) if is_ident_part(c) => {
+                    self.bump();
+                    if let Some(name) = escaped.as_mut() {
+                        name.push(c);
+                    }
+                }
+                _ => break,
+            }
+        }
+        self.last_pos = self.offset();
+
+        let Some(name) = escaped else {
+            let word = self.interner.intern(&self.source[start..self.last_pos]);
+            return keyword_or_identifier(word);
+        };
+        match keyword_or_identifier(self.interner.intern_owned(&name)) {
+            TokenKind::Word(WordKind::Identifier(name)) => {
+                TokenKind::Word(WordKind::Identifier(name))
+            }
+            keyword => {
                 self.errors.push(
-                    UnexpectedToken(
-                        c,
-                        Span {
-                            start: self.last_pos,
-                            end: self.offset(),
-                        },
-                    )
+                    EscapedKeyword(Span {
+                        start,
+                        end: self.last_pos,
+                    })
                     .into(),
                 );
-                self.chars.next();
+                keyword
             }
         }
+    }
 
-        match ident.as_str() {
-            "break" => TokenKind::Word(WordKind::Keyword(Keyword::Break)),
-            "case" => TokenKind::Word(WordKind::Keyword(Keyword::Case)),
-            "catch" => TokenKind::Word(WordKind::Keyword(Keyword::Catch)),
-            "class" => TokenKind::Word(WordKind::Keyword(Keyword::Class)),
-            "continue" => TokenKind::Word(WordKind::Keyword(Keyword::Continue)),
-            "const" => TokenKind::Word(WordKind::Keyword(Keyword::Const)),
-            "debugger" => TokenKind::Word(WordKind::Keyword(Keyword::Debugger)),
-            "default" => TokenKind::Word(WordKind::Keyword(Keyword::Default)),
-            "delete" => TokenKind::Word(WordKind::Keyword(Keyword::Delete)),
-            "do" => TokenKind::Word(WordKind::Keyword(Keyword::Do)),
-            "else" => TokenKind::Word(WordKind::Keyword(Keyword::Else)),
-            "export" => TokenKind::Word(WordKind::Keyword(Keyword::Export)),
-            "extends" => TokenKind::Word(WordKind::Keyword(Keyword::Extends)),
-            "finally" => TokenKind::Word(WordKind::Keyword(Keyword::Finally)),
-            "for" => TokenKind::Word(WordKind::Keyword(Keyword::For)),
-            "function" => TokenKind::Word(WordKind::Keyword(Keyword::Function)),
-            "if" => TokenKind::Word(WordKind::Keyword(Keyword::If)),
-            "import" => TokenKind::Word(WordKind::Keyword(Keyword::Import)),
-            "in" => TokenKind::Word(WordKind::Keyword(Keyword::In)),
-            "instanceof" => TokenKind::Word(WordKind::Keyword(Keyword::Instanceof)),
-            "new" => TokenKind::Word(WordKind::Keyword(Keyword::New)),
-            "return" => TokenKind::Word(WordKind::Keyword(Keyword::Return)),
-            "let" => TokenKind::Word(WordKind::Keyword(Keyword::Let)),
-            "super" => TokenKind::Word(WordKind::Keyword(Keyword::Super)),
-            "switch" => TokenKind::Word(WordKind::Keyword(Keyword::Switch)),
-            "this" => TokenKind::Word(WordKind::Keyword(Keyword::This)),
-            "throw" => TokenKind::Word(WordKind::Keyword(Keyword::Throw)),
-            "try" => TokenKind::Word(WordKind::Keyword(Keyword::Try)),
-            "typeof" => TokenKind::Word(WordKind::Keyword(Keyword::Typeof)),
-            "var" => TokenKind::Word(WordKind::Keyword(Keyword::Var)),
-            "void" => TokenKind::Word(WordKind::Keyword(Keyword::Void)),
-            "while" => TokenKind::Word(WordKind::Keyword(Keyword::While)),
-            "with" => TokenKind::Word(WordKind::Keyword(Keyword::With)),
-            "yield" => TokenKind::Word(WordKind::Keyword(Keyword::Yield)),
-            "null" => TokenKind::Word(WordKind::Null),
-            "true" => TokenKind::Word(WordKind::True),
-            "false" => TokenKind::Word(WordKind::False),
-
-            "any" => TokenKind::Word(WordKind::Keyword(Keyword::Any)),
-            "boolean" => TokenKind::Word(WordKind::Keyword(Keyword::Boolean)),
-            "declare" => TokenKind::Word(WordKind::Keyword(Keyword::Declare)),
-            "enum" => TokenKind::Word(WordKind::Keyword(Keyword::Enum)),
-            "implements" => TokenKind::Word(WordKind::Keyword(Keyword::Implements)),
-            "interface" => TokenKind::Word(WordKind::Keyword(Keyword::Interface)),
-            "module" => TokenKind::Word(WordKind::Keyword(Keyword::Module)),
-            "namespace" => TokenKind::Word(WordKind::Keyword(Keyword::Namespace)),
-            "number" => TokenKind::Word(WordKind::Keyword(Keyword::Number)),
-            "private" => TokenKind::Word(WordKind::Keyword(Keyword::Private)),
-            "protected" => TokenKind::Word(WordKind::Keyword(Keyword::Protected)),
-            "public" => TokenKind::Word(WordKind::Keyword(Keyword::Public)),
-            "require" => TokenKind::Word(WordKind::Keyword(Keyword::Require)),
-            "string" => TokenKind::Word(WordKind::Keyword(Keyword::String)),
-            "symbol" => TokenKind::Word(WordKind::Keyword(Keyword::Symbol)),
-            _ => TokenKind::Word(WordKind::Identifier(ident)),
+    /// Reads the `uXXXX` or `u{X...}` of an escape in an identifier after its `\\`
+    /// starting at `start`. Returns `None` if the escape is malformed or does not
+    /// denote a character allowed at this position.
+    fn read_identifier_escape(&mut self, start: usize, is_start: bool) -> Option<char> {
+        self.bump();
+        let c = self.read_unicode_escape().and_then(char::from_u32);
+        let span = Span {
+            start,
+            end: self.offset(),
+        };
+        match c {
+            Some(c) if is_start && is_ident_start(c) => Some(c),
+            Some(c) if !is_start && is_ident_part(c) => Some(c),
+            Some(c) => {
+                self.errors.push(InvalidIdentifierEscape(c, span).into());
+                None
+            }
+            None => {
+                self.errors.push(InvalidUnicodeEscape(span).into());
+                None
+            }
         }
     }
 
-    fn read_number(&mut self, head: char) -> f64 {
-        let mut number = String::from(head);
-        if let Some(c) = self.cur() {
-            if !c.is_ascii_digit() && c != 'e' && c != '.' {
-                return number
-                    .parse::<f64>()
-                    .expect("failed to parse number as f64");
-            }
+    /// Reads the rest of a decimal literal starting at `start` whose leading digit or `.`
+    /// has already been consumed into `number`.
+    fn read_number(&mut self, start: usize, mut number: String) -> TokenKind<'a> {
+        let mut is_integer = !number.starts_with('.');
+        // legacy literals like `019` cannot be bigints either
+        let is_legacy = number.len() > 1 && number.starts_with('0');
+        self.read_digits(10, &mut number);
+        if is_integer && self.cur() == Some('.') {
+            self.bump();
+            number.push('.');
+            is_integer = false;
+            self.read_digits(10, &mut number);
         }
-        while let Some(c) = self.chars.next() {
-            if c.is_ascii_digit() {
-                number.push(c);
-                self.last_pos = self.offset();
-            } else if c.is_whitespace() {
-                break;
-            } else if c == '.' {
-                number.push(c);
-                self.last_pos = self.offset();
-                match self.peek() {
-                    Some(c) => {
-                        if !c.is_ascii_digit() && c != 'e' && c != 'E' {
-                            self.errors.push(
-                                InvalidOrUnexpectedToken(
-                                    c,
-                                    Span {
-                                        start: self.last_pos,
-                                        end: self.offset(),
-                                    },
-                                )
-                                .into(),
-                            );
-                        }
-                    }
-                    _ => {
-                        self.errors.push(
-                            InvalidOrUnexpectedToken(
-                                c,
-                                Span {
-                                    start: self.last_pos,
-                                    end: self.offset(),
-                                },
-                            )
-                            .into(),
-                        );
-                    }
-                };
-            } else if c == 'e' || c == 'E' {
-                number.push(c);
-                self.last_pos = self.offset();
-                match self.chars.next() {
-                    Some(c) if matches!(c, '+') | matches!(c, '-') => {
-                        number.push(c);
-                        self.last_pos = self.offset();
-                        match self.peek() {
-                            Some(c) if !c.is_ascii_digit() => {
-                                self.errors.push(
-                                    InvalidOrUnexpectedToken(
-                                        c,
-                                        Span {
-                                            start: self.last_pos,
-                                            end: self.offset(),
-                                        },
-                                    )
-                                    .into(),
-                                );
-                            }
-                            None | Some(_) => {}
-                        }
-                    }
-                    Some(c) if c.is_ascii_digit() => {
-                        number.push(c);
-                        self.last_pos = self.offset();
-                    }
-                    _ => {
-                        self.errors.push(
-                            InvalidOrUnexpectedToken(
-                                c,
-                                Span {
-                                    start: self.last_pos,
-                                    end: self.offset(),
-                                },
-                            )
-                            .into(),
-                        );
-                    }
-                };
-            } else {
+        if let Some(e @ ('e' | 'E')) = self.cur() {
+            let mantissa = number.len();
+            let exponent_start = self.offset();
+            self.bump();
+            number.push(e);
+            is_integer = false;
+            if let Some(sign @ ('+' | '-')) = self.cur() {
+                self.bump();
+                number.push(sign);
+            }
+            if !self.read_digits(10, &mut number) {
                 self.errors.push(
                     InvalidOrUnexpectedToken(
-                        c,
+                        e,
                         Span {
-                            start: self.last_pos,
+                            start: exponent_start,
                             end: self.offset(),
                         },
                     )
                     .into(),
                 );
+                number.truncate(mantissa);
             }
         }
-        number
-            .parse::<f64>()
-            .unwrap_or_else(|_| panic!("failed to parse number as f64: {}", number.as_str()))
-    }
-
-    fn read_binary_number(&mut self) -> f64 {
-        self.chars.next();
-        let mut number = String::new();
-        while let Some(c) = self.chars.next() {
-            if c.is_ascii_digit() {
-                number.push(c);
-                self.last_pos = self.offset();
-            } else if c.is_whitespace() {
-                break;
-            } else if c == '.' {
-                self.errors.push(
-                    UnexpectedNumber(
-                        c,
-                        Span {
-                            start: self.last_pos,
-                            end: self.offset(),
-                        },
-                    )
-                    .into(),
-                )
-            } else {
+        if self.cur() == Some('n') {
+            self.bump();
+            if !is_integer || is_legacy {
                 self.errors.push(
-                    InvalidOrUnexpectedToken(
-                        c,
-                        Span {
-                            start: self.last_pos,
-                            end: self.offset(),
-                        },
-                    )
+                    InvalidBigInt(Span {
+                        start,
+                        end: self.offset(),
+                    })
                     .into(),
                 );
             }
+            self.check_after_number();
+            return TokenKind::BigInt {
+                value: self.allocator.alloc_str(&to_decimal_digits(&number, 10)),
+                raw: &self.source[start..self.offset()],
+            };
+        }
+        self.check_after_number();
+        TokenKind::Number {
+            value: number.parse::<f64>().unwrap_or(f64::NAN),
+            raw: &self.source[start..self.offset()],
         }
-        isize::from_str_radix(&number, 2).expect("failed to parse number as binary") as f64
     }
 
-    fn read_octal_number(&mut self) -> f64 {
-        self.chars.next();
-        let mut number = String::new();
-        while let Some(c) = self.chars.next() {
-            if c.is_ascii_digit() && ('0'..'7').contains(&c) {
-                number.push(c);
-                self.last_pos = self.offset();
-            } else if c.is_whitespace() {
-                break;
-            } else if c == '.' {
-                self.errors.push(
+    /// Reads digits of `radix` into `number`, skipping `_` numeric separators and
+    /// reporting the ones in invalid positions. Returns whether any digit was read.
+    fn read_digits(&mut self, radix: u32, number: &mut String) -> bool {
+        let len = number.len();
+        // offset of the separator right before the current character, if any
+        let mut separator = None;
+        let mut reported = false;
+        while let Some(c) = self.cur() {
+            let offset = self.offset();
+            if c == '_' {
+                self.bump();
+                let span = Span {
+                    start: offset,
+                    end: offset + 1,
+                };
+                if separator.is_some() {
+                    if !reported {
+                        self.errors.push(ConsecutiveNumericSeparators(span).into());
+                    }
+                    reported = true;
+                } else if !self.source[..offset].ends_with(|c: char| c.is_digit(radix)) {
+                    self.errors.push(NumericSeparatorNotAllowed(span).into());
+                    reported = true;
+                } else {
+                    reported = false;
+                }
+                separator = Some(offset);
+                continue;
+            }
+            match c.to_digit(radix) {
+                Some(_) => number.push(c),
+                None if c.is_ascii_digit() => self.errors.push(
                     UnexpectedNumber(
                         c,
                         Span {
-                            start: self.last_pos,
-                            end: self.offset(),
+                            start: offset,
+                            end: offset + 1,
                         },
                     )
                     .into(),
+                ),
+                None => break,
+            }
+            self.bump();
+            separator = None;
+        }
+        if let (Some(offset), false) = (separator, reported) {
+            self.errors.push(
+                NumericSeparatorNotAllowed(Span {
+                    start: offset,
+                    end: offset + 1,
+                })
+                .into(),
+            );
+        }
+        number.len() > len
+    }
+
+    /// Reads a `0b`, `0o` or `0x` literal. The leading `0` has already been consumed.
+    fn read_radix_number(&mut self, start: usize, radix: u32) -> TokenKind<'a> {
+        self.bump();
+        let mut digits = String::new();
+        if !self.read_digits(radix, &mut digits) {
+            self.errors.push(
+                InvalidOrUnexpectedToken(
+                    '0',
+                    Span {
+                        start,
+                        end: self.offset(),
+                    },
                 )
+                .into(),
+            );
+        }
+        if self.eat('n') {
+            self.check_after_number();
+            return TokenKind::BigInt {
+                value: self.allocator.alloc_str(&to_decimal_digits(&digits, radix)),
+                raw: &self.source[start..self.offset()],
+            };
+        }
+        self.check_after_number();
+        TokenKind::Number {
+            value: digits.chars().fold(0_f64, |value, c| {
+                value * radix as f64 + c.to_digit(radix).unwrap_or_default() as f64
+            }),
+            raw: &self.source[start..self.offset()],
+        }
+    }
+
+    /// Reads a legacy literal with a leading zero like `017` (octal) or `019` (decimal).
+    /// The leading `0` has already been consumed.
+    fn read_legacy_number(&mut self, start: usize) -> TokenKind<'a> {
+        let mut number = String::from('0');
+        while let Some(c) = self.cur() {
+            if c == '_' {
+                // separators are not allowed after a leading zero
+                let offset = self.offset();
+                self.errors.push(
+                    NumericSeparatorNotAllowed(Span {
+                        start: offset,
+                        end: offset + 1,
+                    })
+                    .into(),
+                );
+            } else if c.is_ascii_digit() {
+                number.push(c);
             } else {
+                break;
+            }
+            self.bump();
+        }
+        let span = Span {
+            start,
+            end: self.offset(),
+        };
+        if number.chars().all(|c| ('0'..='7').contains(&c)) {
+            if !self.options.allows_legacy_octal_literal() {
+                self.errors.push(LegacyOctalLiteral(span).into());
+            }
+            if self.cur() == Some('n') {
+                self.bump();
                 self.errors.push(
-                    InvalidOrUnexpectedToken(
-                        c,
-                        Span {
-                            start: self.last_pos,
-                            end: self.offset(),
-                        },
-                    )
+                    InvalidBigInt(Span {
+                        start,
+                        end: self.offset(),
+                    })
                     .into(),
                 );
             }
+            self.check_after_number();
+            TokenKind::Number {
+                value: number
+                    .chars()
+                    .fold(0_f64, |value, c| value * 8.0 + (c as u8 - b'0') as f64),
+                raw: &self.source[start..self.offset()],
+            }
+        } else {
+            if !self.options.allows_legacy_octal_literal() {
+                self.errors.push(LeadingZeroDecimal(span).into());
+            }
+            self.read_number(start, number)
         }
-        isize::from_str_radix(&number, 8).expect("failed to parse number as octal") as f64
     }
 
-    fn read_hex_number(&mut self) -> f64 {
-        self.chars.next();
-        let mut number = String::new();
-        while let Some(c) = self.chars.next() {
-            if c.is_ascii_digit() || ('a'..='e').contains(&c) || ('A'..='E').contains(&c) {
-                number.push(c);
-                self.last_pos = self.offset();
-            } else if c.is_whitespace() {
-                break;
-            } else if c == '.' {
-                self.errors.push(
-                    UnexpectedNumber(
-                        c,
-                        Span {
-                            start: self.last_pos,
-                            end: self.offset(),
-                        },
-                    )
-                    .into(),
-                )
-            } else {
+    /// An identifier or keyword cannot immediately follow a numeric literal, e.g. `3in`.
+    fn check_after_number(&mut self) {
+        if let Some(c) = self.cur() {
+            if is_ident_start(c) {
+                let offset = self.offset();
                 self.errors.push(
                     InvalidOrUnexpectedToken(
                         c,
                         Span {
-                            start: self.last_pos,
-                            end: self.offset(),
+                            start: offset,
+                            end: offset + c.len_utf8(),
                         },
                     )
                     .into(),
                 );
             }
         }
-        isize::from_str_radix(&number, 16).expect("failed to parse number as hex") as f64
-    }
-
-    fn read_string_literal(&mut self, start_quote: char) -> (String, String) {
-        let mut string = String::new();
-        let mut raw = String::from(start_quote);
-        while let Some(c) = self.chars.next() {
-            if c == '\\' {
-                if let Some(next_char) = self.chars.next() {
-                    raw.push(c);
-                    match next_char {
-                        'n' => {
-                            string.push('\n');
-                            raw.push('n')
-                        }
-                        'r' => {
-                            string.push('\r');
-                            raw.push('r')
-                        }
-                        't' => {
-                            string.push('\t');
-                            raw.push('t')
-                        }
-                        '\\' => {
-                            string.push('\\');
-                            raw.push('\\')
-                        }
-                        '\'' => {
-                            string.push('\'');
-                            raw.push('\'');
-                        }
-                        '"' => {
-                            string.push('"');
-                            raw.push('"');
+    }
+
+    /// Reads a string literal after its opening quote and returns its cooked value and
+    /// its source text including the quotes.
+    fn read_string_literal(&mut self, quote: char) -> (Atom<'a>, &'a str) {
+        let start = self.offset() - 1;
+        // the cooked value, only allocated once an escape shows up
+        let mut escaped = None;
+        let end = loop {
+            match self.cur() {
+                Some(c) if c == quote => {
+                    let end = self.offset();
+                    self.bump();
+                    break end;
+                }
+                Some('\\') => {
+                    let escape_start = self.offset();
+                    let value = escaped
+                        .get_or_insert_with(|| self.source[start + 1..escape_start].to_string());
+                    self.bump();
+                    if let Some(c) = self.read_string_escape(escape_start) {
+                        value.push(c);
+                    }
+                }
+                Some(c @ ('\n' | '\r')) => {
+                    // the string ends here, the line terminator is not part of it
+                    let offset = self.offset();
+                    self.errors.push(
+                        NewlineInString(Span {
+                            start: offset,
+                            end: offset + c.len_utf8(),
+                        })
+                        .into(),
+                    );
+                    break offset;
+                }
+                Some(c) => {
+                    self.bump();
+                    if let Some(value) = escaped.as_mut() {
+                        value.push(c);
+                    }
+                }
+                None => {
+                    self.errors.push(
+                        UnterminatedString(Span {
+                            start,
+                            end: self.offset(),
+                        })
+                        .into(),
+                    );
+                    break self.offset();
+                }
+            }
+        };
+        let value = match escaped {
+            Some(value) => self.interner.intern_owned(&value),
+            None => self.interner.intern(&self.source[start + 1..end]),
+        };
+        (value, &self.source[start..self.offset()])
+    }
+
+    /// Reads the escape sequence after the `\\` at `start` in a string literal.
+    /// Returns `None` for a line continuation or an escape that has been reported.
+    fn read_string_escape(&mut self, start: usize) -> Option<char> {
+        let c = self.bump()?;
+        let value = match c {
+            '\r' => {
+                self.eat('\n');
+                return None;
+            }
+            '\n' | '\u{2028}' | '\u{2029}' => return None,
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'v' => '\u{b}',
+            '0' if !matches!(self.cur(), Some(c) if c.is_ascii_digit()) => '\0',
+            '0'..='7' => {
+                // LegacyOctalEscapeSequence: up to three digits when it starts with 0-3
+                let mut code = c.to_digit(8)?;
+                let max_digits = if c <= '3' { 3 } else { 2 };
+                for _ in 1..max_digits {
+                    match self.cur().and_then(|c| c.to_digit(8)) {
+                        Some(digit) => {
+                            self.bump();
+                            code = code * 8 + digit;
                         }
-                        _ => {
-                            string.push(next_char);
-                            raw.push(next_char);
+                        None => break,
+                    }
+                }
+                if self.options.is_strict() {
+                    self.errors.push(
+                        LegacyOctalEscape(Span {
+                            start,
+                            end: self.offset(),
+                        })
+                        .into(),
+                    );
+                }
+                char::from_u32(code)?
+            }
+            '8' | '9' => {
+                if self.options.is_strict() {
+                    self.errors.push(
+                        LegacyDecimalEscape(Span {
+                            start,
+                            end: self.offset(),
+                        })
+                        .into(),
+                    );
+                }
+                c
+            }
+            'x' => {
+                let value = self
+                    .read_hex_digit()
+                    .and_then(|hi| Some(hi * 16 + self.read_hex_digit()?));
+                match value.and_then(char::from_u32) {
+                    Some(c) => c,
+                    None => {
+                        self.errors.push(
+                            InvalidHexEscape(Span {
+                                start,
+                                end: self.offset(),
+                            })
+                            .into(),
+                        );
+                        return None;
+                    }
+                }
+            }
+            'u' => match self.read_unicode_escape_sequence() {
+                Some(0xd800..=0xdfff) => {
+                    // a `String` cannot hold a lone surrogate, keep going with U+FFFD
+                    self.errors.push(
+                        LoneSurrogate(Span {
+                            start,
+                            end: self.offset(),
+                        })
+                        .into(),
+                    );
+                    char::REPLACEMENT_CHARACTER
+                }
+                Some(code) => char::from_u32(code)?,
+                None => {
+                    self.errors.push(
+                        InvalidUnicodeEscape(Span {
+                            start,
+                            end: self.offset(),
+                        })
+                        .into(),
+                    );
+                    return None;
+                }
+            },
+            c => c,
+        };
+        Some(value)
+    }
+
+    /// Reads a template span. `head` is true when the opening backquote was consumed,
+    /// false when we are resuming after the `}` that closes a substitution.
+    fn read_template(&mut self, head: bool) -> TokenKind<'a> {
+        let start = self.offset() - 1;
+        // the cooked value, only allocated once an escape or a <CR> shows up
+        let mut escaped = None;
+        let mut invalid = false;
+        let (end, tail) = loop {
+            let end = self.offset();
+            match self.bump() {
+                Some('`') => break (end, true),
+                Some('$') if self.cur() == Some('{') => {
+                    self.bump();
+                    self.template_braces.push(0);
+                    break (end, false);
+                }
+                Some('\\') => {
+                    let value =
+                        escaped.get_or_insert_with(|| self.source[start + 1..end].to_string());
+                    match self.read_template_escape() {
+                        Some(Some(c)) => value.push(c),
+                        Some(None) => {}
+                        None => invalid = true,
+                    }
+                }
+                Some('\r') => {
+                    // <CR><LF> and <CR> are both normalized to <LF>
+                    let value =
+                        escaped.get_or_insert_with(|| self.source[start + 1..end].to_string());
+                    if self.cur() == Some('\n') {
+                        self.bump();
+                    }
+                    value.push('\n');
+                }
+                Some(c) => {
+                    if let Some(value) = escaped.as_mut() {
+                        value.push(c);
+                    }
+                }
+                None => {
+                    self.errors.push(
+                        UnterminatedTemplate(Span {
+                            start,
+                            end: self.offset(),
+                        })
+                        .into(),
+                    );
+                    break (end, true);
+                }
+            }
+        };
+        self.last_pos = self.offset();
+        let text = &self.source[start + 1..end];
+        let raw: &'a str = if text.contains('\r') {
+            let raw = text.replace("\r\n", "\n").replace('\r', "\n");
+            self.allocator.alloc_str(&raw)
+        } else {
+            text
+        };
+        let cooked: Option<&'a str> = match escaped {
+            _ if invalid => None,
+            Some(value) => Some(self.allocator.alloc_str(&value)),
+            None => Some(text),
+        };
+        match (head, tail) {
+            (true, true) => TokenKind::NoSubstitutionTemplate { cooked, raw },
+            (false, true) => TokenKind::TemplateTail { cooked, raw },
+            (true, false) => TokenKind::TemplateHead { cooked, raw },
+            (false, false) => TokenKind::TemplateMiddle { cooked, raw },
+        }
+    }
+
+    /// Reads the escape sequence after a `\` in a template.
+    /// Returns `None` for an invalid escape (the cooked value is then undefined),
+    /// `Some(None)` for a line continuation and `Some(Some(c))` otherwise.
+    fn read_template_escape(&mut self) -> Option<Option<char>> {
+        let c = self.bump()?;
+        match c {
+            '\r' => {
+                if self.cur() == Some('\n') {
+                    self.bump();
+                }
+                return Some(None);
+            }
+            '\n' | '\u{2028}' | '\u{2029}' => return Some(None),
+            _ => {}
+        }
+        let value = match c {
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'v' => '\u{b}',
+            '0' if !matches!(self.cur(), Some(c) if c.is_ascii_digit()) => '\0',
+            '0'..='9' => return None,
+            'x' => {
+                let hi = self.read_hex_digit()?;
+                let lo = self.read_hex_digit()?;
+                char::from_u32(hi * 16 + lo)?
+            }
+            'u' => match self.read_unicode_escape_sequence()? {
+                0xd800..=0xdfff => char::REPLACEMENT_CHARACTER,
+                code => char::from_u32(code)?,
+            },
+            c => c,
+        };
+        Some(Some(value))
+    }
+
+    /// Reads the rest of a `\u` escape like [`Self::read_unicode_escape`], combining a
+    /// surrogate pair written as `\uXXXX\uXXXX` into one code point. A lone surrogate
+    /// is returned as is.
+    fn read_unicode_escape_sequence(&mut self) -> Option<u32> {
+        let code = self.read_unicode_escape()?;
+        if !(0xd800..=0xdbff).contains(&code) || !self.source[self.pos..].starts_with("\\u") {
+            return Some(code);
+        }
+        let saved = self.pos;
+        self.bump();
+        self.bump();
+        match self.read_unicode_escape() {
+            Some(trail @ 0xdc00..=0xdfff) => {
+                Some(0x10000 + ((code - 0xd800) << 10) + (trail - 0xdc00))
+            }
+            _ => {
+                self.pos = saved;
+                Some(code)
+            }
+        }
+    }
+
+    /// Reads `XXXX` or `{X...}` after `\u`.
+    fn read_unicode_escape(&mut self) -> Option<u32> {
+        if self.cur() == Some('{') {
+            self.bump();
+            let mut code: u32 = 0;
+            let mut digits = 0;
+            loop {
+                match self.cur() {
+                    Some('}') if digits > 0 => {
+                        self.bump();
+                        return Some(code);
+                    }
+                    Some(c) if c.is_ascii_hexdigit() => {
+                        self.bump();
+                        digits += 1;
+                        code = code * 16 + c.to_digit(16)?;
+                        if code > 0x10ffff {
+                            return None;
                         }
                     }
+                    _ => return None,
                 }
-            } else if c == start_quote {
-                raw.push(c);
-                break;
-            } else {
-                string.push(c);
-                raw.push(c);
             }
-            self.last_pos = self.offset();
         }
-        (string, raw)
+        let mut code = 0;
+        for _ in 0..4 {
+            code = code * 16 + self.read_hex_digit()?;
+        }
+        Some(code)
     }
-}
 
-// FIXME: support unicode like 'let ユニコード = 10'
-fn is_ident_start(ch: char) -> bool {
-    ch.is_ascii_lowercase() || ch.is_ascii_uppercase() || ch == '$' || ch == '_'
+    fn read_hex_digit(&mut self) -> Option<u32> {
+        let c = self.cur()?;
+        let digit = c.to_digit(16)?;
+        self.bump();
+        Some(digit)
+    }
 }
 
-fn is_ident_part(ch: char) -> bool {
-    ch.is_ascii_lowercase()
-        || ch.is_ascii_uppercase()
-        || ch == '$'
-        || ch == '_'
-        || ch.is_ascii_digit()
-        || ch == '\u{200c}'
+/// Yields the tokens [`Lexer::lex`] returns, comments included, up to the end of
+/// the source.
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        if let Some((token, _)) = self.peeked.pop_front() {
+            return Some(token);
+        }
+        let token = self.read_token();
+        (token.kind != TokenKind::Eof).then_some(token)
+    }
 }
 
-fn is_line_terminator(ch: char) -> bool {
-    ch == '\n' || ch == '\r' || ch == '\u{2028}' || ch == '\u{2029}'
+/// Tells keywords from identifiers by the atom of the word, as the interner starts
+/// with the keywords and literal words at the front.
+fn keyword_or_identifier(atom: Atom<'_>) -> TokenKind<'_> {
+    let index = atom.index();
+    let word = match Keyword::ALL.get(index) {
+        Some(&keyword) => WordKind::Keyword(keyword),
+        None => match index.checked_sub(Keyword::ALL.len()) {
+            Some(0) => WordKind::Null,
+            Some(1) => WordKind::True,
+            Some(2) => WordKind::False,
+            _ => WordKind::Identifier(atom),
+        },
+    };
+    TokenKind::Word(word)
 }
 
-pub fn lex(source: &str) -> Vec<Token> {
-    let mut lexer = Lexer::new(source);
-    let mut tokens = vec![];
-    loop {
-        let token = lexer.read_next_token();
-        if token.kind == TokenKind::Eof {
-            break;
-        }
-        tokens.push(token);
+fn is_ident_start(ch: char) -> bool {
+    if ch.is_ascii() {
+        return ch.is_ascii_alphabetic() || ch == '$' || ch == '_';
     }
-    tokens
+    unicode_id_start::is_id_start_unicode(ch)
 }
 
-pub fn lex_error(source: &str) -> Vec<Error> {
-    let mut lexer = Lexer::new(source);
-    let mut tokens = vec![];
-    loop {
-        let token = lexer.read_next_token();
-        if token.kind == TokenKind::Eof {
-            break;
-        }
-        tokens.push(token);
+fn is_ident_part(ch: char) -> bool {
+    if ch.is_ascii() {
+        return ch.is_ascii_alphanumeric() || ch == '$' || ch == '_';
     }
-    lexer.errors
+    // ZWNJ and ZWJ are allowed in IdentifierPart on top of ID_Continue
+    ch == '\u{200c}' || ch == '\u{200d}' || unicode_id_start::is_id_continue_unicode(ch)
 }
 
-pub fn run_lexer(source: &str) -> Result<Vec<Token>, Vec<miette::Report>> {
-    let mut l = Lexer::new(source);
-    let mut tokens = vec![];
-    loop {
-        let token = l.read_next_token();
-        if token.kind == TokenKind::Eof {
-            break;
+/// Converts digits of `radix` into their decimal representation without going through
+/// a fixed-size integer, so bigints of any length keep every digit.
+fn to_decimal_digits(digits: &str, radix: u32) -> String {
+    if radix == 10 {
+        let trimmed = digits.trim_start_matches('0');
+        return if trimmed.is_empty() { "0" } else { trimmed }.to_string();
+    }
+    // little-endian limbs in base 10^9
+    let mut limbs: Vec<u64> = vec![0];
+    for c in digits.chars() {
+        let mut carry = c.to_digit(radix).unwrap_or_default() as u64;
+        for limb in limbs.iter_mut() {
+            let value = *limb * radix as u64 + carry;
+            *limb = value % 1_000_000_000;
+            carry = value / 1_000_000_000;
         }
-        tokens.push(token)
+        if carry > 0 {
+            limbs.push(carry);
+        }
+    }
+    let mut decimal = limbs.pop().unwrap_or_default().to_string();
+    for limb in limbs.iter().rev() {
+        decimal.push_str(&format!("{:09}", limb));
+    }
+    decimal
+}
+
+fn is_line_terminator(ch: char) -> bool {
+    ch == '\n' || ch == '\r' || ch == '\u{2028}' || ch == '\u{2029}'
+}
+
+/// Whether `ch` is whitespace other than a line terminator. Unlike
+/// [`char::is_whitespace`], this includes the byte order mark.
+fn is_whitespace(ch: char) -> bool {
+    match ch {
+        '\t' | '\u{b}' | '\u{c}' | '\u{feff}' => true,
+        // the space separators of Unicode
+        ' ' | '\u{a0}' | '\u{1680}' | '\u{2000}'..='\u{200a}' => true,
+        '\u{202f}' | '\u{205f}' | '\u{3000}' => true,
+        _ => false,
     }
-    if l.errors.is_empty() {
+}
+
+pub fn lex<'a>(allocator: &'a Allocator, source: &'a str) -> Vec<Token<'a>> {
+    let mut lexer = Lexer::new(allocator, source);
+    lexer.read_tokens()
+}
+
+pub fn lex_error(source: &str) -> Vec<Error> {
+    let allocator = Allocator::new();
+    let mut lexer = Lexer::new(&allocator, source);
+    lexer.read_tokens();
+    lexer.errors
+}
+
+/// Patches `tokens`, read by [`lex`] from the source before `edit`, into the tokens
+/// of `source`, the source after it. See [`Lexer::relex`].
+pub fn relex<'a>(
+    allocator: &'a Allocator,
+    source: &'a str,
+    tokens: Vec<Token<'_>>,
+    edit: &TextEdit,
+) -> Vec<Token<'a>> {
+    Lexer::new(allocator, source).relex(tokens, edit)
+}
+
+/// Reads the directive comments of `source`, like `// @ts-ignore`.
+pub fn lex_directives(source: &str) -> Vec<Directive> {
+    let allocator = Allocator::new();
+    let mut lexer = Lexer::new(&allocator, source);
+    lexer.read_tokens();
+    lexer.directives
+}
+
+pub fn run_lexer<'a>(
+    allocator: &'a Allocator,
+    source: &'a str,
+) -> Result<Vec<Token<'a>>, Vec<miette::Report>> {
+    run_lexer_with_options(allocator, source, LexerOptions::default())
+}
+
+pub fn run_lexer_with_options<'a>(
+    allocator: &'a Allocator,
+    source: &'a str,
+    options: LexerOptions,
+) -> Result<Vec<Token<'a>>, Vec<miette::Report>> {
+    let (tokens, errors) = if options.syntax.is_jsx() {
+        Parser::with_options(allocator, source, options).lex()
+    } else {
+        let mut l = Lexer::with_options(allocator, source, options);
+        let tokens = l.read_tokens();
+        (tokens, l.errors)
+    };
+    // warnings like lone surrogates do not make the source invalid
+    if errors
+        .iter()
+        .all(|e| e.severity() == Some(miette::Severity::Warning))
+    {
         Ok(tokens)
     } else {
-        Err(l.errors)
+        Err(errors)
     }
 }
 
@@ -1058,52 +2176,75 @@ mod tests {
 
     #[test]
     fn simple_add() {
+        let allocator = Allocator::new();
         assert_eq!(
-            lex("1+2"),
+            lex(&allocator, "1+2"),
             vec![
                 Token {
                     span: Span { start: 0, end: 1 },
-                    kind: TokenKind::Number { value: 1_f64 },
+                    kind: TokenKind::Number {
+                        value: 1_f64,
+                        raw: "1",
+                    },
+                    had_line_break_before: false,
                 },
                 Token {
                     span: Span { start: 1, end: 2 },
                     kind: TokenKind::BinaryOp(BinaryOp::Add),
+                    had_line_break_before: false,
                 },
                 Token {
                     span: Span { start: 2, end: 3 },
-                    kind: TokenKind::Number { value: 2_f64 },
+                    kind: TokenKind::Number {
+                        value: 2_f64,
+                        raw: "2",
+                    },
+                    had_line_break_before: false,
                 },
             ]
         );
     }
     #[test]
     fn variable_decl() {
+        let allocator = Allocator::new();
         assert_eq!(
-            lex("const foo = 1 + 1"),
+            lex(&allocator, "const foo = 1 + 1"),
             vec![
                 Token {
                     span: Span { start: 0, end: 5 },
                     kind: TokenKind::Word(WordKind::Keyword(Keyword::Const)),
+                    had_line_break_before: false,
                 },
                 Token {
                     span: Span { start: 6, end: 9 },
-                    kind: TokenKind::Word(WordKind::Identifier("foo".to_string())),
+                    kind: TokenKind::Word(WordKind::Identifier(Atom::new_in("foo", &allocator))),
+                    had_line_break_before: false,
                 },
                 Token {
                     span: Span { start: 10, end: 11 },
                     kind: TokenKind::AssignOp(AssignOp::Assign),
+                    had_line_break_before: false,
                 },
                 Token {
                     span: Span { start: 12, end: 13 },
-                    kind: TokenKind::Number { value: 1_f64 },
+                    kind: TokenKind::Number {
+                        value: 1_f64,
+                        raw: "1",
+                    },
+                    had_line_break_before: false,
                 },
                 Token {
                     span: Span { start: 14, end: 15 },
-                    kind: TokenKind::BinaryOp(BinaryOp::Add)
+                    kind: TokenKind::BinaryOp(BinaryOp::Add),
+                    had_line_break_before: false,
                 },
                 Token {
                     span: Span { start: 16, end: 17 },
-                    kind: TokenKind::Number { value: 1_f64 },
+                    kind: TokenKind::Number {
+                        value: 1_f64,
+                        raw: "1",
+                    },
+                    had_line_break_before: false,
                 },
             ]
         );
@@ -1111,122 +2252,197 @@ mod tests {
 
     #[test]
     fn sigleline_commnet() {
+        let allocator = Allocator::new();
         assert_eq!(
-            lex("// this is single line comment
+            lex(
+                &allocator,
+                "// this is single line comment
 const foo = 1 + 1
-"),
+"
+            ),
             vec![
                 Token {
                     span: Span { start: 0, end: 31 },
                     kind: TokenKind::SingleLineComment,
+                    had_line_break_before: false,
                 },
                 Token {
                     span: Span { start: 31, end: 36 },
                     kind: TokenKind::Word(WordKind::Keyword(Keyword::Const)),
+                    had_line_break_before: true,
                 },
                 Token {
                     span: Span { start: 37, end: 40 },
-                    kind: TokenKind::Word(WordKind::Identifier("foo".to_string())),
+                    kind: TokenKind::Word(WordKind::Identifier(Atom::new_in("foo", &allocator))),
+                    had_line_break_before: false,
                 },
                 Token {
                     span: Span { start: 41, end: 42 },
                     kind: TokenKind::AssignOp(AssignOp::Assign),
+                    had_line_break_before: false,
                 },
                 Token {
                     span: Span { start: 43, end: 44 },
-                    kind: TokenKind::Number { value: 1_f64 },
+                    kind: TokenKind::Number {
+                        value: 1_f64,
+                        raw: "1",
+                    },
+                    had_line_break_before: false,
                 },
                 Token {
                     span: Span { start: 45, end: 46 },
                     kind: TokenKind::BinaryOp(BinaryOp::Add),
+                    had_line_break_before: false,
                 },
                 Token {
                     span: Span { start: 47, end: 48 },
-                    kind: TokenKind::Number { value: 1_f64 },
+                    kind: TokenKind::Number {
+                        value: 1_f64,
+                        raw: "1",
+                    },
+                    had_line_break_before: false,
                 }
             ]
         );
     }
 
+    #[test]
+    fn line_break_before() {
+        let allocator = Allocator::new();
+        let breaks = |source| {
+            lex(&allocator, source)
+                .into_iter()
+                .map(|token| token.had_line_break_before)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            breaks("a b\nc\r\nd\u{2028}e"),
+            [false, false, true, true, true]
+        );
+        // comments keep the line breaks in and after them
+        assert_eq!(
+            breaks("a /* \n */ b /* */ c // d\ne"),
+            [false, false, true, false, false, false, true]
+        );
+        assert_eq!(breaks("\n/* a */ b"), [true, true]);
+    }
+
     #[test]
     fn multiline_commnet() {
+        let allocator = Allocator::new();
         assert_eq!(
-            lex("/* This is
+            lex(
+                &allocator,
+                "/* This is
 multiline
 comment
-*/"),
+*/"
+            ),
             vec![Token {
                 span: Span { start: 0, end: 31 },
                 kind: TokenKind::MultiLineComment,
+                had_line_break_before: false,
             }]
         );
     }
 
     #[test]
     fn identifier_names() {
+        let allocator = Allocator::new();
         assert_eq!(
-            lex("myVariable"),
+            lex(&allocator, "myVariable"),
             vec![Token {
                 span: Span { start: 0, end: 10 },
-                kind: TokenKind::Word(WordKind::Identifier("myVariable".to_string())),
+                kind: TokenKind::Word(WordKind::Identifier(Atom::new_in("myVariable", &allocator))),
+                had_line_break_before: false,
             }]
         );
         assert_eq!(
-            lex("_myVariable"),
+            lex(&allocator, "_myVariable"),
             vec![Token {
                 span: Span { start: 0, end: 11 },
-                kind: TokenKind::Word(WordKind::Identifier("_myVariable".to_string())),
+                kind: TokenKind::Word(WordKind::Identifier(Atom::new_in(
+                    "_myVariable",
+                    &allocator
+                ))),
+                had_line_break_before: false,
             }]
         );
         assert_eq!(
-            lex("$myVariable"),
+            lex(&allocator, "$myVariable"),
             vec![Token {
                 span: Span { start: 0, end: 11 },
-                kind: TokenKind::Word(WordKind::Identifier("$myVariable".to_string())),
+                kind: TokenKind::Word(WordKind::Identifier(Atom::new_in(
+                    "$myVariable",
+                    &allocator
+                ))),
+                had_line_break_before: false,
             }]
         );
         assert_eq!(
-            lex("\u{006D}yVariable"),
+            lex(&allocator, "\u{006D}yVariable"),
             vec![Token {
                 span: Span { start: 0, end: 10 },
-                kind: TokenKind::Word(WordKind::Identifier("myVariable".to_string())),
+                kind: TokenKind::Word(WordKind::Identifier(Atom::new_in("myVariable", &allocator))),
+                had_line_break_before: false,
             }]
         );
-        // FIXME: `unicode-xid` crate is not resolved
-        // assert_eq!(
-        //     lex("Åaaaaaaaaa"),
-        //     vec![Token {
-        //         span: Span { start: 0, end: 11 },
-        //         kind: TokenKind::Word(WordKind::Identifier("Åaaaaaaaaa".to_string())),
-        //     }]
-        // );
+        assert_eq!(
+            lex(&allocator, "Åaaaaaaaaa"),
+            vec![Token {
+                span: Span { start: 0, end: 11 },
+                kind: TokenKind::Word(WordKind::Identifier(Atom::new_in("Åaaaaaaaaa", &allocator))),
+                had_line_break_before: false,
+            }]
+        );
+        assert_eq!(
+            lex(&allocator, "let ユニコード = 10")[1].kind,
+            TokenKind::Word(WordKind::Identifier(Atom::new_in("ユニコード", &allocator)))
+        );
 
         assert_eq!(
-            lex("my$Variable"),
+            lex(&allocator, "my$Variable"),
             vec![Token {
                 span: Span { start: 0, end: 11 },
-                kind: TokenKind::Word(WordKind::Identifier("my$Variable".to_string())),
+                kind: TokenKind::Word(WordKind::Identifier(Atom::new_in(
+                    "my$Variable",
+                    &allocator
+                ))),
+                had_line_break_before: false,
             },]
         );
         assert_eq!(
-            lex("my_variable"),
+            lex(&allocator, "my_variable"),
             vec![Token {
                 span: Span { start: 0, end: 11 },
-                kind: TokenKind::Word(WordKind::Identifier("my_variable".to_string())),
+                kind: TokenKind::Word(WordKind::Identifier(Atom::new_in(
+                    "my_variable",
+                    &allocator
+                ))),
+                had_line_break_before: false,
             },]
         );
         assert_eq!(
-            lex("my\u{0056}ariable"),
+            lex(&allocator, "my\u{0056}ariable"),
             vec![Token {
                 span: Span { start: 0, end: 10 },
-                kind: TokenKind::Word(WordKind::Identifier("my\u{0056}ariable".to_string())),
+                kind: TokenKind::Word(WordKind::Identifier(Atom::new_in(
+                    "my\u{0056}ariable",
+                    &allocator
+                ))),
+                had_line_break_before: false,
             }]
         );
         assert_eq!(
-            lex("myVariable\u{200C}"),
+            lex(&allocator, "myVariable\u{200C}"),
             vec![Token {
-                span: Span { start: 0, end: 11 },
-                kind: TokenKind::Word(WordKind::Identifier("myVariable\u{200C}".to_string())),
+                // spans are UTF-8 byte offsets, U+200C takes three bytes
+                span: Span { start: 0, end: 13 },
+                kind: TokenKind::Word(WordKind::Identifier(Atom::new_in(
+                    "myVariable\u{200C}",
+                    &allocator
+                ))),
+                had_line_break_before: false,
             }]
         );
 
@@ -1234,168 +2450,262 @@ comment
         // 123Var
         // var //reserved
         // my-Variable // - is not allowed
-        assert_eq!(lex_error("my-Variable").len(), 1);
+        assert_eq!(lex(&allocator, "my-Variable").len(), 3);
+    }
+
+    #[test]
+    fn escaped_identifier_names() {
+        let allocator = Allocator::new();
+        let ident =
+            |name: &str| TokenKind::Word(WordKind::Identifier(Atom::new_in(name, &allocator)));
+        assert_eq!(
+            lex(&allocator, "\\u006DyVariable"),
+            vec![Token {
+                span: Span { start: 0, end: 15 },
+                kind: ident("myVariable"),
+                had_line_break_before: false,
+            }]
+        );
+        assert_eq!(
+            lex(&allocator, "my\\u{56}ariable")[0].kind,
+            ident("myVariable")
+        );
+        assert_eq!(lex(&allocator, "\\u{1D400}x")[0].kind, ident("\u{1D400}x"));
+        assert_eq!(lex(&allocator, "a\\u200Db")[0].kind, ident("a\u{200D}b"));
+        // escaped TypeScript type names are plain identifiers
+        assert_eq!(lex(&allocator, "\\u0061ny")[0].kind, ident("any"));
+        assert!(lex_error("\\u006DyVariable \\u0061ny").is_empty());
+
+        // escaped keywords keep their kind but are reported
+        assert_eq!(
+            lex(&allocator, "\\u0076ar")[0].kind,
+            TokenKind::Word(WordKind::Keyword(Keyword::Var))
+        );
+        assert_eq!(lex_error("\\u0076ar").len(), 1);
+        assert_eq!(lex_error("v\\u{61}r").len(), 1);
+        assert_eq!(lex_error("\\u0074rue").len(), 1);
+
+        // not a valid identifier character or not an escape at all
+        assert_eq!(lex_error("\\u0031abc").len(), 1);
+        assert_eq!(lex_error("a\\u002Db").len(), 1);
+        assert_eq!(lex_error("\\u{1F600}").len(), 1);
+        assert_eq!(lex_error("a\\u{110000}").len(), 1);
+        assert_eq!(lex_error("a\\u00").len(), 1);
     }
 
     #[test]
     fn reserved_keyword() {
+        let allocator = Allocator::new();
         assert_eq!(
-            lex("var"),
+            lex(&allocator, "var"),
             vec![Token {
                 span: Span { start: 0, end: 3 },
                 kind: TokenKind::Word(WordKind::Keyword(Keyword::Var)),
+                had_line_break_before: false,
             }]
         );
         assert_eq!(
-            lex("if"),
+            lex(&allocator, "if"),
             vec![Token {
                 span: Span { start: 0, end: 2 },
                 kind: TokenKind::Word(WordKind::Keyword(Keyword::If)),
+                had_line_break_before: false,
             }]
         );
         assert_eq!(
-            lex("else"),
+            lex(&allocator, "else"),
             vec![Token {
                 span: Span { start: 0, end: 4 },
                 kind: TokenKind::Word(WordKind::Keyword(Keyword::Else)),
+                had_line_break_before: false,
             }]
         );
     }
 
     #[test]
     fn numeric_literals() {
+        let allocator = Allocator::new();
         assert_eq!(
-            lex("0"),
+            lex(&allocator, "0"),
             vec![Token {
                 span: Span { start: 0, end: 1 },
-                kind: TokenKind::Number { value: 0_f64 },
+                kind: TokenKind::Number {
+                    value: 0_f64,
+                    raw: "0",
+                },
+                had_line_break_before: false,
             }]
         );
         assert_eq!(
-            lex("123"),
+            lex(&allocator, "123"),
             vec![Token {
                 span: Span { start: 0, end: 3 },
-                kind: TokenKind::Number { value: 123_f64 },
+                kind: TokenKind::Number {
+                    value: 123_f64,
+                    raw: "123",
+                },
+                had_line_break_before: false,
             }]
         );
 
         // DecimalIntegerLiteral ExponentPart
         assert_eq!(
-            lex("124e4"),
+            lex(&allocator, "124e4"),
             vec![Token {
                 span: Span { start: 0, end: 5 },
-                kind: TokenKind::Number { value: 124e4_f64 },
+                kind: TokenKind::Number {
+                    value: 124e4_f64,
+                    raw: "124e4",
+                },
+                had_line_break_before: false,
             }]
         );
 
         // DecimalIntegerLiteral . DecimalDigitsopt ExponentPartopt
         assert_eq!(
-            lex("125.456"),
+            lex(&allocator, "125.456"),
             vec![Token {
                 span: Span { start: 0, end: 7 },
-                kind: TokenKind::Number { value: 125.456_f64 },
+                kind: TokenKind::Number {
+                    value: 125.456_f64,
+                    raw: "125.456",
+                },
+                had_line_break_before: false,
             }]
         );
         assert_eq!(
-            lex("127e-4"),
+            lex(&allocator, "127e-4"),
             vec![Token {
                 span: Span { start: 0, end: 6 },
-                kind: TokenKind::Number { value: 127e-4_f64 },
+                kind: TokenKind::Number {
+                    value: 127e-4_f64,
+                    raw: "127e-4",
+                },
+                had_line_break_before: false,
             }]
         );
         assert_eq!(
-            lex("128e+4"),
+            lex(&allocator, "128e+4"),
             vec![Token {
                 span: Span { start: 0, end: 6 },
-                kind: TokenKind::Number { value: 128e+4_f64 },
+                kind: TokenKind::Number {
+                    value: 128e+4_f64,
+                    raw: "128e+4",
+                },
+                had_line_break_before: false,
             }]
         );
 
         // // // DecimalDigits ExponentPartopt
         assert_eq!(
-            lex(".456"),
+            lex(&allocator, ".456"),
             vec![Token {
                 span: Span { start: 0, end: 4 },
-                kind: TokenKind::Number { value: 0.456_f64 },
+                kind: TokenKind::Number {
+                    value: 0.456_f64,
+                    raw: ".456",
+                },
+                had_line_break_before: false,
             }]
         );
 
         // BinaryIntegerLiteral
         assert_eq!(
-            lex("0b1010"),
+            lex(&allocator, "0b1010"),
             vec![Token {
                 span: Span { start: 0, end: 6 },
-                kind: TokenKind::Number { value: 10_f64 },
+                kind: TokenKind::Number {
+                    value: 10_f64,
+                    raw: "0b1010",
+                },
+                had_line_break_before: false,
             }]
         );
 
         // OctalIntegerLiteral
         assert_eq!(
-            lex("0o123"),
+            lex(&allocator, "0o123"),
             vec![Token {
                 span: Span { start: 0, end: 5 },
-                kind: TokenKind::Number { value: 83_f64 },
+                kind: TokenKind::Number {
+                    value: 83_f64,
+                    raw: "0o123",
+                },
+                had_line_break_before: false,
             }]
         );
 
         // HexIntegerLiteral
         assert_eq!(
-            lex("0x123"),
+            lex(&allocator, "0x123"),
             vec![Token {
                 span: Span { start: 0, end: 5 },
-                kind: TokenKind::Number { value: 291_f64 },
+                kind: TokenKind::Number {
+                    value: 291_f64,
+                    raw: "0x123",
+                },
+                had_line_break_before: false,
             }]
         );
     }
 
     #[test]
     fn string_literals() {
+        let allocator = Allocator::new();
         assert_eq!(
-            lex("let str1 = 'This is a simple string.'"),
+            lex(&allocator, "let str1 = 'This is a simple string.'"),
             vec![
                 Token {
                     span: Span { start: 0, end: 3 },
                     kind: TokenKind::Word(WordKind::Keyword(Keyword::Let)),
+                    had_line_break_before: false,
                 },
                 Token {
                     span: Span { start: 4, end: 8 },
-                    kind: TokenKind::Word(WordKind::Identifier("str1".to_string())),
+                    kind: TokenKind::Word(WordKind::Identifier(Atom::new_in("str1", &allocator))),
+                    had_line_break_before: false,
                 },
                 Token {
                     span: Span { start: 9, end: 10 },
                     kind: TokenKind::AssignOp(AssignOp::Assign),
+                    had_line_break_before: false,
                 },
                 Token {
                     span: Span { start: 11, end: 37 },
                     kind: TokenKind::String {
-                        value: "This is a simple string.".to_string(),
-                        raw: "'This is a simple string.'".to_string()
+                        value: Atom::new_in("This is a simple string.", &allocator),
+                        raw: "'This is a simple string.'"
                     },
+                    had_line_break_before: false,
                 }
             ]
         );
 
         assert_eq!(
-            lex(r#"let str2 = "This is a simple string.""#),
+            lex(&allocator, r#"let str2 = "This is a simple string.""#),
             vec![
                 Token {
                     span: Span { start: 0, end: 3 },
                     kind: TokenKind::Word(WordKind::Keyword(Keyword::Let)),
+                    had_line_break_before: false,
                 },
                 Token {
                     span: Span { start: 4, end: 8 },
-                    kind: TokenKind::Word(WordKind::Identifier("str2".to_string())),
+                    kind: TokenKind::Word(WordKind::Identifier(Atom::new_in("str2", &allocator))),
+                    had_line_break_before: false,
                 },
                 Token {
                     span: Span { start: 9, end: 10 },
                     kind: TokenKind::AssignOp(AssignOp::Assign),
+                    had_line_break_before: false,
                 },
                 Token {
                     span: Span { start: 11, end: 37 },
                     kind: TokenKind::String {
-                        value: "This is a simple string.".to_string(),
-                        raw: "\"This is a simple string.\"".to_string()
+                        value: Atom::new_in("This is a simple string.", &allocator),
+                        raw: "\"This is a simple string.\""
                     },
+                    had_line_break_before: false,
                 }
             ]
         )
@@ -1403,110 +2713,1010 @@ comment
 
     #[test]
     fn escape_string_literal() {
+        let allocator = Allocator::new();
         assert_eq!(
-            lex(r"'say \'Hello\''"),
+            lex(&allocator, r"'say \'Hello\''"),
             vec![Token {
                 span: Span { start: 0, end: 15 },
                 kind: TokenKind::String {
-                    value: "say 'Hello'".to_string(),
-                    raw: r"'say \'Hello\''".to_string()
+                    value: Atom::new_in("say 'Hello'", &allocator),
+                    raw: r"'say \'Hello\''"
                 },
+                had_line_break_before: false,
             }]
         );
         assert_eq!(
-            lex(r#"'say \"Hello\"'"#),
+            lex(&allocator, r#"'say \"Hello\"'"#),
             vec![Token {
                 span: Span { start: 0, end: 15 },
                 kind: TokenKind::String {
-                    value: r#"say "Hello""#.to_string(),
-                    raw: r#"'say \"Hello\"'"#.to_string()
+                    value: Atom::new_in(r#"say "Hello""#, &allocator),
+                    raw: r#"'say \"Hello\"'"#
                 },
+                had_line_break_before: false,
             }]
         );
         assert_eq!(
-            lex(r#""Line1\nLine2""#),
+            lex(&allocator, r#""Line1\nLine2""#),
             vec![Token {
                 span: Span { start: 0, end: 14 },
                 kind: TokenKind::String {
-                    value: "Line1\nLine2".to_string(),
-                    raw: r#""Line1\nLine2""#.to_string()
+                    value: Atom::new_in("Line1\nLine2", &allocator),
+                    raw: r#""Line1\nLine2""#
                 },
+                had_line_break_before: false,
             }]
         );
     }
 
+    #[test]
+    fn string_escapes() {
+        let allocator = Allocator::new();
+        let value = |source: &'static str| match lex(&allocator, source).remove(0).kind {
+            TokenKind::String { value, .. } => value,
+            kind => panic!("expected a string, got {:?}", kind),
+        };
+        assert_eq!(value(r"'\b\f\n\r\t\v\0'"), "\u{8}\u{c}\n\r\t\u{b}\0");
+        assert_eq!(value(r"'\x41B\u{43}\u{1F600}'"), "ABC😀");
+        assert_eq!(value(r"'😀'"), "😀");
+        assert_eq!(value(r"'\a\'\\'"), "a'\\");
+        // line continuations
+        assert_eq!(value("'a\\\nb\\\r\nc\\\u{2028}d'"), "abcd");
+        // LS and PS may appear unescaped
+        assert_eq!(value("'a\u{2028}b'"), "a\u{2028}b");
+        assert!(lex_error(r"'\x41B\u{43}😀\0'").is_empty());
+
+        assert_eq!(
+            lex(&allocator, r"'\u{61}'")[0].kind,
+            TokenKind::String {
+                value: Atom::new_in("a", &allocator),
+                raw: r"'\u{61}'",
+            }
+        );
+
+        assert_eq!(lex_error(r"'\x4'").len(), 1);
+        assert_eq!(lex_error(r"'\xZZ'").len(), 1);
+        assert_eq!(lex_error(r"'\u12'").len(), 1);
+        assert_eq!(lex_error(r"'\u{110000}'").len(), 1);
+        assert_eq!(value(r"'\uD83D!'"), "\u{FFFD}!");
+        assert_eq!(lex_error(r"'\uD83D!'").len(), 1);
+        assert_eq!(lex_error(r"'\uDE00'").len(), 1);
+    }
+
+    #[test]
+    fn unterminated_strings() {
+        let allocator = Allocator::new();
+        assert_eq!(
+            lex(&allocator, "'abc"),
+            vec![Token {
+                span: Span { start: 0, end: 4 },
+                kind: TokenKind::String {
+                    value: Atom::new_in("abc", &allocator),
+                    raw: "'abc",
+                },
+                had_line_break_before: false,
+            }]
+        );
+        assert_eq!(lex_error("'abc").len(), 1);
+        assert_eq!(lex_error(r#""abc\"#).len(), 1);
+
+        // the string stops before the line break and lexing resumes on the next line
+        let tokens = lex(&allocator, "'abc\nfoo");
+        assert_eq!(tokens[0].span, Span { start: 0, end: 4 });
+        assert_eq!(
+            tokens[1].kind,
+            TokenKind::Word(WordKind::Identifier(Atom::new_in("foo", &allocator)))
+        );
+        assert_eq!(lex_error("'abc\nfoo").len(), 1);
+        assert_eq!(lex_error("'abc\r\nfoo'").len(), 2);
+    }
+
+    #[test]
+    fn legacy_octal_escapes() {
+        let allocator = Allocator::new();
+        let lex_script = |source: &'static str, target, strict| {
+            let options = LexerOptions {
+                target,
+                source_type: SourceType::Script,
+                strict,
+                ..Default::default()
+            };
+            Lexer::with_options(&allocator, source, options).lex()
+        };
+        let lex_sloppy = |source: &'static str| lex_script(source, EsVersion::Es3, false);
+        let (tokens, errors) = lex_sloppy(r"'\0\7\12\101\400\8\08'");
+        assert!(errors.is_empty());
+        assert_eq!(
+            tokens[0].kind,
+            TokenKind::String {
+                value: Atom::new_in("\0\u{7}\n\u{41}\u{20}0\u{38}\0\u{38}", &allocator),
+                raw: r"'\0\7\12\101\400\8\08'",
+            }
+        );
+        assert!(lex_sloppy("017 019").1.is_empty());
+        assert_eq!(lex_script("017", EsVersion::Es5, false).1.len(), 1);
+        assert_eq!(lex_script("017", EsVersion::Es3, true).1.len(), 1);
+        // only the literals depend on the target
+        assert!(lex_script(r"'\07\8'", EsVersion::Es2015, false)
+            .1
+            .is_empty());
+        assert_eq!(lex_script(r"'\07\8'", EsVersion::Es3, true).1.len(), 2);
+
+        assert_eq!(lex_error(r"'\7'").len(), 1);
+        assert_eq!(lex_error(r"'\101'").len(), 1);
+        assert_eq!(lex_error(r"'\8'").len(), 1);
+        assert_eq!(lex_error(r"'\08'").len(), 1);
+        assert_eq!(lex_error("017").len(), 1);
+
+        let errors = lex_error("019.5");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string(),
+            "Decimals with leading zeros are not allowed"
+        );
+        assert_eq!(
+            lex_error(r"'\9'")[0].to_string(),
+            "Legacy decimal escape is not permitted in strict mode"
+        );
+    }
+
+    #[test]
+    fn hashbang() {
+        let allocator = Allocator::new();
+        let raws = |source: &str| {
+            let (tokens, errors) = Lexer::new(&allocator, source).lex();
+            assert!(errors.is_empty(), "{errors:?}");
+            tokens
+                .iter()
+                .map(|t| t.raw(source).to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            raws("#!/usr/bin/env node\nfoo"),
+            ["#!/usr/bin/env node", "foo"]
+        );
+        let tokens = lex(&allocator, "\u{feff}#!node\r\nfoo");
+        assert_eq!(tokens[0].kind, TokenKind::Hashbang);
+        assert!(tokens[1].had_line_break_before);
+
+        // a hashbang does not end the comments a directive may follow
+        let mut lexer = Lexer::new(&allocator, "#!node\n// @ts-nocheck\nfoo");
+        lexer.read_tokens();
+        assert_eq!(lexer.directives().len(), 1);
+
+        let source = "foo\n#!node";
+        let tokens = lex(&allocator, source);
+        assert_eq!(tokens[1].kind, TokenKind::Unknown);
+        assert_eq!(tokens[1].raw(source), "#!");
+        assert_eq!(
+            lex_error(source)[0].to_string(),
+            "'#!' can only be used at the start of a file"
+        );
+    }
+
+    #[test]
+    fn html_comments() {
+        let allocator = Allocator::new();
+        let lex_script = |source: &str| {
+            let options = LexerOptions {
+                source_type: SourceType::Script,
+                ..Default::default()
+            };
+            let (tokens, errors) = Lexer::with_options(&allocator, source, options).lex();
+            assert!(errors.is_empty(), "{errors:?}");
+            tokens
+                .into_iter()
+                .filter(|t| {
+                    !matches!(
+                        t.kind,
+                        TokenKind::SingleLineComment | TokenKind::MultiLineComment
+                    )
+                })
+                .map(|t| t.raw(source).to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(lex_script("x <!-- y\nz"), ["x", "z"]);
+        assert_eq!(lex_script("--> x\ny"), ["y"]);
+        assert_eq!(lex_script("x\n  /* a */ --> y\nz"), ["x", "z"]);
+        // `-->` after a token on the same line is a decrement and a `>`
+        assert_eq!(lex_script("x --> y"), ["x", "--", ">", "y"]);
+
+        // modules have no HTML-like comments
+        let source = "x <!-- y\n--> z";
+        let raws = lex(&allocator, source)
+            .iter()
+            .map(|t| t.raw(source).to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(raws, ["x", "<", "!", "--", "y", "--", ">", "z"]);
+    }
+
+    #[test]
+    fn whitespace_and_line_terminators() {
+        let allocator = Allocator::new();
+        // NBSP, ZWNBSP and the other space separators are whitespace
+        let source = "a\u{a0}\u{feff}\u{2003}\u{3000}b";
+        let tokens = lex(&allocator, source);
+        assert_eq!(tokens[1].raw(source), "b");
+        assert!(!tokens[1].had_line_break_before);
+
+        // LS and PS end lines, also for ASI and line comments
+        for terminator in ["\u{2028}", "\u{2029}"] {
+            let source = format!("a{terminator}b // c{terminator}d");
+            let tokens = lex(&allocator, &source);
+            assert!(tokens[1].had_line_break_before);
+            assert_eq!(tokens[3].raw(&source), "d");
+            assert!(tokens[3].had_line_break_before);
+        }
+
+        // the next line character and the zero width space are neither
+        assert_eq!(lex_error("a\u{85}b").len(), 1);
+        assert_eq!(lex_error("a\u{200b}b").len(), 1);
+        // but they can be in strings and comments
+        assert!(lex_error("'\u{85}' // \u{85}").is_empty());
+    }
+
     #[test]
     fn ex() {
+        let allocator = Allocator::new();
         assert_eq!(
-            lex("/="),
+            lex(&allocator, "/="),
             vec![Token {
                 span: Span { start: 0, end: 2 },
                 kind: TokenKind::AssignOp(AssignOp::DivAssign),
+                had_line_break_before: false,
             }]
         );
     }
 
     #[test]
     fn ts() {
+        let allocator = Allocator::new();
         assert_eq!(
-            lex("const foo: any = 1"),
+            lex(&allocator, "const foo: any = 1"),
             vec![
                 Token {
                     span: Span { start: 0, end: 5 },
                     kind: TokenKind::Word(WordKind::Keyword(Keyword::Const)),
+                    had_line_break_before: false,
                 },
                 Token {
                     span: Span { start: 6, end: 9 },
-                    kind: TokenKind::Word(WordKind::Identifier("foo".to_string())),
+                    kind: TokenKind::Word(WordKind::Identifier(Atom::new_in("foo", &allocator))),
+                    had_line_break_before: false,
                 },
                 Token {
                     span: Span { start: 9, end: 10 },
                     kind: TokenKind::Colon,
+                    had_line_break_before: false,
                 },
                 Token {
                     span: Span { start: 11, end: 14 },
-                    kind: TokenKind::Word(WordKind::Keyword(Keyword::Any)),
+                    kind: TokenKind::Word(WordKind::Identifier(Atom::new_in("any", &allocator))),
+                    had_line_break_before: false,
                 },
                 Token {
                     span: Span { start: 15, end: 16 },
                     kind: TokenKind::AssignOp(AssignOp::Assign),
+                    had_line_break_before: false,
                 },
                 Token {
                     span: Span { start: 17, end: 18 },
-                    kind: TokenKind::Number { value: 1_f64 },
+                    kind: TokenKind::Number {
+                        value: 1_f64,
+                        raw: "1",
+                    },
+                    had_line_break_before: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn template_literal() {
+        let allocator = Allocator::new();
+        assert_eq!(
+            lex(&allocator, "`Hello World!`"),
+            vec![Token {
+                span: Span { start: 0, end: 14 },
+                kind: TokenKind::NoSubstitutionTemplate {
+                    cooked: Some("Hello World!"),
+                    raw: "Hello World!"
+                },
+                had_line_break_before: false,
+            }]
+        );
+        assert_eq!(
+            lex(&allocator, r"`Hello ${name}!`"),
+            vec![
+                Token {
+                    span: Span { start: 0, end: 9 },
+                    kind: TokenKind::TemplateHead {
+                        cooked: Some("Hello "),
+                        raw: "Hello "
+                    },
+                    had_line_break_before: false,
+                },
+                Token {
+                    span: Span { start: 9, end: 13 },
+                    kind: TokenKind::Word(WordKind::Identifier(Atom::new_in("name", &allocator))),
+                    had_line_break_before: false,
+                },
+                Token {
+                    span: Span { start: 13, end: 16 },
+                    kind: TokenKind::TemplateTail {
+                        cooked: Some("!"),
+                        raw: "!"
+                    },
+                    had_line_break_before: false,
                 },
             ]
         );
     }
 
-    // #[test]
-    // fn template_literal() {
-    //     assert_eq!(
-    //         lex("`Hello World!`"),
-    //         vec![
-    //             Token {
-    //                 kind: TokenKind::Backquote,
-    //                 span: Span { start: 0, end: 1 },
-    //             },
-    //             Token {
-    //                 kind: TokenKind::Word(WordKind::Identifier("Hello World!".to_string())),
-    //                 span: Span { start: 1, end: 14 },
-    //             },
-    //             Token {
-    //                 kind: TokenKind::Backquote,
-    //                 span: Span { start: 14, end: 15 },
-    //             },
-    //         ]
-    //     );
-    //     assert_eq!(
-    //         lex(r"`Hello ${name}!`"),
-    //         vec![Token {
-    //             span: Span { start: 0, end: 17 },
-    //             kind: TokenKind::String {
-    //                 value: r"Hello, ${name}!".to_string(),
-    //                 raw: r"Hello, ${name}!".to_string()
-    //             },
-    //         }]
-    //     );
-    // }
+    #[test]
+    fn template_literal_substitutions() {
+        let allocator = Allocator::new();
+        let kinds = |source| {
+            lex(&allocator, source)
+                .into_iter()
+                .map(|token| token.kind)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            kinds("`a${b}c${d}e`"),
+            vec![
+                TokenKind::TemplateHead {
+                    cooked: Some("a"),
+                    raw: "a"
+                },
+                TokenKind::Word(WordKind::Identifier(Atom::new_in("b", &allocator))),
+                TokenKind::TemplateMiddle {
+                    cooked: Some("c"),
+                    raw: "c"
+                },
+                TokenKind::Word(WordKind::Identifier(Atom::new_in("d", &allocator))),
+                TokenKind::TemplateTail {
+                    cooked: Some("e"),
+                    raw: "e"
+                },
+            ]
+        );
+        // braces inside a substitution do not close it
+        assert_eq!(
+            kinds("`${ {a: `${b}`} }`"),
+            vec![
+                TokenKind::TemplateHead {
+                    cooked: Some(""),
+                    raw: ""
+                },
+                TokenKind::LBrace,
+                TokenKind::Word(WordKind::Identifier(Atom::new_in("a", &allocator))),
+                TokenKind::Colon,
+                TokenKind::TemplateHead {
+                    cooked: Some(""),
+                    raw: ""
+                },
+                TokenKind::Word(WordKind::Identifier(Atom::new_in("b", &allocator))),
+                TokenKind::TemplateTail {
+                    cooked: Some(""),
+                    raw: ""
+                },
+                TokenKind::RBrace,
+                TokenKind::TemplateTail {
+                    cooked: Some(""),
+                    raw: ""
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn template_literal_cooked_and_raw() {
+        let allocator = Allocator::new();
+        assert_eq!(
+            lex(&allocator, r"`\n\x41\u{1F600}\uD83D\uDE00\``")[0].kind,
+            TokenKind::NoSubstitutionTemplate {
+                cooked: Some("\nA\u{1F600}\u{1F600}`"),
+                raw: r"\n\x41\u{1F600}\uD83D\uDE00\`"
+            }
+        );
+        // line continuations are dropped from the cooked value, CRLF is normalized
+        assert_eq!(
+            lex(&allocator, "`a\\\r\nb\r\nc`")[0].kind,
+            TokenKind::NoSubstitutionTemplate {
+                cooked: Some("ab\nc"),
+                raw: "a\\\nb\nc"
+            }
+        );
+        // invalid escapes are allowed in tagged templates, but have no cooked value
+        assert_eq!(
+            lex(&allocator, r"`\unicode and \u{110000} and \01`")[0].kind,
+            TokenKind::NoSubstitutionTemplate {
+                cooked: None,
+                raw: r"\unicode and \u{110000} and \01"
+            }
+        );
+        // without escapes, both are slices of the source
+        let source = "`plain`";
+        let TokenKind::NoSubstitutionTemplate {
+            cooked: Some(cooked),
+            raw,
+        } = lex(&allocator, source)[0].kind
+        else {
+            panic!("expected a template")
+        };
+        assert!(std::ptr::eq(cooked, &source[1..6]) && std::ptr::eq(raw, &source[1..6]));
+    }
+
+    #[test]
+    fn unterminated_template_literal() {
+        assert_eq!(lex_error("`abc").len(), 1);
+        assert_eq!(lex_error("`abc ${ d }").len(), 1);
+    }
+
+    #[test]
+    fn regexp_literal() {
+        let allocator = Allocator::new();
+        assert_eq!(
+            lex(&allocator, "/ab+c/gi"),
+            vec![Token {
+                span: Span { start: 0, end: 8 },
+                kind: TokenKind::RegExp {
+                    pattern: "ab+c",
+                    flags: "gi"
+                },
+                had_line_break_before: false,
+            }]
+        );
+        // `/` inside a class and escaped `/` do not terminate the pattern
+        assert_eq!(
+            lex(&allocator, r"x = /[/]\//")[2].kind,
+            TokenKind::RegExp {
+                pattern: r"[/]\/",
+                flags: ""
+            }
+        );
+        assert_eq!(
+            lex(&allocator, "/=a/")[0].kind,
+            TokenKind::RegExp {
+                pattern: "=a",
+                flags: ""
+            }
+        );
+        // after an operand, `/` is a division
+        assert_eq!(
+            lex(&allocator, "a / b / c")[3].kind,
+            TokenKind::BinaryOp(BinaryOp::Div)
+        );
+        assert_eq!(
+            lex(&allocator, "(1) /2/ 3")[3].kind,
+            TokenKind::BinaryOp(BinaryOp::Div)
+        );
+    }
+
+    #[test]
+    fn rescan_slash_as_regex() {
+        let allocator = Allocator::new();
+        let mut lexer = Lexer::new(&allocator, "/ab+c/gi.test(s)");
+        let token = lexer.read_next_token();
+        assert_eq!(token.kind, TokenKind::BinaryOp(BinaryOp::Div));
+        let token = lexer.rescan_slash_as_regex(&token);
+        assert_eq!(
+            token,
+            Token {
+                span: Span { start: 0, end: 8 },
+                kind: TokenKind::RegExp {
+                    pattern: "ab+c",
+                    flags: "gi"
+                },
+                had_line_break_before: false,
+            }
+        );
+        assert_eq!(lexer.read_next_token().kind, TokenKind::Dot);
+    }
+
+    #[test]
+    fn token_iterator() {
+        let allocator = Allocator::new();
+        let source = "a = /re/g; // c\nb / 2";
+        assert_eq!(
+            Lexer::new(&allocator, source).collect::<Vec<_>>(),
+            lex(&allocator, source)
+        );
+        assert_eq!(Lexer::new(&allocator, "").next(), None);
+    }
+
+    #[test]
+    fn peek_and_rewind() {
+        let allocator = Allocator::new();
+        let word = |token: Option<&Token>| match token.map(|token| &token.kind) {
+            Some(TokenKind::Word(word)) => word.as_str().to_string(),
+            kind => format!("{kind:?}"),
+        };
+        // whether `(` starts an arrow function depends on the token after the `)`
+        let mut lexer = Lexer::new(&allocator, "(a, b) => a");
+        assert_eq!(lexer.peek_nth(5).map(|t| &t.kind), Some(&TokenKind::Arrow));
+        assert_eq!(word(lexer.peek_nth(1)), "a");
+        assert_eq!(lexer.peek_nth(7), None);
+        assert_eq!(lexer.next().map(|t| t.kind), Some(TokenKind::LParen));
+
+        let checkpoint = lexer.checkpoint();
+        assert_eq!(word(lexer.nth(5).as_ref()), "a");
+        assert_eq!(lexer.next(), None);
+        lexer.rewind(checkpoint);
+        assert_eq!(word(lexer.peek_nth(0)), "a");
+        assert_eq!(lexer.count(), 6);
+    }
+
+    #[test]
+    fn rescan_after_peek() {
+        let allocator = Allocator::new();
+        let mut lexer = Lexer::new(&allocator, "x / 1 /g; y");
+        lexer.next();
+        let slash = lexer.next().unwrap();
+        assert_eq!(
+            lexer.peek_nth(1).map(|t| &t.kind),
+            Some(&TokenKind::BinaryOp(BinaryOp::Div))
+        );
+        let regex = lexer.rescan_slash_as_regex(&slash);
+        assert_eq!(regex.span, Span::new(2, 8));
+        assert_eq!(lexer.next().map(|t| t.kind), Some(TokenKind::Semicolon));
+
+        let mut lexer = Lexer::new(&allocator, "data-id='\\'\nb c");
+        let word = lexer.next().unwrap();
+        lexer.peek_nth(1);
+        let name = lexer.rescan_jsx_identifier(&word);
+        assert_eq!(name.span, Span::new(0, 7));
+        assert_eq!(
+            lexer.next().map(|t| t.kind),
+            Some(TokenKind::AssignOp(AssignOp::Assign))
+        );
+        let string = lexer.next().unwrap();
+        lexer.peek_nth(1);
+        let value = lexer.rescan_jsx_attribute_string(&string);
+        assert_eq!(value.span, Span::new(8, 11));
+        assert!(lexer.errors.is_empty());
+        assert_eq!(lexer.count(), 2);
+    }
+
+    #[test]
+    fn regexp_errors() {
+        let allocator = Allocator::new();
+        let unterminated = |source| {
+            let mut lexer = Lexer::new(&allocator, source);
+            let token = lexer.read_next_token();
+            lexer.rescan_slash_as_regex(&token);
+            lexer.errors.len()
+        };
+        assert_eq!(unterminated("/abc"), 1);
+        assert_eq!(unterminated("/abc\n/"), 1);
+        assert_eq!(unterminated("/[/abc"), 1);
+        assert_eq!(lex_error("/abc/gg").len(), 1);
+        assert_eq!(lex_error("/abc/x").len(), 1);
+        assert_eq!(lex_error("/abc/uv").len(), 1);
+        assert!(lex_error("/abc/dgimsuy").is_empty());
+    }
+
+    #[test]
+    fn unknown_characters() {
+        let allocator = Allocator::new();
+        let (tokens, errors) = Lexer::new(&allocator, "a ¤ b § \\c").lex();
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    span: Span { start: 0, end: 1 },
+                    kind: TokenKind::Word(WordKind::Identifier(Atom::new_in("a", &allocator))),
+                    had_line_break_before: false,
+                },
+                Token {
+                    span: Span { start: 2, end: 4 },
+                    kind: TokenKind::Unknown,
+                    had_line_break_before: false,
+                },
+                Token {
+                    span: Span { start: 5, end: 6 },
+                    kind: TokenKind::Word(WordKind::Identifier(Atom::new_in("b", &allocator))),
+                    had_line_break_before: false,
+                },
+                Token {
+                    span: Span { start: 7, end: 9 },
+                    kind: TokenKind::Unknown,
+                    had_line_break_before: false,
+                },
+                Token {
+                    span: Span { start: 10, end: 11 },
+                    kind: TokenKind::Unknown,
+                    had_line_break_before: false,
+                },
+                Token {
+                    span: Span { start: 11, end: 12 },
+                    kind: TokenKind::Word(WordKind::Identifier(Atom::new_in("c", &allocator))),
+                    had_line_break_before: false,
+                },
+            ]
+        );
+        assert_eq!(errors.len(), 3);
+    }
+
+    #[test]
+    fn malformed_numbers() {
+        let allocator = Allocator::new();
+        assert_eq!(lex_error("1e").len(), 1);
+        assert_eq!(lex_error("1e+").len(), 1);
+        assert_eq!(lex_error("0x").len(), 1);
+        assert_eq!(lex_error("0b102").len(), 1);
+        assert_eq!(lex_error("3in x").len(), 1);
+        assert_eq!(
+            lex(&allocator, "0xffffffffffffffffffff")[0].kind,
+            TokenKind::Number {
+                value: 0xffffffffffffffffffff_u128 as f64,
+                raw: "0xffffffffffffffffffff",
+            }
+        );
+        assert_eq!(
+            lex(&allocator, "017")[0].kind,
+            TokenKind::Number {
+                value: 15_f64,
+                raw: "017",
+            }
+        );
+        assert_eq!(
+            lex(&allocator, "019.5")[0].kind,
+            TokenKind::Number {
+                value: 19.5_f64,
+                raw: "019.5",
+            }
+        );
+    }
+
+    #[test]
+    fn bigint_literals() {
+        let allocator = Allocator::new();
+        let bigint = |value: &'static str, raw: &'static str| TokenKind::BigInt { value, raw };
+        assert_eq!(
+            lex(&allocator, "123n"),
+            vec![Token {
+                span: Span { start: 0, end: 4 },
+                kind: bigint("123", "123n"),
+                had_line_break_before: false,
+            }]
+        );
+        assert_eq!(lex(&allocator, "0n")[0].kind, bigint("0", "0n"));
+        assert_eq!(lex(&allocator, "0x1Fn")[0].kind, bigint("31", "0x1Fn"));
+        assert_eq!(lex(&allocator, "0o17n")[0].kind, bigint("15", "0o17n"));
+        assert_eq!(lex(&allocator, "0b1010n")[0].kind, bigint("10", "0b1010n"));
+        assert_eq!(
+            lex(&allocator, "0xffffffffffffffffffffffffn")[0].kind,
+            bigint(
+                "79228162514264337593543950335",
+                "0xffffffffffffffffffffffffn"
+            )
+        );
+        assert!(lex_error("123n 0x1Fn").is_empty());
+        assert_eq!(lex_error("1.5n").len(), 1);
+        assert_eq!(lex_error("1e3n").len(), 1);
+        assert_eq!(lex_error(".5n").len(), 1);
+        // legacy literals are reported twice: once for the leading zero, once for `n`
+        assert_eq!(lex_error("017n").len(), 2);
+        assert_eq!(lex_error("019n").len(), 2);
+    }
+
+    #[test]
+    fn numeric_separators() {
+        let allocator = Allocator::new();
+        let number = |source: &str| match lex(&allocator, source).remove(0).kind {
+            TokenKind::Number { value, .. } => value,
+            kind => panic!("expected a number, got {:?}", kind),
+        };
+        assert_eq!(number("1_000_000"), 1_000_000_f64);
+        assert_eq!(number("0xFF_FF"), 65535_f64);
+        assert_eq!(number("0b1_0"), 2_f64);
+        assert_eq!(number("0o7_7"), 63_f64);
+        assert_eq!(number("1_0.0_1e1_0"), 10.01e10_f64);
+        assert_eq!(
+            lex(&allocator, "1_000n")[0].kind,
+            TokenKind::BigInt {
+                value: "1000",
+                raw: "1_000n",
+            }
+        );
+        assert!(lex_error("1_000_000 0xFF_FF 1_0.0_1e1_0").is_empty());
+
+        assert_eq!(lex_error("1__0").len(), 1);
+        assert_eq!(lex_error("1___0").len(), 1);
+        assert_eq!(lex_error("1_").len(), 1);
+        assert_eq!(lex_error("1__").len(), 1);
+        assert_eq!(lex_error("0x_1").len(), 1);
+        assert_eq!(lex_error("1_.5").len(), 1);
+        assert_eq!(lex_error("1._5").len(), 1);
+        assert_eq!(lex_error("1e_5").len(), 1);
+        assert_eq!(lex_error("1_e5").len(), 1);
+        assert_eq!(lex_error("1_n").len(), 1);
+        // a separator after a leading zero is reported on top of the legacy literal itself
+        assert_eq!(lex_error("0_1").len(), 2);
+        assert_eq!(
+            lex_error("1__0")[0]
+                .labels()
+                .unwrap()
+                .next()
+                .unwrap()
+                .offset(),
+            2
+        );
+    }
+
+    #[test]
+    fn half_typed_input_does_not_panic() {
+        let allocator = Allocator::new();
+        let sources = [
+            "@",
+            "#",
+            "\\",
+            "\\u",
+            "ユニコード",
+            "\u{0}",
+            "1.2.3",
+            "1..",
+            "0.",
+            "0b",
+            "0o9",
+            "09e",
+            "'abc",
+            "\"abc\n",
+            "`${",
+            "`${a}",
+            "}",
+            "{}}",
+            "/*",
+            "//",
+            "/[",
+            "a /= ",
+            "x ?. y",
+            "<!--",
+            "#!/usr/bin/env node",
+        ];
+        for source in sources {
+            let _ = Lexer::new(&allocator, source).lex();
+        }
+    }
+
+    #[test]
+    fn byte_offsets() {
+        let allocator = Allocator::new();
+        let source = "'ユニコード' + a";
+        let tokens = lex(&allocator, source);
+        assert_eq!(tokens[0].span, Span { start: 0, end: 17 });
+        assert_eq!(tokens[2].span, Span { start: 20, end: 21 });
+        assert_eq!(&source[tokens[2].span.start..tokens[2].span.end], "a");
+    }
+
+    #[test]
+    fn punctuators() {
+        let allocator = Allocator::new();
+        let kinds = |source| {
+            lex(&allocator, source)
+                .into_iter()
+                .map(|token| token.kind)
+                .collect::<Vec<_>>()
+        };
+        let cases = [
+            ("!", TokenKind::Bang),
+            ("!=", TokenKind::BinaryOp(BinaryOp::Ne)),
+            ("!==", TokenKind::BinaryOp(BinaryOp::NeNe)),
+            ("=", TokenKind::AssignOp(AssignOp::Assign)),
+            ("==", TokenKind::BinaryOp(BinaryOp::Eq)),
+            ("===", TokenKind::BinaryOp(BinaryOp::EqEq)),
+            ("=>", TokenKind::Arrow),
+            ("+=", TokenKind::AssignOp(AssignOp::AddAssign)),
+            ("++", TokenKind::PlusPlus),
+            ("-=", TokenKind::AssignOp(AssignOp::SubAssign)),
+            ("--", TokenKind::MinusMinus),
+            ("*", TokenKind::BinaryOp(BinaryOp::Mul)),
+            ("*=", TokenKind::AssignOp(AssignOp::MulAssign)),
+            ("**", TokenKind::BinaryOp(BinaryOp::Exp)),
+            ("**=", TokenKind::AssignOp(AssignOp::ExpAssign)),
+            ("%=", TokenKind::AssignOp(AssignOp::ModAssign)),
+            ("<<", TokenKind::BinaryOp(BinaryOp::LShift)),
+            ("<<=", TokenKind::AssignOp(AssignOp::LeftShiftAssign)),
+            ("<=", TokenKind::BinaryOp(BinaryOp::Le)),
+            (">=", TokenKind::BinaryOp(BinaryOp::Ge)),
+            (">>", TokenKind::BinaryOp(BinaryOp::RShift)),
+            (">>=", TokenKind::AssignOp(AssignOp::RightShiftAssign)),
+            (">>>", TokenKind::BinaryOp(BinaryOp::ZeroFillRightShift)),
+            (
+                ">>>=",
+                TokenKind::AssignOp(AssignOp::ZeroFillRightShiftAssign),
+            ),
+            ("&&", TokenKind::BinaryOp(BinaryOp::LogicalAnd)),
+            ("&&=", TokenKind::AssignOp(AssignOp::LogicalAndAssign)),
+            ("&=", TokenKind::AssignOp(AssignOp::BitAndAssign)),
+            ("||", TokenKind::BinaryOp(BinaryOp::LogicalOr)),
+            ("||=", TokenKind::AssignOp(AssignOp::LogicalOrAssign)),
+            ("|=", TokenKind::AssignOp(AssignOp::BitOrAssign)),
+            ("^=", TokenKind::AssignOp(AssignOp::BitXorAssign)),
+            ("?", TokenKind::Question),
+            ("?.", TokenKind::QuestionDot),
+            ("??", TokenKind::BinaryOp(BinaryOp::NullishCoalescing)),
+            ("??=", TokenKind::AssignOp(AssignOp::NullishAssign)),
+            ("...", TokenKind::DotDotDot),
+            ("@", TokenKind::At),
+            ("#", TokenKind::Hash),
+        ];
+        for (source, kind) in cases {
+            assert_eq!(kinds(source), vec![kind], "{}", source);
+        }
+    }
+
+    #[test]
+    fn optional_chaining_and_conditional() {
+        let allocator = Allocator::new();
+        let kinds = |source| {
+            lex(&allocator, source)
+                .into_iter()
+                .map(|token| token.kind)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            kinds("a?.b"),
+            vec![
+                TokenKind::Word(WordKind::Identifier(Atom::new_in("a", &allocator))),
+                TokenKind::QuestionDot,
+                TokenKind::Word(WordKind::Identifier(Atom::new_in("b", &allocator))),
+            ]
+        );
+        // `?.` followed by a digit is `?` and a number
+        assert_eq!(
+            kinds("a?.5:b"),
+            vec![
+                TokenKind::Word(WordKind::Identifier(Atom::new_in("a", &allocator))),
+                TokenKind::Question,
+                TokenKind::Number {
+                    value: 0.5,
+                    raw: ".5",
+                },
+                TokenKind::Colon,
+                TokenKind::Word(WordKind::Identifier(Atom::new_in("b", &allocator))),
+            ]
+        );
+        assert_eq!(
+            kinds("a?.[0]"),
+            vec![
+                TokenKind::Word(WordKind::Identifier(Atom::new_in("a", &allocator))),
+                TokenKind::QuestionDot,
+                TokenKind::LBracket,
+                TokenKind::Number {
+                    value: 0.0,
+                    raw: "0",
+                },
+                TokenKind::RBracket,
+            ]
+        );
+        assert_eq!(
+            kinds("a ?? b ?. c"),
+            vec![
+                TokenKind::Word(WordKind::Identifier(Atom::new_in("a", &allocator))),
+                TokenKind::BinaryOp(BinaryOp::NullishCoalescing),
+                TokenKind::Word(WordKind::Identifier(Atom::new_in("b", &allocator))),
+                TokenKind::QuestionDot,
+                TokenKind::Word(WordKind::Identifier(Atom::new_in("c", &allocator))),
+            ]
+        );
+        assert_eq!(
+            kinds("2***3"),
+            vec![
+                TokenKind::Number {
+                    value: 2.0,
+                    raw: "2",
+                },
+                TokenKind::BinaryOp(BinaryOp::Exp),
+                TokenKind::BinaryOp(BinaryOp::Mul),
+                TokenKind::Number {
+                    value: 3.0,
+                    raw: "3",
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn decorators_and_private_names() {
+        let allocator = Allocator::new();
+        let kinds = |source| {
+            lex(&allocator, source)
+                .into_iter()
+                .map(|token| token.kind)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            kinds("@dec class C { #x = 1 }"),
+            vec![
+                TokenKind::At,
+                TokenKind::Word(WordKind::Identifier(Atom::new_in("dec", &allocator))),
+                TokenKind::Word(WordKind::Keyword(Keyword::Class)),
+                TokenKind::Word(WordKind::Identifier(Atom::new_in("C", &allocator))),
+                TokenKind::LBrace,
+                TokenKind::Hash,
+                TokenKind::Word(WordKind::Identifier(Atom::new_in("x", &allocator))),
+                TokenKind::AssignOp(AssignOp::Assign),
+                TokenKind::Number {
+                    value: 1.0,
+                    raw: "1",
+                },
+                TokenKind::RBrace,
+            ]
+        );
+    }
+
+    #[test]
+    fn jsx_contexts() {
+        let allocator = Allocator::new();
+        let source = "<a-b c=\"x\\\n\">text } {x}</a-b>";
+        let mut lexer = Lexer::new(&allocator, source);
+        lexer.read_next_token();
+        let word = lexer.read_next_token();
+        let name = lexer.rescan_jsx_identifier(&word);
+        assert_eq!(
+            name.kind,
+            TokenKind::Word(WordKind::Identifier(Atom::new_in("a-b", &allocator)))
+        );
+        assert_eq!(lexer.read_next_token().raw(source), "c");
+        lexer.read_next_token();
+        let string = lexer.read_next_token();
+        let string = lexer.rescan_jsx_attribute_string(&string);
+        assert_eq!(
+            string.kind,
+            TokenKind::String {
+                value: Atom::new_in("x\\\n", &allocator),
+                raw: "\"x\\\n\"",
+            }
+        );
+        assert_eq!(lexer.read_next_token().raw(source), ">");
+        let text = lexer.read_jsx_child();
+        assert_eq!(text.kind, TokenKind::JsxText { raw: "text } " });
+        assert_eq!(lexer.read_jsx_child().kind, TokenKind::LBrace);
+        assert_eq!(
+            lexer
+                .errors
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>(),
+            ["Unexpected token. Did you mean `{'}'}` or `&rbrace;`?"]
+        );
+    }
+
+    #[test]
+    fn contextual_keywords() {
+        let allocator = Allocator::new();
+        let words = lex(&allocator, "declare module enum type")
+            .into_iter()
+            .map(|token| match token.kind {
+                TokenKind::Word(word) => word,
+                kind => panic!("expected a word, got {kind:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            words,
+            [
+                WordKind::Identifier(Atom::new_in("declare", &allocator)),
+                WordKind::Identifier(Atom::new_in("module", &allocator)),
+                WordKind::Keyword(Keyword::Enum),
+                WordKind::Identifier(Atom::new_in("type", &allocator)),
+            ]
+        );
+        assert_eq!(
+            words
+                .iter()
+                .map(WordKind::contextual_keyword)
+                .collect::<Vec<_>>(),
+            [
+                Some(ContextualKeyword::Declare),
+                Some(ContextualKeyword::Module),
+                None,
+                Some(ContextualKeyword::Type),
+            ]
+        );
+        assert!(ContextualKeyword::Type.is_typescript_only());
+        assert!(!ContextualKeyword::Async.is_typescript_only());
+    }
 }
@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+
+/// Zero-based line and column of a position. `col` counts UTF-8 bytes from
+/// the start of the line, like the offsets stored in [`Span`](crate::Span).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineCol {
+    pub line: usize,
+    pub col: usize,
+}
+
+/// The code units a [`WideLineCol`] column is counted in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WideEncoding {
+    /// UTF-16 code units, as used by editors speaking LSP and by `tsc`.
+    Utf16,
+    /// Unicode scalar values, i.e. Rust `char`s.
+    Utf32,
+}
+
+/// Zero-based line and column where `col` is counted in [`WideEncoding`] code units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WideLineCol {
+    pub line: usize,
+    pub col: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct WideChar {
+    // byte column of the character within its line
+    col: usize,
+    len: usize,
+}
+
+impl WideChar {
+    fn wide_len(&self, encoding: WideEncoding) -> usize {
+        match encoding {
+            WideEncoding::Utf16 if self.len == 4 => 2,
+            _ => 1,
+        }
+    }
+}
+
+/// Converts between UTF-8 byte offsets, UTF-16 offsets and line/column
+/// positions in O(log lines).
+///
+/// Lines are split at every ECMAScript line terminator: `\n`, `\r\n`, `\r`,
+/// U+2028 and U+2029. Only lines with non-ASCII characters pay for the
+/// wide column mapping.
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    line_starts: Vec<usize>,
+    // the UTF-16 offset where each line starts
+    utf16_line_starts: Vec<usize>,
+    wide_chars: HashMap<usize, Vec<WideChar>>,
+    len: usize,
+}
+
+impl LineIndex {
+    pub fn new(source: &str) -> Self {
+        let mut line_starts = vec![0];
+        let mut wide_chars = HashMap::new();
+        let mut line_wide_chars = vec![];
+        let mut chars = source.char_indices().peekable();
+        while let Some((offset, c)) = chars.next() {
+            let line_end = match c {
+                '\r' if matches!(chars.peek(), Some((_, '\n'))) => None,
+                '\n' | '\r' | '\u{2028}' | '\u{2029}' => Some(offset + c.len_utf8()),
+                _ => None,
+            };
+            if !c.is_ascii() {
+                line_wide_chars.push(WideChar {
+                    col: offset - line_starts[line_starts.len() - 1],
+                    len: c.len_utf8(),
+                });
+            }
+            if let Some(line_end) = line_end {
+                if !line_wide_chars.is_empty() {
+                    wide_chars.insert(line_starts.len() - 1, std::mem::take(&mut line_wide_chars));
+                }
+                line_starts.push(line_end);
+            }
+        }
+        if !line_wide_chars.is_empty() {
+            wide_chars.insert(line_starts.len() - 1, line_wide_chars);
+        }
+        let mut utf16_line_starts = Vec::with_capacity(line_starts.len());
+        let mut utf16_start = 0;
+        for (line, window) in line_starts.windows(2).enumerate() {
+            utf16_line_starts.push(utf16_start);
+            let len = window[1] - window[0];
+            utf16_start += wide_chars.get(&line).map_or(len, |chars: &Vec<WideChar>| {
+                chars
+                    .iter()
+                    .fold(len, |len, c| len - c.len + c.wide_len(WideEncoding::Utf16))
+            });
+        }
+        utf16_line_starts.push(utf16_start);
+        Self {
+            line_starts,
+            utf16_line_starts,
+            wide_chars,
+            len: source.len(),
+        }
+    }
+
+    pub fn line_col(&self, offset: usize) -> LineCol {
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        LineCol {
+            line,
+            col: offset - self.line_starts[line],
+        }
+    }
+
+    /// The offset where a line starts, if the source has that many lines.
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        self.line_starts.get(line).copied()
+    }
+
+    /// The byte offset of a position, if the source has that line. A column past
+    /// the end of the line is clamped to the start of the next one.
+    pub fn offset(&self, line_col: LineCol) -> Option<usize> {
+        let start = self.line_start(line_col.line)?;
+        let end = self.line_start(line_col.line + 1).unwrap_or(self.len);
+        Some((start + line_col.col).min(end))
+    }
+
+    pub fn to_wide(&self, encoding: WideEncoding, line_col: LineCol) -> WideLineCol {
+        let mut col = line_col.col;
+        if let Some(wide_chars) = self.wide_chars.get(&line_col.line) {
+            for c in wide_chars.iter().take_while(|c| c.col < line_col.col) {
+                col = col - c.len + c.wide_len(encoding);
+            }
+        }
+        WideLineCol {
+            line: line_col.line,
+            col,
+        }
+    }
+
+    /// The reverse of [`LineIndex::to_wide`].
+    pub fn to_utf8(&self, encoding: WideEncoding, line_col: WideLineCol) -> LineCol {
+        let mut col = line_col.col;
+        if let Some(wide_chars) = self.wide_chars.get(&line_col.line) {
+            for c in wide_chars {
+                if c.col >= col {
+                    break;
+                }
+                col = col + c.len - c.wide_len(encoding);
+            }
+        }
+        LineCol {
+            line: line_col.line,
+            col,
+        }
+    }
+
+    /// The UTF-16 offset of a byte offset, as used by `tsc` and JavaScript strings.
+    pub fn to_utf16_offset(&self, offset: usize) -> usize {
+        let line_col = self.line_col(offset);
+        self.utf16_line_starts[line_col.line] + self.to_wide(WideEncoding::Utf16, line_col).col
+    }
+
+    /// The byte offset of a UTF-16 offset. An offset past the end of the source is
+    /// clamped to it.
+    pub fn from_utf16_offset(&self, offset: usize) -> usize {
+        let line = self
+            .utf16_line_starts
+            .partition_point(|&start| start <= offset)
+            - 1;
+        let wide = WideLineCol {
+            line,
+            col: offset - self.utf16_line_starts[line],
+        };
+        let line_col = self.to_utf8(WideEncoding::Utf16, wide);
+        self.offset(line_col).unwrap_or(self.len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_col() {
+        let index = LineIndex::new("ab\ncd\r\nef\rg\u{2028}h");
+        assert_eq!(index.line_col(0), LineCol { line: 0, col: 0 });
+        assert_eq!(index.line_col(2), LineCol { line: 0, col: 2 });
+        assert_eq!(index.line_col(3), LineCol { line: 1, col: 0 });
+        assert_eq!(index.line_col(6), LineCol { line: 1, col: 3 });
+        assert_eq!(index.line_col(7), LineCol { line: 2, col: 0 });
+        assert_eq!(index.line_col(10), LineCol { line: 3, col: 0 });
+        assert_eq!(index.line_col(14), LineCol { line: 4, col: 0 });
+    }
+
+    #[test]
+    fn wide_columns() {
+        // `ユ` takes 3 UTF-8 bytes and one UTF-16 unit, `😀` takes 4 and 2
+        let source = "let ユ = '😀';";
+        let index = LineIndex::new(source);
+        let offset = source.find(';').unwrap();
+        let line_col = index.line_col(offset);
+        assert_eq!(line_col, LineCol { line: 0, col: 16 });
+        assert_eq!(
+            index.to_wide(WideEncoding::Utf16, line_col),
+            WideLineCol { line: 0, col: 12 }
+        );
+        assert_eq!(
+            index.to_wide(WideEncoding::Utf32, line_col),
+            WideLineCol { line: 0, col: 11 }
+        );
+    }
+
+    #[test]
+    fn reverse_conversions() {
+        let source = "a\nlet ユ = '😀';\r\nb";
+        let index = LineIndex::new(source);
+        let semi = source.find(';').unwrap();
+        let line_col = index.line_col(semi);
+        assert_eq!(index.offset(line_col), Some(semi));
+        assert_eq!(index.offset(LineCol { line: 1, col: 100 }), Some(semi + 3));
+        assert_eq!(index.offset(LineCol { line: 3, col: 0 }), None);
+        for encoding in [WideEncoding::Utf16, WideEncoding::Utf32] {
+            let wide = index.to_wide(encoding, line_col);
+            assert_eq!(index.to_utf8(encoding, wide), line_col);
+        }
+
+        // the second line starts at UTF-16 offset 2 and the emoji takes two units
+        let b = source.len() - 1;
+        assert_eq!(index.to_utf16_offset(semi), 14);
+        assert_eq!(index.to_utf16_offset(b), 17);
+        assert_eq!(index.from_utf16_offset(14), semi);
+        assert_eq!(index.from_utf16_offset(17), b);
+        assert_eq!(index.from_utf16_offset(100), source.len());
+        for (offset, _) in source.char_indices() {
+            assert_eq!(
+                index.from_utf16_offset(index.to_utf16_offset(offset)),
+                offset
+            );
+        }
+    }
+}
@@ -0,0 +1,106 @@
+/// The language of the source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Syntax {
+    /// TypeScript, as in `.ts` files.
+    #[default]
+    Typescript,
+    /// TypeScript with JSX, as in `.tsx` files. A `<` in front of an expression always
+    /// starts a JSX element there, so `<T>expr` type assertions are not available and
+    /// generic arrow functions need a `,` or a constraint, as in `<T,>(a: T) => a`.
+    Tsx,
+    /// Plain JavaScript, where TypeScript's contextual keywords like `declare` and
+    /// `namespace` are only identifiers.
+    Javascript,
+    /// JavaScript with JSX, as in `.jsx` files.
+    Jsx,
+}
+
+impl Syntax {
+    pub fn is_typescript(&self) -> bool {
+        matches!(self, Syntax::Typescript | Syntax::Tsx)
+    }
+
+    pub fn is_jsx(&self) -> bool {
+        matches!(self, Syntax::Tsx | Syntax::Jsx)
+    }
+}
+
+/// The ECMAScript version the source is compiled to, TypeScript's `target` option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum EsVersion {
+    Es3,
+    Es5,
+    Es2015,
+    Es2016,
+    Es2017,
+    Es2018,
+    Es2019,
+    Es2020,
+    Es2021,
+    Es2022,
+    #[default]
+    EsNext,
+}
+
+impl EsVersion {
+    /// Reads the value of the `target` option, like `ES2015`, `es6` or `ESNext`.
+    pub fn from_target(target: &str) -> Option<Self> {
+        let version = match target.to_ascii_lowercase().as_str() {
+            "es3" => EsVersion::Es3,
+            "es5" => EsVersion::Es5,
+            "es6" | "es2015" => EsVersion::Es2015,
+            "es2016" => EsVersion::Es2016,
+            "es2017" => EsVersion::Es2017,
+            "es2018" => EsVersion::Es2018,
+            "es2019" => EsVersion::Es2019,
+            "es2020" => EsVersion::Es2020,
+            "es2021" => EsVersion::Es2021,
+            "es2022" => EsVersion::Es2022,
+            "esnext" => EsVersion::EsNext,
+            _ => return None,
+        };
+        Some(version)
+    }
+}
+
+/// The goal symbol the source is read with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SourceType {
+    /// An ES module, which is always strict mode code.
+    #[default]
+    Module,
+    /// A classic script, which is only strict with [`LexerOptions::strict`].
+    Script,
+}
+
+/// Options that change how the [`Lexer`](crate::Lexer) reads source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LexerOptions {
+    /// Legacy octal literals like `017` are only allowed in sloppy mode code targeting
+    /// ES3. Octal escapes like `"\07"` are allowed in sloppy mode code at any target.
+    pub target: EsVersion,
+    pub source_type: SourceType,
+    /// Whether a script is strict mode code, as with a `"use strict"` directive or
+    /// `alwaysStrict`. Modules are strict either way.
+    pub strict: bool,
+    /// JSX text and attributes can only be told apart from other tokens by the
+    /// parser, which asks the lexer to read them in [`Syntax::Tsx`] and
+    /// [`Syntax::Jsx`].
+    pub syntax: Syntax,
+}
+
+impl LexerOptions {
+    /// Whether the source is strict mode code.
+    pub fn is_strict(&self) -> bool {
+        self.strict || self.source_type == SourceType::Module
+    }
+
+    /// Whether legacy octal literals like `017` are allowed.
+    pub(crate) fn allows_legacy_octal_literal(&self) -> bool {
+        !self.is_strict() && self.target == EsVersion::Es3
+    }
+}
+
+/// The options of the [`Parser`](crate::Parser), which reads the source with a lexer
+/// made with them.
+pub type ParserOptions = LexerOptions;
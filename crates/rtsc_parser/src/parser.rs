@@ -0,0 +1,1455 @@
+//! A recursive-descent parser for TypeScript built on top of [`Lexer`].
+//!
+//! The parser pulls tokens from the lexer one at a time, so that it can ask it to
+//! rescan a `/` as a regular expression where an expression is expected. Lookahead is
+//! done by saving a checkpoint of both and rewinding to it.
+//!
+//! Syntax errors do not stop the parser. A statement or member that fails to parse is
+//! reported, its tokens are skipped up to the next boundary and a placeholder takes its
+//! place, so that a tree for the whole source is always returned.
+
+mod class;
+mod expr;
+mod jsx;
+mod stmt;
+mod typescript;
+
+use miette::Error;
+
+use crate::{
+    allocator::{self, Box},
+    ast::{Ident, Program, Stmt},
+    comments::Comments,
+    cst::{self, SyntaxNode},
+    diagnostics::{Expected, StrictModeReservedWord, UnexpectedToken},
+    Allocator, AssignOp, BinaryOp, ContextualKeyword, Keyword, Lexer, LexerCheckpoint,
+    ParserOptions, Span, Token, TokenKind, WordKind,
+};
+
+type PResult<T> = Result<T, Error>;
+
+pub struct Parser<'a> {
+    source: &'a str,
+    /// The allocator the tree is built in.
+    allocator: &'a Allocator,
+    lexer: Lexer<'a>,
+    /// The current token. Comments are skipped.
+    token: Token<'a>,
+    /// The end of the previous token, used for spans and to find line breaks.
+    prev_end: usize,
+    ctx: Context,
+    errors: Vec<Error>,
+    /// Every token consumed so far, kept only when building a CST.
+    tokens: Option<Vec<Token<'a>>>,
+    /// The spans of the comments skipped so far.
+    comments: Vec<Span>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Context {
+    in_function: bool,
+    in_async: bool,
+    in_generator: bool,
+    /// `in` is not a binary operator in the initializer of a `for` statement.
+    no_in: bool,
+    /// In the `extends` clause of a conditional type, `A extends B ? C : D` cannot nest
+    /// without parentheses.
+    no_conditional_types: bool,
+    /// In the `?` branch of a conditional, the `:` after `(b)` in `a ? (b) : c => d`
+    /// is the conditional's, unless the arrow function is followed by another `:`.
+    no_arrow_return_type: bool,
+}
+
+struct Checkpoint<'a> {
+    lexer: LexerCheckpoint<'a>,
+    token: Token<'a>,
+    prev_end: usize,
+    ctx: Context,
+    errors: usize,
+    tokens: usize,
+    comments: usize,
+}
+
+/// The lists the parser recovers in, named after the `ParsingContext` of the
+/// TypeScript compiler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ListKind {
+    SourceElements,
+    BlockStatements,
+    SwitchClauseStatements,
+    ClassMembers,
+    TypeMembers,
+    EnumMembers,
+}
+
+impl<'a> Parser<'a> {
+    /// A parser of `source` which builds the tree, with its names and strings, in
+    /// `allocator`.
+    pub fn new(allocator: &'a Allocator, source: &'a str) -> Self {
+        Self::with_options(allocator, source, ParserOptions::default())
+    }
+
+    pub fn with_options(allocator: &'a Allocator, source: &'a str, options: ParserOptions) -> Self {
+        let mut parser = Self {
+            source,
+            allocator,
+            lexer: Lexer::with_options(allocator, source, options),
+            token: Token {
+                kind: TokenKind::Eof,
+                span: Span::new(0, 0),
+                had_line_break_before: false,
+            },
+            prev_end: 0,
+            ctx: Context::default(),
+            errors: vec![],
+            tokens: None,
+            comments: vec![],
+        };
+        parser.token = parser.next_token();
+        parser
+    }
+
+    /// Parses the whole source. The errors of both the lexer and the parser are
+    /// returned in source order, and the program is returned even if there are any.
+    /// Like in TypeScript, `@ts-ignore` and `@ts-nocheck` comments do not suppress them.
+    pub fn parse(mut self) -> (Program<'a>, Vec<Error>) {
+        self.finish()
+    }
+
+    /// Parses the whole source into a lossless concrete syntax tree, which prints back
+    /// to the exact source text. Errors are reported the same way as by [`Self::parse`].
+    pub fn parse_cst(mut self) -> (SyntaxNode<'a>, Vec<Error>) {
+        self.tokens = Some(vec![]);
+        let (program, errors) = self.finish();
+        let tokens = self.tokens.take().unwrap_or_default();
+        let green = cst::build(self.source, tokens, &program);
+        (SyntaxNode::new_root(green), errors)
+    }
+
+    /// Parses the whole source like [`Self::parse`], and also returns its comments
+    /// attached to the nodes of the program.
+    pub fn parse_with_comments(mut self) -> (Program<'a>, Comments, Vec<Error>) {
+        let (program, errors) = self.finish();
+        let comments = Comments::new(self.source, &self.comments, &program);
+        (program, comments, errors)
+    }
+
+    /// Parses the whole source and returns the tokens read on the way, without the
+    /// comments, and the errors of the lexer alone. This is how a TSX source is lexed,
+    /// since only the parser knows where JSX text and attributes are.
+    pub fn lex(mut self) -> (Vec<Token<'a>>, Vec<Error>) {
+        self.tokens = Some(vec![]);
+        self.parse_program();
+        let tokens = self.tokens.take().unwrap_or_default();
+        (tokens, std::mem::take(&mut self.lexer.errors))
+    }
+
+    fn finish(&mut self) -> (Program<'a>, Vec<Error>) {
+        let program = self.parse_program();
+        let mut errors = std::mem::take(&mut self.lexer.errors);
+        errors.append(&mut self.errors);
+        errors.sort_by_key(error_offset);
+        (program, errors)
+    }
+
+    fn parse_program(&mut self) -> Program<'a> {
+        let body = self.parse_list(
+            ListKind::SourceElements,
+            |p| p.parse_stmt(),
+            |span| Some(Stmt::Invalid(span)),
+        );
+        Program {
+            body,
+            span: Span::new(0, self.source.len()),
+        }
+    }
+
+    /// Parses the elements of a list up to its end. An element that fails to parse is
+    /// reported and skipped, and `invalid` makes a placeholder from the skipped span if
+    /// the list has one.
+    fn parse_list<T>(
+        &mut self,
+        kind: ListKind,
+        mut parse_element: impl FnMut(&mut Self) -> PResult<T>,
+        invalid: impl Fn(Span) -> Option<T>,
+    ) -> allocator::Vec<'a, T> {
+        let mut list = self.vec();
+        while !self.is_list_end(kind) {
+            let start = self.start();
+            let result = if self.is_list_element_start(kind) {
+                parse_element(self)
+            } else {
+                Err(self.expected(list_element_name(kind)))
+            };
+            match result {
+                Ok(element) => list.push(element),
+                Err(error) => {
+                    self.error(error);
+                    self.recover(kind, start);
+                    list.extend(invalid(self.span_from(start)));
+                }
+            }
+        }
+        list
+    }
+
+    fn is_list_end(&self, kind: ListKind) -> bool {
+        self.is(&TokenKind::Eof)
+            || match kind {
+                ListKind::SourceElements => false,
+                ListKind::SwitchClauseStatements => {
+                    self.is(&TokenKind::RBrace)
+                        || self.is_keyword(Keyword::Case)
+                        || self.is_keyword(Keyword::Default)
+                }
+                _ => self.is(&TokenKind::RBrace),
+            }
+    }
+
+    fn is_list_element_start(&self, kind: ListKind) -> bool {
+        match kind {
+            ListKind::SourceElements
+            | ListKind::BlockStatements
+            | ListKind::SwitchClauseStatements => {
+                self.is_start_of_expr() || self.is(&TokenKind::Semicolon)
+            }
+            ListKind::ClassMembers => {
+                self.is_prop_name_start()
+                    || matches!(
+                        self.token.kind,
+                        TokenKind::At | TokenKind::Semicolon | TokenKind::BinaryOp(BinaryOp::Mul)
+                    )
+            }
+            ListKind::TypeMembers => {
+                self.is_prop_name_start() || self.is(&TokenKind::LParen) || self.is_lt()
+            }
+            ListKind::EnumMembers => self.is_prop_name_start(),
+        }
+    }
+
+    /// Skips the rest of a list element that failed to parse, up to the separator
+    /// ending it, the end of the list, or a token on a new line that can start the
+    /// next element. Brackets are skipped as a whole, and at least one token is
+    /// always skipped so that the list makes progress.
+    ///
+    /// An element that failed on its first token, like a stray `)`, only skips the
+    /// tokens which can neither start an element nor end the list, so that the next
+    /// element on the same line is kept.
+    fn recover(&mut self, kind: ListKind, start: usize) {
+        if self.start() == start {
+            self.bump();
+            while !(self.is_list_end(kind) || self.is_list_element_start(kind)) {
+                self.bump();
+            }
+            return;
+        }
+        let separator = match kind {
+            ListKind::EnumMembers => TokenKind::Comma,
+            _ => TokenKind::Semicolon,
+        };
+        let mut depth = 0usize;
+        loop {
+            match self.token.kind {
+                TokenKind::Eof => return,
+                TokenKind::LBrace | TokenKind::LParen | TokenKind::LBracket => depth += 1,
+                TokenKind::RBrace | TokenKind::RParen | TokenKind::RBracket if depth > 0 => {
+                    depth -= 1
+                }
+                _ if depth > 0 => {}
+                _ if self.token.kind == separator => {
+                    self.bump();
+                    return;
+                }
+                _ if self.is_list_end(kind)
+                    || self.has_line_break_before() && self.is_list_element_start(kind) =>
+                {
+                    return
+                }
+                _ => {}
+            }
+            self.bump();
+        }
+    }
+
+    /// Consumes the `}` closing a list, which can only be missing at the end of the file.
+    fn expect_list_close(&mut self) {
+        if !self.eat(&TokenKind::RBrace) {
+            self.error(self.expected("`}`"));
+        }
+    }
+
+    /// Reports a syntax error the parser recovers from. A second error at the same
+    /// position is dropped, since it is almost always caused by the first.
+    fn error(&mut self, error: Error) {
+        let offset = error_offset(&error);
+        if offset.is_some() && self.errors.last().and_then(error_offset) == offset {
+            return;
+        }
+        self.errors.push(error);
+    }
+
+    fn next_token(&mut self) -> Token<'a> {
+        loop {
+            let token = self.lexer.read_next_token();
+            match token.kind {
+                TokenKind::SingleLineComment | TokenKind::MultiLineComment => {
+                    self.comments.push(token.span)
+                }
+                TokenKind::Hashbang => {}
+                _ => return token,
+            }
+        }
+    }
+
+    /// Moves to the next token and returns the current one.
+    fn bump(&mut self) -> Token<'a> {
+        let next = self.next_token();
+        self.advance(next)
+    }
+
+    /// Moves to the next child of a JSX element, after the `>` of an opening tag or
+    /// the `}` of an expression, and returns the current token.
+    fn bump_jsx_child(&mut self) -> Token<'a> {
+        let next = self.lexer.read_jsx_child();
+        self.advance(next)
+    }
+
+    fn advance(&mut self, next: Token<'a>) -> Token<'a> {
+        let token = std::mem::replace(&mut self.token, next);
+        self.prev_end = token.span.end;
+        if let Some(tokens) = &mut self.tokens {
+            tokens.push(token.clone());
+        }
+        token
+    }
+
+    fn start(&self) -> usize {
+        self.token.span.start
+    }
+
+    /// Moves a node into the allocator of the tree.
+    fn alloc<T>(&self, node: T) -> Box<'a, T> {
+        Box::new_in(node, self.allocator)
+    }
+
+    /// An empty list of nodes in the allocator of the tree.
+    fn vec<T>(&self) -> allocator::Vec<'a, T> {
+        allocator::Vec::new_in(self.allocator)
+    }
+
+    /// Copies text which is not in the source, like the raw text of a negated number
+    /// type, into the allocator of the tree.
+    fn alloc_str(&self, text: &str) -> &'a str {
+        self.allocator.alloc_str(text)
+    }
+
+    /// The span from `start` to the end of the previous token.
+    fn span_from(&self, start: usize) -> Span {
+        Span::new(start, self.prev_end)
+    }
+
+    fn is(&self, kind: &TokenKind) -> bool {
+        &self.token.kind == kind
+    }
+
+    fn eat(&mut self, kind: &TokenKind) -> bool {
+        if self.is(kind) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, kind: &TokenKind) -> PResult<()> {
+        if self.eat(kind) {
+            Ok(())
+        } else {
+            Err(self.expected(token_text(kind)))
+        }
+    }
+
+    fn keyword(&self) -> Option<Keyword> {
+        match self.token.kind {
+            TokenKind::Word(WordKind::Keyword(keyword)) => Some(keyword),
+            _ => None,
+        }
+    }
+
+    fn is_keyword(&self, keyword: Keyword) -> bool {
+        matches!(&self.token.kind, TokenKind::Word(WordKind::Keyword(k)) if *k == keyword)
+    }
+
+    fn eat_keyword(&mut self, keyword: Keyword) -> bool {
+        if self.is_keyword(keyword) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: Keyword) -> PResult<()> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.expected(keyword.as_str()))
+        }
+    }
+
+    /// The text of the current token if it is a word: an identifier, keyword or literal name.
+    fn word(&self) -> Option<&str> {
+        match &self.token.kind {
+            TokenKind::Word(word) => Some(word.as_str()),
+            _ => None,
+        }
+    }
+
+    /// The contextual keyword the current token can be, like `type` or `of`. Those
+    /// TypeScript adds are only keywords in TypeScript.
+    fn contextual_keyword(&self) -> Option<ContextualKeyword> {
+        match &self.token.kind {
+            TokenKind::Word(word) => word.contextual_keyword().filter(|keyword| {
+                self.lexer.options.syntax.is_typescript() || !keyword.is_typescript_only()
+            }),
+            _ => None,
+        }
+    }
+
+    fn is_contextual(&self, keyword: ContextualKeyword) -> bool {
+        self.contextual_keyword() == Some(keyword)
+    }
+
+    fn eat_contextual(&mut self, keyword: ContextualKeyword) -> bool {
+        if self.is_contextual(keyword) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_contextual(&mut self, keyword: ContextualKeyword) -> PResult<()> {
+        if self.eat_contextual(keyword) {
+            Ok(())
+        } else {
+            Err(self.expected(keyword.as_str()))
+        }
+    }
+
+    /// Whether the current token can be used as an identifier. The words reserved in
+    /// strict mode code only are reported by [`Self::parse_ident`] instead.
+    fn is_ident(&self) -> bool {
+        match &self.token.kind {
+            TokenKind::Word(WordKind::Identifier(_)) => true,
+            TokenKind::Word(WordKind::Keyword(keyword)) => match keyword {
+                Keyword::Yield => !self.ctx.in_generator,
+                keyword => is_contextual_keyword(*keyword),
+            },
+            _ => false,
+        }
+    }
+
+    fn parse_ident(&mut self) -> PResult<Ident<'a>> {
+        if !self.is_ident() {
+            return Err(self.expected("Identifier"));
+        }
+        let ident = self.parse_ident_name()?;
+        if self.lexer.options.is_strict() && is_strict_mode_reserved_word(&ident.name) {
+            self.error(StrictModeReservedWord(ident.name.to_string(), ident.span).into());
+        }
+        Ok(ident)
+    }
+
+    /// Parses an IdentifierName, where reserved words are allowed, e.g. after `.`.
+    fn parse_ident_name(&mut self) -> PResult<Ident<'a>> {
+        let name = match &self.token.kind {
+            TokenKind::Word(word) => word.atom(),
+            _ => return Err(self.expected("Identifier")),
+        };
+        let span = self.bump().span;
+        Ok(Ident { name, span })
+    }
+
+    fn has_line_break_before(&self) -> bool {
+        self.token.had_line_break_before
+    }
+
+    /// Whether a `;` can be inserted before the current token: if it is a `}` or the
+    /// end of the source, or if a line break comes before it.
+    fn can_insert_semi(&self) -> bool {
+        self.is(&TokenKind::Semicolon)
+            || self.is(&TokenKind::RBrace)
+            || self.is(&TokenKind::Eof)
+            || self.has_line_break_before()
+    }
+
+    /// Consumes a `;`, or does nothing where automatic semicolon insertion applies.
+    /// A missing `;` is reported, and parsing goes on as if it was there.
+    fn expect_semi(&mut self) {
+        if !self.eat(&TokenKind::Semicolon) && !self.can_insert_semi() {
+            self.error(self.expected("`;`"));
+        }
+    }
+
+    fn is_jsx(&self) -> bool {
+        self.lexer.options.syntax.is_jsx()
+    }
+
+    fn is_lt(&self) -> bool {
+        self.is(&TokenKind::BinaryOp(BinaryOp::Lt))
+    }
+
+    /// Consumes a `>` closing type parameters or arguments. The lexer reads `>>`, `>=`
+    /// and friends as one token, so the rest of such a token stays as the current one.
+    fn expect_gt(&mut self) -> PResult<()> {
+        let rest = match self.token.kind {
+            TokenKind::BinaryOp(BinaryOp::Gt) => {
+                self.bump();
+                return Ok(());
+            }
+            TokenKind::BinaryOp(BinaryOp::RShift) => TokenKind::BinaryOp(BinaryOp::Gt),
+            TokenKind::BinaryOp(BinaryOp::ZeroFillRightShift) => {
+                TokenKind::BinaryOp(BinaryOp::RShift)
+            }
+            TokenKind::BinaryOp(BinaryOp::Ge) => TokenKind::AssignOp(AssignOp::Assign),
+            TokenKind::AssignOp(AssignOp::RightShiftAssign) => TokenKind::BinaryOp(BinaryOp::Ge),
+            TokenKind::AssignOp(AssignOp::ZeroFillRightShiftAssign) => {
+                TokenKind::AssignOp(AssignOp::RightShiftAssign)
+            }
+            _ => return Err(self.expected("`>`")),
+        };
+        if let Some(tokens) = &mut self.tokens {
+            tokens.push(Token {
+                kind: TokenKind::BinaryOp(BinaryOp::Gt),
+                span: Span::new(self.token.span.start, self.token.span.start + 1),
+                had_line_break_before: self.token.had_line_break_before,
+            });
+        }
+        self.token.span.start += 1;
+        self.token.had_line_break_before = false;
+        self.prev_end = self.token.span.start;
+        self.token.kind = rest;
+        Ok(())
+    }
+
+    fn expected(&self, what: &'static str) -> Error {
+        Expected(what, self.token.span).into()
+    }
+
+    fn expected_at(&self, what: &'static str, span: Span) -> Error {
+        Expected(what, span).into()
+    }
+
+    fn unexpected(&self) -> Error {
+        UnexpectedToken(self.token.span).into()
+    }
+
+    fn checkpoint(&self) -> Checkpoint<'a> {
+        Checkpoint {
+            lexer: self.lexer.checkpoint(),
+            token: self.token.clone(),
+            prev_end: self.prev_end,
+            ctx: self.ctx,
+            errors: self.errors.len(),
+            tokens: self.tokens.as_ref().map_or(0, Vec::len),
+            comments: self.comments.len(),
+        }
+    }
+
+    fn rewind(&mut self, checkpoint: Checkpoint<'a>) {
+        self.lexer.rewind(checkpoint.lexer);
+        self.token = checkpoint.token;
+        self.prev_end = checkpoint.prev_end;
+        self.ctx = checkpoint.ctx;
+        self.errors.truncate(checkpoint.errors);
+        if let Some(tokens) = &mut self.tokens {
+            tokens.truncate(checkpoint.tokens);
+        }
+        self.comments.truncate(checkpoint.comments);
+    }
+
+    /// Runs `f` and keeps its result, or rewinds as if nothing happened if it fails
+    /// or reports an error it recovered from.
+    fn try_parse<T>(&mut self, f: impl FnOnce(&mut Self) -> PResult<T>) -> Option<T> {
+        let checkpoint = self.checkpoint();
+        let errors = checkpoint.errors;
+        match f(self) {
+            Ok(value) if self.errors.len() == errors => Some(value),
+            _ => {
+                self.rewind(checkpoint);
+                None
+            }
+        }
+    }
+
+    /// Runs `f` and rewinds afterwards.
+    fn lookahead<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let checkpoint = self.checkpoint();
+        let value = f(self);
+        self.rewind(checkpoint);
+        value
+    }
+
+    /// Whether the token after the current one satisfies `f`.
+    fn next_is(&mut self, f: impl FnOnce(&mut Self) -> bool) -> bool {
+        self.lookahead(|p| {
+            p.bump();
+            f(p)
+        })
+    }
+
+    /// Whether the token after the current one is on the same line and satisfies `f`.
+    fn next_on_same_line(&mut self, f: impl FnOnce(&mut Self) -> bool) -> bool {
+        self.lookahead(|p| {
+            p.bump();
+            !p.has_line_break_before() && f(p)
+        })
+    }
+
+    fn with_ctx<T>(&mut self, ctx: Context, f: impl FnOnce(&mut Self) -> PResult<T>) -> PResult<T> {
+        let saved = std::mem::replace(&mut self.ctx, ctx);
+        let result = f(self);
+        self.ctx = saved;
+        result
+    }
+
+    /// Runs `f` with `in` and return types of arrow functions allowed again, for
+    /// expressions nested in brackets.
+    fn allow_in<T>(&mut self, f: impl FnOnce(&mut Self) -> PResult<T>) -> PResult<T> {
+        let ctx = Context {
+            no_in: false,
+            no_arrow_return_type: false,
+            ..self.ctx
+        };
+        self.with_ctx(ctx, f)
+    }
+}
+
+/// Keywords which are reserved in strict mode code only, and `let`, which are valid
+/// identifiers everywhere they do not start a declaration.
+fn is_contextual_keyword(keyword: Keyword) -> bool {
+    matches!(
+        keyword,
+        Keyword::Implements
+            | Keyword::Interface
+            | Keyword::Private
+            | Keyword::Protected
+            | Keyword::Public
+            | Keyword::Let
+    )
+}
+
+/// Words which are only identifiers in sloppy mode code.
+fn is_strict_mode_reserved_word(name: &str) -> bool {
+    matches!(
+        name,
+        "implements"
+            | "interface"
+            | "let"
+            | "package"
+            | "private"
+            | "protected"
+            | "public"
+            | "static"
+            | "yield"
+    )
+}
+
+fn list_element_name(kind: ListKind) -> &'static str {
+    match kind {
+        ListKind::SourceElements | ListKind::BlockStatements | ListKind::SwitchClauseStatements => {
+            "Declaration or statement"
+        }
+        ListKind::ClassMembers => "Class member",
+        ListKind::TypeMembers => "Property or signature",
+        ListKind::EnumMembers => "Enum member",
+    }
+}
+
+/// The offset of the first label of an error, which is where it points in the source.
+fn error_offset(error: &Error) -> Option<usize> {
+    error.labels()?.next().map(|label| label.offset())
+}
+
+fn token_text(kind: &TokenKind) -> &'static str {
+    match kind {
+        TokenKind::LBrace => "`{`",
+        TokenKind::RBrace => "`}`",
+        TokenKind::LParen => "`(`",
+        TokenKind::RParen => "`)`",
+        TokenKind::LBracket => "`[`",
+        TokenKind::RBracket => "`]`",
+        TokenKind::Comma => "`,`",
+        TokenKind::Semicolon => "`;`",
+        TokenKind::Colon => "`:`",
+        TokenKind::Dot => "`.`",
+        TokenKind::Arrow => "`=>`",
+        TokenKind::Question => "`?`",
+        TokenKind::AssignOp(AssignOp::Assign) => "`=`",
+        TokenKind::BinaryOp(BinaryOp::Lt) => "`<`",
+        TokenKind::BinaryOp(BinaryOp::Gt) => "`>`",
+        _ => "Token",
+    }
+}
+
+/// Parses `source` as a TypeScript module, returning the program along with every
+/// syntax error found, the same way [`Lexer::lex`] returns tokens and errors.
+pub fn parse<'a>(allocator: &'a Allocator, source: &'a str) -> (Program<'a>, Vec<Error>) {
+    Parser::new(allocator, source).parse()
+}
+
+/// Parses `source` and its comments. See [`Parser::parse_with_comments`].
+pub fn parse_with_comments<'a>(
+    allocator: &'a Allocator,
+    source: &'a str,
+) -> (Program<'a>, Comments, Vec<Error>) {
+    Parser::new(allocator, source).parse_with_comments()
+}
+
+/// Parses `source` into a lossless concrete syntax tree. See [`Parser::parse_cst`].
+pub fn parse_cst<'a>(allocator: &'a Allocator, source: &'a str) -> (SyntaxNode<'a>, Vec<Error>) {
+    Parser::new(allocator, source).parse_cst()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ast::*, SourceType, Syntax};
+
+    fn parse_ok<'a>(allocator: &'a Allocator, source: &'a str) -> allocator::Vec<'a, Stmt<'a>> {
+        let (program, errors) = parse(allocator, source);
+        assert!(errors.is_empty(), "failed to parse {source:?}: {errors:?}");
+        program.body
+    }
+
+    fn parse_with_errors<'a>(
+        allocator: &'a Allocator,
+        source: &'a str,
+    ) -> (allocator::Vec<'a, Stmt<'a>>, Vec<String>) {
+        let (program, errors) = parse(allocator, source);
+        let errors = errors.iter().map(|e| e.to_string()).collect();
+        (program.body, errors)
+    }
+
+    fn parse_tsx<'a>(
+        allocator: &'a Allocator,
+        source: &'a str,
+    ) -> (allocator::Vec<'a, Stmt<'a>>, Vec<String>) {
+        let options = ParserOptions {
+            syntax: Syntax::Tsx,
+            ..Default::default()
+        };
+        let (program, errors) = Parser::with_options(allocator, source, options).parse();
+        let errors = errors.iter().map(|e| e.to_string()).collect();
+        (program.body, errors)
+    }
+
+    fn parse_expr<'a>(allocator: &'a Allocator, source: &'a str) -> Expr<'a> {
+        match parse_ok(allocator, source).remove(0) {
+            Stmt::Expr(stmt) => stmt.expr.unbox(),
+            stmt => panic!("expected an expression statement, got {stmt:?}"),
+        }
+    }
+
+    #[test]
+    fn statements() {
+        let allocator = Allocator::new();
+        let body = parse_ok(&allocator,
+            "let a = 1, b;\nif (a) b = 2; else { b = 3 }\nfor (const x of xs) {}\nfor (k in o);\nlabel: while (true) break label;\ntry {} catch {} finally {}\nswitch (a) { case 1: default: }",
+        );
+        assert!(matches!(
+            &body[..],
+            [
+                Stmt::Var(VarDecl {
+                    kind: VarKind::Let,
+                    ..
+                }),
+                Stmt::If(_),
+                Stmt::ForOf(_),
+                Stmt::ForIn(_),
+                Stmt::Labeled(_),
+                Stmt::Try(_),
+                Stmt::Switch(_),
+            ]
+        ));
+        let Stmt::Var(decl) = &body[0] else {
+            unreachable!()
+        };
+        assert_eq!(decl.decls.len(), 2);
+        assert_eq!(decl.span, Span::new(0, 13));
+    }
+
+    #[test]
+    fn automatic_semicolon_insertion() {
+        let allocator = Allocator::new();
+        assert_eq!(parse_ok(&allocator, "a\nb").len(), 2);
+        assert!(matches!(
+            &parse_ok(&allocator, "{ a; b }")[0],
+            Stmt::Block(BlockStmt { stmts, .. }) if stmts.len() == 2
+        ));
+        let Stmt::Fn(decl) = &parse_ok(&allocator, "function f() { return\na }")[0] else {
+            panic!("expected a function")
+        };
+        let body = &decl.function.body.as_ref().unwrap().stmts;
+        assert!(matches!(
+            &body[0],
+            Stmt::Return(ReturnStmt { arg: None, .. })
+        ));
+        assert_eq!(body.len(), 2);
+        let (body, errors) = parse_with_errors(&allocator, "a b");
+        assert_eq!(body.len(), 2);
+        assert_eq!(errors, vec!["`;` expected"]);
+        // a comment with a line break counts as one
+        assert_eq!(parse_ok(&allocator, "a /*\n*/ b").len(), 2);
+        assert_eq!(parse_ok(&allocator, "a // c\nb").len(), 2);
+        assert_eq!(parse_ok(&allocator, "do {} while (a) b").len(), 2);
+
+        // a line break only ends a statement that cannot go on
+        assert!(matches!(parse_expr(&allocator, "a\n(b)"), Expr::Call(_)));
+        assert!(matches!(parse_expr(&allocator, "a\n[b]"), Expr::Member(_)));
+        assert!(matches!(
+            parse_expr(&allocator, "a\n`b`"),
+            Expr::TaggedTpl(_)
+        ));
+        assert!(matches!(parse_expr(&allocator, "a\n+ b"), Expr::Binary(_)));
+        // and never inserts the `;`s of a `for` header or an empty statement
+        for source in ["for (a\nb\nc) {}", "if (a)\nelse b"] {
+            assert!(
+                !parse(&allocator, source).1.is_empty(),
+                "{source:?} should not parse"
+            );
+        }
+    }
+
+    /// The productions with `[no LineTerminator here]` in ECMA-262.
+    #[test]
+    fn restricted_productions() {
+        let allocator = Allocator::new();
+        // UpdateExpression: LeftHandSideExpression ++
+        assert!(matches!(
+            &parse_ok(&allocator, "a\n++b")[..],
+            [Stmt::Expr(a), Stmt::Expr(b)]
+                if matches!(*a.expr, Expr::Ident(_))
+                    && matches!(*b.expr, Expr::Update(UpdateExpr { prefix: true, .. }))
+        ));
+        assert!(matches!(parse_expr(&allocator, "a\n--\nb"), Expr::Ident(_)));
+
+        // ContinueStatement and BreakStatement: `continue` LabelIdentifier
+        let body = parse_ok(&allocator, "a: while (x) { continue\na; break\na }");
+        let Stmt::Labeled(LabeledStmt { body, .. }) = &body[0] else {
+            panic!("expected a labeled statement")
+        };
+        let Stmt::While(WhileStmt { body, .. }) = &**body else {
+            panic!("expected a while statement")
+        };
+        let Stmt::Block(BlockStmt { stmts, .. }) = &**body else {
+            panic!("expected a block")
+        };
+        assert!(matches!(
+            &stmts[..],
+            [
+                Stmt::Continue(ContinueStmt { label: None, .. }),
+                Stmt::Expr(_),
+                Stmt::Break(BreakStmt { label: None, .. }),
+                Stmt::Expr(_),
+            ]
+        ));
+
+        // ReturnStatement: `return` Expression
+        let Stmt::Fn(decl) = &parse_ok(&allocator, "function f() { return\na }")[0] else {
+            panic!("expected a function")
+        };
+        assert!(matches!(
+            &decl.function.body.as_ref().unwrap().stmts[..],
+            [Stmt::Return(ReturnStmt { arg: None, .. }), Stmt::Expr(_)]
+        ));
+
+        // ThrowStatement: `throw` Expression, where no `;` can be inserted
+        let (_, errors) = parse_with_errors(&allocator, "throw\na");
+        assert_eq!(errors, vec!["Expression expected"]);
+
+        // YieldExpression: `yield` AssignmentExpression and `yield` `*`
+        let Stmt::Fn(decl) = &parse_ok(&allocator, "function* g() { yield\na }")[0] else {
+            panic!("expected a function")
+        };
+        assert!(matches!(
+            &decl.function.body.as_ref().unwrap().stmts[..],
+            [Stmt::Expr(stmt), Stmt::Expr(_)]
+                if matches!(*stmt.expr, Expr::Yield(YieldExpr { arg: None, .. }))
+        ));
+        assert!(!parse(&allocator, "function* g() { yield\n* a }")
+            .1
+            .is_empty());
+
+        // ArrowFunction: ArrowParameters `=>`, also for async arrows
+        for source in ["(a)\n=> a", "a\n=> a", "async (a)\n=> a"] {
+            assert!(
+                !parse(&allocator, source).1.is_empty(),
+                "{source:?} should not parse"
+            );
+        }
+
+        // AsyncFunctionDeclaration and AsyncArrowFunction: `async` on its own line is
+        // an identifier
+        assert!(matches!(
+            &parse_ok(&allocator, "async\nfunction f() {}")[..],
+            [
+                Stmt::Expr(_),
+                Stmt::Fn(FnDecl {
+                    function: Function {
+                        is_async: false,
+                        ..
+                    },
+                    ..
+                })
+            ]
+        ));
+        assert!(matches!(
+            &parse_ok(&allocator, "async\na => a")[..],
+            [Stmt::Expr(_), Stmt::Expr(arrow)]
+                if matches!(*arrow.expr, Expr::Arrow(ArrowExpr { is_async: false, .. }))
+        ));
+
+        // AsyncMethod: `async` PropertyName
+        let Stmt::Class(decl) = &parse_ok(&allocator, "class C { async\nm() {} }")[0] else {
+            panic!("expected a class")
+        };
+        assert!(matches!(
+            &decl.class.body[..],
+            [
+                ClassMember::Prop(_),
+                ClassMember::Method(ClassMethod {
+                    function: Function {
+                        is_async: false,
+                        ..
+                    },
+                    ..
+                })
+            ]
+        ));
+        assert!(!parse(&allocator, "({ async\nm() {} })").1.is_empty());
+    }
+
+    #[test]
+    fn binary_precedence() {
+        let allocator = Allocator::new();
+        let Expr::Binary(add) = parse_expr(&allocator, "1 + 2 * 3 ** 4 ** 5") else {
+            panic!("expected a binary expression")
+        };
+        assert_eq!(add.op, BinaryOperator::Add);
+        let Expr::Binary(mul) = add.right.unbox() else {
+            panic!("expected a binary expression")
+        };
+        assert_eq!(mul.op, BinaryOperator::Mul);
+        let Expr::Binary(exp) = mul.right.unbox() else {
+            panic!("expected a binary expression")
+        };
+        assert_eq!(exp.op, BinaryOperator::Exp);
+        assert!(matches!(*exp.right, Expr::Binary(_)));
+    }
+
+    #[test]
+    fn regex_after_operator() {
+        let allocator = Allocator::new();
+        let Expr::Binary(div) = parse_expr(&allocator, "a / b / c") else {
+            panic!("expected a binary expression")
+        };
+        assert!(matches!(*div.left, Expr::Binary(_)));
+        let Expr::Assign(assign) = parse_expr(&allocator, "x = /a+b/g") else {
+            panic!("expected an assignment")
+        };
+        assert!(matches!(*assign.right, Expr::Lit(Lit::Regex(_))));
+    }
+
+    #[test]
+    fn arrow_functions_and_generic_calls() {
+        let allocator = Allocator::new();
+        let Expr::Arrow(arrow) = parse_expr(&allocator, "async <T,>(a: T, { b }, ...c): T => a")
+        else {
+            panic!("expected an arrow function")
+        };
+        assert!(arrow.is_async);
+        assert_eq!(arrow.params.len(), 3);
+        assert!(arrow.type_params.is_some());
+        assert!(matches!(arrow.body, ArrowBody::Expr(_)));
+
+        let Expr::Call(call) = parse_expr(&allocator, "f<A, B>(c)") else {
+            panic!("expected a call")
+        };
+        assert_eq!(call.type_args.unwrap().params.len(), 2);
+        assert!(matches!(
+            parse_expr(&allocator, "a < b > c"),
+            Expr::Binary(_)
+        ));
+        assert!(matches!(parse_expr(&allocator, "(a)"), Expr::Paren(_)));
+    }
+
+    #[test]
+    fn nested_parens_are_not_parsed_again_and_again() {
+        let allocator = Allocator::new();
+        let depth = 40;
+        let mut source = "x = ".to_string();
+        for i in 0..depth {
+            source += &format!("(a{i} = ");
+        }
+        source += "0";
+        source += &")".repeat(depth);
+        source += ";\n(a = (b = (c = (d = 0)))) => a;";
+        let start = std::time::Instant::now();
+        let body = parse_ok(&allocator, &source);
+        assert!(start.elapsed() < std::time::Duration::from_secs(1));
+        assert!(matches!(&body[1], Stmt::Expr(stmt) if matches!(*stmt.expr, Expr::Arrow(_))));
+    }
+
+    #[test]
+    fn arrow_functions_in_conditionals() {
+        let allocator = Allocator::new();
+        // `(b)` is the consequent and `c => d` the alternate
+        let Expr::Assign(assign) = parse_expr(&allocator, "x = a ? (b) : c => d;") else {
+            panic!("expected an assignment")
+        };
+        let Expr::Cond(cond) = &*assign.right else {
+            panic!("expected a conditional")
+        };
+        assert!(matches!(*cond.cons, Expr::Paren(_)));
+        assert!(matches!(*cond.alt, Expr::Arrow(_)));
+
+        // `(b): c => d` is the consequent, since another `:` follows it
+        let Expr::Cond(cond) = parse_expr(&allocator, "a ? (b): c => d : e") else {
+            panic!("expected a conditional")
+        };
+        assert!(matches!(&*cond.cons, Expr::Arrow(arrow) if arrow.return_type.is_some()));
+        assert!(matches!(*cond.alt, Expr::Ident(_)));
+
+        // return types are allowed again in brackets
+        let Expr::Cond(cond) = parse_expr(&allocator, "a ? [(b): c => d] : e") else {
+            panic!("expected a conditional")
+        };
+        assert!(matches!(*cond.cons, Expr::Array(_)));
+    }
+
+    #[test]
+    fn classes() {
+        let allocator = Allocator::new();
+        let body = parse_ok(&allocator,
+            "@dec export abstract class A<T> extends B<T> implements C {\n  #x = 1;\n  static readonly y?: number;\n  constructor(private a: string) { super(); }\n  get z() { return 1 }\n  abstract m(): void;\n  static { init(); }\n}",
+        );
+        let Stmt::ExportDecl(export) = &body[0] else {
+            panic!("expected an export")
+        };
+        let Stmt::Class(decl) = &*export.decl else {
+            panic!("expected a class")
+        };
+        let class = &decl.class;
+        assert_eq!(decl.id.name, "A");
+        assert!(class.is_abstract);
+        assert_eq!(class.decorators.len(), 1);
+        assert!(class.super_type_args.is_some());
+        assert_eq!(class.implements.len(), 1);
+        assert!(matches!(
+            &class.body[..],
+            [
+                ClassMember::Prop(ClassProp {
+                    key: PropName::Private(_),
+                    ..
+                }),
+                ClassMember::Prop(ClassProp {
+                    is_static: true,
+                    is_readonly: true,
+                    ..
+                }),
+                ClassMember::Method(ClassMethod {
+                    kind: MethodKind::Constructor,
+                    ..
+                }),
+                ClassMember::Method(ClassMethod {
+                    kind: MethodKind::Getter,
+                    ..
+                }),
+                ClassMember::Method(ClassMethod {
+                    is_abstract: true,
+                    ..
+                }),
+                ClassMember::StaticBlock(_),
+            ]
+        ));
+        let ClassMember::Method(ctor) = &class.body[2] else {
+            unreachable!()
+        };
+        assert_eq!(
+            ctor.function.params[0].accessibility,
+            Some(Accessibility::Private)
+        );
+    }
+
+    #[test]
+    fn modules() {
+        let allocator = Allocator::new();
+        let body = parse_ok(&allocator,
+            "import a, { b as c, type D } from \"m\";\nimport * as ns from 'n';\nimport type E from \"e\";\nimport x = require(\"x\");\nexport { c as default, ns };\nexport * from \"m\";\nexport default function () {}\nexport = a;",
+        );
+        assert!(matches!(
+            &body[..],
+            [
+                Stmt::Import(_),
+                Stmt::Import(_),
+                Stmt::Import(ImportDecl {
+                    type_only: true,
+                    ..
+                }),
+                Stmt::ImportEquals(_),
+                Stmt::ExportNamed(_),
+                Stmt::ExportAll(_),
+                Stmt::ExportDefault(_),
+                Stmt::ExportAssign(_),
+            ]
+        ));
+        let Stmt::Import(import) = &body[0] else {
+            unreachable!()
+        };
+        assert_eq!(import.specifiers.len(), 3);
+        assert_eq!(import.src.value, "m");
+    }
+
+    #[test]
+    fn type_declarations() {
+        let allocator = Allocator::new();
+        let body = parse_ok(&allocator,
+            "interface I<T extends object = {}> extends J { a?: T; (x: number): string; [k: string]: any; m<U>(): U }\ntype F = keyof T extends infer U ? U[] : never;\ntype M = { readonly [K in keyof T]?: T[K] };\nconst enum E { A = 1, B }\ndeclare namespace N.O { let x: number }\ndeclare module \"m\";",
+        );
+        let Stmt::Interface(interface) = &body[0] else {
+            panic!("expected an interface")
+        };
+        assert_eq!(interface.body.len(), 4);
+        assert_eq!(interface.extends.len(), 1);
+        let Stmt::TypeAlias(alias) = &body[1] else {
+            panic!("expected a type alias")
+        };
+        assert!(matches!(*alias.ty, TsType::Conditional(_)));
+        let Stmt::TypeAlias(alias) = &body[2] else {
+            panic!("expected a type alias")
+        };
+        assert!(matches!(*alias.ty, TsType::Mapped(_)));
+        assert!(matches!(
+            &body[3],
+            Stmt::Enum(TsEnumDecl { is_const: true, .. })
+        ));
+        let Stmt::Module(module) = &body[4] else {
+            panic!("expected a namespace")
+        };
+        assert!(module.declare);
+        assert!(matches!(module.body, Some(TsModuleBody::Namespace(_))));
+        assert!(matches!(
+            &body[5],
+            Stmt::Module(TsModuleDecl { body: None, .. })
+        ));
+    }
+
+    #[test]
+    fn types() {
+        let allocator = Allocator::new();
+        let Stmt::TypeAlias(alias) = &parse_ok(
+            &allocator,
+            "type A = (a: string) => Map<string, Array<number[]>>;",
+        )[0] else {
+            panic!("expected a type alias")
+        };
+        let TsType::Fn(ty) = &*alias.ty else {
+            panic!("expected a function type")
+        };
+        let TsType::Ref(map) = &*ty.return_type else {
+            panic!("expected a type reference")
+        };
+        assert_eq!(map.type_args.as_ref().unwrap().params.len(), 2);
+
+        let Stmt::TypeAlias(alias) = &parse_ok(
+            &allocator,
+            "type B = | \"a\" | `b${string}` | [x: number, ...rest: string[]];",
+        )[0] else {
+            panic!("expected a type alias")
+        };
+        let TsType::Union(union) = &*alias.ty else {
+            panic!("expected a union type")
+        };
+        assert_eq!(union.types.len(), 3);
+    }
+
+    #[test]
+    fn reports_syntax_errors() {
+        let allocator = Allocator::new();
+        for source in ["var = 1;", "a +;", "class { }", "(a, b) => => c", "f(a b)"] {
+            assert!(
+                !parse(&allocator, source).1.is_empty(),
+                "{source:?} should not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn missing_expression_placeholder() {
+        let allocator = Allocator::new();
+        let (body, errors) = parse_with_errors(&allocator, "let x = ;\nfoo();");
+        assert_eq!(errors, vec!["Expression expected"]);
+        let Stmt::Var(decl) = &body[0] else {
+            panic!("expected a variable declaration")
+        };
+        assert_eq!(
+            decl.decls[0].init.as_deref(),
+            Some(&Expr::Invalid(Span::new(7, 7)))
+        );
+        assert!(matches!(body[1], Stmt::Expr(_)));
+
+        // the failed arrow function lookahead does not report twice
+        let (_, errors) = parse_with_errors(&allocator, "(x +);");
+        assert_eq!(errors, vec!["Expression expected"]);
+    }
+
+    #[test]
+    fn recovers_at_statement_boundaries() {
+        let allocator = Allocator::new();
+        let (body, errors) = parse_with_errors(&allocator, "if (a b) { c }\nd();");
+        assert_eq!(errors, vec!["`)` expected"]);
+        assert!(matches!(
+            &body[..],
+            [Stmt::Invalid(span), Stmt::Expr(_)] if *span == Span::new(0, 14)
+        ));
+
+        let (body, errors) = parse_with_errors(&allocator, "function f() { var 1; g() }\nh()");
+        assert_eq!(errors, vec!["Identifier expected"]);
+        let Stmt::Fn(decl) = &body[0] else {
+            panic!("expected a function")
+        };
+        assert!(matches!(
+            &decl.function.body.as_ref().unwrap().stmts[..],
+            [Stmt::Invalid(_), Stmt::Expr(_)]
+        ));
+        assert_eq!(body.len(), 2);
+
+        let (body, errors) = parse_with_errors(&allocator, ")\na;");
+        assert_eq!(errors, vec!["Declaration or statement expected"]);
+        assert!(matches!(&body[..], [Stmt::Invalid(_), Stmt::Expr(_)]));
+
+        // a stray token only skips itself, not the statement after it on the same line
+        for source in [
+            "} let x = 1;",
+            ") let x = 1;",
+            "function f() { ] let x = 1; }",
+        ] {
+            let (body, errors) = parse_with_errors(&allocator, source);
+            assert_eq!(
+                errors,
+                vec!["Declaration or statement expected"],
+                "{source}"
+            );
+            let body = match &body[..] {
+                [Stmt::Fn(decl)] => &decl.function.body.as_ref().unwrap().stmts,
+                _ => &body,
+            };
+            assert!(
+                matches!(&body[..], [Stmt::Invalid(span), Stmt::Var(_)] if span.end - span.start == 1),
+                "{source}: {body:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn recovers_at_member_boundaries() {
+        let allocator = Allocator::new();
+        let (body, errors) = parse_with_errors(
+            &allocator,
+            "class A {\n  a: number = ;\n  b(: number) {}\n  c() {}\n}",
+        );
+        assert_eq!(errors.len(), 2);
+        let Stmt::Class(decl) = &body[0] else {
+            panic!("expected a class")
+        };
+        assert!(matches!(
+            &decl.class.body[..],
+            [
+                ClassMember::Prop(_),
+                ClassMember::Invalid(_),
+                ClassMember::Method(_)
+            ]
+        ));
+
+        let (body, errors) = parse_with_errors(
+            &allocator,
+            "interface I { a: ; b(): void }\nenum E { A = 1 B }",
+        );
+        assert_eq!(errors, vec!["Type expected", "`,` expected"]);
+        let Stmt::Interface(interface) = &body[0] else {
+            panic!("expected an interface")
+        };
+        assert!(matches!(
+            &interface.body[..],
+            [TsTypeElement::Invalid(_), TsTypeElement::Method(_)]
+        ));
+        let Stmt::Enum(decl) = &body[1] else {
+            panic!("expected an enum")
+        };
+        assert_eq!(decl.members.len(), 2);
+    }
+
+    #[test]
+    fn inserts_missing_closing_brace() {
+        let allocator = Allocator::new();
+        let (body, errors) = parse_with_errors(&allocator, "function f() {\n  g();\n");
+        assert_eq!(errors, vec!["`}` expected"]);
+        let Stmt::Fn(decl) = &body[0] else {
+            panic!("expected a function")
+        };
+        assert_eq!(decl.function.body.as_ref().unwrap().stmts.len(), 1);
+    }
+
+    #[test]
+    fn errors_in_source_order() {
+        let allocator = Allocator::new();
+        let (body, errors) = parse_with_errors(&allocator, "x = 'a\ny = ;");
+        assert_eq!(body.len(), 2);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[1], "Expression expected");
+    }
+
+    #[test]
+    fn jsx() {
+        let allocator = Allocator::new();
+        let (body, errors) = parse_tsx(
+            &allocator,
+            "<div data-id=\"a\" {...props}>hi {name}<svg:rect /><A.B></A.B><>x</></div>;",
+        );
+        assert!(errors.is_empty(), "{errors:?}");
+        let Stmt::Expr(stmt) = &body[0] else {
+            panic!("expected an expression statement")
+        };
+        let Expr::JsxElement(element) = &*stmt.expr else {
+            panic!("expected a JSX element")
+        };
+        assert!(matches!(
+            &element.opening.attrs[..],
+            [
+                JsxAttrOrSpread::Attr(JsxAttr {
+                    name: JsxAttrName::Ident(Ident { name, .. }),
+                    value: Some(JsxAttrValue::Str(_)),
+                    ..
+                }),
+                JsxAttrOrSpread::Spread(_),
+            ] if name == "data-id"
+        ));
+        assert!(matches!(
+            &element.children[..],
+            [
+                JsxChild::Text(JsxText { value, .. }),
+                JsxChild::ExprContainer(_),
+                JsxChild::Element(JsxElement {
+                    opening: JsxOpeningElement {
+                        name: JsxElementName::Namespaced(_),
+                        self_closing: true,
+                        ..
+                    },
+                    ..
+                }),
+                JsxChild::Element(JsxElement {
+                    opening: JsxOpeningElement {
+                        name: JsxElementName::Member(_),
+                        ..
+                    },
+                    closing: Some(_),
+                    ..
+                }),
+                JsxChild::Fragment(_),
+            ] if *value == "hi "
+        ));
+    }
+
+    #[test]
+    fn tsx_type_params_and_jsx() {
+        let allocator = Allocator::new();
+        for source in [
+            "<T,>(a: T) => a;",
+            "<T extends U>(a: T) => a;",
+            "<const T,>(a: T) => a;",
+        ] {
+            let (body, errors) = parse_tsx(&allocator, source);
+            assert!(errors.is_empty(), "{errors:?}");
+            assert!(
+                matches!(&body[0], Stmt::Expr(stmt) if matches!(*stmt.expr, Expr::Arrow(_))),
+                "{source:?} should be an arrow function"
+            );
+        }
+        let (body, errors) = parse_tsx(&allocator, "<T>(a)</T>;");
+        assert!(errors.is_empty(), "{errors:?}");
+        assert!(matches!(&body[0], Stmt::Expr(stmt) if matches!(*stmt.expr, Expr::JsxElement(_))));
+        // a type assertion outside TSX
+        assert!(matches!(
+            parse_expr(&allocator, "<T>(a);"),
+            Expr::TsTypeAssertion(_)
+        ));
+    }
+
+    #[test]
+    fn jsx_errors() {
+        let allocator = Allocator::new();
+        assert_eq!(
+            parse_tsx(&allocator, "<a></b>;").1,
+            ["Expected corresponding JSX closing tag for 'a'"]
+        );
+        assert_eq!(
+            parse_tsx(&allocator, "<a/><b/>;").1,
+            ["JSX expressions must have one parent element"]
+        );
+        assert_eq!(
+            parse_tsx(&allocator, "<a b={} />;").1,
+            ["JSX attributes must only be assigned a non-empty expression"]
+        );
+        assert_eq!(
+            parse_tsx(&allocator, "<a>text").1,
+            ["JSX element 'a' has no corresponding closing tag"]
+        );
+    }
+
+    #[test]
+    fn strict_mode_reserved_words() {
+        let allocator = Allocator::new();
+        assert_eq!(
+            parse_with_errors(&allocator, "var package = 1; let x = private;").1,
+            [
+                "Identifier expected. 'package' is a reserved word in strict mode",
+                "Identifier expected. 'private' is a reserved word in strict mode",
+            ]
+        );
+        let script = ParserOptions {
+            source_type: SourceType::Script,
+            ..Default::default()
+        };
+        let (_, errors) =
+            Parser::with_options(&allocator, "var package = 1; let x = private;", script).parse();
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn contextual_keywords() {
+        let allocator = Allocator::new();
+        let body = parse_ok(&allocator,
+            "let string = 1, type = obj.module;\ntype A = string;\ndeclare module \"m\" {}\nnamespace.x = async;",
+        );
+        assert!(matches!(
+            &body[..],
+            [
+                Stmt::Var(_),
+                Stmt::TypeAlias(_),
+                Stmt::Module(_),
+                Stmt::Expr(_)
+            ]
+        ));
+
+        let javascript = ParserOptions {
+            syntax: Syntax::Javascript,
+            ..Default::default()
+        };
+        let (program, errors) =
+            Parser::with_options(&allocator, "declare\nnamespace\nA", javascript).parse();
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(program.body.len(), 3);
+    }
+}
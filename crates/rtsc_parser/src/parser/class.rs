@@ -0,0 +1,615 @@
+use crate::{
+    allocator::{Box, Vec},
+    ast::*,
+    AssignOp, BinaryOp, ContextualKeyword, Keyword, TokenKind, WordKind,
+};
+
+use super::{stmt::with_type_ann, Context, ListKind, PResult, Parser};
+
+impl<'a> Parser<'a> {
+    /// Parses the part of a function after its name: type parameters, parameters,
+    /// return type and body. The body is optional, for overloads and ambient functions.
+    pub(super) fn parse_function_rest(
+        &mut self,
+        start: usize,
+        is_async: bool,
+        is_generator: bool,
+    ) -> PResult<Function<'a>> {
+        let ctx = Context {
+            in_function: true,
+            in_async: is_async,
+            in_generator: is_generator,
+            ..Context::default()
+        };
+        self.with_ctx(ctx, |p| {
+            let type_params = p.parse_type_params_opt()?;
+            let params = p.parse_params()?;
+            let return_type = if p.eat(&TokenKind::Colon) {
+                Some(p.parse_return_type()?)
+            } else {
+                None
+            };
+            let body = if p.is(&TokenKind::LBrace) {
+                Some(p.parse_block()?)
+            } else {
+                None
+            };
+            Ok(Function {
+                params,
+                body,
+                is_async,
+                is_generator,
+                type_params,
+                return_type,
+                span: p.span_from(start),
+            })
+        })
+    }
+
+    /// Parses a parenthesized parameter list.
+    pub(super) fn parse_params(&mut self) -> PResult<Vec<'a, Param<'a>>> {
+        self.expect(&TokenKind::LParen)?;
+        let mut params = self.vec();
+        while !self.eat(&TokenKind::RParen) {
+            let start = self.start();
+            let decorators = if self.is(&TokenKind::At) {
+                self.parse_decorators()?
+            } else {
+                self.vec()
+            };
+            let mut accessibility = None;
+            let mut is_readonly = false;
+            let mut is_override = false;
+            loop {
+                if !self.is_param_modifier() {
+                    break;
+                }
+                match self.bump().kind {
+                    TokenKind::Word(WordKind::Keyword(Keyword::Public)) => {
+                        accessibility = Some(Accessibility::Public)
+                    }
+                    TokenKind::Word(WordKind::Keyword(Keyword::Protected)) => {
+                        accessibility = Some(Accessibility::Protected)
+                    }
+                    TokenKind::Word(WordKind::Keyword(Keyword::Private)) => {
+                        accessibility = Some(Accessibility::Private)
+                    }
+                    TokenKind::Word(WordKind::Identifier(name)) if name == "readonly" => {
+                        is_readonly = true
+                    }
+                    _ => is_override = true,
+                }
+            }
+            let pat = self.parse_param_pat()?;
+            params.push(Param {
+                decorators,
+                accessibility,
+                is_readonly,
+                is_override,
+                pat,
+                span: self.span_from(start),
+            });
+            if !self.eat(&TokenKind::Comma) {
+                self.expect(&TokenKind::RParen)?;
+                break;
+            }
+        }
+        Ok(params)
+    }
+
+    /// `public`, `private`, `protected`, `readonly` and `override` before a parameter,
+    /// unless they are the name of the parameter themselves.
+    fn is_param_modifier(&mut self) -> bool {
+        let is_modifier = matches!(
+            self.keyword(),
+            Some(Keyword::Public | Keyword::Protected | Keyword::Private)
+        ) || self.is_contextual(ContextualKeyword::Readonly)
+            || self.is_contextual(ContextualKeyword::Override);
+        is_modifier
+            && self.next_on_same_line(|p| {
+                p.is_ident()
+                    || p.is_keyword(Keyword::This)
+                    || p.is(&TokenKind::LBrace)
+                    || p.is(&TokenKind::LBracket)
+                    || p.is(&TokenKind::DotDotDot)
+            })
+    }
+
+    /// A parameter without modifiers: `...rest`, `a?: T = b` and `this: T`.
+    pub(super) fn parse_param_pat(&mut self) -> PResult<Pat<'a>> {
+        let start = self.start();
+        let is_rest = self.eat(&TokenKind::DotDotDot);
+        let pat = if self.is_keyword(Keyword::This) {
+            let span = self.bump().span;
+            Pat::Ident(BindingIdent {
+                id: Ident {
+                    name: Keyword::This.atom(),
+                    span,
+                },
+                type_ann: None,
+                optional: false,
+                span,
+            })
+        } else {
+            self.parse_binding_pat()?
+        };
+        let optional = self.eat(&TokenKind::Question);
+        let type_ann = if self.eat(&TokenKind::Colon) {
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+        if is_rest {
+            return Ok(Pat::Rest(RestPat {
+                arg: self.alloc(pat),
+                type_ann,
+                span: self.span_from(start),
+            }));
+        }
+        let pat = with_type_ann(pat, optional, type_ann, self.prev_end);
+        if self.eat(&TokenKind::AssignOp(AssignOp::Assign)) {
+            let right = self.allow_in(|p| p.parse_assignment_expr())?;
+            return Ok(Pat::Assign(AssignPat {
+                left: self.alloc(pat),
+                right,
+                span: self.span_from(start),
+            }));
+        }
+        Ok(pat)
+    }
+
+    /// An identifier, array or object binding pattern, without a type annotation.
+    pub(super) fn parse_binding_pat(&mut self) -> PResult<Pat<'a>> {
+        let start = self.start();
+        match self.token.kind {
+            TokenKind::LBracket => {
+                self.bump();
+                let mut elems = self.vec();
+                while !self.eat(&TokenKind::RBracket) {
+                    if self.eat(&TokenKind::Comma) {
+                        elems.push(None);
+                        continue;
+                    }
+                    let elem_start = self.start();
+                    let elem = if self.eat(&TokenKind::DotDotDot) {
+                        let arg = self.parse_binding_pat()?;
+                        Pat::Rest(RestPat {
+                            arg: self.alloc(arg),
+                            type_ann: None,
+                            span: self.span_from(elem_start),
+                        })
+                    } else {
+                        self.parse_binding_element()?
+                    };
+                    elems.push(Some(elem));
+                    if !self.eat(&TokenKind::Comma) {
+                        self.expect(&TokenKind::RBracket)?;
+                        break;
+                    }
+                }
+                Ok(Pat::Array(ArrayPat {
+                    elems,
+                    type_ann: None,
+                    optional: false,
+                    span: self.span_from(start),
+                }))
+            }
+            TokenKind::LBrace => {
+                self.bump();
+                let mut props = self.vec();
+                while !self.eat(&TokenKind::RBrace) {
+                    let prop_start = self.start();
+                    if self.eat(&TokenKind::DotDotDot) {
+                        let arg = self.parse_binding_pat()?;
+                        props.push(ObjectPatProp::Rest(RestPat {
+                            arg: self.alloc(arg),
+                            type_ann: None,
+                            span: self.span_from(prop_start),
+                        }));
+                    } else {
+                        let key = self.parse_prop_name()?;
+                        if self.eat(&TokenKind::Colon) {
+                            let value = self.parse_binding_element()?;
+                            props.push(ObjectPatProp::KeyValue(KeyValuePatProp {
+                                key,
+                                value: self.alloc(value),
+                                span: self.span_from(prop_start),
+                            }));
+                        } else {
+                            let key = match key {
+                                PropName::Ident(id) => id,
+                                key => return Err(self.expected_at("`:`", key.span())),
+                            };
+                            let value = if self.eat(&TokenKind::AssignOp(AssignOp::Assign)) {
+                                Some(self.allow_in(|p| p.parse_assignment_expr())?)
+                            } else {
+                                None
+                            };
+                            props.push(ObjectPatProp::Assign(AssignPatProp {
+                                key,
+                                value,
+                                span: self.span_from(prop_start),
+                            }));
+                        }
+                    }
+                    if !self.eat(&TokenKind::Comma) {
+                        self.expect(&TokenKind::RBrace)?;
+                        break;
+                    }
+                }
+                Ok(Pat::Object(ObjectPat {
+                    props,
+                    type_ann: None,
+                    optional: false,
+                    span: self.span_from(start),
+                }))
+            }
+            _ => {
+                let id = self.parse_ident()?;
+                Ok(Pat::Ident(BindingIdent {
+                    span: id.span,
+                    id,
+                    type_ann: None,
+                    optional: false,
+                }))
+            }
+        }
+    }
+
+    /// A binding pattern with an optional default value, inside array and object patterns.
+    fn parse_binding_element(&mut self) -> PResult<Pat<'a>> {
+        let start = self.start();
+        let pat = self.parse_binding_pat()?;
+        if self.eat(&TokenKind::AssignOp(AssignOp::Assign)) {
+            let right = self.allow_in(|p| p.parse_assignment_expr())?;
+            return Ok(Pat::Assign(AssignPat {
+                left: self.alloc(pat),
+                right,
+                span: self.span_from(start),
+            }));
+        }
+        Ok(pat)
+    }
+
+    /// Parses the name of a property, which can be any IdentifierName, a string or
+    /// numeric literal, a computed `[expr]` or a `#private` name.
+    pub(super) fn parse_prop_name(&mut self) -> PResult<PropName<'a>> {
+        let start = self.start();
+        match &self.token.kind {
+            TokenKind::Word(_) => Ok(PropName::Ident(self.parse_ident_name()?)),
+            TokenKind::String { .. } => Ok(PropName::Str(self.parse_str()?)),
+            TokenKind::Number { value, raw } => {
+                let (value, raw) = (*value, *raw);
+                let span = self.bump().span;
+                Ok(PropName::Num(Num { value, raw, span }))
+            }
+            TokenKind::BigInt { value, raw } => {
+                let (value, raw) = (*value, *raw);
+                let span = self.bump().span;
+                Ok(PropName::BigInt(BigInt { value, raw, span }))
+            }
+            TokenKind::LBracket => {
+                self.bump();
+                let expr = self.allow_in(|p| p.parse_assignment_expr())?;
+                self.expect(&TokenKind::RBracket)?;
+                Ok(PropName::Computed(ComputedPropName {
+                    expr,
+                    span: self.span_from(start),
+                }))
+            }
+            TokenKind::Hash => Ok(PropName::Private(self.parse_private_name()?)),
+            _ => Err(self.expected("Property name")),
+        }
+    }
+
+    pub(super) fn parse_private_name(&mut self) -> PResult<PrivateName<'a>> {
+        let start = self.start();
+        self.expect(&TokenKind::Hash)?;
+        let name = self.parse_ident_name()?.name;
+        Ok(PrivateName {
+            name,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Whether the current token can start a property name, which tells a modifier like
+    /// `get` or `static` apart from a property of that name.
+    pub(super) fn is_prop_name_start(&self) -> bool {
+        matches!(
+            self.token.kind,
+            TokenKind::Word(_)
+                | TokenKind::String { .. }
+                | TokenKind::Number { .. }
+                | TokenKind::BigInt { .. }
+                | TokenKind::LBracket
+                | TokenKind::Hash
+        )
+    }
+
+    pub(super) fn parse_decorators(&mut self) -> PResult<Vec<'a, Decorator<'a>>> {
+        let mut decorators = self.vec();
+        while self.is(&TokenKind::At) {
+            let start = self.start();
+            self.bump();
+            let expr = self.parse_decorator_expr()?;
+            decorators.push(Decorator {
+                expr,
+                span: self.span_from(start),
+            });
+        }
+        Ok(decorators)
+    }
+
+    /// `@(expr)`, or a member chain like `@a.b<T>(c)` with an optional call.
+    fn parse_decorator_expr(&mut self) -> PResult<Box<'a, Expr<'a>>> {
+        let start = self.start();
+        if self.is(&TokenKind::LParen) {
+            return self.parse_primary_expr();
+        }
+        let mut expr = Box::new_in(Expr::Ident(self.parse_ident()?), self.allocator);
+        while self.eat(&TokenKind::Dot) {
+            let prop = if self.is(&TokenKind::Hash) {
+                MemberProp::Private(self.parse_private_name()?)
+            } else {
+                MemberProp::Ident(self.parse_ident_name()?)
+            };
+            expr = self.alloc(Expr::Member(MemberExpr {
+                object: expr,
+                prop,
+                optional: false,
+                span: self.span_from(start),
+            }));
+        }
+        let type_args = if self.is_lt() {
+            Some(self.parse_type_args()?)
+        } else {
+            None
+        };
+        if self.is(&TokenKind::LParen) {
+            let args = self.parse_args()?;
+            expr = self.alloc(Expr::Call(CallExpr {
+                callee: expr,
+                type_args,
+                args,
+                optional: false,
+                span: self.span_from(start),
+            }));
+        }
+        Ok(expr)
+    }
+
+    /// Parses a class from the `class` keyword. `decorators` and `abstract` in front of
+    /// it have already been consumed.
+    pub(super) fn parse_class(
+        &mut self,
+        start: usize,
+        decorators: Vec<'a, Decorator<'a>>,
+        is_abstract: bool,
+    ) -> PResult<(Option<Ident<'a>>, Class<'a>)> {
+        self.expect_keyword(Keyword::Class)?;
+        let id = if self.is_ident() && !self.is_keyword(Keyword::Implements) {
+            Some(self.parse_ident()?)
+        } else {
+            None
+        };
+        let type_params = self.parse_type_params_opt()?;
+        let (super_class, super_type_args) = if self.eat_keyword(Keyword::Extends) {
+            let expr = self.parse_lhs_expr()?;
+            match expr.unbox() {
+                Expr::TsInstantiation(TsInstantiation {
+                    expr, type_args, ..
+                }) => (Some(expr), Some(type_args)),
+                expr => {
+                    let type_args = if self.is_lt() {
+                        Some(self.parse_type_args()?)
+                    } else {
+                        None
+                    };
+                    (Some(self.alloc(expr)), type_args)
+                }
+            }
+        } else {
+            (None, None)
+        };
+        let implements = if self.eat_keyword(Keyword::Implements) {
+            self.parse_heritage_list()?
+        } else {
+            self.vec()
+        };
+
+        self.expect(&TokenKind::LBrace)?;
+        let body = self.parse_list(
+            ListKind::ClassMembers,
+            |p| p.parse_class_member(),
+            |span| Some(ClassMember::Invalid(span)),
+        );
+        self.expect_list_close();
+        Ok((
+            id,
+            Class {
+                decorators,
+                is_abstract,
+                type_params,
+                super_class,
+                super_type_args,
+                implements,
+                body,
+                span: self.span_from(start),
+            },
+        ))
+    }
+
+    fn parse_class_member(&mut self) -> PResult<ClassMember<'a>> {
+        let start = self.start();
+        if self.eat(&TokenKind::Semicolon) {
+            return Ok(ClassMember::Empty(self.span_from(start)));
+        }
+        let decorators = self.parse_decorators()?;
+
+        if self.is_contextual(ContextualKeyword::Static)
+            && self.next_on_same_line(|p| p.is(&TokenKind::LBrace))
+        {
+            self.bump();
+            let body = self.with_ctx(Context::default(), |p| p.parse_block())?;
+            return Ok(ClassMember::StaticBlock(StaticBlock {
+                body,
+                span: self.span_from(start),
+            }));
+        }
+
+        let mut is_static = false;
+        let mut accessibility = None;
+        let mut is_abstract = false;
+        let mut is_override = false;
+        let mut is_readonly = false;
+        let mut is_declare = false;
+        let mut is_accessor = false;
+        while self.is_class_modifier() {
+            let keyword = self.contextual_keyword();
+            match self.bump().kind {
+                TokenKind::Word(WordKind::Keyword(Keyword::Public)) => {
+                    accessibility = Some(Accessibility::Public)
+                }
+                TokenKind::Word(WordKind::Keyword(Keyword::Protected)) => {
+                    accessibility = Some(Accessibility::Protected)
+                }
+                TokenKind::Word(WordKind::Keyword(Keyword::Private)) => {
+                    accessibility = Some(Accessibility::Private)
+                }
+                _ => match keyword {
+                    Some(ContextualKeyword::Static) => is_static = true,
+                    Some(ContextualKeyword::Abstract) => is_abstract = true,
+                    Some(ContextualKeyword::Override) => is_override = true,
+                    Some(ContextualKeyword::Readonly) => is_readonly = true,
+                    Some(ContextualKeyword::Declare) => is_declare = true,
+                    _ => is_accessor = true,
+                },
+            }
+        }
+
+        if self.is(&TokenKind::LBracket) && self.is_index_signature() {
+            return self
+                .parse_index_signature(start, is_readonly, is_static)
+                .map(ClassMember::IndexSignature);
+        }
+
+        let is_async = self.is_contextual(ContextualKeyword::Async)
+            && self.next_on_same_line(|p| {
+                p.is_prop_name_start() || p.is(&TokenKind::BinaryOp(BinaryOp::Mul))
+            });
+        if is_async {
+            self.bump();
+        }
+        let is_generator = self.eat(&TokenKind::BinaryOp(BinaryOp::Mul));
+        let mut kind = MethodKind::Method;
+        if !is_async
+            && !is_generator
+            && (self.is_contextual(ContextualKeyword::Get)
+                || self.is_contextual(ContextualKeyword::Set))
+            && self.next_on_same_line(|p| p.is_prop_name_start())
+        {
+            kind = if self.eat_contextual(ContextualKeyword::Get) {
+                MethodKind::Getter
+            } else {
+                self.bump();
+                MethodKind::Setter
+            };
+        }
+
+        let key = self.parse_prop_name()?;
+        let is_constructor = !is_static
+            && kind == MethodKind::Method
+            && match &key {
+                PropName::Ident(id) => id.name == "constructor",
+                PropName::Str(s) => s.value == "constructor",
+                _ => false,
+            };
+        let is_optional = self.eat(&TokenKind::Question);
+
+        if self.is(&TokenKind::LParen) || self.is_lt() {
+            let function = self.parse_function_rest(start, is_async, is_generator)?;
+            if function.body.is_none() {
+                self.expect_semi();
+            }
+            return Ok(ClassMember::Method(ClassMethod {
+                decorators,
+                kind: if is_constructor {
+                    MethodKind::Constructor
+                } else {
+                    kind
+                },
+                key,
+                function,
+                is_static,
+                accessibility,
+                is_abstract,
+                is_override,
+                is_optional,
+                span: self.span_from(start),
+            }));
+        }
+
+        let definite = !is_optional && self.eat(&TokenKind::Bang);
+        let type_ann = if self.eat(&TokenKind::Colon) {
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+        let value = if self.eat(&TokenKind::AssignOp(AssignOp::Assign)) {
+            let ctx = Context {
+                in_function: true,
+                ..Context::default()
+            };
+            Some(self.with_ctx(ctx, |p| p.parse_assignment_expr())?)
+        } else {
+            None
+        };
+        self.expect_semi();
+        Ok(ClassMember::Prop(ClassProp {
+            decorators,
+            key,
+            value,
+            type_ann,
+            is_static,
+            accessibility,
+            is_abstract,
+            is_override,
+            is_readonly,
+            is_declare,
+            is_accessor,
+            is_optional,
+            definite,
+            span: self.span_from(start),
+        }))
+    }
+
+    /// Whether the current token is a modifier of a class member rather than its name.
+    fn is_class_modifier(&mut self) -> bool {
+        let is_modifier = matches!(
+            self.keyword(),
+            Some(Keyword::Public | Keyword::Protected | Keyword::Private)
+        ) || matches!(
+            self.contextual_keyword(),
+            Some(
+                ContextualKeyword::Static
+                    | ContextualKeyword::Abstract
+                    | ContextualKeyword::Override
+                    | ContextualKeyword::Readonly
+                    | ContextualKeyword::Accessor
+                    | ContextualKeyword::Declare
+            )
+        );
+        if !is_modifier {
+            return false;
+        }
+        let is_static = self.is_contextual(ContextualKeyword::Static);
+        self.lookahead(|p| {
+            p.bump();
+            (is_static || !p.has_line_break_before())
+                && (p.is_prop_name_start()
+                    || p.is(&TokenKind::LBrace)
+                    || p.is(&TokenKind::BinaryOp(BinaryOp::Mul))
+                    || p.is(&TokenKind::DotDotDot))
+        })
+    }
+}
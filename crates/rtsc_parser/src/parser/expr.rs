@@ -0,0 +1,1221 @@
+use crate::{
+    allocator::{Box, Vec},
+    ast::*,
+    diagnostics::InvalidAssignmentTarget,
+    AssignOp, BinaryOp, ContextualKeyword, Keyword, Span, TokenKind, WordKind,
+};
+
+use super::{Context, PResult, Parser};
+
+/// The type parameters, parameters and return type of an arrow function.
+type ArrowHead<'a> = (
+    Option<TsTypeParamDecl<'a>>,
+    Vec<'a, Param<'a>>,
+    Option<Box<'a, TsType<'a>>>,
+);
+
+/// An answer which may not be known yet.
+enum Tristate {
+    True,
+    False,
+    Unknown,
+}
+
+/// The operators handled by precedence climbing in [`Parser::parse_binary_expr`].
+enum BinaryKind {
+    Op(BinaryOperator),
+    As,
+    Satisfies,
+}
+
+impl<'a> Parser<'a> {
+    /// Parses an Expression, which may be a comma-separated sequence.
+    pub(super) fn parse_expr(&mut self) -> PResult<Box<'a, Expr<'a>>> {
+        let start = self.start();
+        let expr = self.parse_assignment_expr()?;
+        if !self.is(&TokenKind::Comma) {
+            return Ok(expr);
+        }
+        let mut exprs = Vec::from_iter_in([expr.unbox()], self.allocator);
+        while self.eat(&TokenKind::Comma) {
+            exprs.push(self.parse_assignment_expr()?.unbox());
+        }
+        Ok(self.alloc(Expr::Seq(SeqExpr {
+            exprs,
+            span: self.span_from(start),
+        })))
+    }
+
+    pub(super) fn parse_assignment_expr(&mut self) -> PResult<Box<'a, Expr<'a>>> {
+        let start = self.start();
+        if self.is_keyword(Keyword::Yield) && self.ctx.in_generator {
+            return self.parse_yield_expr();
+        }
+        if let Some(arrow) = self.try_parse_arrow_expr()? {
+            return Ok(arrow);
+        }
+
+        let left = self.parse_conditional_expr()?;
+        let op = match self.token.kind {
+            TokenKind::AssignOp(op) => assign_operator(op),
+            _ => return Ok(left),
+        };
+        let left = match (left.unbox(), op) {
+            (left @ (Expr::Array(_) | Expr::Object(_)), AssignOperator::Assign) => {
+                self.expr_to_pat(self.alloc(left))?
+            }
+            (Expr::Ident(id), _) => Pat::Ident(BindingIdent {
+                span: id.span,
+                id,
+                type_ann: None,
+                optional: false,
+            }),
+            (left, _) if is_simple_target(&left) => Pat::Expr(self.alloc(left)),
+            (left, _) => {
+                self.error(InvalidAssignmentTarget(left.span()).into());
+                Pat::Expr(self.alloc(left))
+            }
+        };
+        self.bump();
+        let right = self.parse_assignment_expr()?;
+        Ok(self.alloc(Expr::Assign(AssignExpr {
+            op,
+            left: self.alloc(left),
+            right,
+            span: self.span_from(start),
+        })))
+    }
+
+    fn parse_yield_expr(&mut self) -> PResult<Box<'a, Expr<'a>>> {
+        let start = self.start();
+        self.bump();
+        let (arg, delegate) = if self.has_line_break_before() {
+            (None, false)
+        } else if self.eat(&TokenKind::BinaryOp(BinaryOp::Mul)) {
+            (Some(self.parse_assignment_expr()?), true)
+        } else if self.is_start_of_expr() {
+            (Some(self.parse_assignment_expr()?), false)
+        } else {
+            (None, false)
+        };
+        Ok(self.alloc(Expr::Yield(YieldExpr {
+            arg,
+            delegate,
+            span: self.span_from(start),
+        })))
+    }
+
+    /// Parses an arrow function if one starts here. Parenthesized and generic heads
+    /// look like other expressions until the `=>`, so they are parsed speculatively.
+    fn try_parse_arrow_expr(&mut self) -> PResult<Option<Box<'a, Expr<'a>>>> {
+        let start = self.start();
+        let is_async = self.is_contextual(ContextualKeyword::Async)
+            && self.next_on_same_line(|p| p.is_ident() || p.is(&TokenKind::LParen) || p.is_lt());
+
+        // `a => ...` and `async a => ...`
+        let is_simple = self.lookahead(|p| {
+            if is_async {
+                p.bump();
+            }
+            if !p.is_ident() {
+                return false;
+            }
+            p.bump();
+            p.is(&TokenKind::Arrow) && !p.has_line_break_before()
+        });
+        if is_simple {
+            if is_async {
+                self.bump();
+            }
+            let id = self.parse_ident()?;
+            let param = Pat::Ident(BindingIdent {
+                span: id.span,
+                id,
+                type_ann: None,
+                optional: false,
+            });
+            self.bump();
+            return self
+                .parse_arrow_body(
+                    start,
+                    Vec::from_iter_in([param], self.allocator),
+                    is_async,
+                    None,
+                    None,
+                )
+                .map(Some);
+        }
+
+        if !(is_async || self.is(&TokenKind::LParen) || self.is_lt()) {
+            return Ok(None);
+        }
+        // `<T>(a) => a` starts a JSX element in TSX
+        if self.is_jsx() && !is_async && self.is_lt() && !self.is_tsx_arrow_type_params() {
+            return Ok(None);
+        }
+        let checkpoint = self.checkpoint();
+        let head = match self.is_paren_arrow_head(is_async) {
+            Tristate::False => return Ok(None),
+            Tristate::True if !is_async => Some(self.parse_arrow_head(is_async)?),
+            // `async (a)` may be a call as well
+            _ => self.try_parse(|p| p.parse_arrow_head(is_async)),
+        };
+        let Some((type_params, params, return_type)) = head else {
+            return Ok(None);
+        };
+        self.bump();
+        let params = Vec::from_iter_in(params.into_iter().map(|param| param.pat), self.allocator);
+        if !(self.ctx.no_arrow_return_type && return_type.is_some()) {
+            return self
+                .parse_arrow_body(start, params, is_async, type_params, return_type)
+                .map(Some);
+        }
+        // `a ? (b) : c => d` is `(b)` unless the arrow function is followed by a `:`,
+        // like in `a ? (b) : c => d : e`
+        let arrow = self.try_parse(|p| {
+            let arrow = p.parse_arrow_body(start, params, is_async, type_params, return_type)?;
+            if !p.is(&TokenKind::Colon) {
+                return Err(p.expected("`:`"));
+            }
+            Ok(arrow)
+        });
+        if arrow.is_none() {
+            self.rewind(checkpoint);
+        }
+        Ok(arrow)
+    }
+
+    /// Parses the head of a parenthesized arrow function up to its `=>`, which is
+    /// left as the current token.
+    fn parse_arrow_head(&mut self, is_async: bool) -> PResult<ArrowHead<'a>> {
+        if is_async {
+            self.bump();
+        }
+        let ctx = Context {
+            in_async: is_async,
+            in_generator: false,
+            no_in: false,
+            ..self.ctx
+        };
+        self.with_ctx(ctx, |p| {
+            let type_params = p.parse_type_params_opt()?;
+            let params = p.parse_params()?;
+            let return_type = if p.eat(&TokenKind::Colon) {
+                Some(p.parse_return_type()?)
+            } else {
+                None
+            };
+            if p.has_line_break_before() || !p.is(&TokenKind::Arrow) {
+                return Err(p.expected("`=>`"));
+            }
+            Ok((type_params, params, return_type))
+        })
+    }
+
+    /// Tells from the tokens alone whether the `(` or `<` here starts an arrow
+    /// function where it can, like `isParenthesizedArrowFunctionExpression` in tsc.
+    /// A parenthesized expression is skipped to its `)`, which is followed by `=>` or
+    /// the `:` of a return type if it is the head of an arrow function.
+    fn is_paren_arrow_head(&mut self, is_async: bool) -> Tristate {
+        self.lookahead(|p| {
+            if is_async {
+                p.bump();
+            }
+            if !p.eat(&TokenKind::LParen) {
+                return Tristate::Unknown;
+            }
+            match p.token.kind {
+                TokenKind::RParen => {
+                    p.bump();
+                    return if p.is(&TokenKind::Arrow) || p.is(&TokenKind::Colon) {
+                        Tristate::True
+                    } else {
+                        Tristate::False
+                    };
+                }
+                TokenKind::DotDotDot => return Tristate::True,
+                TokenKind::LBracket | TokenKind::LBrace => {}
+                _ if p.is_ident() || p.is_keyword(Keyword::This) => {
+                    p.bump();
+                    match p.token.kind {
+                        TokenKind::Colon => return Tristate::True,
+                        TokenKind::Question => {
+                            p.bump();
+                            return match p.token.kind {
+                                TokenKind::Colon
+                                | TokenKind::Comma
+                                | TokenKind::AssignOp(AssignOp::Assign)
+                                | TokenKind::RParen => Tristate::True,
+                                _ => Tristate::False,
+                            };
+                        }
+                        TokenKind::Comma
+                        | TokenKind::AssignOp(AssignOp::Assign)
+                        | TokenKind::RParen => {}
+                        _ => return Tristate::False,
+                    }
+                }
+                _ => return Tristate::False,
+            }
+            // skip to the matching `)`, unless a token on the way may be read
+            // differently by the parser
+            let mut depth = 0usize;
+            loop {
+                match p.token.kind {
+                    TokenKind::Eof
+                    | TokenKind::TemplateHead { .. }
+                    | TokenKind::BinaryOp(BinaryOp::Div)
+                    | TokenKind::AssignOp(AssignOp::DivAssign) => return Tristate::Unknown,
+                    TokenKind::BinaryOp(BinaryOp::Lt) if p.is_jsx() => return Tristate::Unknown,
+                    TokenKind::LParen | TokenKind::LBracket | TokenKind::LBrace => depth += 1,
+                    TokenKind::RParen if depth == 0 => break,
+                    TokenKind::RParen | TokenKind::RBracket | TokenKind::RBrace => {
+                        depth = depth.saturating_sub(1)
+                    }
+                    _ => {}
+                }
+                p.bump();
+            }
+            p.bump();
+            if p.is(&TokenKind::Arrow) || p.is(&TokenKind::Colon) {
+                Tristate::Unknown
+            } else {
+                Tristate::False
+            }
+        })
+    }
+
+    fn parse_arrow_body(
+        &mut self,
+        start: usize,
+        params: Vec<'a, Pat<'a>>,
+        is_async: bool,
+        type_params: Option<TsTypeParamDecl<'a>>,
+        return_type: Option<Box<'a, TsType<'a>>>,
+    ) -> PResult<Box<'a, Expr<'a>>> {
+        let ctx = Context {
+            in_function: true,
+            in_async: is_async,
+            in_generator: false,
+            ..self.ctx
+        };
+        let body = if self.is(&TokenKind::LBrace) {
+            let ctx = Context {
+                no_in: false,
+                no_conditional_types: false,
+                no_arrow_return_type: false,
+                ..ctx
+            };
+            ArrowBody::Block(self.with_ctx(ctx, |p| p.parse_block())?)
+        } else {
+            ArrowBody::Expr(self.with_ctx(ctx, |p| p.parse_assignment_expr())?)
+        };
+        Ok(self.alloc(Expr::Arrow(ArrowExpr {
+            params,
+            body,
+            is_async,
+            type_params,
+            return_type,
+            span: self.span_from(start),
+        })))
+    }
+
+    fn parse_conditional_expr(&mut self) -> PResult<Box<'a, Expr<'a>>> {
+        let start = self.start();
+        let test = self.parse_binary_expr(0)?;
+        if !self.eat(&TokenKind::Question) {
+            return Ok(test);
+        }
+        let ctx = Context {
+            no_in: false,
+            no_arrow_return_type: true,
+            ..self.ctx
+        };
+        let cons = self.with_ctx(ctx, |p| p.parse_assignment_expr())?;
+        self.expect(&TokenKind::Colon)?;
+        let alt = self.parse_assignment_expr()?;
+        Ok(self.alloc(Expr::Cond(CondExpr {
+            test,
+            cons,
+            alt,
+            span: self.span_from(start),
+        })))
+    }
+
+    /// Precedence climbing over the binary operators binding tighter than `min_prec`.
+    fn parse_binary_expr(&mut self, min_prec: u8) -> PResult<Box<'a, Expr<'a>>> {
+        let start = self.start();
+        let mut left = if self.is(&TokenKind::Hash) {
+            // `#a in obj`
+            let name = self.parse_private_name()?;
+            if !self.is_keyword(Keyword::In) {
+                return Err(self.expected("in"));
+            }
+            self.alloc(Expr::PrivateName(name))
+        } else {
+            self.parse_unary_expr()?
+        };
+        loop {
+            let Some((prec, kind)) = self.binary_operator() else {
+                break;
+            };
+            if prec <= min_prec {
+                break;
+            }
+            self.bump();
+            left = match kind {
+                BinaryKind::As if self.is_keyword(Keyword::Const) => {
+                    self.bump();
+                    self.alloc(Expr::TsConstAssertion(TsConstAssertion {
+                        expr: left,
+                        span: self.span_from(start),
+                    }))
+                }
+                BinaryKind::As => {
+                    let type_ann = self.parse_type()?;
+                    self.alloc(Expr::TsAs(TsAsExpr {
+                        expr: left,
+                        type_ann,
+                        span: self.span_from(start),
+                    }))
+                }
+                BinaryKind::Satisfies => {
+                    let type_ann = self.parse_type()?;
+                    self.alloc(Expr::TsSatisfies(TsSatisfiesExpr {
+                        expr: left,
+                        type_ann,
+                        span: self.span_from(start),
+                    }))
+                }
+                BinaryKind::Op(op) => {
+                    // `**` is right-associative
+                    let right_prec = if op == BinaryOperator::Exp {
+                        prec - 1
+                    } else {
+                        prec
+                    };
+                    let right = self.parse_binary_expr(right_prec)?;
+                    self.alloc(Expr::Binary(BinaryExpr {
+                        op,
+                        left,
+                        right,
+                        span: self.span_from(start),
+                    }))
+                }
+            };
+        }
+        Ok(left)
+    }
+
+    /// The binary operator at the current token and its precedence.
+    fn binary_operator(&self) -> Option<(u8, BinaryKind)> {
+        let op = match &self.token.kind {
+            TokenKind::BinaryOp(op) => match op {
+                BinaryOp::NullishCoalescing => BinaryOperator::NullishCoalescing,
+                BinaryOp::LogicalOr => BinaryOperator::LogicalOr,
+                BinaryOp::LogicalAnd => BinaryOperator::LogicalAnd,
+                BinaryOp::BitOr => BinaryOperator::BitOr,
+                BinaryOp::BitXor => BinaryOperator::BitXor,
+                BinaryOp::BitAnd => BinaryOperator::BitAnd,
+                BinaryOp::Eq => BinaryOperator::Eq,
+                BinaryOp::EqEq => BinaryOperator::EqEq,
+                BinaryOp::Ne => BinaryOperator::Ne,
+                BinaryOp::NeNe => BinaryOperator::NeNe,
+                BinaryOp::Lt => BinaryOperator::Lt,
+                BinaryOp::Le => BinaryOperator::Le,
+                BinaryOp::Gt => BinaryOperator::Gt,
+                BinaryOp::Ge => BinaryOperator::Ge,
+                BinaryOp::LShift => BinaryOperator::LShift,
+                BinaryOp::RShift => BinaryOperator::RShift,
+                BinaryOp::ZeroFillRightShift => BinaryOperator::ZeroFillRightShift,
+                BinaryOp::Add => BinaryOperator::Add,
+                BinaryOp::Sub => BinaryOperator::Sub,
+                BinaryOp::Mul => BinaryOperator::Mul,
+                BinaryOp::Div => BinaryOperator::Div,
+                BinaryOp::Mod => BinaryOperator::Mod,
+                BinaryOp::Exp => BinaryOperator::Exp,
+            },
+            TokenKind::Word(WordKind::Keyword(Keyword::In)) if !self.ctx.no_in => {
+                BinaryOperator::In
+            }
+            TokenKind::Word(WordKind::Keyword(Keyword::Instanceof)) => BinaryOperator::Instanceof,
+            TokenKind::Word(_) if !self.has_line_break_before() => {
+                let kind = match self.contextual_keyword() {
+                    Some(ContextualKeyword::As) => BinaryKind::As,
+                    Some(ContextualKeyword::Satisfies) => BinaryKind::Satisfies,
+                    _ => return None,
+                };
+                return Some((8, kind));
+            }
+            _ => return None,
+        };
+        let prec = match op {
+            BinaryOperator::NullishCoalescing => 1,
+            BinaryOperator::LogicalOr => 2,
+            BinaryOperator::LogicalAnd => 3,
+            BinaryOperator::BitOr => 4,
+            BinaryOperator::BitXor => 5,
+            BinaryOperator::BitAnd => 6,
+            BinaryOperator::Eq
+            | BinaryOperator::EqEq
+            | BinaryOperator::Ne
+            | BinaryOperator::NeNe => 7,
+            BinaryOperator::Lt
+            | BinaryOperator::Le
+            | BinaryOperator::Gt
+            | BinaryOperator::Ge
+            | BinaryOperator::In
+            | BinaryOperator::Instanceof => 8,
+            BinaryOperator::LShift
+            | BinaryOperator::RShift
+            | BinaryOperator::ZeroFillRightShift => 9,
+            BinaryOperator::Add | BinaryOperator::Sub => 10,
+            BinaryOperator::Mul | BinaryOperator::Div | BinaryOperator::Mod => 11,
+            BinaryOperator::Exp => 12,
+        };
+        Some((prec, BinaryKind::Op(op)))
+    }
+
+    fn parse_unary_expr(&mut self) -> PResult<Box<'a, Expr<'a>>> {
+        let start = self.start();
+        let op = match self.token.kind {
+            TokenKind::BinaryOp(BinaryOp::Sub) => Some(UnaryOperator::Minus),
+            TokenKind::BinaryOp(BinaryOp::Add) => Some(UnaryOperator::Plus),
+            TokenKind::Bang => Some(UnaryOperator::Bang),
+            TokenKind::Tilde => Some(UnaryOperator::Tilde),
+            TokenKind::Word(WordKind::Keyword(Keyword::Typeof)) => Some(UnaryOperator::Typeof),
+            TokenKind::Word(WordKind::Keyword(Keyword::Void)) => Some(UnaryOperator::Void),
+            TokenKind::Word(WordKind::Keyword(Keyword::Delete)) => Some(UnaryOperator::Delete),
+            _ => None,
+        };
+        if let Some(op) = op {
+            self.bump();
+            let arg = self.parse_unary_expr()?;
+            return Ok(self.alloc(Expr::Unary(UnaryExpr {
+                op,
+                arg,
+                span: self.span_from(start),
+            })));
+        }
+
+        let update = match self.token.kind {
+            TokenKind::PlusPlus => Some(UpdateOperator::Increment),
+            TokenKind::MinusMinus => Some(UpdateOperator::Decrement),
+            _ => None,
+        };
+        if let Some(op) = update {
+            self.bump();
+            let arg = self.parse_unary_expr()?;
+            return Ok(self.alloc(Expr::Update(UpdateExpr {
+                op,
+                prefix: true,
+                arg,
+                span: self.span_from(start),
+            })));
+        }
+
+        if self.is_lt() {
+            // `<div>...</div>` in TSX, where there are no type assertions
+            if self.is_jsx() {
+                return self.parse_jsx_expr();
+            }
+            // `<T>expr`
+            self.bump();
+            let type_ann = self.parse_type()?;
+            self.expect_gt()?;
+            let expr = self.parse_unary_expr()?;
+            return Ok(self.alloc(Expr::TsTypeAssertion(TsTypeAssertion {
+                type_ann,
+                expr,
+                span: self.span_from(start),
+            })));
+        }
+
+        if self.is_contextual(ContextualKeyword::Await)
+            && (self.ctx.in_async || !self.ctx.in_function)
+        {
+            let is_await =
+                self.ctx.in_async || self.next_on_same_line(|p| p.is_start_of_expr() && !p.is_lt());
+            if is_await {
+                self.bump();
+                let arg = self.parse_unary_expr()?;
+                return Ok(self.alloc(Expr::Await(AwaitExpr {
+                    arg,
+                    span: self.span_from(start),
+                })));
+            }
+        }
+
+        let expr = self.parse_lhs_expr()?;
+        let update = match self.token.kind {
+            TokenKind::PlusPlus if !self.has_line_break_before() => UpdateOperator::Increment,
+            TokenKind::MinusMinus if !self.has_line_break_before() => UpdateOperator::Decrement,
+            _ => return Ok(expr),
+        };
+        self.bump();
+        Ok(self.alloc(Expr::Update(UpdateExpr {
+            op: update,
+            prefix: false,
+            arg: expr,
+            span: self.span_from(start),
+        })))
+    }
+
+    /// Parses a LeftHandSideExpression: member accesses, calls and `new`.
+    pub(super) fn parse_lhs_expr(&mut self) -> PResult<Box<'a, Expr<'a>>> {
+        let start = self.start();
+        let expr = if self.is_keyword(Keyword::New) {
+            self.parse_new_expr()?
+        } else {
+            self.parse_primary_expr()?
+        };
+        self.parse_member_tail(start, expr, true)
+    }
+
+    fn parse_new_expr(&mut self) -> PResult<Box<'a, Expr<'a>>> {
+        let start = self.start();
+        let new_span = self.bump().span;
+        if self.eat(&TokenKind::Dot) {
+            let prop = self.parse_ident_name()?;
+            return Ok(self.alloc(Expr::MetaProp(MetaProp {
+                meta: Ident {
+                    name: Keyword::New.atom(),
+                    span: new_span,
+                },
+                prop,
+                span: self.span_from(start),
+            })));
+        }
+        let callee_start = self.start();
+        let callee = if self.is_keyword(Keyword::New) {
+            self.parse_new_expr()?
+        } else {
+            self.parse_primary_expr()?
+        };
+        let callee = self.parse_member_tail(callee_start, callee, false)?;
+        let (callee, type_args) = match callee.unbox() {
+            Expr::TsInstantiation(TsInstantiation {
+                expr, type_args, ..
+            }) => (expr, Some(type_args)),
+            callee => (self.alloc(callee), None),
+        };
+        let args = if self.is(&TokenKind::LParen) {
+            Some(self.parse_args()?)
+        } else {
+            None
+        };
+        Ok(self.alloc(Expr::New(NewExpr {
+            callee,
+            type_args,
+            args,
+            span: self.span_from(start),
+        })))
+    }
+
+    /// Parses `.a`, `?.a`, `[a]`, `a!`, tagged templates and, if `allow_call` is set,
+    /// calls following `expr`.
+    fn parse_member_tail(
+        &mut self,
+        start: usize,
+        mut expr: Box<'a, Expr<'a>>,
+        allow_call: bool,
+    ) -> PResult<Box<'a, Expr<'a>>> {
+        loop {
+            expr = match self.token.kind {
+                TokenKind::Dot => {
+                    self.bump();
+                    let prop = self.parse_member_prop()?;
+                    self.alloc(Expr::Member(MemberExpr {
+                        object: expr,
+                        prop,
+                        optional: false,
+                        span: self.span_from(start),
+                    }))
+                }
+                TokenKind::QuestionDot if allow_call => {
+                    self.bump();
+                    if self.is(&TokenKind::LParen) || self.is_lt() {
+                        let type_args = if self.is_lt() {
+                            Some(self.parse_type_args()?)
+                        } else {
+                            None
+                        };
+                        let args = self.parse_args()?;
+                        self.alloc(Expr::Call(CallExpr {
+                            callee: expr,
+                            type_args,
+                            args,
+                            optional: true,
+                            span: self.span_from(start),
+                        }))
+                    } else if self.eat(&TokenKind::LBracket) {
+                        let prop = self.parse_computed_member(start)?;
+                        self.alloc(Expr::Member(MemberExpr {
+                            object: expr,
+                            prop,
+                            optional: true,
+                            span: self.span_from(start),
+                        }))
+                    } else {
+                        let prop = self.parse_member_prop()?;
+                        self.alloc(Expr::Member(MemberExpr {
+                            object: expr,
+                            prop,
+                            optional: true,
+                            span: self.span_from(start),
+                        }))
+                    }
+                }
+                TokenKind::LBracket => {
+                    self.bump();
+                    let prop = self.parse_computed_member(start)?;
+                    self.alloc(Expr::Member(MemberExpr {
+                        object: expr,
+                        prop,
+                        optional: false,
+                        span: self.span_from(start),
+                    }))
+                }
+                TokenKind::Bang if !self.has_line_break_before() => {
+                    self.bump();
+                    self.alloc(Expr::TsNonNull(TsNonNullExpr {
+                        expr,
+                        span: self.span_from(start),
+                    }))
+                }
+                TokenKind::NoSubstitutionTemplate { .. } | TokenKind::TemplateHead { .. } => {
+                    let tpl = self.parse_tpl()?;
+                    self.alloc(Expr::TaggedTpl(TaggedTpl {
+                        tag: expr,
+                        type_args: None,
+                        tpl,
+                        span: self.span_from(start),
+                    }))
+                }
+                TokenKind::LParen if allow_call => {
+                    let args = self.parse_args()?;
+                    self.alloc(Expr::Call(CallExpr {
+                        callee: expr,
+                        type_args: None,
+                        args,
+                        optional: false,
+                        span: self.span_from(start),
+                    }))
+                }
+                TokenKind::BinaryOp(BinaryOp::Lt) => {
+                    let Some(type_args) = self.try_parse(|p| {
+                        let type_args = p.parse_type_args()?;
+                        if p.can_follow_type_args() {
+                            Ok(type_args)
+                        } else {
+                            Err(p.unexpected())
+                        }
+                    }) else {
+                        break;
+                    };
+                    match self.token.kind {
+                        TokenKind::LParen if allow_call => {
+                            let args = self.parse_args()?;
+                            self.alloc(Expr::Call(CallExpr {
+                                callee: expr,
+                                type_args: Some(type_args),
+                                args,
+                                optional: false,
+                                span: self.span_from(start),
+                            }))
+                        }
+                        TokenKind::NoSubstitutionTemplate { .. }
+                        | TokenKind::TemplateHead { .. } => {
+                            let tpl = self.parse_tpl()?;
+                            self.alloc(Expr::TaggedTpl(TaggedTpl {
+                                tag: expr,
+                                type_args: Some(type_args),
+                                tpl,
+                                span: self.span_from(start),
+                            }))
+                        }
+                        _ => self.alloc(Expr::TsInstantiation(TsInstantiation {
+                            expr,
+                            type_args,
+                            span: self.span_from(start),
+                        })),
+                    }
+                }
+                _ => break,
+            };
+        }
+        Ok(expr)
+    }
+
+    fn parse_member_prop(&mut self) -> PResult<MemberProp<'a>> {
+        if self.is(&TokenKind::Hash) {
+            Ok(MemberProp::Private(self.parse_private_name()?))
+        } else {
+            Ok(MemberProp::Ident(self.parse_ident_name()?))
+        }
+    }
+
+    /// The rest of `[expr]` after the `[`.
+    fn parse_computed_member(&mut self, start: usize) -> PResult<MemberProp<'a>> {
+        let expr = self.allow_in(|p| p.parse_expr())?;
+        self.expect(&TokenKind::RBracket)?;
+        Ok(MemberProp::Computed(ComputedPropName {
+            expr,
+            span: self.span_from(start),
+        }))
+    }
+
+    /// Whether `<...>` just parsed are type arguments of the expression before them,
+    /// as in `f<T>(a)` and `f<T>;`, rather than comparisons as in `a < b > c`.
+    fn can_follow_type_args(&mut self) -> bool {
+        match self.token.kind {
+            TokenKind::LParen
+            | TokenKind::NoSubstitutionTemplate { .. }
+            | TokenKind::TemplateHead { .. } => true,
+            TokenKind::BinaryOp(BinaryOp::Lt | BinaryOp::Gt | BinaryOp::Add | BinaryOp::Sub) => {
+                false
+            }
+            _ => {
+                self.has_line_break_before()
+                    || self.binary_operator().is_some()
+                    || !self.is_start_of_expr()
+            }
+        }
+    }
+
+    /// Whether the current token can start an expression.
+    pub(super) fn is_start_of_expr(&self) -> bool {
+        match &self.token.kind {
+            TokenKind::Word(WordKind::Keyword(keyword)) => !matches!(
+                keyword,
+                Keyword::In | Keyword::Instanceof | Keyword::Extends | Keyword::Implements
+            ),
+            TokenKind::Word(_)
+            | TokenKind::Number { .. }
+            | TokenKind::BigInt { .. }
+            | TokenKind::String { .. }
+            | TokenKind::RegExp { .. }
+            | TokenKind::NoSubstitutionTemplate { .. }
+            | TokenKind::TemplateHead { .. }
+            | TokenKind::LParen
+            | TokenKind::LBracket
+            | TokenKind::LBrace
+            | TokenKind::Bang
+            | TokenKind::Tilde
+            | TokenKind::PlusPlus
+            | TokenKind::MinusMinus
+            | TokenKind::Hash
+            | TokenKind::At
+            | TokenKind::BinaryOp(BinaryOp::Add | BinaryOp::Sub | BinaryOp::Lt | BinaryOp::Div)
+            | TokenKind::AssignOp(AssignOp::DivAssign) => true,
+            _ => false,
+        }
+    }
+
+    /// Parses `(a, ...b)` call arguments.
+    pub(super) fn parse_args(&mut self) -> PResult<Vec<'a, ExprOrSpread<'a>>> {
+        self.expect(&TokenKind::LParen)?;
+        let mut args = self.vec();
+        while !self.eat(&TokenKind::RParen) {
+            args.push(self.parse_expr_or_spread()?);
+            if !self.eat(&TokenKind::Comma) {
+                self.expect(&TokenKind::RParen)?;
+                break;
+            }
+        }
+        Ok(args)
+    }
+
+    fn parse_expr_or_spread(&mut self) -> PResult<ExprOrSpread<'a>> {
+        let spread = if self.is(&TokenKind::DotDotDot) {
+            Some(self.bump().span)
+        } else {
+            None
+        };
+        let expr = self.allow_in(|p| p.parse_assignment_expr())?;
+        Ok(ExprOrSpread { spread, expr })
+    }
+
+    pub(super) fn parse_primary_expr(&mut self) -> PResult<Box<'a, Expr<'a>>> {
+        let start = self.start();
+        let expr = match &self.token.kind {
+            TokenKind::Word(WordKind::Keyword(keyword)) => match keyword {
+                Keyword::This => Expr::This(self.bump().span),
+                Keyword::Super => Expr::Super(self.bump().span),
+                Keyword::Import => {
+                    let span = self.bump().span;
+                    if self.eat(&TokenKind::Dot) {
+                        let prop = self.parse_ident_name()?;
+                        Expr::MetaProp(MetaProp {
+                            meta: Ident {
+                                name: Keyword::Import.atom(),
+                                span,
+                            },
+                            prop,
+                            span: self.span_from(start),
+                        })
+                    } else {
+                        Expr::Import(span)
+                    }
+                }
+                Keyword::Function => self.parse_fn_expr(start, false)?,
+                Keyword::Class => {
+                    let (id, class) = self.parse_class(start, self.vec(), false)?;
+                    Expr::Class(ClassExpr { id, class })
+                }
+                _ if self.is_ident() => Expr::Ident(self.parse_ident()?),
+                _ => return Err(self.unexpected()),
+            },
+            TokenKind::Word(WordKind::True | WordKind::False) => {
+                let value = self.is(&TokenKind::Word(WordKind::True));
+                let span = self.bump().span;
+                Expr::Lit(Lit::Bool(Bool { value, span }))
+            }
+            TokenKind::Word(WordKind::Null) => Expr::Lit(Lit::Null(self.bump().span)),
+            TokenKind::Word(WordKind::Identifier(name)) => {
+                if name == "async" && self.next_on_same_line(|p| p.is_keyword(Keyword::Function)) {
+                    self.bump();
+                    self.parse_fn_expr(start, true)?
+                } else {
+                    Expr::Ident(self.parse_ident()?)
+                }
+            }
+            TokenKind::Number { value, raw } => {
+                let (value, raw) = (*value, *raw);
+                let span = self.bump().span;
+                Expr::Lit(Lit::Num(Num { value, raw, span }))
+            }
+            TokenKind::BigInt { value, raw } => {
+                let (value, raw) = (*value, *raw);
+                let span = self.bump().span;
+                Expr::Lit(Lit::BigInt(BigInt { value, raw, span }))
+            }
+            TokenKind::String { .. } => Expr::Lit(Lit::Str(self.parse_str()?)),
+            TokenKind::BinaryOp(BinaryOp::Div) | TokenKind::AssignOp(AssignOp::DivAssign) => {
+                self.token = self.lexer.rescan_slash_as_regex(&self.token);
+                return self.parse_primary_expr();
+            }
+            TokenKind::RegExp { pattern, flags } => {
+                let (pattern, flags) = (*pattern, *flags);
+                let span = self.bump().span;
+                Expr::Lit(Lit::Regex(Regex {
+                    pattern,
+                    flags,
+                    span,
+                }))
+            }
+            TokenKind::NoSubstitutionTemplate { .. } | TokenKind::TemplateHead { .. } => {
+                Expr::Tpl(self.parse_tpl()?)
+            }
+            TokenKind::LParen => {
+                self.bump();
+                let expr = self.allow_in(|p| p.parse_expr())?;
+                self.expect(&TokenKind::RParen)?;
+                Expr::Paren(ParenExpr {
+                    expr,
+                    span: self.span_from(start),
+                })
+            }
+            TokenKind::LBracket => self.parse_array_lit()?,
+            TokenKind::LBrace => Expr::Object(self.parse_object_lit()?),
+            TokenKind::At => {
+                let decorators = self.parse_decorators()?;
+                let (id, class) = self.parse_class(start, decorators, false)?;
+                Expr::Class(ClassExpr { id, class })
+            }
+            _ => {
+                // leave the token to whatever comes after the missing expression,
+                // which is placed right after the token before it
+                self.error(self.expected("Expression"));
+                Expr::Invalid(Span::new(self.prev_end, self.prev_end))
+            }
+        };
+        Ok(self.alloc(expr))
+    }
+
+    fn parse_fn_expr(&mut self, start: usize, is_async: bool) -> PResult<Expr<'a>> {
+        self.expect_keyword(Keyword::Function)?;
+        let is_generator = self.eat(&TokenKind::BinaryOp(BinaryOp::Mul));
+        let id = if self.is_ident() || self.is_keyword(Keyword::Yield) {
+            Some(self.parse_ident_name()?)
+        } else {
+            None
+        };
+        if !self.is(&TokenKind::LParen) && !self.is_lt() {
+            return Err(self.expected("`(`"));
+        }
+        let function = self.parse_function_rest(start, is_async, is_generator)?;
+        if function.body.is_none() {
+            return Err(self.expected("`{`"));
+        }
+        Ok(Expr::Fn(FnExpr { id, function }))
+    }
+
+    fn parse_array_lit(&mut self) -> PResult<Expr<'a>> {
+        let start = self.start();
+        self.expect(&TokenKind::LBracket)?;
+        let mut elems = self.vec();
+        while !self.eat(&TokenKind::RBracket) {
+            if self.eat(&TokenKind::Comma) {
+                elems.push(None);
+                continue;
+            }
+            elems.push(Some(self.parse_expr_or_spread()?));
+            if !self.eat(&TokenKind::Comma) {
+                self.expect(&TokenKind::RBracket)?;
+                break;
+            }
+        }
+        Ok(Expr::Array(ArrayLit {
+            elems,
+            span: self.span_from(start),
+        }))
+    }
+
+    pub(super) fn parse_object_lit(&mut self) -> PResult<ObjectLit<'a>> {
+        let start = self.start();
+        self.expect(&TokenKind::LBrace)?;
+        let mut props = self.vec();
+        while !self.eat(&TokenKind::RBrace) {
+            props.push(self.allow_in(|p| p.parse_object_prop())?);
+            if !self.eat(&TokenKind::Comma) {
+                self.expect(&TokenKind::RBrace)?;
+                break;
+            }
+        }
+        Ok(ObjectLit {
+            props,
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_object_prop(&mut self) -> PResult<Prop<'a>> {
+        let start = self.start();
+        if self.eat(&TokenKind::DotDotDot) {
+            let expr = self.parse_assignment_expr()?;
+            return Ok(Prop::Spread(SpreadProp {
+                expr,
+                span: self.span_from(start),
+            }));
+        }
+
+        let is_async = self.is_contextual(ContextualKeyword::Async)
+            && self.next_on_same_line(|p| {
+                p.is_prop_name_start() || p.is(&TokenKind::BinaryOp(BinaryOp::Mul))
+            });
+        if is_async {
+            self.bump();
+        }
+        let is_generator = self.eat(&TokenKind::BinaryOp(BinaryOp::Mul));
+        let mut kind = MethodKind::Method;
+        if !is_async
+            && !is_generator
+            && (self.is_contextual(ContextualKeyword::Get)
+                || self.is_contextual(ContextualKeyword::Set))
+            && self.next_is(|p| p.is_prop_name_start())
+        {
+            kind = if self.eat_contextual(ContextualKeyword::Get) {
+                MethodKind::Getter
+            } else {
+                self.bump();
+                MethodKind::Setter
+            };
+        }
+
+        let key = self.parse_prop_name()?;
+        if is_async
+            || is_generator
+            || kind != MethodKind::Method
+            || self.is(&TokenKind::LParen)
+            || self.is_lt()
+        {
+            let function = self.parse_function_rest(start, is_async, is_generator)?;
+            if function.body.is_none() {
+                return Err(self.expected("`{`"));
+            }
+            return Ok(Prop::Method(MethodProp {
+                kind,
+                key,
+                function,
+                span: self.span_from(start),
+            }));
+        }
+
+        if self.eat(&TokenKind::Colon) {
+            let value = self.parse_assignment_expr()?;
+            return Ok(Prop::KeyValue(KeyValueProp {
+                key,
+                value,
+                span: self.span_from(start),
+            }));
+        }
+
+        let key = match key {
+            PropName::Ident(id) => id,
+            key => return Err(self.expected_at("`:`", key.span())),
+        };
+        let init = if self.eat(&TokenKind::AssignOp(AssignOp::Assign)) {
+            Some(self.parse_assignment_expr()?)
+        } else {
+            None
+        };
+        Ok(Prop::Shorthand(ShorthandProp {
+            key,
+            init,
+            span: self.span_from(start),
+        }))
+    }
+
+    /// Parses a template literal starting at the current template token. The lexer
+    /// continues the template by itself at the `}` closing each substitution.
+    pub(super) fn parse_tpl(&mut self) -> PResult<Tpl<'a>> {
+        let start = self.start();
+        let mut quasis = self.vec();
+        let mut exprs = self.vec();
+        loop {
+            let (cooked, raw, tail) = match &self.token.kind {
+                TokenKind::NoSubstitutionTemplate { cooked, raw }
+                | TokenKind::TemplateTail { cooked, raw } => (*cooked, *raw, true),
+                TokenKind::TemplateHead { cooked, raw }
+                | TokenKind::TemplateMiddle { cooked, raw }
+                    if exprs.len() == quasis.len() =>
+                {
+                    (*cooked, *raw, false)
+                }
+                _ => return Err(self.expected("`}`")),
+            };
+            let span = self.bump().span;
+            quasis.push(TplElement { cooked, raw, span });
+            if tail {
+                break;
+            }
+            exprs.push(self.allow_in(|p| p.parse_expr())?.unbox());
+        }
+        Ok(Tpl {
+            quasis,
+            exprs,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Reinterprets an expression parsed before `=`, `=>`, or `of`/`in` in a `for`
+    /// loop as an assignment pattern.
+    pub(super) fn expr_to_pat(&self, expr: Box<'a, Expr<'a>>) -> PResult<Pat<'a>> {
+        Ok(match expr.unbox() {
+            Expr::Ident(id) => Pat::Ident(BindingIdent {
+                span: id.span,
+                id,
+                type_ann: None,
+                optional: false,
+            }),
+            Expr::Array(ArrayLit { elems, span }) => {
+                let mut pats = Vec::with_capacity_in(elems.len(), self.allocator);
+                for elem in elems {
+                    pats.push(match elem {
+                        None => None,
+                        Some(ExprOrSpread {
+                            spread: Some(spread),
+                            expr,
+                        }) => {
+                            let arg = self.expr_to_pat(expr)?;
+                            Some(Pat::Rest(RestPat {
+                                span: Span::new(spread.start, arg.span().end),
+                                arg: self.alloc(arg),
+                                type_ann: None,
+                            }))
+                        }
+                        Some(ExprOrSpread { spread: None, expr }) => Some(self.expr_to_pat(expr)?),
+                    });
+                }
+                Pat::Array(ArrayPat {
+                    elems: pats,
+                    type_ann: None,
+                    optional: false,
+                    span,
+                })
+            }
+            Expr::Object(ObjectLit { props, span }) => {
+                let mut pats = Vec::with_capacity_in(props.len(), self.allocator);
+                for prop in props {
+                    pats.push(match prop {
+                        Prop::KeyValue(KeyValueProp { key, value, span }) => {
+                            ObjectPatProp::KeyValue(KeyValuePatProp {
+                                key,
+                                value: self.alloc(self.expr_to_pat(value)?),
+                                span,
+                            })
+                        }
+                        Prop::Shorthand(ShorthandProp { key, init, span }) => {
+                            ObjectPatProp::Assign(AssignPatProp {
+                                key,
+                                value: init,
+                                span,
+                            })
+                        }
+                        Prop::Spread(SpreadProp { expr, span }) => ObjectPatProp::Rest(RestPat {
+                            arg: self.alloc(self.expr_to_pat(expr)?),
+                            type_ann: None,
+                            span,
+                        }),
+                        Prop::Method(method) => {
+                            return Err(InvalidAssignmentTarget(method.span).into())
+                        }
+                    });
+                }
+                Pat::Object(ObjectPat {
+                    props: pats,
+                    type_ann: None,
+                    optional: false,
+                    span,
+                })
+            }
+            Expr::Assign(AssignExpr {
+                op: AssignOperator::Assign,
+                left,
+                right,
+                span,
+            }) => Pat::Assign(AssignPat { left, right, span }),
+            expr if is_simple_target(&expr) => Pat::Expr(self.alloc(expr)),
+            expr => return Err(InvalidAssignmentTarget(expr.span()).into()),
+        })
+    }
+}
+
+/// Whether `expr` can be assigned to without destructuring.
+fn is_simple_target(expr: &Expr) -> bool {
+    match expr {
+        // a missing expression has been reported already
+        Expr::Ident(_)
+        | Expr::Invalid(_)
+        | Expr::Member(MemberExpr {
+            optional: false, ..
+        }) => true,
+        Expr::Paren(ParenExpr { expr, .. })
+        | Expr::TsNonNull(TsNonNullExpr { expr, .. })
+        | Expr::TsAs(TsAsExpr { expr, .. })
+        | Expr::TsSatisfies(TsSatisfiesExpr { expr, .. })
+        | Expr::TsTypeAssertion(TsTypeAssertion { expr, .. }) => is_simple_target(expr),
+        _ => false,
+    }
+}
+
+fn assign_operator(op: AssignOp) -> AssignOperator {
+    match op {
+        AssignOp::Assign => AssignOperator::Assign,
+        AssignOp::AddAssign => AssignOperator::AddAssign,
+        AssignOp::SubAssign => AssignOperator::SubAssign,
+        AssignOp::MulAssign => AssignOperator::MulAssign,
+        AssignOp::DivAssign => AssignOperator::DivAssign,
+        AssignOp::ModAssign => AssignOperator::ModAssign,
+        AssignOp::BitOrAssign => AssignOperator::BitOrAssign,
+        AssignOp::BitXorAssign => AssignOperator::BitXorAssign,
+        AssignOp::BitAndAssign => AssignOperator::BitAndAssign,
+        AssignOp::ZeroFillRightShiftAssign => AssignOperator::ZeroFillRightShiftAssign,
+        AssignOp::RightShiftAssign => AssignOperator::RightShiftAssign,
+        AssignOp::LeftShiftAssign => AssignOperator::LeftShiftAssign,
+        AssignOp::ExpAssign => AssignOperator::ExpAssign,
+        AssignOp::LogicalAndAssign => AssignOperator::LogicalAndAssign,
+        AssignOp::LogicalOrAssign => AssignOperator::LogicalOrAssign,
+        AssignOp::NullishAssign => AssignOperator::NullishAssign,
+    }
+}
@@ -0,0 +1,318 @@
+use crate::{
+    allocator::{Box, Vec},
+    ast::*,
+    diagnostics::{
+        EmptyJsxAttribute, MismatchedJsxClosingTag, MismatchedJsxFragmentClosingTag,
+        MultipleJsxRoots, UnclosedJsxElement, UnclosedJsxFragment,
+    },
+    AssignOp, BinaryOp, Keyword, Span, Token, TokenKind,
+};
+
+use super::{PResult, Parser};
+
+impl<'a> Parser<'a> {
+    /// Parses a JSX element or fragment in an expression. A second one right after it
+    /// is reported, since the two need a parent element.
+    pub(super) fn parse_jsx_expr(&mut self) -> PResult<Box<'a, Expr<'a>>> {
+        let start = self.start();
+        let first = self.parse_jsx_element(false)?;
+        if !self.is_lt() {
+            return Ok(self.alloc(first));
+        }
+        let second_start = self.start();
+        let Some(second) = self.try_parse(|p| p.parse_jsx_element(false)) else {
+            return Ok(self.alloc(first));
+        };
+        self.error(MultipleJsxRoots(self.span_from(second_start)).into());
+        Ok(self.alloc(Expr::Seq(SeqExpr {
+            exprs: Vec::from_iter_in([first, second], self.allocator),
+            span: self.span_from(start),
+        })))
+    }
+
+    /// Parses a JSX element or fragment at its `<`. The token after it is read as the
+    /// next child of the parent element if there is one, `in_children`.
+    fn parse_jsx_element(&mut self, in_children: bool) -> PResult<Expr<'a>> {
+        let start = self.start();
+        self.bump();
+        if self.is_gt() {
+            self.expect_jsx_tag_end(true)?;
+            let children = self.parse_jsx_children()?;
+            if self.is(&TokenKind::Eof) {
+                return Err(UnclosedJsxFragment(Span::new(start, start + 2)).into());
+            }
+            self.bump();
+            self.bump();
+            if !self.is_gt() {
+                let name = self.parse_jsx_element_name()?;
+                self.error(MismatchedJsxFragmentClosingTag(name.span()).into());
+            }
+            self.expect_jsx_tag_end(in_children)?;
+            return Ok(Expr::JsxFragment(JsxFragment {
+                children,
+                span: self.span_from(start),
+            }));
+        }
+
+        let name = self.parse_jsx_element_name()?;
+        let type_args = if self.is_lt() {
+            Some(self.parse_type_args()?)
+        } else {
+            None
+        };
+        let mut attrs = self.vec();
+        while !self.is_gt() && !self.is(&TokenKind::BinaryOp(BinaryOp::Div)) {
+            if self.is(&TokenKind::Eof) {
+                return Err(self.expected("`>`"));
+            }
+            attrs.push(self.parse_jsx_attr()?);
+        }
+        let self_closing = self.eat(&TokenKind::BinaryOp(BinaryOp::Div));
+        self.expect_jsx_tag_end(in_children || !self_closing)?;
+        let opening = JsxOpeningElement {
+            name,
+            type_args,
+            attrs,
+            self_closing,
+            span: self.span_from(start),
+        };
+        if self_closing {
+            return Ok(Expr::JsxElement(JsxElement {
+                opening,
+                children: self.vec(),
+                closing: None,
+                span: self.span_from(start),
+            }));
+        }
+
+        let children = self.parse_jsx_children()?;
+        let name = jsx_name_text(&opening.name);
+        if self.is(&TokenKind::Eof) {
+            return Err(UnclosedJsxElement(name, opening.name.span()).into());
+        }
+        let closing_start = self.start();
+        self.bump();
+        self.bump();
+        let closing = if self.is_gt() {
+            self.error(
+                MismatchedJsxClosingTag(name, Span::new(closing_start, self.start())).into(),
+            );
+            None
+        } else {
+            let closing_name = self.parse_jsx_element_name()?;
+            if jsx_name_text(&closing_name) != name {
+                self.error(MismatchedJsxClosingTag(name, closing_name.span()).into());
+            }
+            Some(closing_name)
+        };
+        self.expect_jsx_tag_end(in_children)?;
+        Ok(Expr::JsxElement(JsxElement {
+            opening,
+            children,
+            closing: closing.map(|name| JsxClosingElement {
+                name,
+                span: self.span_from(closing_start),
+            }),
+            span: self.span_from(start),
+        }))
+    }
+
+    /// Consumes the `>` ending a tag, which the lexer may have read as part of `>>` or
+    /// `>=`. The token after it is read as a child of the element if `children`.
+    fn expect_jsx_tag_end(&mut self, children: bool) -> PResult<()> {
+        if !self.is_gt() {
+            return Err(self.expected("`>`"));
+        }
+        if self.token.span.size() > 1 {
+            let start = self.token.span.start;
+            self.token = Token {
+                kind: TokenKind::BinaryOp(BinaryOp::Gt),
+                span: Span::new(start, start + 1),
+                had_line_break_before: self.token.had_line_break_before,
+            };
+            self.lexer.reset_to(start + 1);
+        }
+        if children {
+            self.bump_jsx_child();
+        } else {
+            self.bump();
+        }
+        Ok(())
+    }
+
+    /// Parses the children of an element up to the `</` of its closing tag, or the end
+    /// of the file.
+    fn parse_jsx_children(&mut self) -> PResult<Vec<'a, JsxChild<'a>>> {
+        let mut children = self.vec();
+        loop {
+            let child = match &self.token.kind {
+                TokenKind::JsxText { raw } => {
+                    let value = *raw;
+                    let span = self.bump_jsx_child().span;
+                    JsxChild::Text(JsxText { value, span })
+                }
+                TokenKind::LBrace => self.parse_jsx_child_container()?,
+                TokenKind::BinaryOp(BinaryOp::Lt) => {
+                    if self.next_is(|p| p.is(&TokenKind::BinaryOp(BinaryOp::Div))) {
+                        return Ok(children);
+                    }
+                    match self.parse_jsx_element(true)? {
+                        Expr::JsxFragment(fragment) => JsxChild::Fragment(fragment),
+                        Expr::JsxElement(element) => JsxChild::Element(element),
+                        _ => unreachable!("JSX elements are elements or fragments"),
+                    }
+                }
+                TokenKind::Eof => return Ok(children),
+                _ => return Err(self.unexpected()),
+            };
+            children.push(child);
+        }
+    }
+
+    /// Parses `{expr}`, `{...expr}` or `{}` among the children of an element.
+    fn parse_jsx_child_container(&mut self) -> PResult<JsxChild<'a>> {
+        let start = self.start();
+        self.bump();
+        let spread = self.eat(&TokenKind::DotDotDot);
+        let expr = if spread || !self.is(&TokenKind::RBrace) {
+            Some(self.allow_in(|p| p.parse_expr())?)
+        } else {
+            None
+        };
+        if !self.is(&TokenKind::RBrace) {
+            return Err(self.expected("`}`"));
+        }
+        self.bump_jsx_child();
+        let span = self.span_from(start);
+        Ok(match expr {
+            Some(expr) if spread => JsxChild::Spread(JsxSpreadChild { expr, span }),
+            expr => JsxChild::ExprContainer(JsxExprContainer { expr, span }),
+        })
+    }
+
+    fn parse_jsx_attr(&mut self) -> PResult<JsxAttrOrSpread<'a>> {
+        let start = self.start();
+        if self.eat(&TokenKind::LBrace) {
+            self.expect(&TokenKind::DotDotDot)?;
+            let expr = self.allow_in(|p| p.parse_expr())?;
+            self.expect(&TokenKind::RBrace)?;
+            return Ok(JsxAttrOrSpread::Spread(JsxSpreadAttr {
+                expr,
+                span: self.span_from(start),
+            }));
+        }
+        let name = match self.parse_jsx_name()? {
+            JsxElementName::Namespaced(name) => JsxAttrName::Namespaced(name),
+            JsxElementName::Ident(name) => JsxAttrName::Ident(name),
+            JsxElementName::Member(_) => unreachable!("names have no `.`"),
+        };
+        let value = if self.eat(&TokenKind::AssignOp(AssignOp::Assign)) {
+            Some(self.parse_jsx_attr_value()?)
+        } else {
+            None
+        };
+        Ok(JsxAttrOrSpread::Attr(JsxAttr {
+            name,
+            value,
+            span: self.span_from(start),
+        }))
+    }
+
+    fn parse_jsx_attr_value(&mut self) -> PResult<JsxAttrValue<'a>> {
+        let start = self.start();
+        match self.token.kind {
+            TokenKind::String { .. } => {
+                self.token = self.lexer.rescan_jsx_attribute_string(&self.token);
+                Ok(JsxAttrValue::Str(self.parse_str()?))
+            }
+            TokenKind::LBrace => {
+                self.bump();
+                let expr = if self.is(&TokenKind::RBrace) {
+                    self.error(EmptyJsxAttribute(Span::new(start, self.token.span.end)).into());
+                    None
+                } else {
+                    Some(self.allow_in(|p| p.parse_expr())?)
+                };
+                self.expect(&TokenKind::RBrace)?;
+                Ok(JsxAttrValue::ExprContainer(JsxExprContainer {
+                    expr,
+                    span: self.span_from(start),
+                }))
+            }
+            TokenKind::BinaryOp(BinaryOp::Lt) => match self.parse_jsx_element(false)? {
+                Expr::JsxFragment(fragment) => Ok(JsxAttrValue::Fragment(fragment)),
+                Expr::JsxElement(element) => Ok(JsxAttrValue::Element(element)),
+                _ => unreachable!("JSX elements are elements or fragments"),
+            },
+            _ => Err(self.expected("`{`")),
+        }
+    }
+
+    /// Parses the name of a tag: a name as in [`Self::parse_jsx_name`], or a member
+    /// expression like `a.b.c`.
+    fn parse_jsx_element_name(&mut self) -> PResult<JsxElementName<'a>> {
+        let start = self.start();
+        let mut name = self.parse_jsx_name()?;
+        if matches!(name, JsxElementName::Namespaced(_)) {
+            return Ok(name);
+        }
+        while self.eat(&TokenKind::Dot) {
+            let prop = self.parse_ident_name()?;
+            name = JsxElementName::Member(JsxMemberExpr {
+                object: self.alloc(name),
+                prop,
+                span: self.span_from(start),
+            });
+        }
+        Ok(name)
+    }
+
+    /// Parses a name which may have `-` in it and a namespace, as in `svg:rect`.
+    fn parse_jsx_name(&mut self) -> PResult<JsxElementName<'a>> {
+        let start = self.start();
+        let ns = self.parse_jsx_ident()?;
+        if !self.eat(&TokenKind::Colon) {
+            return Ok(JsxElementName::Ident(ns));
+        }
+        let name = self.parse_jsx_ident()?;
+        Ok(JsxElementName::Namespaced(JsxNamespacedName {
+            ns,
+            name,
+            span: self.span_from(start),
+        }))
+    }
+
+    fn parse_jsx_ident(&mut self) -> PResult<Ident<'a>> {
+        self.token = self.lexer.rescan_jsx_identifier(&self.token);
+        self.parse_ident_name()
+    }
+
+    /// Whether a `<` starts the type parameters of an arrow function in TSX, where
+    /// they need a `,`, a constraint or a default so as not to look like a tag:
+    /// `<T,>`, `<T extends U>` or `<T = U>`.
+    pub(super) fn is_tsx_arrow_type_params(&mut self) -> bool {
+        self.lookahead(|p| {
+            p.bump();
+            p.eat_keyword(Keyword::Const);
+            p.bump();
+            if p.eat_keyword(Keyword::Extends) {
+                !(p.is_gt()
+                    || p.is(&TokenKind::AssignOp(AssignOp::Assign))
+                    || p.is(&TokenKind::BinaryOp(BinaryOp::Div)))
+            } else {
+                p.is(&TokenKind::Comma) || p.is(&TokenKind::AssignOp(AssignOp::Assign))
+            }
+        })
+    }
+}
+
+/// The name of a tag as written, to match a closing tag with its opening one.
+fn jsx_name_text(name: &JsxElementName) -> String {
+    match name {
+        JsxElementName::Ident(id) => id.name.to_string(),
+        JsxElementName::Member(member) => {
+            format!("{}.{}", jsx_name_text(&member.object), member.prop.name)
+        }
+        JsxElementName::Namespaced(name) => format!("{}:{}", name.ns.name, name.name.name),
+    }
+}
@@ -0,0 +1,1129 @@
+use crate::{
+    allocator::{Box, Vec},
+    ast::*,
+    diagnostics::UnexpectedToken,
+    AssignOp, BinaryOp, ContextualKeyword, Keyword, Span, TokenKind,
+};
+
+use super::{Context, ListKind, PResult, Parser};
+
+impl<'a> Parser<'a> {
+    pub(super) fn parse_stmt(&mut self) -> PResult<Stmt<'a>> {
+        let start = self.start();
+        match self.token.kind {
+            TokenKind::LBrace => return Ok(Stmt::Block(self.parse_block()?)),
+            TokenKind::Semicolon => {
+                self.bump();
+                return Ok(Stmt::Empty(self.span_from(start)));
+            }
+            TokenKind::At => {
+                let decorators = self.parse_decorators()?;
+                let stmt = self.parse_stmt()?;
+                return attach_decorators(stmt, decorators, start);
+            }
+            _ => {}
+        }
+
+        if let Some(keyword) = self.keyword() {
+            match keyword {
+                Keyword::Var | Keyword::Const | Keyword::Let if self.is_var_start() => {
+                    let decl = self.parse_var_decl(start)?;
+                    self.expect_semi();
+                    return Ok(Stmt::Var(VarDecl {
+                        span: self.span_from(start),
+                        ..decl
+                    }));
+                }
+                Keyword::Const => {
+                    // `const enum`
+                    self.bump();
+                    return self.parse_enum(start, true);
+                }
+                Keyword::Enum => return self.parse_enum(start, false),
+                Keyword::If => return self.parse_if(start),
+                Keyword::For => return self.parse_for(start),
+                Keyword::While => {
+                    self.bump();
+                    let test = self.parse_paren_test()?;
+                    let body = Box::new_in(self.parse_stmt()?, self.allocator);
+                    return Ok(Stmt::While(WhileStmt {
+                        test,
+                        body,
+                        span: self.span_from(start),
+                    }));
+                }
+                Keyword::Do => {
+                    self.bump();
+                    let body = Box::new_in(self.parse_stmt()?, self.allocator);
+                    self.expect_keyword(Keyword::While)?;
+                    let test = self.parse_paren_test()?;
+                    // a `;` is always inserted after `do-while`, even on the same line
+                    self.eat(&TokenKind::Semicolon);
+                    return Ok(Stmt::DoWhile(DoWhileStmt {
+                        body,
+                        test,
+                        span: self.span_from(start),
+                    }));
+                }
+                Keyword::Return => {
+                    self.bump();
+                    let arg = if self.can_insert_semi() {
+                        None
+                    } else {
+                        Some(self.parse_expr()?)
+                    };
+                    self.expect_semi();
+                    return Ok(Stmt::Return(ReturnStmt {
+                        arg,
+                        span: self.span_from(start),
+                    }));
+                }
+                Keyword::Break | Keyword::Continue => {
+                    self.bump();
+                    let label = if self.is_ident() && !self.has_line_break_before() {
+                        Some(self.parse_ident()?)
+                    } else {
+                        None
+                    };
+                    self.expect_semi();
+                    let span = self.span_from(start);
+                    return Ok(if keyword == Keyword::Break {
+                        Stmt::Break(BreakStmt { label, span })
+                    } else {
+                        Stmt::Continue(ContinueStmt { label, span })
+                    });
+                }
+                Keyword::Throw => {
+                    self.bump();
+                    if self.has_line_break_before() {
+                        return Err(self.expected("Expression"));
+                    }
+                    let arg = self.parse_expr()?;
+                    self.expect_semi();
+                    return Ok(Stmt::Throw(ThrowStmt {
+                        arg,
+                        span: self.span_from(start),
+                    }));
+                }
+                Keyword::Try => return self.parse_try(start),
+                Keyword::Switch => return self.parse_switch(start),
+                Keyword::With => {
+                    self.bump();
+                    let object = self.parse_paren_test()?;
+                    let body = Box::new_in(self.parse_stmt()?, self.allocator);
+                    return Ok(Stmt::With(WithStmt {
+                        object,
+                        body,
+                        span: self.span_from(start),
+                    }));
+                }
+                Keyword::Debugger => {
+                    self.bump();
+                    self.expect_semi();
+                    return Ok(Stmt::Debugger(self.span_from(start)));
+                }
+                Keyword::Function => return self.parse_fn_decl(start, false),
+                Keyword::Class => {
+                    let (id, class) = self.parse_class(start, self.vec(), false)?;
+                    let id = id.ok_or_else(|| self.expected("Identifier"))?;
+                    return Ok(Stmt::Class(ClassDecl {
+                        id,
+                        declare: false,
+                        class,
+                    }));
+                }
+                Keyword::Import if !self.next_is_import_call() => return self.parse_import(start),
+                Keyword::Export => return self.parse_export(start),
+                Keyword::Interface if self.next_on_same_line(|p| p.is_ident()) => {
+                    return self.parse_interface(start).map(Stmt::Interface)
+                }
+                _ => {}
+            }
+        }
+
+        if matches!(
+            self.contextual_keyword(),
+            Some(ContextualKeyword::Namespace | ContextualKeyword::Module)
+        ) && self
+            .next_on_same_line(|p| p.is_ident() || matches!(p.token.kind, TokenKind::String { .. }))
+        {
+            return self.parse_module_decl(start).map(Stmt::Module);
+        }
+        if self.is_contextual(ContextualKeyword::Declare)
+            && self.next_on_same_line(|p| p.is_decl_start())
+        {
+            self.bump();
+            let stmt = self.parse_stmt()?;
+            return mark_declare(stmt, start);
+        }
+
+        if self.is_contextual(ContextualKeyword::Async)
+            && self.next_on_same_line(|p| p.is_keyword(Keyword::Function))
+        {
+            self.bump();
+            return self.parse_fn_decl(start, true);
+        }
+        if self.is_contextual(ContextualKeyword::Abstract)
+            && self.next_on_same_line(|p| p.is_keyword(Keyword::Class))
+        {
+            self.bump();
+            let (id, class) = self.parse_class(start, self.vec(), true)?;
+            let id = id.ok_or_else(|| self.expected("Identifier"))?;
+            return Ok(Stmt::Class(ClassDecl {
+                id,
+                declare: false,
+                class,
+            }));
+        }
+        if self.is_contextual(ContextualKeyword::Type) && self.next_on_same_line(|p| p.is_ident()) {
+            return self.parse_type_alias(start).map(Stmt::TypeAlias);
+        }
+        if self.is_contextual(ContextualKeyword::Global)
+            && self.next_on_same_line(|p| p.is(&TokenKind::LBrace))
+        {
+            let id = self.parse_ident_name()?;
+            let body = self.parse_module_block()?;
+            return Ok(Stmt::Module(TsModuleDecl {
+                declare: false,
+                global: true,
+                id: TsModuleName::Ident(id),
+                body: Some(TsModuleBody::Block(body)),
+                span: self.span_from(start),
+            }));
+        }
+
+        if self.is_ident() && self.next_is(|p| p.is(&TokenKind::Colon)) {
+            let label = self.parse_ident()?;
+            self.bump();
+            let body = Box::new_in(self.parse_stmt()?, self.allocator);
+            return Ok(Stmt::Labeled(LabeledStmt {
+                label,
+                body,
+                span: self.span_from(start),
+            }));
+        }
+
+        let expr = self.parse_expr()?;
+        self.expect_semi();
+        Ok(Stmt::Expr(ExprStmt {
+            expr,
+            span: self.span_from(start),
+        }))
+    }
+
+    pub(super) fn parse_block(&mut self) -> PResult<BlockStmt<'a>> {
+        let start = self.start();
+        self.expect(&TokenKind::LBrace)?;
+        let stmts = self.parse_list(
+            ListKind::BlockStatements,
+            |p| p.parse_stmt(),
+            |span| Some(Stmt::Invalid(span)),
+        );
+        self.expect_list_close();
+        Ok(BlockStmt {
+            stmts,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Whether `let` starts a declaration rather than being used as an identifier.
+    fn is_var_start(&mut self) -> bool {
+        match self.keyword() {
+            Some(Keyword::Var) => true,
+            Some(Keyword::Const) => !self.next_is(|p| p.is_keyword(Keyword::Enum)),
+            Some(Keyword::Let) => self.next_is(|p| {
+                p.is_ident() || p.is(&TokenKind::LBracket) || p.is(&TokenKind::LBrace)
+            }),
+            _ => false,
+        }
+    }
+
+    /// Whether the current token can start a declaration after `declare` or `export`.
+    fn is_decl_start(&mut self) -> bool {
+        match self.keyword() {
+            Some(
+                Keyword::Var
+                | Keyword::Let
+                | Keyword::Const
+                | Keyword::Function
+                | Keyword::Class
+                | Keyword::Enum
+                | Keyword::Interface,
+            ) => true,
+            _ => {
+                self.is_contextual(ContextualKeyword::Type)
+                    || self.is_contextual(ContextualKeyword::Namespace)
+                    || self.is_contextual(ContextualKeyword::Module)
+                    || self.is_contextual(ContextualKeyword::Abstract)
+                    || self.is_contextual(ContextualKeyword::Global)
+                    || self.is_contextual(ContextualKeyword::Async)
+            }
+        }
+    }
+
+    /// `import(...)` and `import.meta` are expressions.
+    fn next_is_import_call(&mut self) -> bool {
+        self.next_is(|p| p.is(&TokenKind::LParen) || p.is(&TokenKind::Dot))
+    }
+
+    /// Parses `var`, `let` or `const` declarations without the trailing semicolon.
+    pub(super) fn parse_var_decl(&mut self, start: usize) -> PResult<VarDecl<'a>> {
+        let kind = match self.keyword() {
+            Some(Keyword::Var) => VarKind::Var,
+            Some(Keyword::Let) => VarKind::Let,
+            Some(Keyword::Const) => VarKind::Const,
+            _ => return Err(self.unexpected()),
+        };
+        self.bump();
+        let mut decls = self.vec();
+        loop {
+            let decl_start = self.start();
+            let name = self.parse_binding_pat()?;
+            let definite = self.eat(&TokenKind::Bang);
+            let type_ann = if self.eat(&TokenKind::Colon) {
+                Some(self.parse_type()?)
+            } else {
+                None
+            };
+            let name = with_type_ann(name, false, type_ann, self.prev_end);
+            let init = if self.eat(&TokenKind::AssignOp(AssignOp::Assign)) {
+                Some(self.parse_assignment_expr()?)
+            } else {
+                None
+            };
+            decls.push(VarDeclarator {
+                name,
+                definite,
+                init,
+                span: self.span_from(decl_start),
+            });
+            if !self.eat(&TokenKind::Comma) {
+                break;
+            }
+        }
+        Ok(VarDecl {
+            kind,
+            declare: false,
+            decls,
+            span: self.span_from(start),
+        })
+    }
+
+    /// `( expr )` after `if`, `while`, `with` and `switch`.
+    fn parse_paren_test(&mut self) -> PResult<Box<'a, Expr<'a>>> {
+        self.expect(&TokenKind::LParen)?;
+        let expr = self.allow_in(|p| p.parse_expr())?;
+        self.expect(&TokenKind::RParen)?;
+        Ok(expr)
+    }
+
+    fn parse_if(&mut self, start: usize) -> PResult<Stmt<'a>> {
+        self.bump();
+        let test = self.parse_paren_test()?;
+        let cons = Box::new_in(self.parse_stmt()?, self.allocator);
+        let alt = if self.eat_keyword(Keyword::Else) {
+            Some(Box::new_in(self.parse_stmt()?, self.allocator))
+        } else {
+            None
+        };
+        Ok(Stmt::If(IfStmt {
+            test,
+            cons,
+            alt,
+            span: self.span_from(start),
+        }))
+    }
+
+    fn parse_for(&mut self, start: usize) -> PResult<Stmt<'a>> {
+        self.bump();
+        let is_await = self.eat_contextual(ContextualKeyword::Await);
+        self.expect(&TokenKind::LParen)?;
+        let no_in = Context {
+            no_in: true,
+            ..self.ctx
+        };
+        let init = if self.is(&TokenKind::Semicolon) {
+            None
+        } else if self.is_var_start() {
+            let var_start = self.start();
+            Some(ForInit::Var(
+                self.with_ctx(no_in, |p| p.parse_var_decl(var_start))?,
+            ))
+        } else {
+            Some(ForInit::Expr(self.with_ctx(no_in, |p| p.parse_expr())?))
+        };
+
+        if init.is_some()
+            && (self.is_keyword(Keyword::In) || self.is_contextual(ContextualKeyword::Of))
+        {
+            let left = match init.unwrap() {
+                ForInit::Var(decl) => ForHead::Var(decl),
+                ForInit::Expr(expr) => ForHead::Pat(self.alloc(self.expr_to_pat(expr)?)),
+            };
+            if self.eat_keyword(Keyword::In) {
+                let right = self.parse_expr()?;
+                self.expect(&TokenKind::RParen)?;
+                let body = Box::new_in(self.parse_stmt()?, self.allocator);
+                return Ok(Stmt::ForIn(ForInStmt {
+                    left,
+                    right,
+                    body,
+                    span: self.span_from(start),
+                }));
+            }
+            self.bump();
+            let right = self.allow_in(|p| p.parse_assignment_expr())?;
+            self.expect(&TokenKind::RParen)?;
+            let body = Box::new_in(self.parse_stmt()?, self.allocator);
+            return Ok(Stmt::ForOf(ForOfStmt {
+                is_await,
+                left,
+                right,
+                body,
+                span: self.span_from(start),
+            }));
+        }
+
+        self.expect(&TokenKind::Semicolon)?;
+        let test = if self.is(&TokenKind::Semicolon) {
+            None
+        } else {
+            Some(self.parse_expr()?)
+        };
+        self.expect(&TokenKind::Semicolon)?;
+        let update = if self.is(&TokenKind::RParen) {
+            None
+        } else {
+            Some(self.parse_expr()?)
+        };
+        self.expect(&TokenKind::RParen)?;
+        let body = Box::new_in(self.parse_stmt()?, self.allocator);
+        Ok(Stmt::For(ForStmt {
+            init,
+            test,
+            update,
+            body,
+            span: self.span_from(start),
+        }))
+    }
+
+    fn parse_try(&mut self, start: usize) -> PResult<Stmt<'a>> {
+        self.bump();
+        let block = self.parse_block()?;
+        let handler = if self.is_keyword(Keyword::Catch) {
+            let catch_start = self.start();
+            self.bump();
+            let param = if self.eat(&TokenKind::LParen) {
+                let pat = self.parse_binding_pat()?;
+                let type_ann = if self.eat(&TokenKind::Colon) {
+                    Some(self.parse_type()?)
+                } else {
+                    None
+                };
+                self.expect(&TokenKind::RParen)?;
+                Some(with_type_ann(pat, false, type_ann, self.prev_end))
+            } else {
+                None
+            };
+            let body = self.parse_block()?;
+            Some(CatchClause {
+                param,
+                body,
+                span: self.span_from(catch_start),
+            })
+        } else {
+            None
+        };
+        let finalizer = if self.eat_keyword(Keyword::Finally) {
+            Some(self.parse_block()?)
+        } else {
+            None
+        };
+        if handler.is_none() && finalizer.is_none() {
+            return Err(self.expected("catch or finally"));
+        }
+        Ok(Stmt::Try(TryStmt {
+            block,
+            handler,
+            finalizer,
+            span: self.span_from(start),
+        }))
+    }
+
+    fn parse_switch(&mut self, start: usize) -> PResult<Stmt<'a>> {
+        self.bump();
+        let discriminant = self.parse_paren_test()?;
+        self.expect(&TokenKind::LBrace)?;
+        let mut cases = self.vec();
+        while !self.is(&TokenKind::RBrace) && !self.is(&TokenKind::Eof) {
+            let case_start = self.start();
+            let test = if self.eat_keyword(Keyword::Case) {
+                Some(self.allow_in(|p| p.parse_expr())?)
+            } else if self.eat_keyword(Keyword::Default) {
+                None
+            } else {
+                // statements before the first clause belong to no case
+                self.error(self.expected("case or default"));
+                self.bump();
+                while !self.is_list_end(ListKind::SwitchClauseStatements) {
+                    self.bump();
+                }
+                continue;
+            };
+            self.expect(&TokenKind::Colon)?;
+            let cons = self.parse_list(
+                ListKind::SwitchClauseStatements,
+                |p| p.parse_stmt(),
+                |span| Some(Stmt::Invalid(span)),
+            );
+            cases.push(SwitchCase {
+                test,
+                cons,
+                span: self.span_from(case_start),
+            });
+        }
+        self.expect_list_close();
+        Ok(Stmt::Switch(SwitchStmt {
+            discriminant,
+            cases,
+            span: self.span_from(start),
+        }))
+    }
+
+    fn parse_fn_decl(&mut self, start: usize, is_async: bool) -> PResult<Stmt<'a>> {
+        self.expect_keyword(Keyword::Function)?;
+        let is_generator = self.eat(&TokenKind::BinaryOp(BinaryOp::Mul));
+        let id = self.parse_ident()?;
+        let function = self.parse_function_rest(start, is_async, is_generator)?;
+        if function.body.is_none() {
+            self.expect_semi();
+        }
+        Ok(Stmt::Fn(FnDecl {
+            id,
+            declare: false,
+            function: Function {
+                span: self.span_from(start),
+                ..function
+            },
+        }))
+    }
+
+    fn parse_enum(&mut self, start: usize, is_const: bool) -> PResult<Stmt<'a>> {
+        self.expect_keyword(Keyword::Enum)?;
+        let id = self.parse_ident()?;
+        self.expect(&TokenKind::LBrace)?;
+        let members = self.parse_list(
+            ListKind::EnumMembers,
+            |p| {
+                let member_start = p.start();
+                let id = p.parse_prop_name()?;
+                let init = if p.eat(&TokenKind::AssignOp(AssignOp::Assign)) {
+                    Some(p.allow_in(|p| p.parse_assignment_expr())?)
+                } else {
+                    None
+                };
+                let member = TsEnumMember {
+                    id,
+                    init,
+                    span: p.span_from(member_start),
+                };
+                if !p.eat(&TokenKind::Comma) && !p.is(&TokenKind::RBrace) {
+                    p.error(p.expected("`,`"));
+                }
+                Ok(member)
+            },
+            |_| None,
+        );
+        self.expect_list_close();
+        Ok(Stmt::Enum(TsEnumDecl {
+            id,
+            declare: false,
+            is_const,
+            members,
+            span: self.span_from(start),
+        }))
+    }
+
+    fn parse_interface(&mut self, start: usize) -> PResult<TsInterfaceDecl<'a>> {
+        self.expect_keyword(Keyword::Interface)?;
+        let id = self.parse_ident()?;
+        let type_params = self.parse_type_params_opt()?;
+        let extends = if self.eat_keyword(Keyword::Extends) {
+            self.parse_heritage_list()?
+        } else {
+            self.vec()
+        };
+        let body = self.parse_type_members()?;
+        Ok(TsInterfaceDecl {
+            id,
+            declare: false,
+            type_params,
+            extends,
+            body,
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_type_alias(&mut self, start: usize) -> PResult<TsTypeAliasDecl<'a>> {
+        self.expect_contextual(ContextualKeyword::Type)?;
+        let id = self.parse_ident()?;
+        let type_params = self.parse_type_params_opt()?;
+        self.expect(&TokenKind::AssignOp(AssignOp::Assign))?;
+        let ty = if self.is_contextual(ContextualKeyword::Intrinsic)
+            && self.next_on_same_line(|p| !p.can_insert_semi())
+        {
+            self.parse_type()?
+        } else if self.is_contextual(ContextualKeyword::Intrinsic) {
+            let span = self.bump().span;
+            self.alloc(TsType::Keyword(TsKeywordType {
+                kind: TsKeywordKind::Intrinsic,
+                span,
+            }))
+        } else {
+            self.parse_type()?
+        };
+        self.expect_semi();
+        Ok(TsTypeAliasDecl {
+            id,
+            declare: false,
+            type_params,
+            ty,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses `namespace A.B { ... }`, `module A { ... }` and `module "mod" { ... }`.
+    fn parse_module_decl(&mut self, start: usize) -> PResult<TsModuleDecl<'a>> {
+        self.bump();
+        if let TokenKind::String { value, raw } = &self.token.kind {
+            let (value, raw) = (*value, *raw);
+            let span = self.bump().span;
+            let body = if self.is(&TokenKind::LBrace) {
+                Some(TsModuleBody::Block(self.parse_module_block()?))
+            } else {
+                self.expect_semi();
+                None
+            };
+            return Ok(TsModuleDecl {
+                declare: false,
+                global: false,
+                id: TsModuleName::Str(Str { value, raw, span }),
+                body,
+                span: self.span_from(start),
+            });
+        }
+        self.parse_namespace_rest(start)
+    }
+
+    fn parse_namespace_rest(&mut self, start: usize) -> PResult<TsModuleDecl<'a>> {
+        let id = self.parse_ident()?;
+        let body = if self.eat(&TokenKind::Dot) {
+            let inner_start = self.start();
+            TsModuleBody::Namespace(Box::new_in(
+                self.parse_namespace_rest(inner_start)?,
+                self.allocator,
+            ))
+        } else {
+            TsModuleBody::Block(self.parse_module_block()?)
+        };
+        Ok(TsModuleDecl {
+            declare: false,
+            global: false,
+            id: TsModuleName::Ident(id),
+            body: Some(body),
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_module_block(&mut self) -> PResult<TsModuleBlock<'a>> {
+        let BlockStmt { stmts, span } = self.parse_block()?;
+        Ok(TsModuleBlock { body: stmts, span })
+    }
+
+    fn parse_import(&mut self, start: usize) -> PResult<Stmt<'a>> {
+        self.expect_keyword(Keyword::Import)?;
+
+        if let Some(src) = self.parse_str_opt() {
+            let with = self.parse_import_attributes()?;
+            self.expect_semi();
+            return Ok(Stmt::Import(ImportDecl {
+                specifiers: self.vec(),
+                src,
+                type_only: false,
+                with,
+                span: self.span_from(start),
+            }));
+        }
+
+        let type_only = self.is_contextual(ContextualKeyword::Type)
+            && self.lookahead(|p| {
+                p.bump();
+                p.is(&TokenKind::LBrace)
+                    || p.is(&TokenKind::BinaryOp(BinaryOp::Mul))
+                    || (p.is_ident() && !p.is_contextual(ContextualKeyword::From))
+                    || (p.is_contextual(ContextualKeyword::From)
+                        && p.lookahead(|p| {
+                            p.bump();
+                            p.is_contextual(ContextualKeyword::From)
+                                || p.is(&TokenKind::AssignOp(AssignOp::Assign))
+                        }))
+            });
+        if type_only {
+            self.bump();
+        }
+
+        let mut specifiers = self.vec();
+        if self.is_ident() {
+            let id = self.parse_ident()?;
+            if self.eat(&TokenKind::AssignOp(AssignOp::Assign)) {
+                return self.parse_import_equals(start, false, type_only, id);
+            }
+            specifiers.push(ImportSpecifier::Default(id));
+            if !self.eat(&TokenKind::Comma) {
+                return self.finish_import(start, specifiers, type_only);
+            }
+        }
+
+        if self.eat(&TokenKind::BinaryOp(BinaryOp::Mul)) {
+            self.expect_contextual(ContextualKeyword::As)?;
+            specifiers.push(ImportSpecifier::Namespace(self.parse_ident()?));
+        } else if self.eat(&TokenKind::LBrace) {
+            while !self.eat(&TokenKind::RBrace) {
+                specifiers.push(ImportSpecifier::Named(self.parse_import_specifier()?));
+                if !self.eat(&TokenKind::Comma) {
+                    self.expect(&TokenKind::RBrace)?;
+                    break;
+                }
+            }
+        } else {
+            return Err(self.unexpected());
+        }
+        self.finish_import(start, specifiers, type_only)
+    }
+
+    fn finish_import(
+        &mut self,
+        start: usize,
+        specifiers: Vec<'a, ImportSpecifier<'a>>,
+        type_only: bool,
+    ) -> PResult<Stmt<'a>> {
+        self.expect_contextual(ContextualKeyword::From)?;
+        let src = self.parse_str()?;
+        let with = self.parse_import_attributes()?;
+        self.expect_semi();
+        Ok(Stmt::Import(ImportDecl {
+            specifiers,
+            src,
+            type_only,
+            with,
+            span: self.span_from(start),
+        }))
+    }
+
+    fn parse_import_specifier(&mut self) -> PResult<ImportNamedSpecifier<'a>> {
+        let start = self.start();
+        let is_type_only = self.is_type_modifier_in_specifier();
+        if is_type_only {
+            self.bump();
+        }
+        let name = self.parse_module_export_name()?;
+        if self.eat_contextual(ContextualKeyword::As) {
+            let local = self.parse_ident()?;
+            return Ok(ImportNamedSpecifier {
+                imported: Some(name),
+                local,
+                is_type_only,
+                span: self.span_from(start),
+            });
+        }
+        let local = match name {
+            ModuleExportName::Ident(id) => id,
+            ModuleExportName::Str(s) => return Err(self.expected_at("as", s.span)),
+        };
+        Ok(ImportNamedSpecifier {
+            imported: None,
+            local,
+            is_type_only,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Whether the `type` at the start of an import or export specifier is a modifier,
+    /// as in `{ type A }`, rather than the name of the specifier as in `{ type as T }`.
+    fn is_type_modifier_in_specifier(&mut self) -> bool {
+        if !self.is_contextual(ContextualKeyword::Type) {
+            return false;
+        }
+        self.lookahead(|p| {
+            p.bump();
+            if p.is_contextual(ContextualKeyword::As) {
+                // `{ type as }` and `{ type as as b }` import `as`, `{ type as b }` imports `type`
+                p.bump();
+                p.is(&TokenKind::Comma)
+                    || p.is(&TokenKind::RBrace)
+                    || p.is_contextual(ContextualKeyword::As)
+            } else {
+                p.word().is_some() || matches!(p.token.kind, TokenKind::String { .. })
+            }
+        })
+    }
+
+    fn parse_module_export_name(&mut self) -> PResult<ModuleExportName<'a>> {
+        match self.parse_str_opt() {
+            Some(s) => Ok(ModuleExportName::Str(s)),
+            None => Ok(ModuleExportName::Ident(self.parse_ident_name()?)),
+        }
+    }
+
+    /// `with { type: "json" }` or `assert { type: "json" }` after a module specifier.
+    fn parse_import_attributes(&mut self) -> PResult<Option<ObjectLit<'a>>> {
+        let is_attributes = (self.is_keyword(Keyword::With)
+            || self.is_contextual(ContextualKeyword::Assert))
+            && !self.has_line_break_before();
+        if !is_attributes {
+            return Ok(None);
+        }
+        self.bump();
+        Ok(Some(self.parse_object_lit()?))
+    }
+
+    fn parse_import_equals(
+        &mut self,
+        start: usize,
+        is_export: bool,
+        is_type_only: bool,
+        id: Ident<'a>,
+    ) -> PResult<Stmt<'a>> {
+        let module_ref = if self.is_contextual(ContextualKeyword::Require)
+            && self.next_is(|p| p.is(&TokenKind::LParen))
+        {
+            self.bump();
+            self.bump();
+            let src = self.parse_str()?;
+            self.expect(&TokenKind::RParen)?;
+            TsModuleRef::External(src)
+        } else {
+            TsModuleRef::Entity(self.parse_entity_name()?)
+        };
+        self.expect_semi();
+        Ok(Stmt::ImportEquals(TsImportEqualsDecl {
+            is_export,
+            is_type_only,
+            id,
+            module_ref,
+            span: self.span_from(start),
+        }))
+    }
+
+    fn parse_export(&mut self, start: usize) -> PResult<Stmt<'a>> {
+        self.expect_keyword(Keyword::Export)?;
+
+        if self.eat(&TokenKind::AssignOp(AssignOp::Assign)) {
+            let expr = self.allow_in(|p| p.parse_expr())?;
+            self.expect_semi();
+            return Ok(Stmt::ExportAssign(TsExportAssign {
+                expr,
+                span: self.span_from(start),
+            }));
+        }
+        if self.is_contextual(ContextualKeyword::As) {
+            self.bump();
+            self.expect_contextual(ContextualKeyword::Namespace)?;
+            let id = self.parse_ident()?;
+            self.expect_semi();
+            return Ok(Stmt::ExportAsNamespace(TsExportAsNamespace {
+                id,
+                span: self.span_from(start),
+            }));
+        }
+        if self.eat_keyword(Keyword::Default) {
+            let decl = self.parse_export_default()?;
+            return Ok(Stmt::ExportDefault(ExportDefault {
+                decl,
+                span: self.span_from(start),
+            }));
+        }
+        if self.is_keyword(Keyword::Import) {
+            self.bump();
+            let is_type_only = self.is_contextual(ContextualKeyword::Type)
+                && self.next_on_same_line(|p| p.is_ident());
+            if is_type_only {
+                self.bump();
+            }
+            let id = self.parse_ident()?;
+            self.expect(&TokenKind::AssignOp(AssignOp::Assign))?;
+            return self.parse_import_equals(start, true, is_type_only, id);
+        }
+
+        let type_only = self.is_contextual(ContextualKeyword::Type)
+            && self
+                .next_is(|p| p.is(&TokenKind::LBrace) || p.is(&TokenKind::BinaryOp(BinaryOp::Mul)));
+        if type_only {
+            self.bump();
+        }
+
+        if self.eat(&TokenKind::BinaryOp(BinaryOp::Mul)) {
+            let exported = if self.eat_contextual(ContextualKeyword::As) {
+                Some(self.parse_module_export_name()?)
+            } else {
+                None
+            };
+            self.expect_contextual(ContextualKeyword::From)?;
+            let src = self.parse_str()?;
+            let with = self.parse_import_attributes()?;
+            self.expect_semi();
+            let span = self.span_from(start);
+            return Ok(match exported {
+                Some(name) => Stmt::ExportNamed(ExportNamed {
+                    specifiers: Vec::from_iter_in(
+                        [ExportSpecifier::Namespace(name)],
+                        self.allocator,
+                    ),
+                    src: Some(src),
+                    type_only,
+                    with,
+                    span,
+                }),
+                None => Stmt::ExportAll(ExportAll {
+                    src,
+                    type_only,
+                    with,
+                    span,
+                }),
+            });
+        }
+
+        if self.eat(&TokenKind::LBrace) {
+            let mut specifiers = self.vec();
+            while !self.eat(&TokenKind::RBrace) {
+                let spec_start = self.start();
+                let is_type_only = self.is_type_modifier_in_specifier();
+                if is_type_only {
+                    self.bump();
+                }
+                let orig = self.parse_module_export_name()?;
+                let exported = if self.eat_contextual(ContextualKeyword::As) {
+                    Some(self.parse_module_export_name()?)
+                } else {
+                    None
+                };
+                specifiers.push(ExportSpecifier::Named(ExportNamedSpecifier {
+                    orig,
+                    exported,
+                    is_type_only,
+                    span: self.span_from(spec_start),
+                }));
+                if !self.eat(&TokenKind::Comma) {
+                    self.expect(&TokenKind::RBrace)?;
+                    break;
+                }
+            }
+            let (src, with) = if self.eat_contextual(ContextualKeyword::From) {
+                (Some(self.parse_str()?), self.parse_import_attributes()?)
+            } else {
+                (None, None)
+            };
+            self.expect_semi();
+            return Ok(Stmt::ExportNamed(ExportNamed {
+                specifiers,
+                src,
+                type_only,
+                with,
+                span: self.span_from(start),
+            }));
+        }
+
+        if !self.is(&TokenKind::At)
+            && !self.is_decl_start()
+            && !self.is_contextual(ContextualKeyword::Declare)
+        {
+            return Err(self.expected("Declaration"));
+        }
+        let decl = self.parse_stmt()?;
+        Ok(Stmt::ExportDecl(ExportDecl {
+            decl: self.alloc(decl),
+            span: self.span_from(start),
+        }))
+    }
+
+    fn parse_export_default(&mut self) -> PResult<DefaultDecl<'a>> {
+        let start = self.start();
+        if self.is_keyword(Keyword::Function)
+            || (self.is_contextual(ContextualKeyword::Async)
+                && self.next_on_same_line(|p| p.is_keyword(Keyword::Function)))
+        {
+            let is_async = self.eat_contextual(ContextualKeyword::Async);
+            self.bump();
+            let is_generator = self.eat(&TokenKind::BinaryOp(BinaryOp::Mul));
+            let id = if self.is_ident() {
+                Some(self.parse_ident()?)
+            } else {
+                None
+            };
+            let function = self.parse_function_rest(start, is_async, is_generator)?;
+            if function.body.is_none() {
+                self.expect_semi();
+            }
+            return Ok(DefaultDecl::Fn(FnExpr {
+                id,
+                function: Function {
+                    span: self.span_from(start),
+                    ..function
+                },
+            }));
+        }
+        if self.is(&TokenKind::At)
+            || self.is_keyword(Keyword::Class)
+            || (self.is_contextual(ContextualKeyword::Abstract)
+                && self.next_on_same_line(|p| p.is_keyword(Keyword::Class)))
+        {
+            let decorators = self.parse_decorators()?;
+            let is_abstract = self.eat_contextual(ContextualKeyword::Abstract);
+            let (id, class) = self.parse_class(start, decorators, is_abstract)?;
+            return Ok(DefaultDecl::Class(ClassExpr { id, class }));
+        }
+        if self.is_keyword(Keyword::Interface) && self.next_on_same_line(|p| p.is_ident()) {
+            return self.parse_interface(start).map(DefaultDecl::Interface);
+        }
+        let expr = self.allow_in(|p| p.parse_assignment_expr())?;
+        self.expect_semi();
+        Ok(DefaultDecl::Expr(expr))
+    }
+
+    pub(super) fn parse_str_opt(&mut self) -> Option<Str<'a>> {
+        match &self.token.kind {
+            TokenKind::String { value, raw } => {
+                let (value, raw) = (*value, *raw);
+                let span = self.bump().span;
+                Some(Str { value, raw, span })
+            }
+            _ => None,
+        }
+    }
+
+    pub(super) fn parse_str(&mut self) -> PResult<Str<'a>> {
+        match self.parse_str_opt() {
+            Some(s) => Ok(s),
+            None => Err(self.expected("String literal")),
+        }
+    }
+}
+
+/// Adds the decorators in front of a class declaration, or an exported one.
+fn attach_decorators<'a>(
+    stmt: Stmt<'a>,
+    decorators: Vec<'a, Decorator<'a>>,
+    start: usize,
+) -> PResult<Stmt<'a>> {
+    fn attach<'a>(class: &mut Class<'a>, decorators: Vec<'a, Decorator<'a>>, start: usize) {
+        class.decorators = decorators;
+        class.span.start = start;
+    }
+
+    match stmt {
+        Stmt::Class(mut decl) => {
+            attach(&mut decl.class, decorators, start);
+            Ok(Stmt::Class(decl))
+        }
+        Stmt::ExportDecl(ExportDecl { mut decl, span }) if matches!(*decl, Stmt::Class(_)) => {
+            if let Stmt::Class(decl) = &mut *decl {
+                attach(&mut decl.class, decorators, start);
+            }
+            Ok(Stmt::ExportDecl(ExportDecl {
+                decl,
+                span: Span::new(start, span.end),
+            }))
+        }
+        Stmt::ExportDefault(ExportDefault {
+            decl: DefaultDecl::Class(mut class),
+            span,
+        }) => {
+            attach(&mut class.class, decorators, start);
+            Ok(Stmt::ExportDefault(ExportDefault {
+                decl: DefaultDecl::Class(class),
+                span: Span::new(start, span.end),
+            }))
+        }
+        stmt => Err(UnexpectedToken(stmt.span()).into()),
+    }
+}
+
+/// Marks a declaration as ambient after `declare` and extends its span to `start`.
+fn mark_declare(stmt: Stmt<'_>, start: usize) -> PResult<Stmt<'_>> {
+    Ok(match stmt {
+        Stmt::Var(mut decl) => {
+            decl.declare = true;
+            decl.span.start = start;
+            Stmt::Var(decl)
+        }
+        Stmt::Fn(mut decl) => {
+            decl.declare = true;
+            decl.function.span.start = start;
+            Stmt::Fn(decl)
+        }
+        Stmt::Class(mut decl) => {
+            decl.declare = true;
+            decl.class.span.start = start;
+            Stmt::Class(decl)
+        }
+        Stmt::Interface(mut decl) => {
+            decl.declare = true;
+            decl.span.start = start;
+            Stmt::Interface(decl)
+        }
+        Stmt::TypeAlias(mut decl) => {
+            decl.declare = true;
+            decl.span.start = start;
+            Stmt::TypeAlias(decl)
+        }
+        Stmt::Enum(mut decl) => {
+            decl.declare = true;
+            decl.span.start = start;
+            Stmt::Enum(decl)
+        }
+        Stmt::Module(mut decl) => {
+            decl.declare = true;
+            decl.span.start = start;
+            Stmt::Module(decl)
+        }
+        stmt => return Err(UnexpectedToken(stmt.span()).into()),
+    })
+}
+
+/// Attaches `: T` and `?` to a binding pattern, extending its span to `end`.
+pub(super) fn with_type_ann<'a>(
+    pat: Pat<'a>,
+    optional: bool,
+    type_ann: Option<Box<'a, TsType<'a>>>,
+    end: usize,
+) -> Pat<'a> {
+    if type_ann.is_none() && !optional {
+        return pat;
+    }
+    match pat {
+        Pat::Ident(mut p) => {
+            p.type_ann = type_ann;
+            p.optional = optional;
+            p.span.end = end;
+            Pat::Ident(p)
+        }
+        Pat::Array(mut p) => {
+            p.type_ann = type_ann;
+            p.optional = optional;
+            p.span.end = end;
+            Pat::Array(p)
+        }
+        Pat::Object(mut p) => {
+            p.type_ann = type_ann;
+            p.optional = optional;
+            p.span.end = end;
+            Pat::Object(p)
+        }
+        Pat::Rest(mut p) => {
+            p.type_ann = type_ann;
+            p.span.end = end;
+            Pat::Rest(p)
+        }
+        pat => pat,
+    }
+}
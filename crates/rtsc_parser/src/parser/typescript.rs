@@ -0,0 +1,951 @@
+use crate::{
+    allocator::{Box, Vec},
+    ast::*,
+    AssignOp, BinaryOp, ContextualKeyword, Keyword, TokenKind, WordKind,
+};
+
+use super::{Context, ListKind, PResult, Parser};
+
+impl<'a> Parser<'a> {
+    /// Parses a type, where conditional types are allowed again.
+    pub(super) fn parse_type(&mut self) -> PResult<Box<'a, TsType<'a>>> {
+        let ctx = Context {
+            no_conditional_types: false,
+            ..self.ctx
+        };
+        self.with_ctx(ctx, |p| p.parse_type_inner())
+    }
+
+    fn parse_type_inner(&mut self) -> PResult<Box<'a, TsType<'a>>> {
+        let start = self.start();
+        if self.is_start_of_fn_type() {
+            return self.parse_fn_type(start);
+        }
+        if self.is_keyword(Keyword::New)
+            || (self.is_contextual(ContextualKeyword::Abstract)
+                && self.next_on_same_line(|p| p.is_keyword(Keyword::New)))
+        {
+            return self.parse_constructor_type(start);
+        }
+
+        let check = self.parse_union_type()?;
+        if self.ctx.no_conditional_types
+            || self.has_line_break_before()
+            || !self.eat_keyword(Keyword::Extends)
+        {
+            return Ok(check);
+        }
+        let ctx = Context {
+            no_conditional_types: true,
+            ..self.ctx
+        };
+        let extends = self.with_ctx(ctx, |p| p.parse_type_inner())?;
+        self.expect(&TokenKind::Question)?;
+        let true_type = self.parse_type()?;
+        self.expect(&TokenKind::Colon)?;
+        let false_type = self.parse_type()?;
+        Ok(self.alloc(TsType::Conditional(TsConditionalType {
+            check,
+            extends,
+            true_type,
+            false_type,
+            span: self.span_from(start),
+        })))
+    }
+
+    /// Whether a function type starts here: `<T>(...) =>` or a parenthesized list
+    /// that cannot be a parenthesized type, like `(a: T) =>` or `() =>`.
+    fn is_start_of_fn_type(&mut self) -> bool {
+        if self.is_lt() {
+            return true;
+        }
+        if !self.is(&TokenKind::LParen) {
+            return false;
+        }
+        self.lookahead(|p| {
+            p.bump();
+            if p.is(&TokenKind::RParen) || p.is(&TokenKind::DotDotDot) {
+                return true;
+            }
+            if !p.skip_param_start() {
+                return false;
+            }
+            if matches!(
+                p.token.kind,
+                TokenKind::Colon
+                    | TokenKind::Comma
+                    | TokenKind::Question
+                    | TokenKind::AssignOp(AssignOp::Assign)
+            ) {
+                return true;
+            }
+            p.eat(&TokenKind::RParen) && p.is(&TokenKind::Arrow)
+        })
+    }
+
+    /// Skips what could be the start of a parameter: modifiers, then a name or pattern.
+    fn skip_param_start(&mut self) -> bool {
+        while matches!(
+            self.keyword(),
+            Some(Keyword::Public | Keyword::Private | Keyword::Protected)
+        ) || self.is_contextual(ContextualKeyword::Readonly)
+        {
+            self.bump();
+        }
+        if self.is_ident() || self.is_keyword(Keyword::This) {
+            self.bump();
+            return true;
+        }
+        if self.is(&TokenKind::LBracket) || self.is(&TokenKind::LBrace) {
+            return self.parse_binding_pat().is_ok();
+        }
+        false
+    }
+
+    fn parse_fn_type(&mut self, start: usize) -> PResult<Box<'a, TsType<'a>>> {
+        let type_params = self.parse_type_params_opt()?;
+        let params = self.parse_signature_params()?;
+        self.expect(&TokenKind::Arrow)?;
+        let return_type = self.parse_return_type()?;
+        Ok(self.alloc(TsType::Fn(TsFnType {
+            type_params,
+            params,
+            return_type,
+            span: self.span_from(start),
+        })))
+    }
+
+    fn parse_constructor_type(&mut self, start: usize) -> PResult<Box<'a, TsType<'a>>> {
+        let is_abstract = self.eat_contextual(ContextualKeyword::Abstract);
+        self.expect_keyword(Keyword::New)?;
+        let type_params = self.parse_type_params_opt()?;
+        let params = self.parse_signature_params()?;
+        self.expect(&TokenKind::Arrow)?;
+        let return_type = self.parse_return_type()?;
+        Ok(self.alloc(TsType::Constructor(TsConstructorType {
+            is_abstract,
+            type_params,
+            params,
+            return_type,
+            span: self.span_from(start),
+        })))
+    }
+
+    /// Parameters of signatures and function types, which have no modifiers.
+    fn parse_signature_params(&mut self) -> PResult<Vec<'a, Pat<'a>>> {
+        let params = self.parse_params()?;
+        Ok(Vec::from_iter_in(
+            params.into_iter().map(|param| param.pat),
+            self.allocator,
+        ))
+    }
+
+    fn parse_union_type(&mut self) -> PResult<Box<'a, TsType<'a>>> {
+        let start = self.start();
+        let leading = self.eat(&TokenKind::BinaryOp(BinaryOp::BitOr));
+        let first = self.parse_intersection_type()?;
+        if !leading && !self.is(&TokenKind::BinaryOp(BinaryOp::BitOr)) {
+            return Ok(first);
+        }
+        let mut types = Vec::from_iter_in([first.unbox()], self.allocator);
+        while self.eat(&TokenKind::BinaryOp(BinaryOp::BitOr)) {
+            types.push(self.parse_intersection_type()?.unbox());
+        }
+        Ok(self.alloc(TsType::Union(TsUnionType {
+            types,
+            span: self.span_from(start),
+        })))
+    }
+
+    fn parse_intersection_type(&mut self) -> PResult<Box<'a, TsType<'a>>> {
+        let start = self.start();
+        let leading = self.eat(&TokenKind::BinaryOp(BinaryOp::BitAnd));
+        let first = self.parse_type_operator()?;
+        if !leading && !self.is(&TokenKind::BinaryOp(BinaryOp::BitAnd)) {
+            return Ok(first);
+        }
+        let mut types = Vec::from_iter_in([first.unbox()], self.allocator);
+        while self.eat(&TokenKind::BinaryOp(BinaryOp::BitAnd)) {
+            types.push(self.parse_type_operator()?.unbox());
+        }
+        Ok(self.alloc(TsType::Intersection(TsIntersectionType {
+            types,
+            span: self.span_from(start),
+        })))
+    }
+
+    /// `keyof T`, `unique symbol`, `readonly T[]` and `infer U`.
+    fn parse_type_operator(&mut self) -> PResult<Box<'a, TsType<'a>>> {
+        let start = self.start();
+        let op = match self.contextual_keyword() {
+            Some(ContextualKeyword::Keyof) => Some(TsTypeOperatorOp::KeyOf),
+            Some(ContextualKeyword::Unique) => Some(TsTypeOperatorOp::Unique),
+            Some(ContextualKeyword::Readonly) => Some(TsTypeOperatorOp::ReadOnly),
+            _ => None,
+        };
+        if let Some(op) = op {
+            self.bump();
+            let ty = self.parse_type_operator()?;
+            return Ok(self.alloc(TsType::TypeOperator(TsTypeOperator {
+                op,
+                ty,
+                span: self.span_from(start),
+            })));
+        }
+        if self.is_contextual(ContextualKeyword::Infer) {
+            self.bump();
+            let name = self.parse_ident()?;
+            let constraint = self.try_parse(|p| {
+                p.expect_keyword(Keyword::Extends)?;
+                let ctx = Context {
+                    no_conditional_types: true,
+                    ..p.ctx
+                };
+                let constraint = p.with_ctx(ctx, |p| p.parse_type_inner())?;
+                // in `T extends infer U extends X ? A : B`, `extends X` starts the conditional
+                if !p.ctx.no_conditional_types && p.is(&TokenKind::Question) {
+                    return Err(p.unexpected());
+                }
+                Ok(constraint)
+            });
+            let span = self.span_from(start);
+            return Ok(self.alloc(TsType::Infer(TsInferType {
+                param: TsTypeParam {
+                    name,
+                    is_in: false,
+                    is_out: false,
+                    is_const: false,
+                    constraint,
+                    default: None,
+                    span,
+                },
+                span,
+            })));
+        }
+        self.parse_postfix_type()
+    }
+
+    /// `T[]` and `T[K]`, which must start on the same line as `T`.
+    fn parse_postfix_type(&mut self) -> PResult<Box<'a, TsType<'a>>> {
+        let start = self.start();
+        let mut ty = self.parse_primary_type()?;
+        while self.is(&TokenKind::LBracket) && !self.has_line_break_before() {
+            self.bump();
+            ty = if self.eat(&TokenKind::RBracket) {
+                self.alloc(TsType::Array(TsArrayType {
+                    elem: ty,
+                    span: self.span_from(start),
+                }))
+            } else {
+                let index = self.parse_type()?;
+                self.expect(&TokenKind::RBracket)?;
+                self.alloc(TsType::IndexedAccess(TsIndexedAccessType {
+                    obj: ty,
+                    index,
+                    span: self.span_from(start),
+                }))
+            };
+        }
+        Ok(ty)
+    }
+
+    fn parse_primary_type(&mut self) -> PResult<Box<'a, TsType<'a>>> {
+        let start = self.start();
+        let ty = match &self.token.kind {
+            TokenKind::Word(word) => {
+                let word = word.clone();
+                let keyword = match &word {
+                    WordKind::Null => Some(TsKeywordKind::Null),
+                    WordKind::Keyword(Keyword::Void) => Some(TsKeywordKind::Void),
+                    _ => match self.contextual_keyword() {
+                        Some(ContextualKeyword::Any) => Some(TsKeywordKind::Any),
+                        Some(ContextualKeyword::Unknown) => Some(TsKeywordKind::Unknown),
+                        Some(ContextualKeyword::Number) => Some(TsKeywordKind::Number),
+                        Some(ContextualKeyword::Object) => Some(TsKeywordKind::Object),
+                        Some(ContextualKeyword::Boolean) => Some(TsKeywordKind::Boolean),
+                        Some(ContextualKeyword::Bigint) => Some(TsKeywordKind::BigInt),
+                        Some(ContextualKeyword::String) => Some(TsKeywordKind::String),
+                        Some(ContextualKeyword::Symbol) => Some(TsKeywordKind::Symbol),
+                        Some(ContextualKeyword::Undefined) => Some(TsKeywordKind::Undefined),
+                        Some(ContextualKeyword::Never) => Some(TsKeywordKind::Never),
+                        _ => None,
+                    },
+                };
+                let keyword = keyword.filter(|_| {
+                    // `string.x` is a name in a namespace called `string`
+                    !self.next_is(|p| p.is(&TokenKind::Dot))
+                });
+                match (keyword, &word) {
+                    (Some(kind), _) => {
+                        let span = self.bump().span;
+                        TsType::Keyword(TsKeywordType { kind, span })
+                    }
+                    (None, WordKind::True | WordKind::False) => {
+                        let value = matches!(word, WordKind::True);
+                        let span = self.bump().span;
+                        TsType::Lit(TsLitType {
+                            lit: TsLit::Bool(Bool { value, span }),
+                            span,
+                        })
+                    }
+                    (None, WordKind::Keyword(Keyword::This)) => TsType::This(self.bump().span),
+                    (None, WordKind::Keyword(Keyword::Typeof)) => {
+                        self.bump();
+                        let expr = if self.is_keyword(Keyword::Import) {
+                            TsTypeQueryExpr::Import(self.parse_import_type()?)
+                        } else {
+                            TsTypeQueryExpr::Entity(self.parse_entity_name()?)
+                        };
+                        let type_args = self.parse_type_args_on_same_line()?;
+                        TsType::Query(TsTypeQuery {
+                            expr,
+                            type_args,
+                            span: self.span_from(start),
+                        })
+                    }
+                    (None, WordKind::Keyword(Keyword::Import)) => {
+                        TsType::Import(self.parse_import_type()?)
+                    }
+                    _ => {
+                        let name = self.parse_entity_name()?;
+                        let type_args = self.parse_type_args_on_same_line()?;
+                        TsType::Ref(TsTypeRef {
+                            name,
+                            type_args,
+                            span: self.span_from(start),
+                        })
+                    }
+                }
+            }
+            TokenKind::String { .. } => {
+                let s = self.parse_str()?;
+                TsType::Lit(TsLitType {
+                    span: s.span,
+                    lit: TsLit::Str(s),
+                })
+            }
+            TokenKind::Number { .. } | TokenKind::BigInt { .. } => {
+                TsType::Lit(self.parse_number_lit_type(start, false)?)
+            }
+            TokenKind::BinaryOp(BinaryOp::Sub) => {
+                self.bump();
+                TsType::Lit(self.parse_number_lit_type(start, true)?)
+            }
+            TokenKind::NoSubstitutionTemplate { .. } | TokenKind::TemplateHead { .. } => {
+                TsType::Lit(self.parse_tpl_lit_type()?)
+            }
+            TokenKind::LParen => {
+                self.bump();
+                let ty = self.parse_type()?;
+                self.expect(&TokenKind::RParen)?;
+                TsType::Paren(TsParenType {
+                    ty,
+                    span: self.span_from(start),
+                })
+            }
+            TokenKind::LBracket => self.parse_tuple_type()?,
+            TokenKind::LBrace => {
+                if self.is_start_of_mapped_type() {
+                    self.parse_mapped_type()?
+                } else {
+                    let members = self.parse_type_members()?;
+                    TsType::TypeLit(TsTypeLit {
+                        members,
+                        span: self.span_from(start),
+                    })
+                }
+            }
+            _ => return Err(self.expected("Type")),
+        };
+        Ok(self.alloc(ty))
+    }
+
+    /// The text of a number, or a copy of it after a `-` if it is negated.
+    fn number_text(&self, text: &'a str, negative: bool) -> &'a str {
+        if negative {
+            self.alloc_str(&format!("-{text}"))
+        } else {
+            text
+        }
+    }
+
+    /// A number or bigint literal type, negated when it follows a `-`.
+    fn parse_number_lit_type(&mut self, start: usize, negative: bool) -> PResult<TsLitType<'a>> {
+        let lit = match &self.token.kind {
+            TokenKind::Number { value, raw } => {
+                let value = if negative { -*value } else { *value };
+                let raw = self.number_text(raw, negative);
+                self.bump();
+                TsLit::Num(Num {
+                    value,
+                    raw,
+                    span: self.span_from(start),
+                })
+            }
+            TokenKind::BigInt { value, raw } => {
+                let value = self.number_text(value, negative);
+                let raw = self.number_text(raw, negative);
+                self.bump();
+                TsLit::BigInt(BigInt {
+                    value,
+                    raw,
+                    span: self.span_from(start),
+                })
+            }
+            _ => return Err(self.expected("Type")),
+        };
+        Ok(TsLitType {
+            lit,
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_tpl_lit_type(&mut self) -> PResult<TsLitType<'a>> {
+        let start = self.start();
+        let mut quasis = self.vec();
+        let mut types = self.vec();
+        loop {
+            let (cooked, raw, tail) = match &self.token.kind {
+                TokenKind::NoSubstitutionTemplate { cooked, raw }
+                | TokenKind::TemplateTail { cooked, raw } => (*cooked, *raw, true),
+                TokenKind::TemplateHead { cooked, raw }
+                | TokenKind::TemplateMiddle { cooked, raw }
+                    if types.len() == quasis.len() =>
+                {
+                    (*cooked, *raw, false)
+                }
+                _ => return Err(self.expected("`}`")),
+            };
+            let span = self.bump().span;
+            quasis.push(TplElement { cooked, raw, span });
+            if tail {
+                break;
+            }
+            types.push(self.parse_type()?.unbox());
+        }
+        let span = self.span_from(start);
+        Ok(TsLitType {
+            lit: TsLit::Tpl(TsTplLitType {
+                quasis,
+                types,
+                span,
+            }),
+            span,
+        })
+    }
+
+    /// `import("mod").A.B<T>`
+    fn parse_import_type(&mut self) -> PResult<TsImportType<'a>> {
+        let start = self.start();
+        self.expect_keyword(Keyword::Import)?;
+        self.expect(&TokenKind::LParen)?;
+        let arg = self.parse_str()?;
+        self.expect(&TokenKind::RParen)?;
+        let qualifier = if self.eat(&TokenKind::Dot) {
+            Some(self.parse_entity_name()?)
+        } else {
+            None
+        };
+        let type_args = self.parse_type_args_on_same_line()?;
+        Ok(TsImportType {
+            arg,
+            qualifier,
+            type_args,
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_tuple_type(&mut self) -> PResult<TsType<'a>> {
+        let start = self.start();
+        self.expect(&TokenKind::LBracket)?;
+        let mut elems = self.vec();
+        while !self.eat(&TokenKind::RBracket) {
+            let elem_start = self.start();
+            let rest = self.eat(&TokenKind::DotDotDot);
+            let is_labeled = self.word().is_some()
+                && self.lookahead(|p| {
+                    p.bump();
+                    p.eat(&TokenKind::Question);
+                    p.is(&TokenKind::Colon)
+                });
+            let (label, ty, optional) = if is_labeled {
+                let label = self.parse_ident_name()?;
+                let optional = self.eat(&TokenKind::Question);
+                self.expect(&TokenKind::Colon)?;
+                (Some(label), self.parse_type()?, optional)
+            } else {
+                let ty = self.parse_type()?;
+                (None, ty, self.eat(&TokenKind::Question))
+            };
+            elems.push(TsTupleElement {
+                label,
+                ty,
+                optional,
+                rest,
+                span: self.span_from(elem_start),
+            });
+            if !self.eat(&TokenKind::Comma) {
+                self.expect(&TokenKind::RBracket)?;
+                break;
+            }
+        }
+        Ok(TsType::Tuple(TsTupleType {
+            elems,
+            span: self.span_from(start),
+        }))
+    }
+
+    fn is_start_of_mapped_type(&mut self) -> bool {
+        self.lookahead(|p| {
+            p.bump();
+            if p.eat(&TokenKind::BinaryOp(BinaryOp::Add))
+                || p.eat(&TokenKind::BinaryOp(BinaryOp::Sub))
+            {
+                return p.is_contextual(ContextualKeyword::Readonly);
+            }
+            p.eat_contextual(ContextualKeyword::Readonly);
+            if !p.eat(&TokenKind::LBracket) || !p.is_ident() {
+                return false;
+            }
+            p.bump();
+            p.is_keyword(Keyword::In)
+        })
+    }
+
+    fn parse_mapped_type(&mut self) -> PResult<TsType<'a>> {
+        let start = self.start();
+        self.expect(&TokenKind::LBrace)?;
+        let readonly = self.parse_mapped_modifier("readonly")?;
+        self.expect(&TokenKind::LBracket)?;
+        let param_start = self.start();
+        let name = self.parse_ident()?;
+        self.expect_keyword(Keyword::In)?;
+        let constraint = self.parse_type()?;
+        let type_param = TsTypeParam {
+            name,
+            is_in: false,
+            is_out: false,
+            is_const: false,
+            constraint: Some(constraint),
+            default: None,
+            span: self.span_from(param_start),
+        };
+        let name_type = if self.eat_contextual(ContextualKeyword::As) {
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+        self.expect(&TokenKind::RBracket)?;
+        let optional = self.parse_mapped_modifier("?")?;
+        let ty = if self.eat(&TokenKind::Colon) {
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+        if !self.eat(&TokenKind::Semicolon) {
+            self.eat(&TokenKind::Comma);
+        }
+        self.expect(&TokenKind::RBrace)?;
+        Ok(TsType::Mapped(TsMappedType {
+            readonly,
+            type_param,
+            name_type,
+            optional,
+            ty,
+            span: self.span_from(start),
+        }))
+    }
+
+    /// `readonly`, `+readonly`, `-readonly` and the same for `?` in mapped types.
+    fn parse_mapped_modifier(&mut self, modifier: &'static str) -> PResult<Option<TruePlusMinus>> {
+        let is_modifier = |p: &Self| match modifier {
+            "?" => p.is(&TokenKind::Question),
+            _ => p.is_contextual(ContextualKeyword::Readonly),
+        };
+        let sign = if self.eat(&TokenKind::BinaryOp(BinaryOp::Add)) {
+            TruePlusMinus::Plus
+        } else if self.eat(&TokenKind::BinaryOp(BinaryOp::Sub)) {
+            TruePlusMinus::Minus
+        } else if is_modifier(self) {
+            TruePlusMinus::True
+        } else {
+            return Ok(None);
+        };
+        if !is_modifier(self) {
+            return Err(self.expected(modifier));
+        }
+        self.bump();
+        Ok(Some(sign))
+    }
+
+    /// Parses a return type after `:`, which may be a type predicate.
+    pub(super) fn parse_return_type(&mut self) -> PResult<Box<'a, TsType<'a>>> {
+        let start = self.start();
+        let asserts = self.is_contextual(ContextualKeyword::Asserts)
+            && self.next_on_same_line(|p| p.is_ident() || p.is_keyword(Keyword::This));
+        if asserts {
+            self.bump();
+        }
+        let is_predicate = asserts
+            || ((self.is_ident() || self.is_keyword(Keyword::This))
+                && self.next_on_same_line(|p| p.is_contextual(ContextualKeyword::Is)));
+        if !is_predicate {
+            return self.parse_type();
+        }
+        let param = if self.is_keyword(Keyword::This) {
+            TsPredicateParam::This(self.bump().span)
+        } else {
+            TsPredicateParam::Ident(self.parse_ident()?)
+        };
+        let ty = if self.is_contextual(ContextualKeyword::Is) && !self.has_line_break_before() {
+            self.bump();
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+        Ok(self.alloc(TsType::Predicate(TsTypePredicate {
+            asserts,
+            param,
+            ty,
+            span: self.span_from(start),
+        })))
+    }
+
+    /// Parses an entity name like `A.B.C`. Reserved words are allowed after dots.
+    pub(super) fn parse_entity_name(&mut self) -> PResult<TsEntityName<'a>> {
+        let start = self.start();
+        let first = if self.is_keyword(Keyword::This) {
+            self.parse_ident_name()?
+        } else {
+            self.parse_ident()?
+        };
+        let mut name = TsEntityName::Ident(first);
+        while self.is(&TokenKind::Dot) {
+            // `typeof a.#b` is not an entity name
+            if !self.next_is(|p| p.word().is_some()) {
+                break;
+            }
+            self.bump();
+            let right = self.parse_ident_name()?;
+            name = TsEntityName::Qualified(self.alloc(TsQualifiedName {
+                left: name,
+                right,
+                span: self.span_from(start),
+            }));
+        }
+        Ok(name)
+    }
+
+    pub(super) fn parse_type_params_opt(&mut self) -> PResult<Option<TsTypeParamDecl<'a>>> {
+        if !self.is_lt() {
+            return Ok(None);
+        }
+        let start = self.start();
+        self.bump();
+        let mut params = self.vec();
+        loop {
+            let param_start = self.start();
+            let mut is_in = false;
+            let mut is_out = false;
+            let mut is_const = false;
+            loop {
+                let is_modifier = (self.is_keyword(Keyword::In)
+                    || self.is_contextual(ContextualKeyword::Out)
+                    || self.is_keyword(Keyword::Const))
+                    && self.next_is(|p| {
+                        p.is_ident() || p.is_keyword(Keyword::In) || p.is_keyword(Keyword::Const)
+                    });
+                if !is_modifier {
+                    break;
+                }
+                match self.bump().kind {
+                    TokenKind::Word(WordKind::Keyword(Keyword::In)) => is_in = true,
+                    TokenKind::Word(WordKind::Keyword(Keyword::Const)) => is_const = true,
+                    _ => is_out = true,
+                }
+            }
+            let name = self.parse_ident()?;
+            let constraint = if self.eat_keyword(Keyword::Extends) {
+                Some(self.parse_type()?)
+            } else {
+                None
+            };
+            let default = if self.eat(&TokenKind::AssignOp(AssignOp::Assign)) {
+                Some(self.parse_type()?)
+            } else {
+                None
+            };
+            params.push(TsTypeParam {
+                name,
+                is_in,
+                is_out,
+                is_const,
+                constraint,
+                default,
+                span: self.span_from(param_start),
+            });
+            if !self.eat(&TokenKind::Comma) || self.is_gt() {
+                break;
+            }
+        }
+        self.expect_gt()?;
+        Ok(Some(TsTypeParamDecl {
+            params,
+            span: self.span_from(start),
+        }))
+    }
+
+    pub(super) fn parse_type_args(&mut self) -> PResult<TsTypeArgs<'a>> {
+        let start = self.start();
+        if !self.is_lt() {
+            return Err(self.expected("`<`"));
+        }
+        self.bump();
+        let mut params = self.vec();
+        while !self.is_gt() {
+            params.push(self.parse_type()?.unbox());
+            if !self.eat(&TokenKind::Comma) {
+                break;
+            }
+        }
+        self.expect_gt()?;
+        Ok(TsTypeArgs {
+            params,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Whether the current token starts with `>`, which may close type arguments.
+    pub(super) fn is_gt(&self) -> bool {
+        matches!(
+            self.token.kind,
+            TokenKind::BinaryOp(
+                BinaryOp::Gt | BinaryOp::RShift | BinaryOp::ZeroFillRightShift | BinaryOp::Ge
+            ) | TokenKind::AssignOp(
+                AssignOp::RightShiftAssign | AssignOp::ZeroFillRightShiftAssign
+            )
+        )
+    }
+
+    fn parse_type_args_on_same_line(&mut self) -> PResult<Option<TsTypeArgs<'a>>> {
+        if self.is_lt() && !self.has_line_break_before() {
+            Ok(Some(self.parse_type_args()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// The names after `extends` in interfaces and `implements` in classes.
+    pub(super) fn parse_heritage_list(&mut self) -> PResult<Vec<'a, TsExprWithTypeArgs<'a>>> {
+        let mut list = self.vec();
+        loop {
+            let start = self.start();
+            let mut expr = Box::new_in(Expr::Ident(self.parse_ident()?), self.allocator);
+            while self.eat(&TokenKind::Dot) {
+                let prop = self.parse_ident_name()?;
+                expr = self.alloc(Expr::Member(MemberExpr {
+                    object: expr,
+                    prop: MemberProp::Ident(prop),
+                    optional: false,
+                    span: self.span_from(start),
+                }));
+            }
+            let type_args = if self.is_lt() {
+                Some(self.parse_type_args()?)
+            } else {
+                None
+            };
+            list.push(TsExprWithTypeArgs {
+                expr,
+                type_args,
+                span: self.span_from(start),
+            });
+            if !self.eat(&TokenKind::Comma) {
+                break;
+            }
+        }
+        Ok(list)
+    }
+
+    /// Parses the `{ ... }` body of an interface or a type literal.
+    pub(super) fn parse_type_members(&mut self) -> PResult<Vec<'a, TsTypeElement<'a>>> {
+        self.expect(&TokenKind::LBrace)?;
+        let members = self.parse_list(
+            ListKind::TypeMembers,
+            |p| {
+                let member = p.parse_type_member()?;
+                if !p.eat(&TokenKind::Semicolon)
+                    && !p.eat(&TokenKind::Comma)
+                    && !p.is(&TokenKind::RBrace)
+                    && !p.has_line_break_before()
+                {
+                    p.error(p.expected("`;`"));
+                }
+                Ok(member)
+            },
+            |span| Some(TsTypeElement::Invalid(span)),
+        );
+        self.expect_list_close();
+        Ok(members)
+    }
+
+    fn parse_type_member(&mut self) -> PResult<TsTypeElement<'a>> {
+        let start = self.start();
+        if self.is(&TokenKind::LParen) || self.is_lt() {
+            let (type_params, params, return_type) = self.parse_signature()?;
+            return Ok(TsTypeElement::Call(TsCallSignature {
+                type_params,
+                params,
+                return_type,
+                span: self.span_from(start),
+            }));
+        }
+        if self.is_keyword(Keyword::New) && self.next_is(|p| p.is(&TokenKind::LParen) || p.is_lt())
+        {
+            self.bump();
+            let (type_params, params, return_type) = self.parse_signature()?;
+            return Ok(TsTypeElement::Construct(TsConstructSignature {
+                type_params,
+                params,
+                return_type,
+                span: self.span_from(start),
+            }));
+        }
+
+        let readonly = self.is_contextual(ContextualKeyword::Readonly)
+            && self.next_on_same_line(|p| p.is_prop_name_start());
+        if readonly {
+            self.bump();
+        }
+        if self.is(&TokenKind::LBracket) && self.is_index_signature() {
+            return self
+                .parse_index_signature(start, readonly, false)
+                .map(TsTypeElement::Index);
+        }
+
+        if (self.is_contextual(ContextualKeyword::Get)
+            || self.is_contextual(ContextualKeyword::Set))
+            && self.next_on_same_line(|p| p.is_prop_name_start())
+        {
+            let is_getter = self.eat_contextual(ContextualKeyword::Get);
+            if !is_getter {
+                self.bump();
+            }
+            let key = self.parse_prop_name()?;
+            let (_, mut params, return_type) = self.parse_signature()?;
+            return Ok(if is_getter {
+                TsTypeElement::Getter(TsGetterSignature {
+                    key,
+                    return_type,
+                    span: self.span_from(start),
+                })
+            } else {
+                if params.is_empty() {
+                    return Err(self.expected("Parameter"));
+                }
+                TsTypeElement::Setter(TsSetterSignature {
+                    key,
+                    param: params.remove(0),
+                    span: self.span_from(start),
+                })
+            });
+        }
+
+        let key = self.parse_prop_name()?;
+        let optional = self.eat(&TokenKind::Question);
+        if self.is(&TokenKind::LParen) || self.is_lt() {
+            let (type_params, params, return_type) = self.parse_signature()?;
+            return Ok(TsTypeElement::Method(TsMethodSignature {
+                key,
+                optional,
+                type_params,
+                params,
+                return_type,
+                span: self.span_from(start),
+            }));
+        }
+        let type_ann = if self.eat(&TokenKind::Colon) {
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+        Ok(TsTypeElement::Property(TsPropertySignature {
+            readonly,
+            key,
+            optional,
+            type_ann,
+            span: self.span_from(start),
+        }))
+    }
+
+    /// Type parameters, parameters and an optional return type of a signature.
+    #[allow(clippy::type_complexity)]
+    fn parse_signature(
+        &mut self,
+    ) -> PResult<(
+        Option<TsTypeParamDecl<'a>>,
+        Vec<'a, Pat<'a>>,
+        Option<Box<'a, TsType<'a>>>,
+    )> {
+        let type_params = self.parse_type_params_opt()?;
+        let params = self.parse_signature_params()?;
+        let return_type = if self.eat(&TokenKind::Colon) {
+            Some(self.parse_return_type()?)
+        } else {
+            None
+        };
+        Ok((type_params, params, return_type))
+    }
+
+    /// Whether `[` starts an index signature like `[key: string]: T` rather than a
+    /// computed property name.
+    pub(super) fn is_index_signature(&mut self) -> bool {
+        self.lookahead(|p| {
+            p.bump();
+            if p.is(&TokenKind::DotDotDot) || p.is(&TokenKind::RBracket) {
+                return true;
+            }
+            while matches!(
+                p.keyword(),
+                Some(Keyword::Public | Keyword::Private | Keyword::Protected)
+            ) || p.is_contextual(ContextualKeyword::Readonly)
+            {
+                p.bump();
+            }
+            if !p.is_ident() {
+                return false;
+            }
+            p.bump();
+            p.is(&TokenKind::Colon) || p.is(&TokenKind::Comma)
+        })
+    }
+
+    pub(super) fn parse_index_signature(
+        &mut self,
+        start: usize,
+        readonly: bool,
+        is_static: bool,
+    ) -> PResult<TsIndexSignature<'a>> {
+        self.expect(&TokenKind::LBracket)?;
+        let mut params = self.vec();
+        while !self.eat(&TokenKind::RBracket) {
+            params.push(self.parse_param_pat()?);
+            if !self.eat(&TokenKind::Comma) {
+                self.expect(&TokenKind::RBracket)?;
+                break;
+            }
+        }
+        let type_ann = if self.eat(&TokenKind::Colon) {
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+        Ok(TsIndexSignature {
+            readonly,
+            is_static,
+            params,
+            type_ann,
+            span: self.span_from(start),
+        })
+    }
+}
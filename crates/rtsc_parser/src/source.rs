@@ -0,0 +1,50 @@
+//! Reading source files from disk.
+
+use std::{fs, io, path::Path};
+
+/// Reads a source file, decoding UTF-16 files marked with a byte order mark. Other
+/// files must be UTF-8, and keep their byte order mark if they have one.
+pub fn read_source(path: &Path) -> io::Result<String> {
+    decode_source(fs::read(path)?)
+}
+
+/// Decodes the bytes of a source file like [`read_source`].
+pub fn decode_source(bytes: Vec<u8>) -> io::Result<String> {
+    let utf16 = match bytes.as_slice() {
+        [0xff, 0xfe, rest @ ..] => Some((rest, u16::from_le_bytes as fn([u8; 2]) -> u16)),
+        [0xfe, 0xff, rest @ ..] => Some((rest, u16::from_be_bytes as fn([u8; 2]) -> u16)),
+        _ => None,
+    };
+    match utf16 {
+        Some((rest, from_bytes)) => {
+            let units = rest
+                .chunks_exact(2)
+                .map(|pair| from_bytes([pair[0], pair[1]]))
+                .collect::<Vec<_>>();
+            String::from_utf16(&units).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        }
+        None => String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_byte_order_marks() {
+        let utf16 = |bom: [u8; 2], to_bytes: fn(u16) -> [u8; 2]| {
+            let mut bytes = bom.to_vec();
+            bytes.extend("a\u{e9}".encode_utf16().flat_map(to_bytes));
+            decode_source(bytes).unwrap()
+        };
+        assert_eq!(utf16([0xff, 0xfe], u16::to_le_bytes), "a\u{e9}");
+        assert_eq!(utf16([0xfe, 0xff], u16::to_be_bytes), "a\u{e9}");
+        assert_eq!(
+            decode_source("\u{feff}a".as_bytes().to_vec()).unwrap(),
+            "\u{feff}a"
+        );
+        assert!(decode_source(vec![0xff, 0xfe, 0x00, 0xd8]).is_err());
+        assert!(decode_source(vec![0xc3]).is_err());
+    }
+}
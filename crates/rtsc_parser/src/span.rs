@@ -0,0 +1,107 @@
+use miette::{SourceOffset, SourceSpan};
+
+use crate::{LineCol, LineIndex};
+
+/// A range of UTF-8 byte offsets into the source, `start` inclusive and `end`
+/// exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    pub fn size(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// The text of the span in `source`.
+    pub fn source_text<'a>(&self, source: &'a str) -> &'a str {
+        &source[self.start..self.end]
+    }
+
+    /// The smallest span covering both spans.
+    pub fn merge(&self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+
+    /// Whether `offset` is in the span. The end of a span is not in it.
+    pub fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset < self.end
+    }
+
+    /// Whether `other` is inside the span, which includes an empty span at its end.
+    pub fn contains_span(&self, other: Span) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    /// The part both spans cover, if they overlap or touch.
+    pub fn intersect(&self, other: Span) -> Option<Span> {
+        let start = self.start.max(other.start);
+        let end = self.end.min(other.end);
+        (start <= end).then(|| Span::new(start, end))
+    }
+
+    /// The span without `start` bytes at its start and `end` bytes at its end, like
+    /// the contents of a string literal without its quotes. It shrinks to an empty
+    /// span rather than past itself.
+    pub fn shrink(&self, start: usize, end: usize) -> Span {
+        let new_start = (self.start + start).min(self.end);
+        let new_end = self.end.saturating_sub(end).max(new_start);
+        Span::new(new_start, new_end)
+    }
+
+    /// The line and column of the start and the end of the span.
+    pub fn to_line_cols(&self, index: &LineIndex) -> (LineCol, LineCol) {
+        (index.line_col(self.start), index.line_col(self.end))
+    }
+}
+
+impl From<Span> for SourceSpan {
+    fn from(val: Span) -> Self {
+        Self::new(
+            SourceOffset::from(val.start),
+            SourceOffset::from(val.size()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn span_utilities() {
+        let a = Span::new(2, 6);
+        let b = Span::new(4, 9);
+        assert_eq!(a.merge(b), Span::new(2, 9));
+        assert_eq!(a.intersect(b), Some(Span::new(4, 6)));
+        assert_eq!(a.intersect(Span::new(6, 8)), Some(Span::new(6, 6)));
+        assert_eq!(a.intersect(Span::new(7, 8)), None);
+        assert!(a.contains(2) && a.contains(5) && !a.contains(6));
+        assert!(a.contains_span(Span::new(3, 6)) && !a.contains_span(b));
+        assert_eq!(a.shrink(1, 1), Span::new(3, 5));
+        assert_eq!(a.shrink(3, 3), Span::new(5, 5));
+        assert!(a.shrink(2, 2).is_empty());
+    }
+
+    #[test]
+    fn source_text_and_line_cols() {
+        let source = "let s =\n  'ab';";
+        let span = Span::new(10, 14);
+        assert_eq!(span.source_text(source), "'ab'");
+        assert_eq!(span.shrink(1, 1).source_text(source), "ab");
+        assert_eq!(
+            span.to_line_cols(&LineIndex::new(source)),
+            (LineCol { line: 1, col: 2 }, LineCol { line: 1, col: 6 })
+        );
+    }
+}
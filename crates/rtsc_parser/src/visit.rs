@@ -0,0 +1,1087 @@
+//! Visitors over the AST: [`Visit`] walks it by reference, [`VisitMut`] changes it in
+//! place and [`Fold`] takes it apart and builds it again.
+//!
+//! Each trait has a method for every node, named after it, like [`Visit::visit_binary_expr`].
+//! The default methods walk into the children of the node, so a visitor only overrides
+//! the nodes it cares about and calls `visit_children_with` to keep going below them.
+//! The traits and the walks are generated from the table of nodes at the bottom of this
+//! file, which lists every field of every node, the ones walked into first: the nodes
+//! it holds and its span, which [`VisitMut::visit_mut_span`] and the like can rewrite.
+//!
+//! Every node, including leaves like `this` which are no struct of their own, is
+//! entered and exited through [`Visit::enter_node`] and [`Visit::exit_node`] around its
+//! children. An [`AstPath`] kept up to date there knows the ancestors of the node being
+//! visited.
+
+use crate::{
+    allocator::{self, Box},
+    ast::*,
+    cst::SyntaxKind,
+    Span,
+};
+
+/// The ancestors of the node a visitor is in, innermost last, for visitors which keep
+/// it in their `enter_node` and `exit_node`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AstPath {
+    nodes: Vec<(SyntaxKind, Span)>,
+}
+
+impl AstPath {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn enter(&mut self, kind: SyntaxKind, span: Span) {
+        self.nodes.push((kind, span));
+    }
+
+    pub fn exit(&mut self) {
+        self.nodes.pop();
+    }
+
+    /// The innermost node entered, which is the parent of the nodes visited in it.
+    pub fn parent(&self) -> Option<(SyntaxKind, Span)> {
+        self.nodes.last().copied()
+    }
+
+    /// The nodes entered, from the innermost one up to the program.
+    pub fn ancestors(&self) -> impl Iterator<Item = (SyntaxKind, Span)> + '_ {
+        self.nodes.iter().rev().copied()
+    }
+
+    /// Whether a node of `kind` is one of the ancestors.
+    pub fn is_inside(&self, kind: SyntaxKind) -> bool {
+        self.nodes.iter().any(|&(ancestor, _)| ancestor == kind)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+/// The struct of a node, which an [`AstNode`] can hold.
+pub trait Node<'a>: Sized + 'a {
+    const KIND: SyntaxKind;
+
+    fn from_node(node: AstNode<'a>) -> Option<&'a Self>;
+}
+
+/// A node a [`Visit`] can walk.
+pub trait VisitWith<'a, V: ?Sized + Visit<'a>> {
+    /// Calls the method of the visitor for this node.
+    fn visit_with(&'a self, v: &mut V);
+
+    /// Visits the children of this node.
+    fn visit_children_with(&'a self, v: &mut V);
+}
+
+/// A node a [`VisitMut`] can walk.
+pub trait VisitMutWith<'a, V: ?Sized + VisitMut<'a>> {
+    fn visit_mut_with(&mut self, v: &mut V);
+
+    fn visit_mut_children_with(&mut self, v: &mut V);
+}
+
+/// A node a [`Fold`] can rebuild.
+pub trait FoldWith<'a, F: ?Sized + Fold<'a>>: Sized {
+    fn fold_with(self, f: &mut F) -> Self;
+
+    fn fold_children_with(self, f: &mut F) -> Self;
+}
+
+impl<'a, V: ?Sized + Visit<'a>, T: VisitWith<'a, V>> VisitWith<'a, V> for Box<'_, T> {
+    fn visit_with(&'a self, v: &mut V) {
+        (**self).visit_with(v);
+    }
+
+    fn visit_children_with(&'a self, v: &mut V) {
+        (**self).visit_children_with(v);
+    }
+}
+
+impl<'a, V: ?Sized + Visit<'a>, T: VisitWith<'a, V>> VisitWith<'a, V> for Option<T> {
+    fn visit_with(&'a self, v: &mut V) {
+        if let Some(node) = self {
+            node.visit_with(v);
+        }
+    }
+
+    fn visit_children_with(&'a self, v: &mut V) {
+        if let Some(node) = self {
+            node.visit_children_with(v);
+        }
+    }
+}
+
+impl<'a, V: ?Sized + Visit<'a>, T: VisitWith<'a, V>> VisitWith<'a, V> for allocator::Vec<'_, T> {
+    fn visit_with(&'a self, v: &mut V) {
+        for node in self {
+            node.visit_with(v);
+        }
+    }
+
+    fn visit_children_with(&'a self, v: &mut V) {
+        for node in self {
+            node.visit_children_with(v);
+        }
+    }
+}
+
+impl<'a, V: ?Sized + VisitMut<'a>, T: VisitMutWith<'a, V>> VisitMutWith<'a, V> for Box<'_, T> {
+    fn visit_mut_with(&mut self, v: &mut V) {
+        (**self).visit_mut_with(v);
+    }
+
+    fn visit_mut_children_with(&mut self, v: &mut V) {
+        (**self).visit_mut_children_with(v);
+    }
+}
+
+impl<'a, V: ?Sized + VisitMut<'a>, T: VisitMutWith<'a, V>> VisitMutWith<'a, V> for Option<T> {
+    fn visit_mut_with(&mut self, v: &mut V) {
+        if let Some(node) = self {
+            node.visit_mut_with(v);
+        }
+    }
+
+    fn visit_mut_children_with(&mut self, v: &mut V) {
+        if let Some(node) = self {
+            node.visit_mut_children_with(v);
+        }
+    }
+}
+
+impl<'a, V: ?Sized + VisitMut<'a>, T: VisitMutWith<'a, V>> VisitMutWith<'a, V>
+    for allocator::Vec<'_, T>
+{
+    fn visit_mut_with(&mut self, v: &mut V) {
+        for node in self {
+            node.visit_mut_with(v);
+        }
+    }
+
+    fn visit_mut_children_with(&mut self, v: &mut V) {
+        for node in self {
+            node.visit_mut_children_with(v);
+        }
+    }
+}
+
+impl<'a, F: ?Sized + Fold<'a>, T: FoldWith<'a, F>> FoldWith<'a, F> for Box<'_, T> {
+    fn fold_with(self, f: &mut F) -> Self {
+        self.map(|node| node.fold_with(f))
+    }
+
+    fn fold_children_with(self, f: &mut F) -> Self {
+        self.map(|node| node.fold_children_with(f))
+    }
+}
+
+impl<'a, F: ?Sized + Fold<'a>, T: FoldWith<'a, F>> FoldWith<'a, F> for Option<T> {
+    fn fold_with(self, f: &mut F) -> Self {
+        self.map(|node| node.fold_with(f))
+    }
+
+    fn fold_children_with(self, f: &mut F) -> Self {
+        self.map(|node| node.fold_children_with(f))
+    }
+}
+
+impl<'a, F: ?Sized + Fold<'a>, T: FoldWith<'a, F>> FoldWith<'a, F> for allocator::Vec<'_, T> {
+    fn fold_with(self, f: &mut F) -> Self {
+        self.map(|node| node.fold_with(f))
+    }
+
+    fn fold_children_with(self, f: &mut F) -> Self {
+        self.map(|node| node.fold_children_with(f))
+    }
+}
+
+impl<'a, V: ?Sized + Visit<'a>> VisitWith<'a, V> for Span {
+    fn visit_with(&'a self, v: &mut V) {
+        v.visit_span(self);
+    }
+
+    fn visit_children_with(&'a self, _: &mut V) {}
+}
+
+impl<'a, V: ?Sized + VisitMut<'a>> VisitMutWith<'a, V> for Span {
+    fn visit_mut_with(&mut self, v: &mut V) {
+        v.visit_mut_span(self);
+    }
+
+    fn visit_mut_children_with(&mut self, _: &mut V) {}
+}
+
+impl<'a, F: ?Sized + Fold<'a>> FoldWith<'a, F> for Span {
+    fn fold_with(self, f: &mut F) -> Self {
+        f.fold_span(self)
+    }
+
+    fn fold_children_with(self, _: &mut F) -> Self {
+        self
+    }
+}
+
+/// Generates the visitor traits and the walks of every node from the table below.
+///
+/// `structs` are nodes with a span, or with the span of the field after `=`. The fields
+/// walked into, the nodes and spans, are in the first braces and the others in the
+/// second, so that a field missing from the table fails to compile. `enums`
+/// are not nodes themselves but hold one in each variant, except for the variants
+/// holding just a span which are nodes of the kind after `=>`. `helpers` hold nodes
+/// without being one. The methods of each are named in brackets.
+macro_rules! ast_nodes {
+    (
+        structs {
+            $(
+                $name:ident $(<$lt:lifetime>)? $(= $span:ident)? { $($field:ident),* $(,)? }
+                    { $($other:ident),* $(,)? }
+                    [$visit:ident, $visit_mut:ident, $fold:ident $(,)?]
+            ),* $(,)?
+        }
+        enums {
+            $(
+                $enum:ident $(<$enum_lt:lifetime>)? { $($variant:ident),* $(,)? }
+                    $({ $($leaf:ident => $kind:ident),* $(,)? })?
+                    [$enum_visit:ident, $enum_visit_mut:ident, $enum_fold:ident $(,)?]
+            ),* $(,)?
+        }
+        helpers {
+            $(
+                $helper:ident $(<$helper_lt:lifetime>)? { $($helper_field:ident),* $(,)? }
+                    { $($helper_other:ident),* $(,)? }
+                    [$helper_visit:ident, $helper_visit_mut:ident, $helper_fold:ident $(,)?]
+            ),* $(,)?
+        }
+    ) => {
+        /// A reference to a node in the tree, which [`Visit::enter_node`] is given for
+        /// every node: its struct, or the span of a leaf like [`Expr::This`] which is no
+        /// struct of its own.
+        #[derive(Debug, Clone, Copy)]
+        pub enum AstNode<'a> {
+            $($name(&'a $name $(<$lt>)?),)*
+            Leaf(SyntaxKind, &'a Span),
+        }
+
+        impl<'a> AstNode<'a> {
+            pub fn kind(self) -> SyntaxKind {
+                match self {
+                    $(AstNode::$name(_) => SyntaxKind::$name,)*
+                    AstNode::Leaf(kind, _) => kind,
+                }
+            }
+
+            pub fn span(self) -> Span {
+                match self {
+                    $(AstNode::$name(node) => ast_nodes!(@span node $($span)?),)*
+                    AstNode::Leaf(_, span) => *span,
+                }
+            }
+
+            /// The address of the node in the allocator, which tells it apart from other
+            /// nodes of its kind for as long as the tree lives.
+            pub fn addr(self) -> usize {
+                match self {
+                    $(AstNode::$name(node) => node as *const _ as usize,)*
+                    AstNode::Leaf(_, span) => span as *const _ as usize,
+                }
+            }
+        }
+
+        $(
+            impl<'a> Node<'a> for $name $(<$lt>)? {
+                const KIND: SyntaxKind = SyntaxKind::$name;
+
+                fn from_node(node: AstNode<'a>) -> Option<&'a Self> {
+                    match node {
+                        AstNode::$name(node) => Some(node),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+
+        /// Walks the AST by reference. The nodes live for `'a`, so a visitor can keep
+        /// references to them.
+        pub trait Visit<'a> {
+            /// Called before the children of a node are visited, with the node itself.
+            fn enter_node(&mut self, kind: SyntaxKind, span: Span, node: AstNode<'a>) {
+                let _ = (kind, span, node);
+            }
+
+            /// Called after the children of a node are visited.
+            fn exit_node(&mut self, kind: SyntaxKind, span: Span) {
+                let _ = (kind, span);
+            }
+
+            fn visit_span(&mut self, span: &'a Span) {
+                let _ = span;
+            }
+
+            $(
+                fn $visit(&mut self, n: &'a $name $(<$lt>)?) {
+                    n.visit_children_with(self);
+                }
+            )*
+            $(
+                fn $enum_visit(&mut self, n: &'a $enum $(<$enum_lt>)?) {
+                    n.visit_children_with(self);
+                }
+            )*
+            $(
+                fn $helper_visit(&mut self, n: &'a $helper $(<$helper_lt>)?) {
+                    n.visit_children_with(self);
+                }
+            )*
+        }
+
+        /// Walks the AST changing it in place.
+        pub trait VisitMut<'a> {
+            /// Called before the children of a node are visited.
+            fn enter_node(&mut self, kind: SyntaxKind, span: Span) {
+                let _ = (kind, span);
+            }
+
+            /// Called after the children of a node are visited.
+            fn exit_node(&mut self, kind: SyntaxKind, span: Span) {
+                let _ = (kind, span);
+            }
+
+            fn visit_mut_span(&mut self, span: &mut Span) {
+                let _ = span;
+            }
+
+            $(
+                fn $visit_mut(&mut self, n: &mut $name $(<$lt>)?) {
+                    n.visit_mut_children_with(self);
+                }
+            )*
+            $(
+                fn $enum_visit_mut(&mut self, n: &mut $enum $(<$enum_lt>)?) {
+                    n.visit_mut_children_with(self);
+                }
+            )*
+            $(
+                fn $helper_visit_mut(&mut self, n: &mut $helper $(<$helper_lt>)?) {
+                    n.visit_mut_children_with(self);
+                }
+            )*
+        }
+
+        /// Rebuilds the AST from the nodes each method returns, which may be of another
+        /// variant than the one it was given, like an expression replaced by another.
+        pub trait Fold<'a> {
+            /// Called before the children of a node are folded.
+            fn enter_node(&mut self, kind: SyntaxKind, span: Span) {
+                let _ = (kind, span);
+            }
+
+            /// Called after the children of a node are folded.
+            fn exit_node(&mut self, kind: SyntaxKind, span: Span) {
+                let _ = (kind, span);
+            }
+
+            fn fold_span(&mut self, span: Span) -> Span {
+                span
+            }
+
+            $(
+                fn $fold(&mut self, n: $name $(<$lt>)?) -> $name $(<$lt>)? {
+                    n.fold_children_with(self)
+                }
+            )*
+            $(
+                fn $enum_fold(&mut self, n: $enum $(<$enum_lt>)?) -> $enum $(<$enum_lt>)? {
+                    n.fold_children_with(self)
+                }
+            )*
+            $(
+                fn $helper_fold(&mut self, n: $helper $(<$helper_lt>)?) -> $helper $(<$helper_lt>)? {
+                    n.fold_children_with(self)
+                }
+            )*
+        }
+
+        $(
+            impl<'a, V: ?Sized + Visit<'a>> VisitWith<'a, V> for $name $(<$lt>)? {
+                fn visit_with(&'a self, v: &mut V) {
+                    v.$visit(self);
+                }
+
+                fn visit_children_with(&'a self, v: &mut V) {
+                    let span = ast_nodes!(@span self $($span)?);
+                    v.enter_node(SyntaxKind::$name, span, AstNode::$name(self));
+                    let $name { $($field,)* $($other: _,)* } = self;
+                    $($field.visit_with(v);)*
+                    v.exit_node(SyntaxKind::$name, span);
+                }
+            }
+
+            impl<'a, V: ?Sized + VisitMut<'a>> VisitMutWith<'a, V> for $name $(<$lt>)? {
+                fn visit_mut_with(&mut self, v: &mut V) {
+                    v.$visit_mut(self);
+                }
+
+                fn visit_mut_children_with(&mut self, v: &mut V) {
+                    let span = ast_nodes!(@span self $($span)?);
+                    v.enter_node(SyntaxKind::$name, span);
+                    let $name { $($field,)* $($other: _,)* } = self;
+                    $($field.visit_mut_with(v);)*
+                    v.exit_node(SyntaxKind::$name, span);
+                }
+            }
+
+            impl<'a, F: ?Sized + Fold<'a>> FoldWith<'a, F> for $name $(<$lt>)? {
+                fn fold_with(self, f: &mut F) -> Self {
+                    f.$fold(self)
+                }
+
+                fn fold_children_with(self, f: &mut F) -> Self {
+                    let span = ast_nodes!(@span self $($span)?);
+                    f.enter_node(SyntaxKind::$name, span);
+                    let $name { $($field,)* $($other,)* } = self;
+                    let node = $name {
+                        $($field: $field.fold_with(f),)*
+                        $($other,)*
+                    };
+                    f.exit_node(SyntaxKind::$name, span);
+                    node
+                }
+            }
+        )*
+
+        $(
+            impl<'a, V: ?Sized + Visit<'a>> VisitWith<'a, V> for $enum $(<$enum_lt>)? {
+                fn visit_with(&'a self, v: &mut V) {
+                    v.$enum_visit(self);
+                }
+
+                fn visit_children_with(&'a self, v: &mut V) {
+                    match self {
+                        $($enum::$variant(node) => node.visit_with(v),)*
+                        $($($enum::$leaf(span) => {
+                            v.enter_node(SyntaxKind::$kind, *span, AstNode::Leaf(SyntaxKind::$kind, span));
+                            v.visit_span(span);
+                            v.exit_node(SyntaxKind::$kind, *span);
+                        })*)?
+                    }
+                }
+            }
+
+            impl<'a, V: ?Sized + VisitMut<'a>> VisitMutWith<'a, V> for $enum $(<$enum_lt>)? {
+                fn visit_mut_with(&mut self, v: &mut V) {
+                    v.$enum_visit_mut(self);
+                }
+
+                fn visit_mut_children_with(&mut self, v: &mut V) {
+                    match self {
+                        $($enum::$variant(node) => node.visit_mut_with(v),)*
+                        $($($enum::$leaf(span) => {
+                            v.enter_node(SyntaxKind::$kind, *span);
+                            v.visit_mut_span(span);
+                            v.exit_node(SyntaxKind::$kind, *span);
+                        })*)?
+                    }
+                }
+            }
+
+            impl<'a, F: ?Sized + Fold<'a>> FoldWith<'a, F> for $enum $(<$enum_lt>)? {
+                fn fold_with(self, f: &mut F) -> Self {
+                    f.$enum_fold(self)
+                }
+
+                fn fold_children_with(self, f: &mut F) -> Self {
+                    match self {
+                        $($enum::$variant(node) => $enum::$variant(node.fold_with(f)),)*
+                        $($($enum::$leaf(span) => {
+                            f.enter_node(SyntaxKind::$kind, span);
+                            let folded = f.fold_span(span);
+                            f.exit_node(SyntaxKind::$kind, span);
+                            $enum::$leaf(folded)
+                        })*)?
+                    }
+                }
+            }
+        )*
+
+        $(
+            impl<'a, V: ?Sized + Visit<'a>> VisitWith<'a, V> for $helper $(<$helper_lt>)? {
+                fn visit_with(&'a self, v: &mut V) {
+                    v.$helper_visit(self);
+                }
+
+                fn visit_children_with(&'a self, v: &mut V) {
+                    let $helper { $($helper_field,)* $($helper_other: _,)* } = self;
+                    $($helper_field.visit_with(v);)*
+                }
+            }
+
+            impl<'a, V: ?Sized + VisitMut<'a>> VisitMutWith<'a, V> for $helper $(<$helper_lt>)? {
+                fn visit_mut_with(&mut self, v: &mut V) {
+                    v.$helper_visit_mut(self);
+                }
+
+                fn visit_mut_children_with(&mut self, v: &mut V) {
+                    let $helper { $($helper_field,)* $($helper_other: _,)* } = self;
+                    $($helper_field.visit_mut_with(v);)*
+                }
+            }
+
+            impl<'a, F: ?Sized + Fold<'a>> FoldWith<'a, F> for $helper $(<$helper_lt>)? {
+                fn fold_with(self, f: &mut F) -> Self {
+                    f.$helper_fold(self)
+                }
+
+                fn fold_children_with(self, f: &mut F) -> Self {
+                    let $helper { $($helper_field,)* $($helper_other,)* } = self;
+                    $helper {
+                        $($helper_field: $helper_field.fold_with(f),)*
+                        $($helper_other,)*
+                    }
+                }
+            }
+        )*
+    };
+    (@span $node:ident) => {
+        $node.span
+    };
+    (@span $node:ident $field:ident) => {
+        $node.$field.span
+    };
+}
+
+ast_nodes! {
+    structs {
+        Program<'a> { body, span } {}
+            [visit_program, visit_mut_program, fold_program],
+        Ident<'a> { span } { name }
+            [visit_ident, visit_mut_ident, fold_ident],
+        PrivateName<'a> { span } { name }
+            [visit_private_name, visit_mut_private_name, fold_private_name],
+        BlockStmt<'a> { stmts, span } {}
+            [visit_block_stmt, visit_mut_block_stmt, fold_block_stmt],
+        ExprStmt<'a> { expr, span } {}
+            [visit_expr_stmt, visit_mut_expr_stmt, fold_expr_stmt],
+        VarDecl<'a> { decls, span } { kind, declare }
+            [visit_var_decl, visit_mut_var_decl, fold_var_decl],
+        VarDeclarator<'a> { name, init, span } { definite }
+            [visit_var_declarator, visit_mut_var_declarator, fold_var_declarator],
+        IfStmt<'a> { test, cons, alt, span } {}
+            [visit_if_stmt, visit_mut_if_stmt, fold_if_stmt],
+        ForStmt<'a> { init, test, update, body, span } {}
+            [visit_for_stmt, visit_mut_for_stmt, fold_for_stmt],
+        ForInStmt<'a> { left, right, body, span } {}
+            [visit_for_in_stmt, visit_mut_for_in_stmt, fold_for_in_stmt],
+        ForOfStmt<'a> { left, right, body, span } { is_await }
+            [visit_for_of_stmt, visit_mut_for_of_stmt, fold_for_of_stmt],
+        WhileStmt<'a> { test, body, span } {}
+            [visit_while_stmt, visit_mut_while_stmt, fold_while_stmt],
+        DoWhileStmt<'a> { body, test, span } {}
+            [visit_do_while_stmt, visit_mut_do_while_stmt, fold_do_while_stmt],
+        ReturnStmt<'a> { arg, span } {}
+            [visit_return_stmt, visit_mut_return_stmt, fold_return_stmt],
+        BreakStmt<'a> { label, span } {}
+            [visit_break_stmt, visit_mut_break_stmt, fold_break_stmt],
+        ContinueStmt<'a> { label, span } {}
+            [visit_continue_stmt, visit_mut_continue_stmt, fold_continue_stmt],
+        ThrowStmt<'a> { arg, span } {}
+            [visit_throw_stmt, visit_mut_throw_stmt, fold_throw_stmt],
+        TryStmt<'a> { block, handler, finalizer, span } {}
+            [visit_try_stmt, visit_mut_try_stmt, fold_try_stmt],
+        CatchClause<'a> { param, body, span } {}
+            [visit_catch_clause, visit_mut_catch_clause, fold_catch_clause],
+        SwitchStmt<'a> { discriminant, cases, span } {}
+            [visit_switch_stmt, visit_mut_switch_stmt, fold_switch_stmt],
+        SwitchCase<'a> { test, cons, span } {}
+            [visit_switch_case, visit_mut_switch_case, fold_switch_case],
+        LabeledStmt<'a> { label, body, span } {}
+            [visit_labeled_stmt, visit_mut_labeled_stmt, fold_labeled_stmt],
+        WithStmt<'a> { object, body, span } {}
+            [visit_with_stmt, visit_mut_with_stmt, fold_with_stmt],
+        FnDecl<'a> = function { id, function } { declare }
+            [visit_fn_decl, visit_mut_fn_decl, fold_fn_decl],
+        Function<'a> { params, body, type_params, return_type, span } { is_async, is_generator }
+            [visit_function, visit_mut_function, fold_function],
+        Param<'a> { decorators, pat, span } { accessibility, is_readonly, is_override }
+            [visit_param, visit_mut_param, fold_param],
+        Decorator<'a> { expr, span } {}
+            [visit_decorator, visit_mut_decorator, fold_decorator],
+        ClassDecl<'a> = class { id, class } { declare }
+            [visit_class_decl, visit_mut_class_decl, fold_class_decl],
+        Class<'a> { decorators, type_params, super_class, super_type_args, implements, body, span }
+        { is_abstract }
+            [visit_class, visit_mut_class, fold_class],
+        ClassMethod<'a> { decorators, key, function, span }
+        { kind, is_static, accessibility, is_abstract, is_override, is_optional }
+            [visit_class_method, visit_mut_class_method, fold_class_method],
+        ClassProp<'a> { decorators, key, value, type_ann, span }
+        {
+            is_static, accessibility, is_abstract, is_override, is_readonly, is_declare,
+            is_accessor, is_optional, definite,
+        }
+            [visit_class_prop, visit_mut_class_prop, fold_class_prop],
+        StaticBlock<'a> { body, span } {}
+            [visit_static_block, visit_mut_static_block, fold_static_block],
+        ComputedPropName<'a> { expr, span } {}
+            [visit_computed_prop_name, visit_mut_computed_prop_name, fold_computed_prop_name],
+        Str<'a> { span } { value, raw }
+            [visit_str, visit_mut_str, fold_str],
+        Num<'a> { span } { value, raw }
+            [visit_num, visit_mut_num, fold_num],
+        BigInt<'a> { span } { value, raw }
+            [visit_big_int, visit_mut_big_int, fold_big_int],
+        Bool { span } { value }
+            [visit_bool, visit_mut_bool, fold_bool],
+        Regex<'a> { span } { pattern, flags }
+            [visit_regex, visit_mut_regex, fold_regex],
+        ArrayLit<'a> { elems, span } {}
+            [visit_array_lit, visit_mut_array_lit, fold_array_lit],
+        ObjectLit<'a> { props, span } {}
+            [visit_object_lit, visit_mut_object_lit, fold_object_lit],
+        KeyValueProp<'a> { key, value, span } {}
+            [visit_key_value_prop, visit_mut_key_value_prop, fold_key_value_prop],
+        ShorthandProp<'a> { key, init, span } {}
+            [visit_shorthand_prop, visit_mut_shorthand_prop, fold_shorthand_prop],
+        MethodProp<'a> { key, function, span } { kind }
+            [visit_method_prop, visit_mut_method_prop, fold_method_prop],
+        SpreadProp<'a> { expr, span } {}
+            [visit_spread_prop, visit_mut_spread_prop, fold_spread_prop],
+        FnExpr<'a> = function { id, function } {}
+            [visit_fn_expr, visit_mut_fn_expr, fold_fn_expr],
+        ClassExpr<'a> = class { id, class } {}
+            [visit_class_expr, visit_mut_class_expr, fold_class_expr],
+        ArrowExpr<'a> { params, body, type_params, return_type, span } { is_async }
+            [visit_arrow_expr, visit_mut_arrow_expr, fold_arrow_expr],
+        Tpl<'a> { quasis, exprs, span } {}
+            [visit_tpl, visit_mut_tpl, fold_tpl],
+        TplElement<'a> { span } { cooked, raw }
+            [visit_tpl_element, visit_mut_tpl_element, fold_tpl_element],
+        TaggedTpl<'a> { tag, type_args, tpl, span } {}
+            [visit_tagged_tpl, visit_mut_tagged_tpl, fold_tagged_tpl],
+        UnaryExpr<'a> { arg, span } { op }
+            [visit_unary_expr, visit_mut_unary_expr, fold_unary_expr],
+        UpdateExpr<'a> { arg, span } { op, prefix }
+            [visit_update_expr, visit_mut_update_expr, fold_update_expr],
+        BinaryExpr<'a> { left, right, span } { op }
+            [visit_binary_expr, visit_mut_binary_expr, fold_binary_expr],
+        AssignExpr<'a> { left, right, span } { op }
+            [visit_assign_expr, visit_mut_assign_expr, fold_assign_expr],
+        CondExpr<'a> { test, cons, alt, span } {}
+            [visit_cond_expr, visit_mut_cond_expr, fold_cond_expr],
+        CallExpr<'a> { callee, type_args, args, span } { optional }
+            [visit_call_expr, visit_mut_call_expr, fold_call_expr],
+        NewExpr<'a> { callee, type_args, args, span } {}
+            [visit_new_expr, visit_mut_new_expr, fold_new_expr],
+        MemberExpr<'a> { object, prop, span } { optional }
+            [visit_member_expr, visit_mut_member_expr, fold_member_expr],
+        SeqExpr<'a> { exprs, span } {}
+            [visit_seq_expr, visit_mut_seq_expr, fold_seq_expr],
+        ParenExpr<'a> { expr, span } {}
+            [visit_paren_expr, visit_mut_paren_expr, fold_paren_expr],
+        YieldExpr<'a> { arg, span } { delegate }
+            [visit_yield_expr, visit_mut_yield_expr, fold_yield_expr],
+        AwaitExpr<'a> { arg, span } {}
+            [visit_await_expr, visit_mut_await_expr, fold_await_expr],
+        MetaProp<'a> { meta, prop, span } {}
+            [visit_meta_prop, visit_mut_meta_prop, fold_meta_prop],
+        TsAsExpr<'a> { expr, type_ann, span } {}
+            [visit_ts_as_expr, visit_mut_ts_as_expr, fold_ts_as_expr],
+        TsSatisfiesExpr<'a> { expr, type_ann, span } {}
+            [visit_ts_satisfies_expr, visit_mut_ts_satisfies_expr, fold_ts_satisfies_expr],
+        TsConstAssertion<'a> { expr, span } {}
+            [visit_ts_const_assertion, visit_mut_ts_const_assertion, fold_ts_const_assertion],
+        TsNonNullExpr<'a> { expr, span } {}
+            [visit_ts_non_null_expr, visit_mut_ts_non_null_expr, fold_ts_non_null_expr],
+        TsTypeAssertion<'a> { type_ann, expr, span } {}
+            [visit_ts_type_assertion, visit_mut_ts_type_assertion, fold_ts_type_assertion],
+        TsInstantiation<'a> { expr, type_args, span } {}
+            [visit_ts_instantiation, visit_mut_ts_instantiation, fold_ts_instantiation],
+        BindingIdent<'a> { id, type_ann, span } { optional }
+            [visit_binding_ident, visit_mut_binding_ident, fold_binding_ident],
+        ArrayPat<'a> { elems, type_ann, span } { optional }
+            [visit_array_pat, visit_mut_array_pat, fold_array_pat],
+        ObjectPat<'a> { props, type_ann, span } { optional }
+            [visit_object_pat, visit_mut_object_pat, fold_object_pat],
+        KeyValuePatProp<'a> { key, value, span } {}
+            [visit_key_value_pat_prop, visit_mut_key_value_pat_prop, fold_key_value_pat_prop],
+        AssignPatProp<'a> { key, value, span } {}
+            [visit_assign_pat_prop, visit_mut_assign_pat_prop, fold_assign_pat_prop],
+        AssignPat<'a> { left, right, span } {}
+            [visit_assign_pat, visit_mut_assign_pat, fold_assign_pat],
+        RestPat<'a> { arg, type_ann, span } {}
+            [visit_rest_pat, visit_mut_rest_pat, fold_rest_pat],
+        ImportDecl<'a> { specifiers, src, with, span } { type_only }
+            [visit_import_decl, visit_mut_import_decl, fold_import_decl],
+        ImportNamedSpecifier<'a> { imported, local, span } { is_type_only }
+            [
+                visit_import_named_specifier,
+                visit_mut_import_named_specifier,
+                fold_import_named_specifier,
+            ],
+        ExportDecl<'a> { decl, span } {}
+            [visit_export_decl, visit_mut_export_decl, fold_export_decl],
+        ExportNamed<'a> { specifiers, src, with, span } { type_only }
+            [visit_export_named, visit_mut_export_named, fold_export_named],
+        ExportNamedSpecifier<'a> { orig, exported, span } { is_type_only }
+            [
+                visit_export_named_specifier,
+                visit_mut_export_named_specifier,
+                fold_export_named_specifier,
+            ],
+        ExportAll<'a> { src, with, span } { type_only }
+            [visit_export_all, visit_mut_export_all, fold_export_all],
+        ExportDefault<'a> { decl, span } {}
+            [visit_export_default, visit_mut_export_default, fold_export_default],
+        TsInterfaceDecl<'a> { id, type_params, extends, body, span } { declare }
+            [visit_ts_interface_decl, visit_mut_ts_interface_decl, fold_ts_interface_decl],
+        TsTypeAliasDecl<'a> { id, type_params, ty, span } { declare }
+            [visit_ts_type_alias_decl, visit_mut_ts_type_alias_decl, fold_ts_type_alias_decl],
+        TsEnumDecl<'a> { id, members, span } { declare, is_const }
+            [visit_ts_enum_decl, visit_mut_ts_enum_decl, fold_ts_enum_decl],
+        TsEnumMember<'a> { id, init, span } {}
+            [visit_ts_enum_member, visit_mut_ts_enum_member, fold_ts_enum_member],
+        TsModuleDecl<'a> { id, body, span } { declare, global }
+            [visit_ts_module_decl, visit_mut_ts_module_decl, fold_ts_module_decl],
+        TsModuleBlock<'a> { body, span } {}
+            [visit_ts_module_block, visit_mut_ts_module_block, fold_ts_module_block],
+        TsImportEqualsDecl<'a> { id, module_ref, span } { is_export, is_type_only }
+            [
+                visit_ts_import_equals_decl,
+                visit_mut_ts_import_equals_decl,
+                fold_ts_import_equals_decl,
+            ],
+        TsExportAssign<'a> { expr, span } {}
+            [visit_ts_export_assign, visit_mut_ts_export_assign, fold_ts_export_assign],
+        TsExportAsNamespace<'a> { id, span } {}
+            [
+                visit_ts_export_as_namespace,
+                visit_mut_ts_export_as_namespace,
+                fold_ts_export_as_namespace,
+            ],
+        TsQualifiedName<'a> { left, right, span } {}
+            [visit_ts_qualified_name, visit_mut_ts_qualified_name, fold_ts_qualified_name],
+        TsTypeParamDecl<'a> { params, span } {}
+            [visit_ts_type_param_decl, visit_mut_ts_type_param_decl, fold_ts_type_param_decl],
+        TsTypeParam<'a> { name, constraint, default, span } { is_in, is_out, is_const }
+            [visit_ts_type_param, visit_mut_ts_type_param, fold_ts_type_param],
+        TsTypeArgs<'a> { params, span } {}
+            [visit_ts_type_args, visit_mut_ts_type_args, fold_ts_type_args],
+        TsExprWithTypeArgs<'a> { expr, type_args, span } {}
+            [
+                visit_ts_expr_with_type_args,
+                visit_mut_ts_expr_with_type_args,
+                fold_ts_expr_with_type_args,
+            ],
+        TsKeywordType { span } { kind }
+            [visit_ts_keyword_type, visit_mut_ts_keyword_type, fold_ts_keyword_type],
+        TsTypeRef<'a> { name, type_args, span } {}
+            [visit_ts_type_ref, visit_mut_ts_type_ref, fold_ts_type_ref],
+        TsLitType<'a> { lit, span } {}
+            [visit_ts_lit_type, visit_mut_ts_lit_type, fold_ts_lit_type],
+        TsTplLitType<'a> { quasis, types, span } {}
+            [visit_ts_tpl_lit_type, visit_mut_ts_tpl_lit_type, fold_ts_tpl_lit_type],
+        TsArrayType<'a> { elem, span } {}
+            [visit_ts_array_type, visit_mut_ts_array_type, fold_ts_array_type],
+        TsTupleType<'a> { elems, span } {}
+            [visit_ts_tuple_type, visit_mut_ts_tuple_type, fold_ts_tuple_type],
+        TsTupleElement<'a> { label, ty, span } { optional, rest }
+            [visit_ts_tuple_element, visit_mut_ts_tuple_element, fold_ts_tuple_element],
+        TsUnionType<'a> { types, span } {}
+            [visit_ts_union_type, visit_mut_ts_union_type, fold_ts_union_type],
+        TsIntersectionType<'a> { types, span } {}
+            [visit_ts_intersection_type, visit_mut_ts_intersection_type, fold_ts_intersection_type],
+        TsFnType<'a> { type_params, params, return_type, span } {}
+            [visit_ts_fn_type, visit_mut_ts_fn_type, fold_ts_fn_type],
+        TsConstructorType<'a> { type_params, params, return_type, span } { is_abstract }
+            [visit_ts_constructor_type, visit_mut_ts_constructor_type, fold_ts_constructor_type],
+        TsTypeLit<'a> { members, span } {}
+            [visit_ts_type_lit, visit_mut_ts_type_lit, fold_ts_type_lit],
+        TsParenType<'a> { ty, span } {}
+            [visit_ts_paren_type, visit_mut_ts_paren_type, fold_ts_paren_type],
+        TsTypeOperator<'a> { ty, span } { op }
+            [visit_ts_type_operator, visit_mut_ts_type_operator, fold_ts_type_operator],
+        TsIndexedAccessType<'a> { obj, index, span } {}
+            [
+                visit_ts_indexed_access_type,
+                visit_mut_ts_indexed_access_type,
+                fold_ts_indexed_access_type,
+            ],
+        TsConditionalType<'a> { check, extends, true_type, false_type, span } {}
+            [visit_ts_conditional_type, visit_mut_ts_conditional_type, fold_ts_conditional_type],
+        TsInferType<'a> { param, span } {}
+            [visit_ts_infer_type, visit_mut_ts_infer_type, fold_ts_infer_type],
+        TsMappedType<'a> { type_param, name_type, ty, span } { readonly, optional }
+            [visit_ts_mapped_type, visit_mut_ts_mapped_type, fold_ts_mapped_type],
+        TsTypeQuery<'a> { expr, type_args, span } {}
+            [visit_ts_type_query, visit_mut_ts_type_query, fold_ts_type_query],
+        TsImportType<'a> { arg, qualifier, type_args, span } {}
+            [visit_ts_import_type, visit_mut_ts_import_type, fold_ts_import_type],
+        TsTypePredicate<'a> { param, ty, span } { asserts }
+            [visit_ts_type_predicate, visit_mut_ts_type_predicate, fold_ts_type_predicate],
+        TsPropertySignature<'a> { key, type_ann, span } { readonly, optional }
+            [
+                visit_ts_property_signature,
+                visit_mut_ts_property_signature,
+                fold_ts_property_signature,
+            ],
+        TsMethodSignature<'a> { key, type_params, params, return_type, span } { optional }
+            [visit_ts_method_signature, visit_mut_ts_method_signature, fold_ts_method_signature],
+        TsCallSignature<'a> { type_params, params, return_type, span } {}
+            [visit_ts_call_signature, visit_mut_ts_call_signature, fold_ts_call_signature],
+        TsConstructSignature<'a> { type_params, params, return_type, span } {}
+            [
+                visit_ts_construct_signature,
+                visit_mut_ts_construct_signature,
+                fold_ts_construct_signature,
+            ],
+        TsIndexSignature<'a> { params, type_ann, span } { readonly, is_static }
+            [visit_ts_index_signature, visit_mut_ts_index_signature, fold_ts_index_signature],
+        TsGetterSignature<'a> { key, return_type, span } {}
+            [visit_ts_getter_signature, visit_mut_ts_getter_signature, fold_ts_getter_signature],
+        TsSetterSignature<'a> { key, param, span } {}
+            [visit_ts_setter_signature, visit_mut_ts_setter_signature, fold_ts_setter_signature],
+        JsxElement<'a> { opening, children, closing, span } {}
+            [visit_jsx_element, visit_mut_jsx_element, fold_jsx_element],
+        JsxOpeningElement<'a> { name, type_args, attrs, span } { self_closing }
+            [visit_jsx_opening_element, visit_mut_jsx_opening_element, fold_jsx_opening_element],
+        JsxClosingElement<'a> { name, span } {}
+            [visit_jsx_closing_element, visit_mut_jsx_closing_element, fold_jsx_closing_element],
+        JsxMemberExpr<'a> { object, prop, span } {}
+            [visit_jsx_member_expr, visit_mut_jsx_member_expr, fold_jsx_member_expr],
+        JsxNamespacedName<'a> { ns, name, span } {}
+            [visit_jsx_namespaced_name, visit_mut_jsx_namespaced_name, fold_jsx_namespaced_name],
+        JsxAttr<'a> { name, value, span } {}
+            [visit_jsx_attr, visit_mut_jsx_attr, fold_jsx_attr],
+        JsxSpreadAttr<'a> { expr, span } {}
+            [visit_jsx_spread_attr, visit_mut_jsx_spread_attr, fold_jsx_spread_attr],
+        JsxExprContainer<'a> { expr, span } {}
+            [visit_jsx_expr_container, visit_mut_jsx_expr_container, fold_jsx_expr_container],
+        JsxText<'a> { span } { value }
+            [visit_jsx_text, visit_mut_jsx_text, fold_jsx_text],
+        JsxSpreadChild<'a> { expr, span } {}
+            [visit_jsx_spread_child, visit_mut_jsx_spread_child, fold_jsx_spread_child],
+        JsxFragment<'a> { children, span } {}
+            [visit_jsx_fragment, visit_mut_jsx_fragment, fold_jsx_fragment],
+    }
+    enums {
+        Stmt<'a> {
+            Block, Expr, Var, If, For, ForIn, ForOf, While, DoWhile, Return, Break, Continue, Throw,
+            Try, Switch, Labeled, With, Fn, Class, Interface, TypeAlias, Enum, Module, Import,
+            ImportEquals, ExportDecl, ExportNamed, ExportAll, ExportDefault, ExportAssign,
+            ExportAsNamespace,
+        } { Empty => EmptyStmt, Debugger => DebuggerStmt, Invalid => InvalidStmt }
+            [visit_stmt, visit_mut_stmt, fold_stmt],
+        ForInit<'a> { Var, Expr }
+            [visit_for_init, visit_mut_for_init, fold_for_init],
+        ForHead<'a> { Var, Pat }
+            [visit_for_head, visit_mut_for_head, fold_for_head],
+        ClassMember<'a> { Method, Prop, IndexSignature, StaticBlock }
+        { Empty => EmptyClassMember, Invalid => InvalidClassMember }
+            [visit_class_member, visit_mut_class_member, fold_class_member],
+        PropName<'a> { Ident, Str, Num, BigInt, Computed, Private }
+            [visit_prop_name, visit_mut_prop_name, fold_prop_name],
+        Expr<'a> {
+            Ident, Lit, Array, Object, Fn, Class, Arrow, Tpl, TaggedTpl, Unary, Update, Binary,
+            Assign, Cond, Call, New, Member, Seq, Paren, Yield, Await, MetaProp, PrivateName, TsAs,
+            TsSatisfies, TsConstAssertion, TsNonNull, TsTypeAssertion, TsInstantiation, JsxElement,
+            JsxFragment,
+        } { This => ThisExpr, Super => SuperExpr, Import => ImportExpr, Invalid => InvalidExpr }
+            [visit_expr, visit_mut_expr, fold_expr],
+        Lit<'a> { Str, Num, BigInt, Bool, Regex } { Null => NullLit }
+            [visit_lit, visit_mut_lit, fold_lit],
+        Prop<'a> { KeyValue, Shorthand, Method, Spread }
+            [visit_prop, visit_mut_prop, fold_prop],
+        ArrowBody<'a> { Block, Expr }
+            [visit_arrow_body, visit_mut_arrow_body, fold_arrow_body],
+        MemberProp<'a> { Ident, Private, Computed }
+            [visit_member_prop, visit_mut_member_prop, fold_member_prop],
+        Pat<'a> { Ident, Array, Object, Assign, Rest, Expr }
+            [visit_pat, visit_mut_pat, fold_pat],
+        ObjectPatProp<'a> { KeyValue, Assign, Rest }
+            [visit_object_pat_prop, visit_mut_object_pat_prop, fold_object_pat_prop],
+        ImportSpecifier<'a> { Default, Namespace, Named }
+            [visit_import_specifier, visit_mut_import_specifier, fold_import_specifier],
+        ModuleExportName<'a> { Ident, Str }
+            [visit_module_export_name, visit_mut_module_export_name, fold_module_export_name],
+        ExportSpecifier<'a> { Named, Namespace }
+            [visit_export_specifier, visit_mut_export_specifier, fold_export_specifier],
+        DefaultDecl<'a> { Fn, Class, Interface, Expr }
+            [visit_default_decl, visit_mut_default_decl, fold_default_decl],
+        TsModuleName<'a> { Ident, Str }
+            [visit_ts_module_name, visit_mut_ts_module_name, fold_ts_module_name],
+        TsModuleBody<'a> { Block, Namespace }
+            [visit_ts_module_body, visit_mut_ts_module_body, fold_ts_module_body],
+        TsModuleRef<'a> { Entity, External }
+            [visit_ts_module_ref, visit_mut_ts_module_ref, fold_ts_module_ref],
+        TsEntityName<'a> { Ident, Qualified }
+            [visit_ts_entity_name, visit_mut_ts_entity_name, fold_ts_entity_name],
+        TsType<'a> {
+            Keyword, Ref, Lit, Array, Tuple, Union, Intersection, Fn, Constructor, TypeLit, Paren,
+            TypeOperator, IndexedAccess, Conditional, Infer, Mapped, Query, Import, Predicate,
+        } { This => TsThisType }
+            [visit_ts_type, visit_mut_ts_type, fold_ts_type],
+        TsLit<'a> { Str, Num, BigInt, Bool, Tpl }
+            [visit_ts_lit, visit_mut_ts_lit, fold_ts_lit],
+        TsTypeQueryExpr<'a> { Entity, Import }
+            [visit_ts_type_query_expr, visit_mut_ts_type_query_expr, fold_ts_type_query_expr],
+        TsPredicateParam<'a> { Ident } { This => TsThisType }
+            [visit_ts_predicate_param, visit_mut_ts_predicate_param, fold_ts_predicate_param],
+        TsTypeElement<'a> { Property, Method, Call, Construct, Index, Getter, Setter }
+        { Invalid => InvalidTypeElement }
+            [visit_ts_type_element, visit_mut_ts_type_element, fold_ts_type_element],
+        JsxElementName<'a> { Ident, Member, Namespaced }
+            [visit_jsx_element_name, visit_mut_jsx_element_name, fold_jsx_element_name],
+        JsxAttrOrSpread<'a> { Attr, Spread }
+            [visit_jsx_attr_or_spread, visit_mut_jsx_attr_or_spread, fold_jsx_attr_or_spread],
+        JsxAttrName<'a> { Ident, Namespaced }
+            [visit_jsx_attr_name, visit_mut_jsx_attr_name, fold_jsx_attr_name],
+        JsxAttrValue<'a> { Str, ExprContainer, Element, Fragment }
+            [visit_jsx_attr_value, visit_mut_jsx_attr_value, fold_jsx_attr_value],
+        JsxChild<'a> { Text, ExprContainer, Spread, Element, Fragment }
+            [visit_jsx_child, visit_mut_jsx_child, fold_jsx_child],
+    }
+    helpers {
+        ExprOrSpread<'a> { spread, expr } {}
+            [visit_expr_or_spread, visit_mut_expr_or_spread, fold_expr_or_spread],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, Allocator, Atom};
+
+    fn parse_ok<'a>(allocator: &'a Allocator, source: &'a str) -> Program<'a> {
+        let (program, errors) = parse(allocator, source);
+        assert!(errors.is_empty(), "{errors:?}");
+        program
+    }
+
+    #[derive(Default)]
+    struct Idents<'a> {
+        path: AstPath,
+        found: Vec<(&'a str, Option<SyntaxKind>)>,
+    }
+
+    impl<'a> Visit<'a> for Idents<'a> {
+        fn enter_node(&mut self, kind: SyntaxKind, span: Span, _: AstNode<'a>) {
+            self.path.enter(kind, span);
+        }
+
+        fn exit_node(&mut self, _: SyntaxKind, _: Span) {
+            self.path.exit();
+        }
+
+        fn visit_ident(&mut self, n: &'a Ident) {
+            let parent = self.path.parent().map(|(kind, _)| kind);
+            self.found.push((&n.name, parent));
+        }
+
+        // the body of a function is left out
+        fn visit_block_stmt(&mut self, _: &'a BlockStmt) {}
+    }
+
+    #[test]
+    fn visits_with_path() {
+        let allocator = Allocator::new();
+        let program = parse_ok(&allocator, "let a = b(c, ...d); function f(x) { y; }");
+        let mut idents = Idents::default();
+        program.visit_with(&mut idents);
+        assert_eq!(
+            idents.found,
+            [
+                ("a", Some(SyntaxKind::BindingIdent)),
+                ("b", Some(SyntaxKind::CallExpr)),
+                ("c", Some(SyntaxKind::CallExpr)),
+                ("d", Some(SyntaxKind::CallExpr)),
+                ("f", Some(SyntaxKind::FnDecl)),
+                ("x", Some(SyntaxKind::BindingIdent)),
+            ]
+        );
+        assert!(idents.path.is_empty());
+    }
+
+    #[test]
+    fn visits_mutably() {
+        let allocator = Allocator::new();
+        struct Rename<'a>(&'a Allocator);
+
+        impl<'a> VisitMut<'a> for Rename<'a> {
+            fn visit_mut_ident(&mut self, n: &mut Ident<'a>) {
+                if n.name == "a" {
+                    n.name = Atom::new_in("renamed", self.0);
+                }
+            }
+        }
+
+        let mut program = parse_ok(&allocator, "a + b; this.a; ({ a });");
+        program.visit_mut_with(&mut Rename(&allocator));
+        let mut idents = Idents::default();
+        program.visit_with(&mut idents);
+        let names = idents
+            .found
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["renamed", "b", "renamed", "renamed"]);
+    }
+
+    #[test]
+    fn folds() {
+        let allocator = Allocator::new();
+        /// Drops the parentheses around expressions and counts the `this` leaves.
+        #[derive(Default)]
+        struct Unparen {
+            this: usize,
+        }
+
+        impl<'a> Fold<'a> for Unparen {
+            fn enter_node(&mut self, kind: SyntaxKind, _: Span) {
+                if kind == SyntaxKind::ThisExpr {
+                    self.this += 1;
+                }
+            }
+
+            fn fold_expr(&mut self, n: Expr<'a>) -> Expr<'a> {
+                match n.fold_children_with(self) {
+                    Expr::Paren(paren) => paren.expr.unbox(),
+                    expr => expr,
+                }
+            }
+        }
+
+        let mut unparen = Unparen::default();
+        let program = parse_ok(&allocator, "((a)) + (this);").fold_with(&mut unparen);
+        assert_eq!(unparen.this, 1);
+        let Stmt::Expr(stmt) = &program.body[0] else {
+            panic!("an expression statement");
+        };
+        let Expr::Binary(binary) = &*stmt.expr else {
+            panic!("a binary expression");
+        };
+        assert!(matches!(&*binary.left, Expr::Ident(ident) if ident.name == "a"));
+        assert!(matches!(&*binary.right, Expr::This(_)));
+    }
+    #[test]
+    fn rewrites_spans() {
+        let allocator = Allocator::new();
+        /// Moves every span as if the source had a line in front of it.
+        struct Shift(usize);
+
+        impl VisitMut<'_> for Shift {
+            fn visit_mut_span(&mut self, span: &mut Span) {
+                *span = Span::new(span.start + self.0, span.end + self.0);
+            }
+        }
+
+        let mut program = parse_ok(&allocator, "a + this;");
+        program.visit_mut_with(&mut Shift(3));
+        assert_eq!(program.body, parse_ok(&allocator, "\n\n\na + this;").body);
+    }
+}
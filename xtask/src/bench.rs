@@ -0,0 +1,139 @@
+use std::{
+    ffi::OsStr,
+    fs,
+    io::Write,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use rtsc_parser::{Allocator, Interner, Lexer, TokenKind};
+use walkdir::WalkDir;
+
+const FIXTURES_DIR: &str = "tests";
+const DEFAULT_ITERATIONS: u32 = 5;
+
+struct BenchCase {
+    filename: String,
+    code: String,
+    best: Duration,
+}
+
+/// Lexes every fixture under `tests/` a few times and reports the throughput
+/// together with the slowest files. Run it in release mode: `cargo bench-lexer`.
+pub fn run<W: Write>(out: &mut W, iterations: Option<u32>) {
+    let iterations = iterations.unwrap_or(DEFAULT_ITERATIONS).max(1);
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(FIXTURES_DIR);
+    let mut cases = WalkDir::new(root)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            e.path().extension() == Some(OsStr::new("ts"))
+                || (e.path().extension() == Some(OsStr::new("js")))
+        })
+        // fixtures that are not valid UTF-8 (e.g. UTF-16 BOM tests) are skipped
+        .filter_map(|e| {
+            let code = fs::read_to_string(e.path()).ok()?;
+            Some(BenchCase {
+                filename: e.path().to_str().unwrap().to_string(),
+                code,
+                best: Duration::MAX,
+            })
+        })
+        .collect::<Vec<_>>();
+    if cases.is_empty() {
+        panic!("No test cases found");
+    }
+
+    let mut total = Duration::ZERO;
+    for c in cases.iter_mut() {
+        for _ in 0..iterations {
+            let start = Instant::now();
+            let allocator = Allocator::new();
+            let (tokens, errors) = Lexer::new(&allocator, &c.code).lex();
+            drop((tokens, errors));
+            let elapsed = start.elapsed();
+            c.best = c.best.min(elapsed);
+        }
+        total += c.best;
+    }
+
+    let bytes = cases.iter().map(|c| c.code.len()).sum::<usize>();
+    let throughput = bytes as f64 / total.as_secs_f64() / (1024.0 * 1024.0);
+    writeln!(out, "---------- Benchmark(Lexer) ----------\n").expect("Unable to write benchmark");
+    writeln!(
+        out,
+        "{} files, {} bytes, best of {} runs: {:.2?} ({:.2} MiB/s)\n",
+        cases.len(),
+        bytes,
+        iterations,
+        total,
+        throughput
+    )
+    .expect("Unable to write benchmark");
+
+    cases.sort_by(|a, b| b.best.cmp(&a.best));
+    writeln!(out, "slowest files:").expect("Unable to write benchmark");
+    for c in cases.iter().take(5) {
+        let case = c.filename.split("/tests").nth(1).unwrap();
+        writeln!(out, "  {:>10.2?} {:>8} bytes  {}", c.best, c.code.len(), case)
+            .expect("Unable to write benchmark");
+    }
+
+    atoms(out, &cases, iterations);
+}
+
+/// Compares interning the names and string values of the fixtures with copying each
+/// of them into a `String`, which the lexer did before it had atoms. The raw text of
+/// strings is borrowed from the source now, so only the `String` path copies it.
+fn atoms<W: Write>(out: &mut W, cases: &[BenchCase], iterations: u32) {
+    let allocator = Allocator::new();
+    let mut values = vec![];
+    let mut raws = vec![];
+    for c in cases {
+        for token in Lexer::new(&allocator, &c.code).lex().0 {
+            match token.kind {
+                TokenKind::Word(word) => values.push(word.as_str()),
+                TokenKind::String { value, raw } => {
+                    values.push(value.as_str());
+                    raws.push(raw);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut strings = Duration::MAX;
+    let mut atoms = Duration::MAX;
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let owned = values
+            .iter()
+            .chain(&raws)
+            .map(|text| text.to_string())
+            .collect::<Vec<_>>();
+        drop(owned);
+        strings = strings.min(start.elapsed());
+
+        let start = Instant::now();
+        let allocator = Allocator::new();
+        let mut interner = Interner::new(&allocator);
+        let interned = values
+            .iter()
+            .map(|text| interner.intern(text))
+            .collect::<Vec<_>>();
+        drop(interned);
+        atoms = atoms.min(start.elapsed());
+    }
+
+    writeln!(out, "\n---------- Benchmark(Atoms) ----------\n").expect("Unable to write benchmark");
+    writeln!(
+        out,
+        "{} words and strings, best of {} runs:\n  {:>10.2?} String\n  {:>10.2?} Atom ({:.2}x)",
+        values.len(),
+        iterations,
+        strings,
+        atoms,
+        strings.as_secs_f64() / atoms.as_secs_f64()
+    )
+    .expect("Unable to write benchmark");
+}
@@ -1,18 +1,13 @@
-use std::{
-    ffi::OsStr,
-    fs::File,
-    io::{Read, Write},
-    path::PathBuf,
-};
+use std::{ffi::OsStr, fs::File, io::Write, path::PathBuf};
 
 use ansi_term::Colour::{Green, Purple, Red};
-use rtsc_parser::run_lexer;
+use rtsc_parser::{read_source, run_lexer_with_options, Allocator};
 use tracing::info;
 use walkdir::WalkDir;
 
 use crate::{
     suite::{Case, SuiteSummary, TestResult, TestSuite},
-    utils::remove_bom,
+    utils::{remove_bom, Fixture},
 };
 
 const FIXTURES_NAME: &str = "compiler";
@@ -21,7 +16,7 @@ const FIXTURES_DIR: &str = "tests/compiler";
 #[derive(Debug, Default)]
 pub struct CompilerTestCase {
     filename: String,
-    code: String,
+    fixture: Fixture,
 }
 
 impl Case for CompilerTestCase {
@@ -31,12 +26,20 @@ impl Case for CompilerTestCase {
     {
         Self {
             filename: filename.to_owned(),
-            code: remove_bom(code).to_owned(),
+            fixture: Fixture::new(filename, &remove_bom(code)),
         }
     }
 
     fn run(&self) -> TestResult {
-        match std::panic::catch_unwind(|| run_lexer(&self.code)) {
+        let lex_units = || {
+            self.fixture.units.iter().try_for_each(|unit| {
+                let allocator = Allocator::new();
+                run_lexer_with_options(&allocator, &unit.code, self.fixture.lexer_options(unit))
+                    .map(drop)
+                    .map_err(|_| info!("failed to lex {} in {}", unit.name, self.filename))
+            })
+        };
+        match std::panic::catch_unwind(lex_units) {
             Ok(res) => match res {
                 Ok(_) => TestResult::Success,
                 Err(_) => TestResult::Failure,
@@ -62,12 +65,8 @@ impl TestSuite for CompilerTestSuite {
             dir_name: FIXTURES_NAME.to_string(),
             root: PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(FIXTURES_DIR),
             ignore: vec![
-                "unicodeIdentifierNames.ts".to_string(),
                 "instanceofOperator.ts".to_string(),
-                "bom-utf16le.ts".to_string(),
-                "collisionCodeGenModuleWithUnicodeNames.ts".to_string(),
                 "promiseTest.ts".to_string(),
-                "bom-utf16be.ts".to_string(),
                 "targetTypeBaseCalls.ts".to_string(),
             ],
         }
@@ -80,6 +79,7 @@ impl TestSuite for CompilerTestSuite {
             .filter_map(|e| e.ok())
             .filter(|e| {
                 e.path().extension() == Some(OsStr::new("ts"))
+                    || e.path().extension() == Some(OsStr::new("tsx"))
                     || (e.path().extension() == Some(OsStr::new("js")))
             })
             .map(|e| e.path().to_path_buf())
@@ -96,9 +96,7 @@ impl TestSuite for CompilerTestSuite {
                     .contains(&c.file_name().unwrap().to_str().unwrap().to_string())
             })
             .map(|c| {
-                let mut file = File::open(c).unwrap();
-                let mut contents = String::new();
-                file.read_to_string(&mut contents).unwrap_or_else(|e| {
+                let contents = read_source(c).unwrap_or_else(|e| {
                     panic!("Unable to read file: {}, {}", c.to_str().unwrap(), e)
                 });
                 CompilerTestCase::new(c.to_str().unwrap(), &contents)
@@ -6,7 +6,7 @@ use std::{
 };
 
 use ansi_term::Colour::{Green, Purple, Red};
-use rtsc_parser::run_lexer;
+use rtsc_parser::{run_lexer, Allocator};
 use tracing::info;
 use walkdir::WalkDir;
 
@@ -36,7 +36,7 @@ impl Case for LexerTestCase {
     }
 
     fn run(&self) -> TestResult {
-        match std::panic::catch_unwind(|| run_lexer(&self.code)) {
+        match std::panic::catch_unwind(|| run_lexer(&Allocator::new(), &self.code).map(drop)) {
             Ok(res) => match res {
                 Ok(_) => TestResult::Success,
                 Err(_) => TestResult::Failure,
@@ -84,7 +84,7 @@ impl TestSuite for LexerTestSuite {
                 let mut file = File::open(c).unwrap();
                 let mut contents = String::new();
                 file.read_to_string(&mut contents).unwrap();
-                LexerTestCase::new(c.to_str().unwrap(), &mut contents)
+                LexerTestCase::new(c.to_str().unwrap(), &contents)
             })
             .collect::<Vec<_>>();
 
@@ -0,0 +1,3 @@
+//! The fixture helpers of the test runner, which the tests of the other crates use too.
+
+pub mod utils;
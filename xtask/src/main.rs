@@ -1,20 +1,28 @@
 use conformance::ConformanceTestSuite;
 use lexer::LexerTestSuite;
 use suite::TestSuite;
+use xtask::utils;
 
 use std::io::Write;
 
+mod bench;
 mod compiler;
 mod conformance;
 mod lexer;
 mod suite;
-mod utils;
 fn main() {
     // std::panic::set_hook(Box::new(|_info| {}));
     let subscriber = tracing_subscriber::FmtSubscriber::new();
     tracing::subscriber::set_global_default(subscriber).expect("Unable to set a global subscriber");
     let mut out = std::io::stdout();
 
+    let mut args = std::env::args().skip(1);
+    if args.next().as_deref() == Some("bench") {
+        let iterations = args.next().map(|n| n.parse().expect("iterations must be a number"));
+        bench::run(&mut out, iterations);
+        return;
+    }
+
     let lexer_summary = LexerTestSuite::new().run();
     let conformance_summary = ConformanceTestSuite::new().run();
     let compiler_summary = compiler::CompilerTestSuite::new().run();
@@ -1,6 +1,115 @@
-pub(super) fn remove_bom(src: &str) -> String {
+use std::collections::HashMap;
+
+use rtsc_parser::{
+    directives::{Directive, DirectiveKind},
+    lex_directives, EsVersion, LexerOptions, SourceType, Syntax,
+};
+
+pub fn remove_bom(src: &str) -> String {
     if src.starts_with('\u{feff}') {
         return src.to_owned();
     }
     src.to_owned()
 }
+
+/// A fixture split into its files, with its `// @name: value` compiler options.
+#[derive(Debug, Default, Clone)]
+pub struct Fixture {
+    /// The options of the fixture, keyed by lowercase name.
+    pub options: HashMap<String, String>,
+    pub units: Vec<Unit>,
+}
+
+/// A file of a fixture. A fixture holds several files if it has `// @filename:`
+/// comments, each starting a new file.
+#[derive(Debug, Default, Clone)]
+pub struct Unit {
+    pub name: String,
+    pub code: String,
+}
+
+impl Fixture {
+    /// Reads a fixture like the TypeScript test harness: option comments are left out
+    /// of the files, and code before the first `@filename` goes into the first file.
+    pub fn new(filename: &str, code: &str) -> Self {
+        let mut fixture = Self::default();
+        let mut name = None;
+        let mut content = String::new();
+        let mut pos = 0;
+        for directive in lex_directives(code) {
+            let Directive {
+                kind:
+                    DirectiveKind::CompilerOption {
+                        name: option,
+                        value,
+                    },
+                span,
+            } = directive
+            else {
+                continue;
+            };
+            content.push_str(&code[pos..span.start]);
+            pos = span.end;
+            pos += line_break_len(&code[pos..]);
+            let option = option.to_lowercase();
+            if option != "filename" {
+                fixture.options.insert(option, value);
+                continue;
+            }
+            if let Some(name) = name.replace(value) {
+                fixture.units.push(Unit {
+                    name,
+                    code: std::mem::take(&mut content),
+                });
+            }
+        }
+        content.push_str(&code[pos..]);
+        fixture.units.push(Unit {
+            name: name.unwrap_or_else(|| filename.to_string()),
+            code: content,
+        });
+        fixture
+    }
+
+    /// The lexer options for a file of the fixture. Fixtures are scripts targeting ES5
+    /// unless they say otherwise, and are read as JavaScript or JSX by their extension.
+    pub fn lexer_options(&self, unit: &Unit) -> LexerOptions {
+        // a fixture can list several targets to run with, of which the first will do
+        let target = self
+            .options
+            .get("target")
+            .and_then(|targets| EsVersion::from_target(targets.split(',').next()?.trim()))
+            .unwrap_or(EsVersion::Es5);
+        let strict = self
+            .options
+            .get("alwaysstrict")
+            .is_some_and(|value| value.eq_ignore_ascii_case("true"));
+        let name = unit.name.to_ascii_lowercase();
+        let syntax = if name.ends_with(".tsx") {
+            Syntax::Tsx
+        } else if name.ends_with(".jsx") {
+            Syntax::Jsx
+        } else if name.ends_with(".js") {
+            Syntax::Javascript
+        } else {
+            Syntax::Typescript
+        };
+        LexerOptions {
+            target,
+            source_type: SourceType::Script,
+            strict,
+            syntax,
+        }
+    }
+}
+
+fn line_break_len(text: &str) -> usize {
+    if text.starts_with("\r\n") {
+        2
+    } else {
+        text.chars()
+            .next()
+            .filter(|&c| matches!(c, '\n' | '\r' | '\u{2028}' | '\u{2029}'))
+            .map_or(0, char::len_utf8)
+    }
+}
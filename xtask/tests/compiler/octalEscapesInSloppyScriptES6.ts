@@ -0,0 +1,3 @@
+// @target: es2015
+var a = "\07";
+var b = "\8";